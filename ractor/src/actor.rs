@@ -54,6 +54,7 @@ use std::fmt::Debug;
 #[cfg(not(feature = "async-trait"))]
 use std::future::Future;
 use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
 
 use actor_properties::MuxedMessage;
 use futures::TryFutureExt;
@@ -69,7 +70,15 @@ pub mod actor_id;
 pub(crate) mod actor_properties;
 pub mod actor_ref;
 pub mod derived_actor;
+#[cfg(feature = "cluster")]
+pub mod durable_mailbox;
+pub mod interceptor;
+pub mod mailbox;
+pub mod priority;
+pub(crate) mod stash;
+pub mod stats;
 mod supervision;
+pub mod timers;
 
 #[cfg(test)]
 mod supervision_tests;
@@ -80,10 +89,18 @@ use actor_cell::ActorCell;
 use actor_cell::ActorPortSet;
 use actor_cell::ActorStatus;
 use actor_ref::ActorRef;
+#[cfg(feature = "cluster")]
+use durable_mailbox::DurableMailbox;
+#[cfg(feature = "cluster")]
+use durable_mailbox::DurableMailboxConfig;
+use interceptor::MessageInterceptor;
+use mailbox::MailboxOverflowPolicy;
+use stats::ActorStatsLayer;
 
 use crate::errors::ActorErr;
 use crate::errors::ActorProcessingErr;
 use crate::errors::MessagingErr;
+use crate::errors::RactorErr;
 use crate::errors::SpawnErr;
 use crate::ActorName;
 use crate::Message;
@@ -131,6 +148,43 @@ pub trait Actor: Sized + Sync + Send + 'static {
     /// Initialization arguments
     type Arguments: State;
 
+    /// The maximum number of messages which can be waiting in this actor's mailbox.
+    ///
+    /// Defaults to [None], meaning the mailbox is unbounded. When set, sends to a full
+    /// mailbox are handled according to [Actor::MAILBOX_OVERFLOW_POLICY]. A capacity of
+    /// `0` is treated as `1`.
+    const MAILBOX_CAPACITY: Option<usize> = None;
+
+    /// The policy applied when a message is sent to this actor while its bounded mailbox
+    /// is full. Has no effect unless [Actor::MAILBOX_CAPACITY] is set.
+    ///
+    /// Defaults to [MailboxOverflowPolicy::Reject]
+    const MAILBOX_OVERFLOW_POLICY: MailboxOverflowPolicy = MailboxOverflowPolicy::Reject;
+
+    /// The maximum number of messages this actor can hold in its stash. See
+    /// [ActorRef::stash] for more details.
+    ///
+    /// Defaults to 1000
+    const STASH_CAPACITY: usize = 1000;
+
+    /// The maximum number of queued messages handed to [Actor::handle_batch] at once.
+    ///
+    /// Defaults to 1, meaning messages are handled one at a time by [Actor::handle]. When
+    /// larger, the actor takes the next message along with the ones already waiting behind
+    /// it in its mailbox (up to this many), and handles them together in one call to
+    /// [Actor::handle_batch]. Supervision events and stop requests are handled before or
+    /// after a batch, never in the middle of one.
+    const MAX_BATCH_SIZE: usize = 1;
+
+    /// How long to wait for more messages to fill up a batch, once its first message is
+    /// received. Has no effect unless [Actor::MAX_BATCH_SIZE] is larger than 1.
+    ///
+    /// Defaults to [None], meaning a batch holds only the messages which are already waiting
+    /// in the mailbox. Lingering trades latency for larger batches. A supervision event or
+    /// stop request received while lingering ends the batch early, and a signal (e.g.
+    /// [ActorCell::kill]) interrupts it like it interrupts handling the batch.
+    const BATCH_LINGER: Option<crate::concurrency::Duration> = None;
+
     /// Invoked when an actor is being started by the system.
     ///
     /// Any initialization inherent to the actor's role should be
@@ -245,6 +299,58 @@ pub trait Actor: Sized + Sync + Send + 'static {
         Ok(())
     }
 
+    /// Invoked on the new handler when the actor is hot-upgraded with [ActorCell::upgrade],
+    /// before it replaces the old handler.
+    ///
+    /// Message processing is paused for the duration of the upgrade and the actor's status is
+    /// [ActorStatus::Upgrading]. The state is migrated in place, since it remains owned by the
+    /// actor's processing loop. The actor's id, name registration, links and queued messages
+    /// are all preserved across the upgrade.
+    ///
+    /// The new handler is always of the same type as the old one, with the same
+    /// [Actor::State], as switching types isn't supported. To migrate to a new state value,
+    /// assign it through the reference.
+    ///
+    /// Errors and panics in `code_change` follow the supervision strategy.
+    ///
+    /// * `myself` - A handle to the [ActorCell] representing this actor
+    /// * `state` - A mutable reference to the state to migrate
+    #[allow(unused_variables)]
+    #[cfg(not(feature = "async-trait"))]
+    fn code_change(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        state: &mut Self::State,
+    ) -> impl Future<Output = Result<(), ActorProcessingErr>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Invoked on the new handler when the actor is hot-upgraded with [ActorCell::upgrade],
+    /// before it replaces the old handler.
+    ///
+    /// Message processing is paused for the duration of the upgrade and the actor's status is
+    /// [ActorStatus::Upgrading]. The state is migrated in place, since it remains owned by the
+    /// actor's processing loop. The actor's id, name registration, links and queued messages
+    /// are all preserved across the upgrade.
+    ///
+    /// The new handler is always of the same type as the old one, with the same
+    /// [Actor::State], as switching types isn't supported. To migrate to a new state value,
+    /// assign it through the reference.
+    ///
+    /// Errors and panics in `code_change` follow the supervision strategy.
+    ///
+    /// * `myself` - A handle to the [ActorCell] representing this actor
+    /// * `state` - A mutable reference to the state to migrate
+    #[allow(unused_variables)]
+    #[cfg(feature = "async-trait")]
+    async fn code_change(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        Ok(())
+    }
+
     /// Handle the incoming message from the event processing loop. Unhandled panickes will be
     /// captured and sent to the supervisor(s)
     ///
@@ -278,6 +384,53 @@ pub trait Actor: Sized + Sync + Send + 'static {
         Ok(())
     }
 
+    /// Handle a batch of incoming messages from the event processing loop, see
+    /// [Actor::MAX_BATCH_SIZE]. Unhandled panickes will be captured and sent to the
+    /// supervisor(s)
+    ///
+    /// Defaults to handling the messages one at a time with [Actor::handle], stopping at
+    /// the first error.
+    ///
+    /// * `myself` - A handle to the [ActorCell] representing this actor
+    /// * `messages` - The messages to process, in the order they were received
+    /// * `state` - A mutable reference to the internal actor's state
+    #[cfg(not(feature = "async-trait"))]
+    fn handle_batch(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        messages: Vec<Self::Msg>,
+        state: &mut Self::State,
+    ) -> impl Future<Output = Result<(), ActorProcessingErr>> + Send {
+        async move {
+            for message in messages {
+                self.handle(myself.clone(), message, state).await?;
+            }
+            Ok(())
+        }
+    }
+    /// Handle a batch of incoming messages from the event processing loop, see
+    /// [Actor::MAX_BATCH_SIZE]. Unhandled panickes will be captured and sent to the
+    /// supervisor(s)
+    ///
+    /// Defaults to handling the messages one at a time with [Actor::handle], stopping at
+    /// the first error.
+    ///
+    /// * `myself` - A handle to the [ActorCell] representing this actor
+    /// * `messages` - The messages to process, in the order they were received
+    /// * `state` - A mutable reference to the internal actor's state
+    #[cfg(feature = "async-trait")]
+    async fn handle_batch(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        messages: Vec<Self::Msg>,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        for message in messages {
+            self.handle(myself.clone(), message, state).await?;
+        }
+        Ok(())
+    }
+
     /// Handle the remote incoming message from the event processing loop. Unhandled panickes will be
     /// captured and sent to the supervisor(s)
     ///
@@ -475,6 +628,55 @@ impl ActorLoopResult {
     }
 }
 
+/// Options to configure an actor with when spawning it with [ActorRuntime::spawn_with_options]
+#[derive(bon::Builder)]
+pub struct SpawnOptions<TMsg>
+where
+    TMsg: Message,
+{
+    /// A name to give the actor. Useful for global referencing or debug printing
+    ///
+    /// Default is [None]
+    pub name: Option<ActorName>,
+    /// The [ActorCell] which is to become the supervisor (parent) of this actor
+    ///
+    /// Default is [None]
+    pub supervisor: Option<ActorCell>,
+    /// The chain of [MessageInterceptor]s wrapping the actor's message handling, see
+    /// [crate::actor::interceptor]
+    ///
+    /// Default is no interceptors
+    #[builder(default)]
+    pub interceptors: Vec<Arc<dyn MessageInterceptor<TMsg>>>,
+    /// The stats layer to report the actor's statistics to, see [crate::actor::stats]
+    ///
+    /// Default is [None]
+    pub stats: Option<Arc<dyn ActorStatsLayer>>,
+    /// The durable mailbox to log the actor's messages to, which requires the message type
+    /// to be serializable, see [crate::actor::durable_mailbox]
+    ///
+    /// Default is [None]
+    #[cfg(feature = "cluster")]
+    pub durable_mailbox: Option<DurableMailboxConfig>,
+}
+
+impl<TMsg> Debug for SpawnOptions<TMsg>
+where
+    TMsg: Message,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("SpawnOptions");
+        debug
+            .field("name", &self.name)
+            .field("supervisor", &self.supervisor)
+            .field("interceptors", &self.interceptors.len())
+            .field("stats", &self.stats.is_some());
+        #[cfg(feature = "cluster")]
+        debug.field("durable_mailbox", &self.durable_mailbox);
+        debug.finish()
+    }
+}
+
 /// [ActorRuntime] is a struct which represents the processing actor.
 ///
 ///  This struct is consumed by the `start` operation, but results in an
@@ -488,6 +690,7 @@ where
     handler: TActor,
     id: ActorId,
     name: Option<String>,
+    interceptors: Vec<Arc<dyn MessageInterceptor<TActor::Msg>>>,
 }
 
 impl<TActor: Actor> Debug for ActorRuntime<TActor> {
@@ -553,6 +756,63 @@ where
         result
     }
 
+    /// Spawn an actor with the provided [SpawnOptions], automatically starting the actor
+    ///
+    /// * `handler` The [Actor] defining the logic for this actor
+    /// * `startup_args`: Arguments passed to the `pre_start` call of the [Actor] to facilitate startup and
+    ///   initial state creation
+    /// * `options`: The [SpawnOptions] to configure the actor with
+    ///
+    /// Returns a [Ok((ActorRef, JoinHandle<()>))] upon successful start, denoting the actor reference
+    /// along with the join handle which will complete when the actor terminates. Returns [Err(SpawnErr)] if
+    /// the actor failed to start
+    pub async fn spawn_with_options(
+        handler: TActor,
+        startup_args: TActor::Arguments,
+        options: SpawnOptions<TActor::Msg>,
+    ) -> Result<(ActorRef<TActor::Msg>, JoinHandle<()>), SpawnErr> {
+        let SpawnOptions {
+            name,
+            supervisor,
+            interceptors,
+            stats,
+            #[cfg(feature = "cluster")]
+            durable_mailbox,
+        } = options;
+        #[cfg(feature = "cluster")]
+        let durable_mailbox = match durable_mailbox {
+            Some(_) if !TActor::Msg::serializable() => {
+                return Err(SpawnErr::StartupFailed(From::from(
+                    "A durable mailbox requires a serializable message type",
+                )));
+            }
+            Some(config) => Some(
+                DurableMailbox::open(&config)
+                    .map_err(|err| SpawnErr::StartupFailed(Box::new(err)))?,
+            ),
+            None => None,
+        };
+        let (mut actor, ports) = Self::new(name, handler)?;
+        actor.interceptors = interceptors;
+        if let Some(stats) = stats {
+            actor.actor_ref.inner.inner.stats.set_layer(stats);
+        }
+        #[cfg(feature = "cluster")]
+        if let Some((durable_mailbox, replayed)) = durable_mailbox {
+            actor
+                .actor_ref
+                .inner
+                .inner
+                .replay_durable::<TActor::Msg>(Arc::new(durable_mailbox), replayed);
+        }
+        let aref = actor.actor_ref.clone();
+        let result = actor.start(ports, startup_args, supervisor).await;
+        if result.is_err() {
+            aref.set_status(ActorStatus::Stopped);
+        }
+        result
+    }
+
     /// Spawn an actor instantly, not waiting on the actor's `pre_start` routine. This is helpful
     /// for actors where you want access to the send messages into the actor's message queue
     /// without waiting on an asynchronous context.
@@ -677,6 +937,7 @@ where
                     handler,
                     id,
                     name,
+                    interceptors: vec![],
                 },
                 ports,
             );
@@ -704,6 +965,7 @@ where
                 handler,
                 id,
                 name,
+                interceptors: vec![],
             },
             ports,
         ))
@@ -734,10 +996,11 @@ where
         }
 
         let Self {
-            handler,
+            mut handler,
             actor_ref,
             id,
             name,
+            interceptors,
         } = self;
 
         actor_ref.set_status(ActorStatus::Starting);
@@ -758,8 +1021,16 @@ where
         // run the processing loop, backgrounding the work
         let handle = crate::concurrency::spawn_named(actor_ref.get_name().as_deref(), async move {
             let myself = actor_ref.clone();
-            let evt = match Self::processing_loop(ports, &mut state, &handler, actor_ref, id, name)
-                .await
+            let evt = match Self::processing_loop(
+                ports,
+                &mut state,
+                &mut handler,
+                &interceptors,
+                actor_ref,
+                id,
+                name,
+            )
+            .await
             {
                 Ok(exit_reason) => SupervisionEvent::ActorTerminated(
                     myself.get_cell(),
@@ -794,11 +1065,12 @@ where
         Ok((myself_ret, handle))
     }
 
-    #[tracing::instrument(name = "Actor", skip(ports, state, handler, myself, _id, _name), fields(id = _id.to_string(), name = _name))]
+    #[tracing::instrument(name = "Actor", skip(ports, state, handler, interceptors, myself, _id, _name), fields(id = _id.to_string(), name = _name))]
     async fn processing_loop(
         mut ports: ActorPortSet,
         state: &mut TActor::State,
-        handler: &TActor,
+        handler: &mut TActor,
+        interceptors: &[Arc<dyn MessageInterceptor<TActor::Msg>>],
         myself: ActorRef<TActor::Msg>,
         _id: ActorId,
         _name: Option<String>,
@@ -821,12 +1093,18 @@ where
                     should_exit,
                     exit_reason,
                     was_killed,
-                } = Self::process_message(myself.clone(), state, handler, &mut ports)
-                    .await
-                    .map_err(ActorErr::Failed)?;
+                } = Self::process_message(
+                    myself.clone(),
+                    state,
+                    &mut *handler,
+                    interceptors,
+                    &mut ports,
+                )
+                .await
+                .map_err(ActorErr::Failed)?;
                 // processing loop exit
                 if should_exit {
-                    return Ok((state, exit_reason, was_killed));
+                    return Ok((state, handler, exit_reason, was_killed));
                 }
             }
         };
@@ -839,7 +1117,7 @@ where
         // set status to stopping
         myself_clone.set_status(ActorStatus::Stopping);
 
-        let (exit_state, exit_reason, was_killed) = loop_done??;
+        let (exit_state, handler, exit_reason, was_killed) = loop_done??;
 
         // if we didn't exit in error mode, call `post_stop`
         if !was_killed {
@@ -857,6 +1135,7 @@ where
     /// * `myself` - The current [ActorRef]
     /// * `state` - The current [Actor::State] object
     /// * `handler` - Pointer to the [Actor] definition
+    /// * `interceptors` - The [MessageInterceptor]s wrapping the handling of messages
     /// * `ports` - The mutable [ActorPortSet] which are the message ports for this actor
     ///
     /// Returns a tuple of the next [Actor::State] and a flag to denote if the processing
@@ -864,7 +1143,8 @@ where
     async fn process_message(
         myself: ActorRef<TActor::Msg>,
         state: &mut TActor::State,
-        handler: &TActor,
+        handler: &mut TActor,
+        interceptors: &[Arc<dyn MessageInterceptor<TActor::Msg>>],
         ports: &mut ActorPortSet,
     ) -> Result<ActorLoopResult, ActorProcessingErr> {
         match ports.listen_in_priority().await {
@@ -889,6 +1169,7 @@ where
                     Ok(ActorLoopResult::stop(exit_reason))
                 }
                 actor_cell::ActorPortMessage::Supervision(supervision) => {
+                    ports.stats.supervision_event(&myself, &supervision);
                     let future = Self::handle_supervision_message(
                         myself.clone(),
                         state,
@@ -903,10 +1184,47 @@ where
                         }
                     }
                 }
-                actor_cell::ActorPortMessage::Message(MuxedMessage::Message(msg)) => {
-                    let future = Self::handle_message(myself.clone(), state, handler, msg);
-                    match ports.run_with_signal(future).await {
-                        Ok(Ok(())) => Ok(ActorLoopResult::ok()),
+                actor_cell::ActorPortMessage::Message(MuxedMessage::Message(mut msg)) => {
+                    if msg.is_expired() {
+                        msg.acknowledge();
+                        // the caller has given up on this request, don't bother handling it
+                        tracing::debug!(
+                            "Actor {:?} dropped a request which passed its deadline",
+                            myself.get_id()
+                        );
+                        ports.stats.message_expired(&myself);
+                        return Ok(ActorLoopResult::ok());
+                    }
+                    if TActor::MAX_BATCH_SIZE > 1 && myself.get_id().is_local() {
+                        return Self::process_batch(
+                            myself,
+                            state,
+                            handler,
+                            interceptors,
+                            ports,
+                            msg,
+                        )
+                        .await;
+                    }
+                    // a message of a durable mailbox is only acknowledged once it's been
+                    // handled successfully, otherwise it's replayed when the actor restarts
+                    #[cfg(feature = "cluster")]
+                    let receipt = msg.receipt.take();
+                    let start = crate::concurrency::Instant::now();
+                    let future =
+                        Self::handle_message(myself.clone(), state, handler, interceptors, msg);
+                    let result = ports.run_with_signal(future).await;
+                    if result.is_ok() {
+                        ports.stats.message_handled(&myself, start.elapsed());
+                    }
+                    match result {
+                        Ok(Ok(())) => {
+                            #[cfg(feature = "cluster")]
+                            if let Some(receipt) = receipt {
+                                receipt.ack();
+                            }
+                            Ok(ActorLoopResult::ok())
+                        }
                         Ok(Err(internal_err)) => Err(internal_err),
                         Err(signal) => {
                             Ok(ActorLoopResult::signal(Self::handle_signal(myself, signal)))
@@ -918,6 +1236,38 @@ where
                     // all the messages and we want the actor to die now
                     Ok(ActorLoopResult::stop(Some("Drained".to_string())))
                 }
+                actor_cell::ActorPortMessage::Message(MuxedMessage::Upgrade(
+                    new_handler,
+                    reply,
+                )) => {
+                    let Ok(new_handler) = new_handler.downcast::<TActor>() else {
+                        let _ =
+                            reply.send(Err(RactorErr::Messaging(MessagingErr::InvalidActorType)));
+                        return Ok(ActorLoopResult::ok());
+                    };
+                    let cell = myself.get_cell();
+                    cell.inner
+                        .transition_status(ActorStatus::Running, ActorStatus::Upgrading);
+                    let future = Self::do_code_change(myself.clone(), &new_handler, state);
+                    match ports.run_with_signal(future).await {
+                        Ok(Ok(())) => {
+                            *handler = *new_handler;
+                            cell.inner
+                                .transition_status(ActorStatus::Upgrading, ActorStatus::Running);
+                            let _ = reply.send(Ok(()));
+                            Ok(ActorLoopResult::ok())
+                        }
+                        Ok(Err(internal_err)) => {
+                            let _ = reply.send(Err(RactorErr::Actor(ActorErr::Failed(
+                                From::from(internal_err.to_string()),
+                            ))));
+                            Err(internal_err)
+                        }
+                        Err(signal) => {
+                            Ok(ActorLoopResult::signal(Self::handle_signal(myself, signal)))
+                        }
+                    }
+                }
             },
             Err(MessagingErr::ChannelClosed) => {
                 // one of the channels is closed, this means
@@ -936,7 +1286,11 @@ where
                     Signal::Kill,
                 )))
             }
-            Err(MessagingErr::SendErr(_)) => {
+            Err(
+                MessagingErr::SendErr(_)
+                | MessagingErr::MailboxFull(_)
+                | MessagingErr::StashFull(_),
+            ) => {
                 // not possible. Treat like a channel closed
                 Ok(ActorLoopResult::signal(Self::handle_signal(
                     myself,
@@ -946,10 +1300,120 @@ where
         }
     }
 
+    /// Gather a batch of messages, starting with `first` and followed by the ones waiting in
+    /// the mailbox (lingering up to [Actor::BATCH_LINGER] for more), and handle them
+    /// together with [Actor::handle_batch]
+    ///
+    /// * `first` - The message which starts the batch
+    async fn process_batch(
+        myself: ActorRef<TActor::Msg>,
+        state: &mut TActor::State,
+        handler: &mut TActor,
+        interceptors: &[Arc<dyn MessageInterceptor<TActor::Msg>>],
+        ports: &mut ActorPortSet,
+        first: crate::message::BoxedMessage,
+    ) -> Result<ActorLoopResult, ActorProcessingErr> {
+        let deadline =
+            TActor::BATCH_LINGER.map(|linger| crate::concurrency::Instant::now() + linger);
+        let mut batch = vec![first];
+        while batch.len() < TActor::MAX_BATCH_SIZE {
+            let next = match deadline {
+                Some(deadline) => ports.recv_batched_until(deadline).await,
+                None => Ok(ports.try_recv_batched()),
+            };
+            let mut msg = match next {
+                Ok(Some(msg)) => msg,
+                Ok(None) => break,
+                // the batch isn't handled, like a batch which is interrupted by the signal
+                Err(signal) => {
+                    return Ok(ActorLoopResult::signal(Self::handle_signal(myself, signal)));
+                }
+            };
+            if msg.is_expired() {
+                msg.acknowledge();
+                ports.stats.message_expired(&myself);
+                continue;
+            }
+            batch.push(msg);
+        }
+
+        #[cfg(feature = "cluster")]
+        let receipts = batch
+            .iter_mut()
+            .filter_map(|msg| msg.receipt.take())
+            .collect::<Vec<_>>();
+        let count = batch.len() as u32;
+        let start = crate::concurrency::Instant::now();
+        let future = Self::handle_batch(myself.clone(), state, handler, interceptors, batch);
+        let result = ports.run_with_signal(future).await;
+        if result.is_ok() {
+            // every message is accounted for with its share of the batch's handling time
+            let elapsed = start.elapsed() / count;
+            for _ in 0..count {
+                ports.stats.message_handled(&myself, elapsed);
+            }
+        }
+        match result {
+            Ok(Ok(())) => {
+                #[cfg(feature = "cluster")]
+                for receipt in receipts {
+                    receipt.ack();
+                }
+                Ok(ActorLoopResult::ok())
+            }
+            Ok(Err(internal_err)) => Err(internal_err),
+            Err(signal) => Ok(ActorLoopResult::signal(Self::handle_signal(myself, signal))),
+        }
+    }
+
+    async fn handle_batch(
+        myself: ActorRef<TActor::Msg>,
+        state: &mut TActor::State,
+        handler: &TActor,
+        interceptors: &[Arc<dyn MessageInterceptor<TActor::Msg>>],
+        batch: Vec<crate::message::BoxedMessage>,
+    ) -> Result<(), ActorProcessingErr> {
+        let cell = myself.get_cell();
+        let mut messages = Vec::with_capacity(batch.len());
+        'batch: for msg in batch {
+            // An error here will bubble up to terminate the actor
+            let mut typed_msg = TActor::Msg::from_boxed(msg)?;
+            for interceptor in interceptors {
+                match interceptor.before_handle(&cell, typed_msg) {
+                    Some(msg) => typed_msg = msg,
+                    None => {
+                        tracing::trace!(
+                            "Actor {:?} message rejected by an interceptor",
+                            cell.get_id()
+                        );
+                        continue 'batch;
+                    }
+                }
+            }
+            messages.push(typed_msg);
+        }
+        if messages.is_empty() {
+            return Ok(());
+        }
+
+        let count = messages.len() as u32;
+        let start = crate::concurrency::Instant::now();
+        let result = handler.handle_batch(myself, messages, state).await;
+        // every message is accounted for with its share of the batch's handling time
+        let elapsed = start.elapsed() / count;
+        for _ in 0..count {
+            for interceptor in interceptors.iter().rev() {
+                interceptor.after_handle(&cell, &result, elapsed);
+            }
+        }
+        result
+    }
+
     async fn handle_message(
         myself: ActorRef<TActor::Msg>,
         state: &mut TActor::State,
         handler: &TActor,
+        interceptors: &[Arc<dyn MessageInterceptor<TActor::Msg>>],
         mut msg: crate::message::BoxedMessage,
     ) -> Result<(), ActorProcessingErr> {
         // panic in order to kill the actor
@@ -980,15 +1444,59 @@ where
         let current_span_when_message_was_sent = msg.span.take();
 
         // An error here will bubble up to terminate the actor
-        let typed_msg = TActor::Msg::from_boxed(msg)?;
+        let mut typed_msg = TActor::Msg::from_boxed(msg)?;
 
-        if let Some(span) = current_span_when_message_was_sent {
-            handler
-                .handle(myself, typed_msg, state)
-                .instrument(span)
-                .await
+        if interceptors.is_empty() {
+            return Self::handle_typed_message(
+                myself,
+                state,
+                handler,
+                typed_msg,
+                current_span_when_message_was_sent,
+            )
+            .await;
+        }
+
+        let cell = myself.get_cell();
+        for interceptor in interceptors {
+            match interceptor.before_handle(&cell, typed_msg) {
+                Some(msg) => typed_msg = msg,
+                None => {
+                    tracing::trace!(
+                        "Actor {:?} message rejected by an interceptor",
+                        cell.get_id()
+                    );
+                    return Ok(());
+                }
+            }
+        }
+        let start = crate::concurrency::Instant::now();
+        let result = Self::handle_typed_message(
+            myself,
+            state,
+            handler,
+            typed_msg,
+            current_span_when_message_was_sent,
+        )
+        .await;
+        let elapsed = start.elapsed();
+        for interceptor in interceptors.iter().rev() {
+            interceptor.after_handle(&cell, &result, elapsed);
+        }
+        result
+    }
+
+    async fn handle_typed_message(
+        myself: ActorRef<TActor::Msg>,
+        state: &mut TActor::State,
+        handler: &TActor,
+        msg: TActor::Msg,
+        span: Option<tracing::Span>,
+    ) -> Result<(), ActorProcessingErr> {
+        if let Some(span) = span {
+            handler.handle(myself, msg, state).instrument(span).await
         } else {
-            handler.handle(myself, typed_msg, state).await
+            handler.handle(myself, msg, state).await
         }
     }
 
@@ -1032,6 +1540,17 @@ where
             .map_err(|err| ActorErr::Failed(get_panic_string(err)))
     }
 
+    async fn do_code_change(
+        myself: ActorRef<TActor::Msg>,
+        handler: &TActor,
+        state: &mut TActor::State,
+    ) -> Result<(), ActorProcessingErr> {
+        let future = handler.code_change(myself, state);
+        futures::FutureExt::catch_unwind(AssertUnwindSafe(future))
+            .await
+            .map_err(get_panic_string)?
+    }
+
     async fn do_post_stop(
         myself: ActorRef<TActor::Msg>,
         handler: &TActor,
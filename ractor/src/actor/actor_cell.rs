@@ -14,16 +14,27 @@ use std::sync::Arc;
 
 #[cfg(feature = "async-std")]
 use futures::FutureExt;
+use tokio::sync::mpsc::error::TryRecvError as MpscTryRecvError;
+use tokio::sync::oneshot::error::TryRecvError as OneshotTryRecvError;
 
 use super::actor_properties::MuxedMessage;
 use super::messages::Signal;
 use super::messages::StopMessage;
 use super::SupervisionEvent;
 use crate::actor::actor_properties::ActorProperties;
+use crate::actor::mailbox::BoundedMailbox;
+use crate::actor::priority::MessagePriority;
+use crate::actor::priority::PriorityLaneReceivers;
+use crate::actor::stash::MessageStash;
+use crate::actor::stats::ActorStats;
+use crate::actor::stats::ActorStatsCollector;
+use crate::actor::timers::TimerKey;
+use crate::concurrency::Instant;
 use crate::concurrency::JoinHandle;
 use crate::concurrency::MpscUnboundedReceiver as InputPortReceiver;
 use crate::concurrency::OneshotReceiver;
 use crate::errors::MessagingErr;
+use crate::message::BoxedMessage;
 #[cfg(feature = "cluster")]
 use crate::message::SerializedMessage;
 use crate::Actor;
@@ -44,7 +55,7 @@ pub enum ActorStatus {
     Starting = 1u8,
     /// Executing (or waiting on messages)
     Running = 2u8,
-    /// Upgrading
+    /// Upgrading its handler, see [ActorCell::upgrade]
     Upgrading = 3u8,
     /// Draining
     Draining = 4u8,
@@ -69,8 +80,17 @@ pub(crate) struct ActorPortSet {
     pub(crate) stop_rx: OneshotReceiver<StopMessage>,
     /// The inner supervisor port
     pub(crate) supervisor_rx: InputPortReceiver<SupervisionEvent>,
-    /// The inner message port
-    pub(crate) message_rx: InputPortReceiver<MuxedMessage>,
+    /// The inner message port, split into priority lanes
+    pub(crate) message_rx: PriorityLaneReceivers,
+    /// The accounting for the message port, if it's bounded
+    pub(crate) mailbox: Option<Arc<BoundedMailbox>>,
+    /// The actor's message stash
+    pub(crate) stash: Arc<MessageStash>,
+    /// The actor's statistics
+    pub(crate) stats: Arc<ActorStatsCollector>,
+    /// A drain marker, upgrade request, stop request or supervision event which was received
+    /// while gathering a batch of messages, and is to be handled after the batch
+    pub(crate) deferred: Option<ActorPortMessage>,
 }
 
 impl Drop for ActorPortSet {
@@ -81,11 +101,16 @@ impl Drop for ActorPortSet {
         self.stop_rx.close();
         self.supervisor_rx.close();
         self.message_rx.close();
+        if let Some(mailbox) = &self.mailbox {
+            // wake up any senders waiting for room in the mailbox
+            mailbox.close();
+        }
+        self.stash.clear();
 
         while self.signal_rx.try_recv().is_ok() {}
         while self.stop_rx.try_recv().is_ok() {}
         while self.supervisor_rx.try_recv().is_ok() {}
-        while self.message_rx.try_recv().is_ok() {}
+        self.message_rx.flush();
     }
 }
 
@@ -147,17 +172,179 @@ impl ActorPortSet {
         }
     }
 
+    /// Receive the next message off the highest priority message lane, releasing its slot in the
+    /// bounded mailbox (if any) and skipping over messages which were displaced
+    /// by [crate::MailboxOverflowPolicy::DropOldest].
+    async fn recv_message(
+        message_rx: &mut PriorityLaneReceivers,
+        mailbox: Option<&BoundedMailbox>,
+        stats: &ActorStatsCollector,
+    ) -> Option<MuxedMessage> {
+        loop {
+            let (priority, message) = message_rx.recv().await?;
+            if let Some(message) = Self::dequeued(priority, message, mailbox, stats) {
+                return Some(message);
+            }
+        }
+    }
+
+    /// Account for a message taken off the message lanes, returning [None] if it was displaced
+    /// by [crate::MailboxOverflowPolicy::DropOldest] and is to be skipped
+    fn dequeued(
+        priority: MessagePriority,
+        mut message: MuxedMessage,
+        mailbox: Option<&BoundedMailbox>,
+        stats: &ActorStatsCollector,
+    ) -> Option<MuxedMessage> {
+        if let MuxedMessage::Message(boxed) = &mut message {
+            stats.message_dequeued();
+            if let Some(mailbox) = mailbox {
+                if mailbox.dequeue(priority) {
+                    boxed.acknowledge();
+                    return None;
+                }
+            }
+        }
+        Some(message)
+    }
+
+    /// Take the next message of a batch which is already waiting, from the unstashed messages
+    /// or the message lanes. A drain marker or upgrade request ends the batch, and is deferred
+    /// until after it.
+    pub(crate) fn try_recv_batched(&mut self) -> Option<BoxedMessage> {
+        if self.deferred.is_some() {
+            return None;
+        }
+        if let Some(message) = self.stash.pop_unstashed() {
+            return Some(message);
+        }
+        loop {
+            let (priority, message) = self.message_rx.try_recv()?;
+            match Self::dequeued(priority, message, self.mailbox.as_deref(), &self.stats) {
+                Some(MuxedMessage::Message(message)) => return Some(message),
+                Some(other) => {
+                    self.deferred = Some(ActorPortMessage::Message(other));
+                    return None;
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// Wait for the next message of a batch, up to a deadline. See [ActorPortSet::try_recv_batched].
+    ///
+    /// The control ports are listened to while waiting: a stop request or supervision event
+    /// ends the batch, and is deferred until after it.
+    ///
+    /// Returns [Err(Signal)] if a signal was received while waiting, which is to be handled
+    /// instead of the batch.
+    pub(crate) async fn recv_batched_until(
+        &mut self,
+        deadline: Instant,
+    ) -> Result<Option<BoxedMessage>, Signal> {
+        if let Some(message) = self.try_recv_batched() {
+            return Ok(Some(message));
+        }
+        if self.deferred.is_some() {
+            return Ok(None);
+        }
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let next = crate::concurrency::timeout(
+            remaining,
+            Self::recv_message(&mut self.message_rx, self.mailbox.as_deref(), &self.stats),
+        );
+        // a closed control port is treated like a kill signal, as it is once the batch is done
+        #[cfg(feature = "async-std")]
+        let received = crate::concurrency::select! {
+            signal = (&mut self.signal_rx).fuse() => {
+                return Err(signal.unwrap_or(Signal::Kill));
+            }
+            stop = (&mut self.stop_rx).fuse() => {
+                ActorPortMessage::Stop(stop.map_err(|_| Signal::Kill)?)
+            }
+            supervision = self.supervisor_rx.recv().fuse() => {
+                ActorPortMessage::Supervision(supervision.ok_or(Signal::Kill)?)
+            }
+            message = next.fuse() => match message {
+                Ok(Some(message)) => ActorPortMessage::Message(message),
+                Ok(None) | Err(_) => return Ok(None),
+            }
+        };
+        #[cfg(not(feature = "async-std"))]
+        let received = crate::concurrency::select! {
+            signal = &mut self.signal_rx => {
+                return Err(signal.unwrap_or(Signal::Kill));
+            }
+            stop = &mut self.stop_rx => {
+                ActorPortMessage::Stop(stop.map_err(|_| Signal::Kill)?)
+            }
+            supervision = self.supervisor_rx.recv() => {
+                ActorPortMessage::Supervision(supervision.ok_or(Signal::Kill)?)
+            }
+            message = next => match message {
+                Ok(Some(message)) => ActorPortMessage::Message(message),
+                Ok(None) | Err(_) => return Ok(None),
+            }
+        };
+        match received {
+            ActorPortMessage::Message(MuxedMessage::Message(message)) => Ok(Some(message)),
+            other => {
+                self.deferred = Some(other);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Check the control ports (signal, stop, and supervision) for a pending message
+    /// without waiting
+    fn try_listen_control(&mut self) -> Result<Option<ActorPortMessage>, MessagingErr<()>> {
+        match self.signal_rx.try_recv() {
+            Ok(signal) => return Ok(Some(ActorPortMessage::Signal(signal))),
+            Err(OneshotTryRecvError::Closed) => return Err(MessagingErr::ChannelClosed),
+            Err(OneshotTryRecvError::Empty) => {}
+        }
+        match self.stop_rx.try_recv() {
+            Ok(stop) => return Ok(Some(ActorPortMessage::Stop(stop))),
+            Err(OneshotTryRecvError::Closed) => return Err(MessagingErr::ChannelClosed),
+            Err(OneshotTryRecvError::Empty) => {}
+        }
+        match self.supervisor_rx.try_recv() {
+            Ok(supervision) => Ok(Some(ActorPortMessage::Supervision(supervision))),
+            Err(MpscTryRecvError::Disconnected) => Err(MessagingErr::ChannelClosed),
+            Err(MpscTryRecvError::Empty) => Ok(None),
+        }
+    }
+
     /// List to the input ports in priority. The priority of listening for messages is
     /// 1. Signal port
     /// 2. Stop port
     /// 3. Supervision message port
-    /// 4. General message port
+    /// 4. Unstashed messages
+    /// 5. A message deferred until after a batch of messages
+    /// 6. General message port, by [MessagePriority]
     ///
     /// Returns [Ok(ActorPortMessage)] on a successful message reception, [MessagingErr]
     /// in the event any of the channels is closed.
     pub(crate) async fn listen_in_priority(
         &mut self,
     ) -> Result<ActorPortMessage, MessagingErr<()>> {
+        if self.stash.has_unstashed() {
+            if let Some(control) = self.try_listen_control()? {
+                return Ok(control);
+            }
+            if let Some(message) = self.stash.pop_unstashed() {
+                return Ok(ActorPortMessage::Message(MuxedMessage::Message(message)));
+            }
+        }
+        if self.deferred.is_some() {
+            if let Some(control) = self.try_listen_control()? {
+                return Ok(control);
+            }
+            if let Some(message) = self.deferred.take() {
+                return Ok(message);
+            }
+        }
+
         #[cfg(feature = "async-std")]
         {
             crate::concurrency::select! {
@@ -170,7 +357,7 @@ impl ActorPortSet {
                 supervision = self.supervisor_rx.recv().fuse() => {
                     supervision.map(ActorPortMessage::Supervision).ok_or(MessagingErr::ChannelClosed)
                 }
-                message = self.message_rx.recv().fuse() => {
+                message = Self::recv_message(&mut self.message_rx, self.mailbox.as_deref(), &self.stats).fuse() => {
                     message.map(ActorPortMessage::Message).ok_or(MessagingErr::ChannelClosed)
                 }
             }
@@ -187,7 +374,7 @@ impl ActorPortSet {
                 supervision = self.supervisor_rx.recv() => {
                     supervision.map(ActorPortMessage::Supervision).ok_or(MessagingErr::ChannelClosed)
                 }
-                message = self.message_rx.recv() => {
+                message = Self::recv_message(&mut self.message_rx, self.mailbox.as_deref(), &self.stats) => {
                     message.map(ActorPortMessage::Message).ok_or(MessagingErr::ChannelClosed)
                 }
             }
@@ -254,6 +441,9 @@ impl ActorCell {
             crate::registry::register(r_name, cell.clone())?;
         }
 
+        let mailbox = cell.inner.mailbox.clone();
+        let stash = cell.inner.stash.clone();
+        let stats = cell.inner.stats.clone();
         Ok((
             cell,
             ActorPortSet {
@@ -261,6 +451,10 @@ impl ActorCell {
                 stop_rx: rx2,
                 supervisor_rx: rx3,
                 message_rx: rx4,
+                mailbox,
+                stash,
+                stats,
+                deferred: None,
             },
         ))
     }
@@ -286,6 +480,9 @@ impl ActorCell {
         // if let Some(r_name) = name {
         //     crate::registry::register(r_name, cell.clone())?;
         // }
+        let mailbox = cell.inner.mailbox.clone();
+        let stash = cell.inner.stash.clone();
+        let stats = cell.inner.stats.clone();
         Ok((
             cell,
             ActorPortSet {
@@ -293,6 +490,10 @@ impl ActorCell {
                 stop_rx: rx2,
                 supervisor_rx: rx3,
                 message_rx: rx4,
+                mailbox,
+                stash,
+                stats,
+                deferred: None,
             },
         ))
     }
@@ -338,23 +539,28 @@ impl ActorCell {
                 // unregistry from the PID registry
                 crate::registry::pid_registry::unregister_pid(self.get_id());
             }
-            // If it's enrolled in the registry, remove it
+            // If it's enrolled in the registry, remove it along with any aliases
             if let Some(name) = self.get_name() {
-                crate::registry::unregister(name);
+                crate::registry::unregister(name, self.get_id());
             }
+            crate::registry::unregister_aliases(self.get_id());
+            // Cancel the timers bound to the actor
+            self.inner.timers.cancel_all();
             // Leave all + stop monitoring pg groups (if any)
             crate::pg::demonitor_all(self.get_id());
+            crate::registry::demonitor_all_names(self.get_id());
             crate::pg::leave_all(self.get_id());
         }
 
+        self.inner.set_status(status);
+
         // Fix for #254. We should only notify the stop listener AFTER post_stop
-        // has executed, which is when the state gets set to `Stopped`.
+        // has executed, which is when the state gets set to `Stopped`. The status is
+        // stored first, so that woken waiters observe it.
         if status == ActorStatus::Stopped {
             // notify whoever might be waiting on the stop signal
             self.inner.notify_stop_listener();
         }
-
-        self.inner.set_status(status)
     }
 
     /// Terminate this [super::Actor] and all it's children
@@ -521,7 +727,145 @@ impl ActorCell {
     where
         TMessage: Message,
     {
-        self.inner.send_message::<TMessage>(message)
+        self.inner
+            .send_message::<TMessage>(message, MessagePriority::Normal, None, false)
+    }
+
+    /// Send a strongly-typed message on the given priority lane. The actor handles messages
+    /// of a higher [MessagePriority] ahead of any lower priority messages waiting in its mailbox.
+    ///
+    /// * `message` - The message to send
+    /// * `priority` - The [MessagePriority] to send the message with
+    ///
+    /// Returns [Ok(())] on successful message send, [Err(MessagingErr)] otherwise
+    pub fn send_message_with_priority<TMessage>(
+        &self,
+        message: TMessage,
+        priority: MessagePriority,
+    ) -> Result<(), MessagingErr<TMessage>>
+    where
+        TMessage: Message,
+    {
+        self.inner
+            .send_message::<TMessage>(message, priority, None, false)
+    }
+
+    /// Send a request carrying an [crate::RpcReplyPort] which expires at `deadline`. If the
+    /// deadline passes before the actor gets to the request, it's dropped without being handled.
+    ///
+    /// * `message` - The message to send
+    /// * `deadline` - The optional deadline of the request
+    ///
+    /// Returns [Ok(())] on successful message send, [Err(MessagingErr)] otherwise
+    pub(crate) fn send_request<TMessage>(
+        &self,
+        message: TMessage,
+        deadline: Option<crate::concurrency::Instant>,
+    ) -> Result<(), MessagingErr<TMessage>>
+    where
+        TMessage: Message,
+    {
+        self.inner
+            .send_message::<TMessage>(message, MessagePriority::Normal, deadline, true)
+    }
+
+    /// Send a strongly-typed message, waiting asynchronously for room in the actor's
+    /// mailbox if it's bounded with [crate::MailboxOverflowPolicy::Block]. For any other
+    /// mailbox this behaves exactly like [ActorCell::send_message].
+    ///
+    /// * `message` - The message to send
+    ///
+    /// Returns [Ok(())] on successful message send, [Err(MessagingErr)] otherwise
+    pub async fn send_message_wait<TMessage>(
+        &self,
+        message: TMessage,
+    ) -> Result<(), MessagingErr<TMessage>>
+    where
+        TMessage: Message,
+    {
+        self.inner
+            .send_message_wait::<TMessage>(message, MessagePriority::Normal)
+            .await
+    }
+
+    /// Send a strongly-typed message on the given priority lane, waiting asynchronously for
+    /// room in the actor's mailbox if it's bounded with [crate::MailboxOverflowPolicy::Block].
+    /// See [ActorCell::send_message_wait].
+    ///
+    /// * `message` - The message to send
+    /// * `priority` - The [MessagePriority] to send the message with
+    ///
+    /// Returns [Ok(())] on successful message send, [Err(MessagingErr)] otherwise
+    pub async fn send_message_wait_with_priority<TMessage>(
+        &self,
+        message: TMessage,
+        priority: MessagePriority,
+    ) -> Result<(), MessagingErr<TMessage>>
+    where
+        TMessage: Message,
+    {
+        self.inner
+            .send_message_wait::<TMessage>(message, priority)
+            .await
+    }
+
+    /// Replay all of this actor's stashed messages, in the order they were stashed.
+    ///
+    /// The messages are handled ahead of any messages waiting in the actor's mailbox, once the
+    /// current message has been handled. See [ActorRef::stash].
+    pub fn unstash_all(&self) {
+        self.inner.stash.unstash_all()
+    }
+
+    /// Retrieve a snapshot of this actor's statistics, see [crate::actor::stats]
+    pub fn get_stats(&self) -> ActorStats {
+        self.inner.stats.snapshot()
+    }
+
+    /// Retrieve the keys of this actor's active keyed timers, sorted. See
+    /// [crate::actor::timers].
+    pub fn get_timers(&self) -> Vec<TimerKey> {
+        self.inner.timers.keys()
+    }
+
+    /// Cancel the active timer of this actor with the provided key
+    ///
+    /// Returns [true] if a timer with the key was active, [false] otherwise
+    pub fn cancel_timer(&self, key: &str) -> bool {
+        self.inner.timers.cancel(key)
+    }
+
+    /// Retrieve the number of messages currently held in this actor's stash
+    pub fn get_stash_len(&self) -> usize {
+        self.inner.stash.len()
+    }
+
+    /// Hot-upgrade the actor's handler, replacing it with `handler`. The upgrade is applied
+    /// in order with the actor's messages, whatever their [MessagePriority]: messages already
+    /// in the mailbox are handled by the old handler, and all subsequent messages by the new
+    /// one. Before the swap,
+    /// [Actor::code_change] is called on the new handler to migrate the actor's state.
+    ///
+    /// Only the same handler type can be installed, since the actor's processing loop is
+    /// typed by its handler and [Actor::State]. An upgrade swaps in a reconfigured instance of
+    /// that type (e.g. with new fields) and migrates the existing state value in place;
+    /// switching to a different handler type or state type isn't supported.
+    ///
+    /// The actor keeps its [ActorId], name registration, links, and mailbox across the upgrade.
+    ///
+    /// * `handler` - The new handler. It must be the same type as the actor's current handler
+    ///
+    /// Returns [Ok(())] once the upgrade is applied. Returns [MessagingErr::InvalidActorType] if
+    /// `handler` is not of the actor's type, [RactorErr::Actor] if `code_change` failed (which
+    /// also fails the actor), and a [MessagingErr] if the actor has exited.
+    pub async fn upgrade<TActor>(&self, handler: TActor) -> Result<(), RactorErr<()>>
+    where
+        TActor: Actor,
+    {
+        if let Some(false) = self.is_message_type_of::<TActor::Msg>() {
+            return Err(RactorErr::Messaging(MessagingErr::InvalidActorType));
+        }
+        self.inner.upgrade(Box::new(handler)).await
     }
 
     /// Drain the actor's message queue and when finished processing, terminate the actor.
@@ -562,7 +906,25 @@ impl ActorCell {
         &self,
         message: SerializedMessage,
     ) -> Result<(), Box<MessagingErr<SerializedMessage>>> {
-        self.inner.send_serialized(message)
+        self.inner.send_serialized(message, MessagePriority::Normal)
+    }
+
+    /// Send a serialized binary message to the actor on the given priority lane.
+    ///
+    /// Messages sent by actors on other nodes always arrive on the [MessagePriority::Normal]
+    /// lane, the priority isn't carried across the network.
+    ///
+    /// * `message` - The message to send
+    /// * `priority` - The [MessagePriority] to send the message with
+    ///
+    /// Returns [Ok(())] on successful message send, [Err(MessagingErr)] otherwise
+    #[cfg(feature = "cluster")]
+    pub fn send_serialized_with_priority(
+        &self,
+        message: SerializedMessage,
+        priority: MessagePriority,
+    ) -> Result<(), Box<MessagingErr<SerializedMessage>>> {
+        self.inner.send_serialized(message, priority)
     }
 
     /// Notify the supervisor and all monitors that a supervision event occurred.
@@ -594,6 +956,11 @@ impl ActorCell {
         self.inner.tree.try_get_supervisor()
     }
 
+    /// Retrieve this actor's path in the supervision tree, see [crate::registry::path]
+    pub fn get_path(&self) -> crate::registry::ActorPath {
+        crate::registry::ActorPath::of(self)
+    }
+
     /// Stop any children of this actor, and wait for their collective exit, optionally
     /// threading the optional reason to all children
     ///
@@ -647,6 +1014,14 @@ impl ActorCell {
         self.inner.type_id
     }
 
+    /// Retrieve the name of the [super::Actor]'s type, as given by
+    /// [std::any::type_name], which is meant for diagnostics.
+    ///
+    /// For remote actors this is the type of the local proxy actor.
+    pub fn get_type_name(&self) -> &'static str {
+        self.inner.type_name
+    }
+
     /// Runtime check the message type of this actor, which only works for
     /// local actors, as remote actors send serializable messages, and can't
     /// have their message type runtime checked.
@@ -5,11 +5,30 @@
 
 use std::sync::atomic::AtomicU8;
 use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use std::sync::Mutex;
 
+#[cfg(feature = "cluster")]
+use once_cell::sync::OnceCell;
+
+#[cfg(feature = "cluster")]
+use crate::actor::durable_mailbox::DurableMailbox;
+#[cfg(feature = "cluster")]
+use crate::actor::durable_mailbox::DurableReceipt;
+use crate::actor::mailbox::AdmitErr;
+use crate::actor::mailbox::BoundedMailbox;
+use crate::actor::mailbox::MailboxOverflowPolicy;
 use crate::actor::messages::StopMessage;
+use crate::actor::priority::priority_lanes;
+use crate::actor::priority::MessagePriority;
+use crate::actor::priority::PriorityLaneReceivers;
+use crate::actor::priority::PriorityLanes;
+use crate::actor::stash::MessageStash;
+use crate::actor::stats::ActorStatsCollector;
 use crate::actor::supervision::SupervisionTree;
+use crate::actor::timers::ActorTimers;
 use crate::concurrency as mpsc;
+use crate::concurrency::Instant;
 use crate::concurrency::MpscUnboundedReceiver as InputPortReceiver;
 use crate::concurrency::MpscUnboundedSender as InputPort;
 use crate::concurrency::OneshotReceiver;
@@ -23,6 +42,7 @@ use crate::ActorName;
 use crate::ActorStatus;
 use crate::Message;
 use crate::MessagingErr;
+use crate::RactorErr;
 use crate::Signal;
 use crate::SupervisionEvent;
 
@@ -31,6 +51,12 @@ use crate::SupervisionEvent;
 pub(crate) enum MuxedMessage {
     Drain,
     Message(BoxedMessage),
+    /// A request to hot-upgrade the actor's handler. The payload is the boxed new handler,
+    /// which is downcast by the actor's runtime
+    Upgrade(
+        Box<dyn std::any::Any + Send>,
+        OneshotInputPort<Result<(), RactorErr<()>>>,
+    ),
 }
 
 // The inner-properties of an Actor
@@ -42,11 +68,20 @@ pub(crate) struct ActorProperties {
     pub(crate) signal: Mutex<Option<OneshotInputPort<Signal>>>,
     pub(crate) stop: Mutex<Option<OneshotInputPort<StopMessage>>>,
     pub(crate) supervision: InputPort<SupervisionEvent>,
-    pub(crate) message: InputPort<MuxedMessage>,
+    pub(crate) message: PriorityLanes,
     pub(crate) tree: SupervisionTree,
     pub(crate) type_id: std::any::TypeId,
+    pub(crate) type_name: &'static str,
     #[cfg(feature = "cluster")]
     pub(crate) supports_remoting: bool,
+    /// The mailbox accounting, if this actor's mailbox is bounded
+    pub(crate) mailbox: Option<Arc<BoundedMailbox>>,
+    pub(crate) stash: Arc<MessageStash>,
+    pub(crate) stats: Arc<ActorStatsCollector>,
+    pub(crate) timers: ActorTimers,
+    /// The actor's durable mailbox, if it was spawned with one
+    #[cfg(feature = "cluster")]
+    pub(crate) durable_mailbox: OnceCell<Arc<DurableMailbox>>,
 }
 
 impl ActorProperties {
@@ -57,7 +92,7 @@ impl ActorProperties {
         OneshotReceiver<Signal>,
         OneshotReceiver<StopMessage>,
         InputPortReceiver<SupervisionEvent>,
-        InputPortReceiver<MuxedMessage>,
+        PriorityLaneReceivers,
     )
     where
         TActor: Actor,
@@ -73,7 +108,7 @@ impl ActorProperties {
         OneshotReceiver<Signal>,
         OneshotReceiver<StopMessage>,
         InputPortReceiver<SupervisionEvent>,
-        InputPortReceiver<MuxedMessage>,
+        PriorityLaneReceivers,
     )
     where
         TActor: Actor,
@@ -81,7 +116,7 @@ impl ActorProperties {
         let (tx_signal, rx_signal) = mpsc::oneshot();
         let (tx_stop, rx_stop) = mpsc::oneshot();
         let (tx_supervision, rx_supervision) = mpsc::mpsc_unbounded();
-        let (tx_message, rx_message) = mpsc::mpsc_unbounded();
+        let (tx_message, rx_message) = priority_lanes();
         (
             Self {
                 id,
@@ -94,8 +129,20 @@ impl ActorProperties {
                 message: tx_message,
                 tree: SupervisionTree::default(),
                 type_id: std::any::TypeId::of::<TActor::Msg>(),
+                type_name: std::any::type_name::<TActor>(),
                 #[cfg(feature = "cluster")]
                 supports_remoting: TActor::Msg::serializable(),
+                mailbox: TActor::MAILBOX_CAPACITY.map(|capacity| {
+                    Arc::new(BoundedMailbox::new(
+                        capacity,
+                        TActor::MAILBOX_OVERFLOW_POLICY,
+                    ))
+                }),
+                stash: Arc::new(MessageStash::new(TActor::STASH_CAPACITY)),
+                stats: Arc::new(ActorStatsCollector::default()),
+                timers: Default::default(),
+                #[cfg(feature = "cluster")]
+                durable_mailbox: Default::default(),
             },
             rx_signal,
             rx_stop,
@@ -120,6 +167,14 @@ impl ActorProperties {
         self.status.store(status as u8, Ordering::SeqCst);
     }
 
+    /// Atomically move from the status `from` to the status `to`, returning [true] if the
+    /// transition happened, [false] if the actor wasn't in the status `from`
+    pub(crate) fn transition_status(&self, from: ActorStatus, to: ActorStatus) -> bool {
+        self.status
+            .compare_exchange(from as u8, to as u8, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+    }
+
     pub(crate) fn send_signal(&self, signal: Signal) -> Result<(), MessagingErr<()>> {
         self.signal
             .lock()
@@ -137,9 +192,18 @@ impl ActorProperties {
         self.supervision.send(message).map_err(|e| e.into())
     }
 
+    /// Send a message to the actor
+    ///
+    /// * `message` - The message to send
+    /// * `priority` - The [MessagePriority] to send the message with
+    /// * `deadline` - The deadline of the request the message carries, if any
+    /// * `request` - Whether the message carries a request (i.e. it's a call)
     pub(crate) fn send_message<TMessage>(
         &self,
         message: TMessage,
+        priority: MessagePriority,
+        deadline: Option<Instant>,
+        request: bool,
     ) -> Result<(), MessagingErr<TMessage>>
     where
         TMessage: Message,
@@ -156,17 +220,218 @@ impl ActorProperties {
             return Err(MessagingErr::SendErr(message));
         }
 
-        let boxed = message
+        if let Some(mailbox) = &self.mailbox {
+            match mailbox.try_admit(priority) {
+                Ok(()) => {}
+                Err(AdmitErr::Full) => return Err(MessagingErr::MailboxFull(message)),
+                Err(AdmitErr::Closed) => return Err(MessagingErr::SendErr(message)),
+            }
+        }
+
+        self.enqueue_message(message, priority, deadline, request)
+    }
+
+    /// Send a message, waiting for room in the mailbox if it's bounded with
+    /// [crate::MailboxOverflowPolicy::Block]. Other mailboxes behave like
+    /// [ActorProperties::send_message].
+    pub(crate) async fn send_message_wait<TMessage>(
+        &self,
+        message: TMessage,
+        priority: MessagePriority,
+    ) -> Result<(), MessagingErr<TMessage>>
+    where
+        TMessage: Message,
+    {
+        let Some(mailbox) = self
+            .mailbox
+            .as_ref()
+            .filter(|mailbox| mailbox.policy() == MailboxOverflowPolicy::Block)
+        else {
+            return self.send_message(message, priority, None, false);
+        };
+
+        if self.id.is_local() && self.type_id != std::any::TypeId::of::<TMessage>() {
+            return Err(MessagingErr::InvalidActorType);
+        }
+        if self.get_status() >= ActorStatus::Draining {
+            return Err(MessagingErr::SendErr(message));
+        }
+
+        if mailbox.admit(priority).await.is_err() {
+            return Err(MessagingErr::SendErr(message));
+        }
+        // the actor may have started draining while we were waiting for room
+        if self.get_status() >= ActorStatus::Draining {
+            return Err(MessagingErr::SendErr(message));
+        }
+
+        self.enqueue_message(message, priority, None, false)
+    }
+
+    #[cfg_attr(not(feature = "cluster"), allow(unused_variables))]
+    fn enqueue_message<TMessage>(
+        &self,
+        message: TMessage,
+        priority: MessagePriority,
+        deadline: Option<Instant>,
+        request: bool,
+    ) -> Result<(), MessagingErr<TMessage>>
+    where
+        TMessage: Message,
+    {
+        #[cfg(feature = "cluster")]
+        let mut boxed = match self.durable_mailbox.get() {
+            // requests aren't durable, so they're boxed as usual
+            Some(durable_mailbox) if !request => self.box_durable(durable_mailbox, message)?,
+            _ => message
+                .box_message(&self.id)
+                .map_err(|_e| MessagingErr::InvalidActorType)?,
+        };
+        #[cfg(not(feature = "cluster"))]
+        let mut boxed = message
             .box_message(&self.id)
             .map_err(|_e| MessagingErr::InvalidActorType)?;
+        boxed.deadline = deadline;
+        self.stats.message_enqueued();
         self.message
-            .send(MuxedMessage::Message(boxed))
-            .map_err(|e| match e.0 {
-                MuxedMessage::Message(m) => MessagingErr::SendErr(TMessage::from_boxed(m).unwrap()),
-                _ => panic!("Expected a boxed message but got a drain message"),
+            .send(MuxedMessage::Message(boxed), priority)
+            .map_err(|e| {
+                self.stats.message_enqueue_failed();
+                match *e {
+                    MuxedMessage::Message(mut m) => {
+                        m.acknowledge();
+                        MessagingErr::SendErr(TMessage::from_boxed(m).unwrap())
+                    }
+                    _ => panic!("Expected a boxed message but got a drain message"),
+                }
             })
     }
 
+    /// Box a message which isn't a request for an actor with a durable mailbox, appending
+    /// it to the mailbox's log first. The message goes through its serialized form, which
+    /// is what's replayed if the actor restarts before handling it.
+    ///
+    /// The append syncs the log to disk on the sender's thread, while holding the log's
+    /// lock, see the [durable mailbox documentation](crate::actor::durable_mailbox).
+    #[cfg(feature = "cluster")]
+    fn box_durable<TMessage>(
+        &self,
+        durable_mailbox: &Arc<DurableMailbox>,
+        message: TMessage,
+    ) -> Result<BoxedMessage, MessagingErr<TMessage>>
+    where
+        TMessage: Message,
+    {
+        let serialized = message
+            .serialize()
+            .map_err(|_e| MessagingErr::InvalidActorType)?;
+        let receipt = match self.append_durable(durable_mailbox, &serialized) {
+            Ok(receipt) => receipt,
+            Err(()) => {
+                let message = TMessage::deserialize(serialized)
+                    .map_err(|_e| MessagingErr::InvalidActorType)?;
+                return Err(MessagingErr::SendErr(message));
+            }
+        };
+        // a message which serializes to a call has no receipt, as its reply port can't be replayed
+        let message =
+            TMessage::deserialize(serialized).map_err(|_e| MessagingErr::InvalidActorType)?;
+        let mut boxed = message
+            .box_message(&self.id)
+            .map_err(|_e| MessagingErr::InvalidActorType)?;
+        boxed.receipt = receipt;
+        Ok(boxed)
+    }
+
+    /// Append a serialized message to the durable mailbox's log if it's a cast, returning
+    /// its receipt, or [None] if it isn't logged
+    #[cfg(feature = "cluster")]
+    fn append_durable(
+        &self,
+        durable_mailbox: &Arc<DurableMailbox>,
+        serialized: &SerializedMessage,
+    ) -> Result<Option<DurableReceipt>, ()> {
+        let SerializedMessage::Cast {
+            variant,
+            args,
+            metadata,
+        } = serialized
+        else {
+            return Ok(None);
+        };
+        match durable_mailbox.append(variant, args, metadata.as_deref()) {
+            Ok(id) => Ok(Some(DurableReceipt::new(durable_mailbox.clone(), id))),
+            Err(err) => {
+                tracing::error!(
+                    "Failed to append a message to the durable mailbox of {:?}: {err}",
+                    self.id
+                );
+                Err(())
+            }
+        }
+    }
+
+    /// Enqueue the messages of a durable mailbox which weren't acknowledged before the
+    /// actor restarted, ahead of any new message
+    #[cfg(feature = "cluster")]
+    pub(crate) fn replay_durable<TMessage>(
+        &self,
+        durable_mailbox: Arc<DurableMailbox>,
+        replayed: Vec<(u64, SerializedMessage)>,
+    ) where
+        TMessage: Message,
+    {
+        for (id, serialized) in replayed {
+            let boxed = TMessage::deserialize(serialized)
+                .ok()
+                .and_then(|message| message.box_message(&self.id).ok());
+            let Some(mut boxed) = boxed else {
+                tracing::error!(
+                    "Skipping durable mailbox message {id} of {:?}, which couldn't be decoded",
+                    self.id
+                );
+                durable_mailbox.ack(id);
+                continue;
+            };
+            boxed.receipt = Some(DurableReceipt::new(durable_mailbox.clone(), id));
+            if let Some(mailbox) = &self.mailbox {
+                mailbox.admit_replayed();
+            }
+            self.stats.message_enqueued();
+            if self
+                .message
+                .send(MuxedMessage::Message(boxed), MessagePriority::Normal)
+                .is_err()
+            {
+                self.stats.message_enqueue_failed();
+            }
+        }
+        let _ = self.durable_mailbox.set(durable_mailbox);
+    }
+
+    pub(crate) fn stash_message<TMessage>(
+        &self,
+        message: TMessage,
+    ) -> Result<(), MessagingErr<TMessage>>
+    where
+        TMessage: Message,
+    {
+        // an actor can only stash messages on itself, which is never remote
+        if !self.id.is_local() {
+            return Err(MessagingErr::SendErr(message));
+        }
+        if self.type_id != std::any::TypeId::of::<TMessage>() {
+            return Err(MessagingErr::InvalidActorType);
+        }
+
+        let boxed = message
+            .box_message(&self.id)
+            .map_err(|_e| MessagingErr::InvalidActorType)?;
+        self.stash
+            .stash(boxed)
+            .map_err(|m| MessagingErr::StashFull(TMessage::from_boxed(*m).unwrap()))
+    }
+
     pub(crate) fn drain(&self) -> Result<(), MessagingErr<()>> {
         let _ = self
             .status
@@ -178,10 +443,28 @@ impl ActorProperties {
                 }
             });
         self.message
-            .send(MuxedMessage::Drain)
+            .send(MuxedMessage::Drain, MessagePriority::Low)
             .map_err(|_| MessagingErr::SendErr(()))
     }
 
+    /// Send a handler upgrade request, waiting for the upgrade to be applied
+    pub(crate) async fn upgrade(
+        &self,
+        handler: Box<dyn std::any::Any + Send>,
+    ) -> Result<(), RactorErr<()>> {
+        if self.get_status() >= ActorStatus::Draining {
+            return Err(RactorErr::Messaging(MessagingErr::SendErr(())));
+        }
+        let (tx, rx) = mpsc::oneshot();
+        self.message
+            .send_upgrade(MuxedMessage::Upgrade(handler, tx))
+            .map_err(|_| RactorErr::Messaging(MessagingErr::SendErr(())))?;
+        match rx.await {
+            Ok(result) => result,
+            Err(_) => Err(RactorErr::Messaging(MessagingErr::ChannelClosed)),
+        }
+    }
+
     /// Start draining, and wait for the actor to exit
     pub(crate) async fn drain_and_wait(&self) -> Result<(), MessagingErr<()>> {
         let rx = self.wait_handler.notified();
@@ -194,18 +477,43 @@ impl ActorProperties {
     pub(crate) fn send_serialized(
         &self,
         message: SerializedMessage,
+        priority: MessagePriority,
     ) -> Result<(), Box<MessagingErr<SerializedMessage>>> {
+        if let Some(mailbox) = &self.mailbox {
+            match mailbox.try_admit(priority) {
+                Ok(()) => {}
+                Err(AdmitErr::Full) => return Err(Box::new(MessagingErr::MailboxFull(message))),
+                Err(AdmitErr::Closed) => return Err(Box::new(MessagingErr::SendErr(message))),
+            }
+        }
+        // casts from remote actors are as durable as local ones
+        let receipt = match self.durable_mailbox.get() {
+            Some(durable_mailbox) => match self.append_durable(durable_mailbox, &message) {
+                Ok(receipt) => receipt,
+                Err(()) => return Err(Box::new(MessagingErr::SendErr(message))),
+            },
+            None => None,
+        };
         let boxed = BoxedMessage {
             msg: None,
             serialized_msg: Some(message),
             span: None,
+            deadline: None,
+            receipt,
         };
+        self.stats.message_enqueued();
         Ok(self
             .message
-            .send(MuxedMessage::Message(boxed))
-            .map_err(|e| match e.0 {
-                MuxedMessage::Message(m) => MessagingErr::SendErr(m.serialized_msg.unwrap()),
-                _ => panic!("Expected a boxed message but got a drain message"),
+            .send(MuxedMessage::Message(boxed), priority)
+            .map_err(|e| {
+                self.stats.message_enqueue_failed();
+                match *e {
+                    MuxedMessage::Message(mut m) => {
+                        m.acknowledge();
+                        MessagingErr::SendErr(m.serialized_msg.unwrap())
+                    }
+                    _ => panic!("Expected a boxed message but got a drain message"),
+                }
             })?)
     }
 
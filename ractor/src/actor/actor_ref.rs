@@ -106,21 +106,73 @@ where
         self.inner.send_message::<TMessage>(message)
     }
 
+    /// Send a strongly-typed message on the given priority lane, see
+    /// [ActorCell::send_message_with_priority]
+    ///
+    /// * `message` - The message to send
+    /// * `priority` - The [crate::MessagePriority] to send the message with
+    ///
+    /// Returns [Ok(())] on successful message send, [Err(MessagingErr)] otherwise
+    pub fn send_message_with_priority(
+        &self,
+        message: TMessage,
+        priority: crate::MessagePriority,
+    ) -> Result<(), MessagingErr<TMessage>> {
+        self.inner
+            .send_message_with_priority::<TMessage>(message, priority)
+    }
+
+    /// Send a strongly-typed message, waiting asynchronously for room in the actor's
+    /// mailbox if it's bounded with [crate::MailboxOverflowPolicy::Block]
+    ///
+    /// * `message` - The message to send
+    ///
+    /// Returns [Ok(())] on successful message send, [Err(MessagingErr)] otherwise
+    pub async fn send_message_wait(&self, message: TMessage) -> Result<(), MessagingErr<TMessage>> {
+        self.inner.send_message_wait::<TMessage>(message).await
+    }
+
+    /// Send a strongly-typed message on the given priority lane, waiting asynchronously for
+    /// room in the actor's mailbox, see [ActorCell::send_message_wait_with_priority]
+    ///
+    /// * `message` - The message to send
+    /// * `priority` - The [crate::MessagePriority] to send the message with
+    ///
+    /// Returns [Ok(())] on successful message send, [Err(MessagingErr)] otherwise
+    pub async fn send_message_wait_with_priority(
+        &self,
+        message: TMessage,
+        priority: crate::MessagePriority,
+    ) -> Result<(), MessagingErr<TMessage>> {
+        self.inner
+            .send_message_wait_with_priority::<TMessage>(message, priority)
+            .await
+    }
+
+    /// Stash a message, deferring it until [ActorCell::unstash_all] is called. This is
+    /// intended to be used by an actor on itself (i.e. `myself.stash(message)`) when it
+    /// receives a message it can't handle in its current state.
+    ///
+    /// The stash is bounded by [crate::Actor::STASH_CAPACITY]. Stashed messages which haven't
+    /// been replayed when the actor exits are dropped.
+    ///
+    /// * `message` - The message to stash
+    ///
+    /// Returns [Ok(())] if the message was stashed, [Err(MessagingErr::StashFull)] with the
+    /// message if the stash is full, or [Err(MessagingErr::SendErr)] with the message if the
+    /// actor is remote.
+    pub fn stash(&self, message: TMessage) -> Result<(), MessagingErr<TMessage>> {
+        self.inner.inner.stash_message::<TMessage>(message)
+    }
+
     // ========================== General Actor Operation Aliases ========================== //
 
     // -------------------------- ActorRegistry -------------------------- //
 
     /// Try and retrieve a strongly-typed actor from the registry.
     ///
-    /// Alias of [crate::registry::where_is]
+    /// Alias of [crate::registry::where_is_typed], discarding the reason for a failed lookup
     pub fn where_is(name: ActorName) -> Option<crate::actor::ActorRef<TMessage>> {
-        if let Some(actor) = crate::registry::where_is(name) {
-            // check the type id when pulling from the registry
-            let check = actor.is_message_type_of::<TMessage>();
-            if check.is_none() || matches!(check, Some(true)) {
-                return Some(actor.into());
-            }
-        }
-        None
+        crate::registry::where_is_typed(name).ok()
     }
 }
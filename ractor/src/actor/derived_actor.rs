@@ -9,6 +9,7 @@
 
 use std::sync::Arc;
 
+use crate::concurrency::Instant;
 use crate::ActorCell;
 use crate::ActorRef;
 use crate::Message;
@@ -145,10 +146,20 @@ use crate::MessagingErr;
 /// }
 /// ```
 pub struct DerivedActorRef<TFrom> {
-    converter: Arc<dyn Fn(TFrom) -> Result<(), MessagingErr<TFrom>> + Send + Sync + 'static>,
+    converter:
+        Arc<dyn Fn(TFrom, Delivery) -> Result<(), MessagingErr<TFrom>> + Send + Sync + 'static>,
     pub(crate) inner: ActorCell,
 }
 
+/// How a message is sent through a [DerivedActorRef]
+#[derive(Clone, Copy)]
+enum Delivery {
+    /// A plain message
+    Message,
+    /// A request carrying an [crate::RpcReplyPort], with its optional deadline
+    Request(Option<Instant>),
+}
+
 impl<TFrom> Clone for DerivedActorRef<TFrom> {
     fn clone(&self) -> Self {
         Self {
@@ -182,7 +193,22 @@ impl<TFrom> DerivedActorRef<TFrom> {
     ///
     /// Returns [Ok(())] on successful message send, [Err(MessagingErr)] otherwise
     pub fn send_message(&self, message: TFrom) -> Result<(), MessagingErr<TFrom>> {
-        (self.converter)(message)
+        (self.converter)(message, Delivery::Message)
+    }
+
+    /// Casts a request carrying an [crate::RpcReplyPort] to the target message type of
+    /// [ActorCell] and sends it, see [ActorCell::send_request]
+    ///
+    /// * `message` - The message to send
+    /// * `deadline` - The optional deadline of the request
+    ///
+    /// Returns [Ok(())] on successful message send, [Err(MessagingErr)] otherwise
+    pub(crate) fn send_request(
+        &self,
+        message: TFrom,
+        deadline: Option<Instant>,
+    ) -> Result<(), MessagingErr<TFrom>> {
+        (self.converter)(message, Delivery::Request(deadline))
     }
 
     /// Retrieve a cloned [ActorCell] representing this [DerivedActorRef]
@@ -208,20 +234,22 @@ impl<TMessage: Message> ActorRef<TMessage> {
         TFrom: TryFrom<TMessage>,
     {
         let actor_ref = self.clone();
-        let cast_and_send = move |msg: TFrom| {
-            actor_ref.send_message(msg.into()).map_err(|err| match err {
-                MessagingErr::SendErr(returned) => {
-                    let Ok(err) = TFrom::try_from(returned) else {
+        let cast_and_send = move |msg: TFrom, delivery: Delivery| {
+            let sent = match delivery {
+                Delivery::Message => actor_ref.send_message(msg.into()),
+                Delivery::Request(deadline) => actor_ref.send_request(msg.into(), deadline),
+            };
+            sent.map_err(|err| {
+                err.map(|returned| {
+                    let Ok(returned) = TFrom::try_from(returned) else {
                         panic!(
                             "Failed to deconvert message from {} to {} when sending to: {actor_ref:?}",
                             std::any::type_name::<TMessage>(),
                             std::any::type_name::<TFrom>()
                         );
                     };
-                    MessagingErr::SendErr(err)
-                }
-                MessagingErr::ChannelClosed => MessagingErr::ChannelClosed,
-                MessagingErr::InvalidActorType => MessagingErr::InvalidActorType,
+                    returned
+                })
             })
         };
         DerivedActorRef::<TFrom> {
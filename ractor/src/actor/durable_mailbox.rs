@@ -0,0 +1,390 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Durable mailboxes, whose messages survive the actor (and the process) restarting.
+//!
+//! An actor whose message type is serializable (see [crate::Message::serialize]) can be
+//! spawned with a durable mailbox, by passing a [DurableMailboxConfig] to
+//! [crate::ActorRuntime::spawn_with_options]. Every cast sent to the actor is then appended
+//! to a local log before it's put in the mailbox, and acknowledged in the log once the actor's
+//! `handle` has completed successfully. Messages which are dropped without being handled (e.g.
+//! because they passed their deadline, were rejected by an interceptor, or were displaced
+//! by [crate::MailboxOverflowPolicy::DropOldest]) are acknowledged as well.
+//!
+//! When an actor is spawned with the same directory again (e.g. restarted by its supervisor,
+//! or after the process restarted), the messages which weren't acknowledged are replayed
+//! into its mailbox, in the order they were sent, ahead of any new message. This gives
+//! at-least-once delivery: a message whose handling fails is handled again by the next
+//! incarnation of the actor, so handlers should be idempotent.
+//!
+//! Only casts are durable, calls are delivered as usual since their caller can't outlive
+//! the process. Casts from remote actors are logged like local ones. Messages which are
+//! stashed are no longer durable once they've been handled.
+//!
+//! Appending a cast syncs the log to disk before the cast returns. This happens on the
+//! sender's thread, and senders to the same actor wait for each other's syncs, so sending
+//! to an actor with a durable mailbox blocks for as long as a write to disk takes. Async
+//! senders which can't afford that should send from a blocking task (e.g. tokio's
+//! `spawn_blocking`).
+//!
+//! The log is split into segment files of about [DurableMailboxConfig::segment_size] bytes,
+//! and a segment is deleted once all of its messages (and those of the segments before it)
+//! have been acknowledged.
+//!
+//! Requires the `cluster` feature.
+
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use crate::message::SerializedMessage;
+
+#[cfg(test)]
+mod tests;
+
+/// The default size of the segment files of a durable mailbox
+pub const DEFAULT_SEGMENT_SIZE: u64 = 16 * 1024 * 1024;
+
+const RECORD_ENQUEUE: u8 = 0;
+const RECORD_ACK: u8 = 1;
+/// The kind, id, length and checksum around the payload of a record
+const RECORD_OVERHEAD: usize = 17;
+
+/// The configuration of a durable mailbox, see the [module documentation](self)
+#[derive(Debug, Clone, bon::Builder)]
+pub struct DurableMailboxConfig {
+    /// The directory the mailbox's log is stored in. It must only be used by one actor at
+    /// a time, and be used again by the actor when it's restarted.
+    #[builder(into)]
+    pub directory: PathBuf,
+    /// The size a segment of the log grows to before a new segment is started
+    ///
+    /// Default = [DEFAULT_SEGMENT_SIZE]
+    #[builder(default = DEFAULT_SEGMENT_SIZE)]
+    pub segment_size: u64,
+}
+
+/// A segment file of the log
+struct Segment {
+    path: PathBuf,
+    /// The messages in this segment which haven't been acknowledged
+    unacked: HashSet<u64>,
+}
+
+struct SegmentLog {
+    next_id: u64,
+    /// The segments by the id of the first message they may contain. The last one is
+    /// the one being appended to.
+    segments: VecDeque<(u64, Segment)>,
+    current: File,
+    current_size: u64,
+}
+
+/// The log of a durable mailbox
+pub(crate) struct DurableMailbox {
+    directory: PathBuf,
+    segment_size: u64,
+    log: Mutex<SegmentLog>,
+}
+
+impl DurableMailbox {
+    /// Open the log in the configured directory, returning it along with the messages
+    /// which haven't been acknowledged, in the order they were sent
+    pub(crate) fn open(
+        config: &DurableMailboxConfig,
+    ) -> std::io::Result<(Self, Vec<(u64, SerializedMessage)>)> {
+        std::fs::create_dir_all(&config.directory)?;
+        let mut paths = BTreeMap::new();
+        for entry in std::fs::read_dir(&config.directory)? {
+            let path = entry?.path();
+            if let Some(first_id) = segment_first_id(&path) {
+                paths.insert(first_id, path);
+            }
+        }
+
+        let mut next_id = 0;
+        let mut pending = BTreeMap::new();
+        let mut segments: VecDeque<(u64, Segment)> = VecDeque::new();
+        let count = paths.len();
+        for (index, (first_id, path)) in paths.into_iter().enumerate() {
+            let bytes = std::fs::read(&path)?;
+            let mut segment = Segment {
+                path,
+                unacked: HashSet::new(),
+            };
+            let mut offset = 0;
+            while let Some((kind, id, payload, len)) = decode_record(&bytes[offset..]) {
+                offset += len;
+                match kind {
+                    RECORD_ENQUEUE => {
+                        segment.unacked.insert(id);
+                        pending.insert(id, payload.to_vec());
+                    }
+                    _ => {
+                        pending.remove(&id);
+                        if !segment.unacked.remove(&id) {
+                            for (_, earlier) in segments.iter_mut() {
+                                earlier.unacked.remove(&id);
+                            }
+                        }
+                    }
+                }
+                next_id = next_id.max(id + 1);
+            }
+            if offset < bytes.len() {
+                tracing::warn!(
+                    "Dropping a partially written record from durable mailbox segment {:?}",
+                    segment.path
+                );
+                // only the segment being appended to can have a partial record
+                if index + 1 == count {
+                    OpenOptions::new()
+                        .write(true)
+                        .open(&segment.path)?
+                        .set_len(offset as u64)?;
+                }
+            }
+            segments.push_back((first_id, segment));
+        }
+
+        // keep appending to the last segment, unless it's full
+        let current_size = match segments.back() {
+            Some((_, segment)) => std::fs::metadata(&segment.path)?.len(),
+            None => 0,
+        };
+        let (current, current_size) = if segments.is_empty() || current_size >= config.segment_size
+        {
+            let (first_id, segment, file) = new_segment(&config.directory, next_id)?;
+            segments.push_back((first_id, segment));
+            (file, 0)
+        } else {
+            let path = &segments.back().unwrap().1.path;
+            (OpenOptions::new().append(true).open(path)?, current_size)
+        };
+
+        let mailbox = Self {
+            directory: config.directory.clone(),
+            segment_size: config.segment_size,
+            log: Mutex::new(SegmentLog {
+                next_id,
+                segments,
+                current,
+                current_size,
+            }),
+        };
+        mailbox.compact(&mut mailbox.log.lock().unwrap());
+
+        let mut replayed = vec![];
+        for (id, payload) in pending {
+            match decode_cast(&payload) {
+                Some(message) => replayed.push((id, message)),
+                None => {
+                    tracing::error!("Skipping corrupted message {id} of a durable mailbox");
+                    mailbox.ack(id);
+                }
+            }
+        }
+        Ok((mailbox, replayed))
+    }
+
+    /// Append a cast to the log, returning its id once it's durably stored
+    pub(crate) fn append(
+        &self,
+        variant: &str,
+        args: &[u8],
+        metadata: Option<&[u8]>,
+    ) -> std::io::Result<u64> {
+        let mut log = self.log.lock().unwrap();
+        let id = log.next_id;
+        let mut buffer = vec![];
+        encode_record(
+            &mut buffer,
+            RECORD_ENQUEUE,
+            id,
+            &encode_cast(variant, args, metadata),
+        );
+        log.current.write_all(&buffer)?;
+        log.current.sync_data()?;
+
+        log.next_id += 1;
+        log.current_size += buffer.len() as u64;
+        if let Some((_, segment)) = log.segments.back_mut() {
+            segment.unacked.insert(id);
+        }
+        if log.current_size >= self.segment_size {
+            match new_segment(&self.directory, log.next_id) {
+                Ok((first_id, segment, file)) => {
+                    log.segments.push_back((first_id, segment));
+                    log.current = file;
+                    log.current_size = 0;
+                }
+                Err(err) => {
+                    tracing::error!("Failed to start a new durable mailbox segment: {err}");
+                }
+            }
+        }
+        Ok(id)
+    }
+
+    /// Acknowledge a message, which won't be replayed anymore
+    pub(crate) fn ack(&self, id: u64) {
+        let mut log = self.log.lock().unwrap();
+        let mut buffer = vec![];
+        encode_record(&mut buffer, RECORD_ACK, id, &[]);
+        // an ack which doesn't make it to the disk only means the message is replayed
+        if let Err(err) = log.current.write_all(&buffer) {
+            tracing::error!("Failed to acknowledge message {id} of a durable mailbox: {err}");
+            return;
+        }
+        log.current_size += buffer.len() as u64;
+        if let Some((_, segment)) = log
+            .segments
+            .iter_mut()
+            .rev()
+            .find(|(first_id, _)| *first_id <= id)
+        {
+            segment.unacked.remove(&id);
+        }
+        self.compact(&mut log);
+    }
+
+    /// Delete the leading segments whose messages have all been acknowledged. The acks
+    /// of a segment's messages are stored in it or after it, so segments are only deleted
+    /// in order.
+    fn compact(&self, log: &mut SegmentLog) {
+        while log.segments.len() > 1 && log.segments[0].1.unacked.is_empty() {
+            let (_, segment) = log.segments.pop_front().unwrap();
+            if let Err(err) = std::fs::remove_file(&segment.path) {
+                tracing::error!(
+                    "Failed to delete durable mailbox segment {:?}: {err}",
+                    segment.path
+                );
+            }
+        }
+    }
+}
+
+/// The proof a message was logged in a durable mailbox, which acknowledges it
+pub(crate) struct DurableReceipt {
+    mailbox: Arc<DurableMailbox>,
+    id: u64,
+}
+
+impl DurableReceipt {
+    pub(crate) fn new(mailbox: Arc<DurableMailbox>, id: u64) -> Self {
+        Self { mailbox, id }
+    }
+
+    /// Acknowledge the message, which won't be replayed anymore
+    pub(crate) fn ack(self) {
+        self.mailbox.ack(self.id);
+    }
+}
+
+fn segment_first_id(path: &Path) -> Option<u64> {
+    path.file_name()?
+        .to_str()?
+        .strip_prefix("segment-")?
+        .strip_suffix(".log")?
+        .parse()
+        .ok()
+}
+
+fn new_segment(directory: &Path, first_id: u64) -> std::io::Result<(u64, Segment, File)> {
+    let path = directory.join(format!("segment-{first_id:020}.log"));
+    let file = OpenOptions::new().create(true).append(true).open(&path)?;
+    Ok((
+        first_id,
+        Segment {
+            path,
+            unacked: HashSet::new(),
+        },
+        file,
+    ))
+}
+
+fn encode_record(buffer: &mut Vec<u8>, kind: u8, id: u64, payload: &[u8]) {
+    let start = buffer.len();
+    buffer.push(kind);
+    buffer.extend_from_slice(&id.to_le_bytes());
+    buffer.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(payload);
+    let checksum = checksum(&buffer[start..start + 9], payload);
+    buffer.extend_from_slice(&checksum.to_le_bytes());
+}
+
+/// Decode the record at the start of the bytes, returning its kind, id, payload and
+/// length, or [None] if it's incomplete or its checksum doesn't match
+fn decode_record(bytes: &[u8]) -> Option<(u8, u64, &[u8], usize)> {
+    if bytes.len() < RECORD_OVERHEAD {
+        return None;
+    }
+    let len = u32::from_le_bytes(bytes[9..13].try_into().ok()?) as usize;
+    let end = 13usize.checked_add(len)?;
+    if bytes.len() < end + 4 {
+        return None;
+    }
+    let payload = &bytes[13..end];
+    let expected = u32::from_le_bytes(bytes[end..end + 4].try_into().ok()?);
+    if checksum(&bytes[..9], payload) != expected {
+        return None;
+    }
+    let id = u64::from_le_bytes(bytes[1..9].try_into().ok()?);
+    Some((bytes[0], id, payload, end + 4))
+}
+
+/// The 32-bit FNV-1a hash of a record's header and payload
+fn checksum(header: &[u8], payload: &[u8]) -> u32 {
+    header
+        .iter()
+        .chain(payload)
+        .fold(0x811c_9dc5, |hash, byte| {
+            (hash ^ u32::from(*byte)).wrapping_mul(0x0100_0193)
+        })
+}
+
+fn encode_cast(variant: &str, args: &[u8], metadata: Option<&[u8]>) -> Vec<u8> {
+    let mut buffer = vec![];
+    for field in [Some(variant.as_bytes()), Some(args), metadata] {
+        if let Some(field) = field {
+            buffer.push(1);
+            buffer.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            buffer.extend_from_slice(field);
+        } else {
+            buffer.push(0);
+        }
+    }
+    buffer
+}
+
+fn decode_cast(mut bytes: &[u8]) -> Option<SerializedMessage> {
+    let mut fields = vec![];
+    for _ in 0..3 {
+        let (present, rest) = bytes.split_first()?;
+        bytes = rest;
+        if *present == 0 {
+            fields.push(None);
+            continue;
+        }
+        let len = u32::from_le_bytes(bytes.get(..4)?.try_into().ok()?) as usize;
+        fields.push(Some(bytes.get(4..4 + len)?.to_vec()));
+        bytes = &bytes[4 + len..];
+    }
+    let metadata = fields.pop()?;
+    let args = fields.pop()??;
+    let variant = String::from_utf8(fields.pop()??).ok()?;
+    Some(SerializedMessage::Cast {
+        variant,
+        args,
+        metadata,
+    })
+}
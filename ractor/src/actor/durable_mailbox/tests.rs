@@ -0,0 +1,259 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Tests of durable mailboxes
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+use super::*;
+use crate::common_test::periodic_check;
+use crate::concurrency::Duration;
+use crate::concurrency::Notify;
+use crate::Actor;
+use crate::ActorProcessingErr;
+use crate::ActorRef;
+use crate::ActorRuntime;
+use crate::ActorStatus;
+use crate::BytesConvertable;
+use crate::Message;
+use crate::SpawnErr;
+use crate::SpawnOptions;
+
+fn temporary_directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!(
+        "ractor_{name}_{}_{}",
+        std::process::id(),
+        crate::concurrency::random_u64()
+    ));
+    let _ = std::fs::remove_dir_all(&directory);
+    directory
+}
+
+fn segment_count(directory: &Path) -> usize {
+    std::fs::read_dir(directory)
+        .unwrap()
+        .filter(|entry| segment_first_id(&entry.as_ref().unwrap().path()).is_some())
+        .count()
+}
+
+fn pending_values(pending: Vec<(u64, SerializedMessage)>) -> Vec<u64> {
+    pending
+        .into_iter()
+        .map(|(_, message)| match message {
+            SerializedMessage::Cast { args, .. } => u64::from_bytes(args),
+            _ => panic!("Expected a cast"),
+        })
+        .collect()
+}
+
+/// Handles `u64`s, recording them. The first 0 fails the actor, once the gate is opened.
+struct Recorder;
+
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+impl Actor for Recorder {
+    type Msg = u64;
+    type State = (Arc<Mutex<Vec<u64>>>, Arc<AtomicBool>, Arc<Notify>);
+    type Arguments = (Arc<Mutex<Vec<u64>>>, Arc<AtomicBool>, Arc<Notify>);
+
+    async fn pre_start(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        args: Self::Arguments,
+    ) -> Result<Self::State, ActorProcessingErr> {
+        Ok(args)
+    }
+
+    async fn handle(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        message: Self::Msg,
+        (handled, failed, gate): &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        if message == 0 && !failed.swap(true, Ordering::SeqCst) {
+            gate.notified().await;
+            return Err(From::from("boom"));
+        }
+        handled.lock().unwrap().push(message);
+        Ok(())
+    }
+}
+
+#[test]
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn test_segment_log() {
+    let directory = temporary_directory("test_segment_log");
+    let config = DurableMailboxConfig::builder()
+        .directory(&directory)
+        .segment_size(64)
+        .build();
+
+    let (mailbox, pending) = DurableMailbox::open(&config).unwrap();
+    assert!(pending.is_empty());
+    for value in 0..6u64 {
+        let id = mailbox.append("", &value.into_bytes(), None).unwrap();
+        assert_eq!(value, id);
+    }
+    // a cast is 36 bytes, so a segment holds 2 messages (and their acks)
+    assert_eq!(4, segment_count(&directory));
+
+    mailbox.ack(0);
+    mailbox.ack(2);
+    assert_eq!(4, segment_count(&directory));
+    mailbox.ack(1);
+    // the first segment is fully acknowledged, the second isn't
+    assert_eq!(3, segment_count(&directory));
+    drop(mailbox);
+
+    let (mailbox, pending) = DurableMailbox::open(&config).unwrap();
+    assert_eq!(vec![3, 4, 5], pending_values(pending));
+    // the ids keep going after a restart
+    assert_eq!(6, mailbox.append("", &6u64.into_bytes(), None).unwrap());
+    for id in 3..=6 {
+        mailbox.ack(id);
+    }
+    assert_eq!(1, segment_count(&directory));
+    drop(mailbox);
+
+    let (_mailbox, pending) = DurableMailbox::open(&config).unwrap();
+    assert!(pending.is_empty());
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn test_segment_log_drops_partial_record() {
+    let directory = temporary_directory("test_segment_log_drops_partial_record");
+    let config = DurableMailboxConfig::builder()
+        .directory(&directory)
+        .build();
+
+    let (mailbox, _) = DurableMailbox::open(&config).unwrap();
+    mailbox.append("", &1u64.into_bytes(), None).unwrap();
+    drop(mailbox);
+
+    let path = directory.join(format!("segment-{:020}.log", 0));
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(&[RECORD_ENQUEUE, 1, 0]).unwrap();
+    drop(file);
+
+    let (mailbox, pending) = DurableMailbox::open(&config).unwrap();
+    assert_eq!(vec![1], pending_values(pending));
+    mailbox.append("", &2u64.into_bytes(), None).unwrap();
+    drop(mailbox);
+
+    let (_mailbox, pending) = DurableMailbox::open(&config).unwrap();
+    assert_eq!(vec![1, 2], pending_values(pending));
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_durable_mailbox_replays_after_failure() {
+    let directory = temporary_directory("test_durable_mailbox_replays_after_failure");
+    let config = DurableMailboxConfig::builder()
+        .directory(&directory)
+        .build();
+    let handled = Arc::new(Mutex::new(vec![]));
+    let failed = Arc::new(AtomicBool::new(false));
+    let gate = Arc::new(Notify::new());
+
+    let (actor, handle) = ActorRuntime::spawn_with_options(
+        Recorder,
+        (handled.clone(), failed.clone(), gate.clone()),
+        SpawnOptions::builder()
+            .durable_mailbox(config.clone())
+            .build(),
+    )
+    .await
+    .expect("Failed to spawn actor");
+    // the actor fails on 0 once the gate is opened, leaving 3 and 4 in its mailbox
+    for value in [1, 2, 0] {
+        actor.cast(value).unwrap();
+    }
+    // casts from remote actors are logged too
+    actor
+        .send_serialized(3u64.serialize().unwrap())
+        .expect("Serialized message send failed!");
+    actor.cast(4).unwrap();
+    gate.notify_one();
+    handle.await.unwrap();
+    assert_eq!(vec![1, 2], *handled.lock().unwrap());
+
+    // the failed message and the ones after it are replayed, ahead of new ones
+    let (actor, handle) = ActorRuntime::spawn_with_options(
+        Recorder,
+        (handled.clone(), failed.clone(), gate.clone()),
+        SpawnOptions::builder()
+            .durable_mailbox(config.clone())
+            .build(),
+    )
+    .await
+    .expect("Failed to spawn actor");
+    actor.cast(5).unwrap();
+    periodic_check(
+        || handled.lock().unwrap().len() == 6,
+        Duration::from_secs(1),
+    )
+    .await;
+    assert_eq!(vec![1, 2, 0, 3, 4, 5], *handled.lock().unwrap());
+
+    actor.stop(None);
+    handle.await.unwrap();
+    assert_eq!(ActorStatus::Stopped, actor.get_status());
+
+    let (_mailbox, pending) = DurableMailbox::open(&config).unwrap();
+    assert!(pending.is_empty());
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_durable_mailbox_requires_serializable_messages() {
+    struct Unserializable;
+
+    enum UnserializableMsg {}
+    impl crate::Message for UnserializableMsg {}
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for Unserializable {
+        type Msg = UnserializableMsg;
+        type State = ();
+        type Arguments = ();
+
+        async fn pre_start(
+            &self,
+            _myself: ActorRef<Self::Msg>,
+            _: (),
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(())
+        }
+    }
+
+    let directory = temporary_directory("test_durable_mailbox_requires_serializable_messages");
+    let result = ActorRuntime::spawn_with_options(
+        Unserializable,
+        (),
+        SpawnOptions::builder()
+            .durable_mailbox(
+                DurableMailboxConfig::builder()
+                    .directory(&directory)
+                    .build(),
+            )
+            .build(),
+    )
+    .await;
+    assert!(matches!(result, Err(SpawnErr::StartupFailed(_))));
+    assert!(!directory.exists());
+}
@@ -0,0 +1,135 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Message interceptors, which wrap an actor's message handling with cross-cutting
+//! logic (authorization, validation, logging, metrics, etc) without touching the
+//! actor's [crate::Actor::handle] implementation.
+//!
+//! Interceptors are configured when spawning the actor, with
+//! [crate::ActorRuntime::spawn_with_options]. For every message, the interceptors'
+//! [MessageInterceptor::before_handle] hooks run in the order they were supplied, each
+//! receiving the (possibly transformed) message from the one before it. If all of them
+//! let the message through it's passed to the actor's handler, after which the
+//! [MessageInterceptor::after_handle] hooks run in reverse order with the handler's result.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use std::sync::Arc;
+//!
+//! use ractor::actor::interceptor::MessageInterceptor;
+//! use ractor::concurrency::Duration;
+//! use ractor::{Actor, ActorCell, ActorProcessingErr, ActorRef, ActorRuntime, SpawnOptions};
+//!
+//! struct Doubler;
+//!
+//! impl MessageInterceptor<u32> for Doubler {
+//!     fn before_handle(&self, _actor: &ActorCell, message: u32) -> Option<u32> {
+//!         // reject zeros, double everything else
+//!         if message == 0 {
+//!             None
+//!         } else {
+//!             Some(message * 2)
+//!         }
+//!     }
+//!
+//!     fn after_handle(
+//!         &self,
+//!         actor: &ActorCell,
+//!         result: &Result<(), ActorProcessingErr>,
+//!         elapsed: Duration,
+//!     ) {
+//!         println!("{:?} handled a message in {elapsed:?} ({result:?})", actor.get_id());
+//!     }
+//! }
+//!
+//! struct Printer;
+//!
+//! #[cfg_attr(feature = "async-trait", ractor::async_trait)]
+//! impl Actor for Printer {
+//!     type Msg = u32;
+//!     type State = ();
+//!     type Arguments = ();
+//!
+//!     async fn pre_start(
+//!         &self,
+//!         _myself: ActorRef<Self::Msg>,
+//!         _args: Self::Arguments,
+//!     ) -> Result<Self::State, ActorProcessingErr> {
+//!         Ok(())
+//!     }
+//!
+//!     async fn handle(
+//!         &self,
+//!         _myself: ActorRef<Self::Msg>,
+//!         message: Self::Msg,
+//!         _state: &mut Self::State,
+//!     ) -> Result<(), ActorProcessingErr> {
+//!         println!("{message}");
+//!         Ok(())
+//!     }
+//! }
+//!
+//! async fn example() {
+//!     let options = SpawnOptions::builder()
+//!         .interceptors(vec![Arc::new(Doubler) as Arc<dyn MessageInterceptor<u32>>])
+//!         .build();
+//!     let (actor, handle) = ActorRuntime::spawn_with_options(Printer, (), options)
+//!         .await
+//!         .expect("Failed to start actor");
+//!     actor.cast(21).unwrap();
+//!     actor.stop(None);
+//!     handle.await.unwrap();
+//! }
+//! ```
+
+use crate::concurrency::Duration;
+use crate::ActorCell;
+use crate::ActorProcessingErr;
+use crate::Message;
+
+/// An interceptor which observes, transforms, or rejects the messages of an actor
+/// before they reach its handler, and observes the outcome of handling them.
+///
+/// Interceptors are shared by all the messages of the actor, so they should be cheap
+/// to call and use interior mutability for any state they need to track.
+pub trait MessageInterceptor<TMsg>: Send + Sync + 'static
+where
+    TMsg: Message,
+{
+    /// Called before the message is handled by the actor.
+    ///
+    /// * `actor` - The actor handling the message
+    /// * `message` - The message
+    ///
+    /// Returns [Some] with the message (which may be transformed) to continue handling it,
+    /// or [None] to reject it. A rejected message is dropped, meaning any reply port
+    /// inside of it is dropped and the caller receives a
+    /// [crate::rpc::CallResult::SenderError]. The remaining interceptors, the handler,
+    /// and the [MessageInterceptor::after_handle] hooks are skipped.
+    #[allow(unused_variables)]
+    fn before_handle(&self, actor: &ActorCell, message: TMsg) -> Option<TMsg> {
+        Some(message)
+    }
+
+    /// Called after the message has been handled by the actor.
+    ///
+    /// * `actor` - The actor that handled the message
+    /// * `result` - The result of the actor's handler. An error will fail the actor
+    ///   once all the interceptors have observed it.
+    /// * `elapsed` - How long the actor's handler took
+    ///
+    /// For messages handled together by [crate::Actor::handle_batch], this is called for
+    /// every message of the batch, with the batch's result and an even share of the time
+    /// the batch took.
+    #[allow(unused_variables)]
+    fn after_handle(
+        &self,
+        actor: &ActorCell,
+        result: &Result<(), ActorProcessingErr>,
+        elapsed: Duration,
+    ) {
+    }
+}
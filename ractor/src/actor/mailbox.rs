@@ -0,0 +1,190 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Bounded mailbox support for actors.
+//!
+//! By default an actor's message port is unbounded, meaning a slow consumer
+//! can grow memory without limit. An actor can opt-in to a bounded mailbox by
+//! overriding [crate::Actor::MAILBOX_CAPACITY], and select what should happen
+//! when the mailbox is full with [crate::Actor::MAILBOX_OVERFLOW_POLICY].
+//!
+//! Only user messages count towards the capacity. Signals, stop requests, supervision
+//! events, and drain markers are never subject to the bound.
+
+#[cfg(feature = "cluster")]
+use std::sync::atomic::AtomicUsize;
+#[cfg(feature = "cluster")]
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+
+use crate::concurrency::Semaphore;
+use crate::MessagePriority;
+
+/// What to do with a message when it's sent to an actor whose bounded mailbox
+/// is full
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum MailboxOverflowPolicy {
+    /// Reject the message, returning it to the sender in
+    /// [crate::MessagingErr::MailboxFull]
+    #[default]
+    Reject,
+    /// Wait asynchronously for space in the mailbox. This only applies to
+    /// [crate::ActorRef::send_message_wait], the synchronous
+    /// [crate::ActorRef::send_message] can't block so it behaves like
+    /// [MailboxOverflowPolicy::Reject] under this policy.
+    Block,
+    /// Accept the message, but discard the oldest message currently in the mailbox
+    /// to make room for it. When messages are sent with different [crate::MessagePriority]s,
+    /// the discarded message is the oldest one of the lowest priority.
+    DropOldest,
+}
+
+/// The reason a message couldn't be admitted into a bounded mailbox
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum AdmitErr {
+    /// The mailbox is at capacity
+    Full,
+    /// The mailbox has been closed, i.e. the actor has exited
+    Closed,
+}
+
+/// The accounting of the messages on one of the mailbox's priority lanes
+#[derive(Clone, Copy, Default)]
+struct LaneCount {
+    /// The number of messages admitted onto the lane which haven't been taken off it yet
+    enqueued: usize,
+    /// The number of messages which should be discarded from the head of the lane, due to
+    /// newer messages displacing them with [MailboxOverflowPolicy::DropOldest]
+    displaced: usize,
+}
+
+/// The accounting side of a bounded mailbox. The messages themselves still flow
+/// through the actor's message port, this only tracks how many are enqueued.
+pub(crate) struct BoundedMailbox {
+    policy: MailboxOverflowPolicy,
+    /// One permit per free slot in the mailbox
+    permits: Semaphore,
+    /// The messages enqueued on each of the priority lanes
+    lanes: Mutex<[LaneCount; MessagePriority::NUM_LANES]>,
+    /// The number of messages admitted beyond the capacity, which don't free up a slot
+    /// when they're taken off the queue
+    #[cfg(feature = "cluster")]
+    overdraft: AtomicUsize,
+}
+
+impl BoundedMailbox {
+    pub(crate) fn new(capacity: usize, policy: MailboxOverflowPolicy) -> Self {
+        // a mailbox which can never hold a message is useless, so we bound to at least 1
+        let capacity = capacity.clamp(1, Semaphore::MAX_PERMITS);
+        Self {
+            policy,
+            permits: Semaphore::new(capacity),
+            lanes: Mutex::new(Default::default()),
+            #[cfg(feature = "cluster")]
+            overdraft: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn policy(&self) -> MailboxOverflowPolicy {
+        self.policy
+    }
+
+    fn lanes(&self) -> std::sync::MutexGuard<'_, [LaneCount; MessagePriority::NUM_LANES]> {
+        self.lanes.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Try to reserve a slot for a new message on the `priority` lane without waiting
+    pub(crate) fn try_admit(&self, priority: MessagePriority) -> Result<(), AdmitErr> {
+        match self.permits.try_acquire() {
+            Ok(permit) => {
+                permit.forget();
+            }
+            Err(tokio::sync::TryAcquireError::Closed) => return Err(AdmitErr::Closed),
+            Err(tokio::sync::TryAcquireError::NoPermits) => {
+                if self.policy != MailboxOverflowPolicy::DropOldest {
+                    return Err(AdmitErr::Full);
+                }
+                // the new message takes over the slot of the oldest one of the lowest
+                // priority, which is discarded when it reaches the head of its lane
+                let mut lanes = self.lanes();
+                let Some(oldest) = lanes
+                    .iter_mut()
+                    .rev()
+                    .find(|lane| lane.enqueued > lane.displaced)
+                else {
+                    return Err(AdmitErr::Full);
+                };
+                oldest.displaced += 1;
+                lanes[priority.lane()].enqueued += 1;
+                return Ok(());
+            }
+        }
+        self.lanes()[priority.lane()].enqueued += 1;
+        Ok(())
+    }
+
+    /// Reserve a slot for a new message on the `priority` lane, waiting for room if the
+    /// policy is [MailboxOverflowPolicy::Block]
+    pub(crate) async fn admit(&self, priority: MessagePriority) -> Result<(), AdmitErr> {
+        if self.policy != MailboxOverflowPolicy::Block {
+            return self.try_admit(priority);
+        }
+        match self.permits.acquire().await {
+            Ok(permit) => {
+                permit.forget();
+                self.lanes()[priority.lane()].enqueued += 1;
+                Ok(())
+            }
+            Err(_) => Err(AdmitErr::Closed),
+        }
+    }
+
+    /// Admit a message replayed from a durable mailbox, which is never rejected nor displaces
+    /// another message, even if the mailbox is full
+    #[cfg(feature = "cluster")]
+    pub(crate) fn admit_replayed(&self) {
+        match self.permits.try_acquire() {
+            Ok(permit) => permit.forget(),
+            Err(_) => {
+                self.overdraft.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+        self.lanes()[MessagePriority::Normal.lane()].enqueued += 1;
+    }
+
+    /// Account for a message having been taken off the head of the `priority` lane.
+    ///
+    /// Returns [true] if the message was displaced by a newer one and should be discarded,
+    /// [false] if it should be processed.
+    pub(crate) fn dequeue(&self, priority: MessagePriority) -> bool {
+        let displaced = {
+            let mut lanes = self.lanes();
+            let lane = &mut lanes[priority.lane()];
+            lane.enqueued = lane.enqueued.saturating_sub(1);
+            let displaced = lane.displaced > 0;
+            if displaced {
+                lane.displaced -= 1;
+            }
+            displaced
+        };
+        #[cfg(feature = "cluster")]
+        let overdrawn = !displaced
+            && self
+                .overdraft
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok();
+        #[cfg(not(feature = "cluster"))]
+        let overdrawn = false;
+        if !displaced && !overdrawn {
+            self.permits.add_permits(1);
+        }
+        displaced
+    }
+
+    /// Close the mailbox, failing any pending or future admissions
+    pub(crate) fn close(&self) {
+        self.permits.close();
+    }
+}
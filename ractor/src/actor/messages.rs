@@ -103,6 +103,9 @@ pub enum SupervisionEvent {
     /// A process lifecycle event occurred
     #[cfg(feature = "cluster")]
     PidLifecycleEvent(crate::registry::PidLifecycleEvent),
+
+    /// A name watched with [crate::registry::monitor_name] changed
+    NameChanged(crate::registry::NameChangeEvent),
 }
 
 #[cfg(feature = "cluster")]
@@ -151,6 +154,7 @@ impl SupervisionEvent {
             }
             #[cfg(feature = "cluster")]
             Self::PidLifecycleEvent(evt) => Self::PidLifecycleEvent(evt.clone()),
+            Self::NameChanged(evt) => Self::NameChanged(evt.clone()),
         }
     }
 }
@@ -189,6 +193,9 @@ impl std::fmt::Display for SupervisionEvent {
             SupervisionEvent::PidLifecycleEvent(change) => {
                 write!(f, "PID lifecycle event {change:?}")
             }
+            SupervisionEvent::NameChanged(change) => {
+                write!(f, "Name changed {change:?}")
+            }
         }
     }
 }
@@ -0,0 +1,220 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Priority lanes for an actor's mailbox.
+//!
+//! An actor's user messages are split across a fixed number of lanes, one per
+//! [MessagePriority]. The actor always handles the next message from the highest
+//! priority lane which has one waiting, and messages within a lane are handled
+//! in FIFO order. Messages sent with [crate::ActorRef::send_message] travel on the
+//! [MessagePriority::Normal] lane, use [crate::ActorRef::send_message_with_priority]
+//! to pick another one.
+//!
+//! Priorities only order user messages amongst themselves. Signals, stop requests, and
+//! supervision events keep their precedence over all of them. Drain markers travel on the
+//! [MessagePriority::Low] lane, so that everything sent before them is handled first.
+//!
+//! A handler upgrade places a barrier on every lane. A lane is paused once it reaches its
+//! barrier, and the upgrade is applied when all of them have, so that every message sent
+//! before the upgrade is handled by the old handler and every message sent after it by the
+//! new one, whatever their priorities.
+
+use std::future::poll_fn;
+use std::sync::Mutex;
+use std::task::Poll;
+
+use crate::actor::actor_properties::MuxedMessage;
+use crate::concurrency as mpsc;
+use crate::concurrency::MpscUnboundedReceiver as InputPortReceiver;
+use crate::concurrency::MpscUnboundedSender as InputPort;
+
+/// The priority of a message sent to an actor
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum MessagePriority {
+    /// Handled ahead of any normal or low priority messages
+    High,
+    /// The priority of messages sent with [crate::ActorRef::send_message]
+    #[default]
+    Normal,
+    /// Handled only once there are no high or normal priority messages waiting
+    Low,
+}
+
+impl MessagePriority {
+    pub(crate) const NUM_LANES: usize = 3;
+
+    /// The priorities of the lanes, by lane index
+    const LANES: [MessagePriority; Self::NUM_LANES] = [Self::High, Self::Normal, Self::Low];
+
+    pub(crate) fn lane(self) -> usize {
+        match self {
+            Self::High => 0,
+            Self::Normal => 1,
+            Self::Low => 2,
+        }
+    }
+}
+
+/// What travels on a message lane
+enum LaneMessage {
+    Muxed(MuxedMessage),
+    /// The barrier of a handler upgrade, which travels on its own on the lanes other than
+    /// the one carrying the upgrade request
+    Barrier,
+}
+
+/// The sending side of an actor's message lanes
+pub(crate) struct PriorityLanes {
+    lanes: [InputPort<LaneMessage>; MessagePriority::NUM_LANES],
+    /// Serializes placing the barriers of concurrent upgrades, so they're in the same order
+    /// on every lane
+    barriers: Mutex<()>,
+}
+
+/// The receiving side of an actor's message lanes
+pub(crate) struct PriorityLaneReceivers {
+    lanes: [InputPortReceiver<LaneMessage>; MessagePriority::NUM_LANES],
+    /// The lanes which have reached the barrier of a pending upgrade
+    paused: [bool; MessagePriority::NUM_LANES],
+    /// The pending upgrade request, held until every lane has reached its barrier
+    upgrade: Option<MuxedMessage>,
+}
+
+/// Create a new set of message lanes
+pub(crate) fn priority_lanes() -> (PriorityLanes, PriorityLaneReceivers) {
+    let (tx_high, rx_high) = mpsc::mpsc_unbounded();
+    let (tx_normal, rx_normal) = mpsc::mpsc_unbounded();
+    let (tx_low, rx_low) = mpsc::mpsc_unbounded();
+    (
+        PriorityLanes {
+            lanes: [tx_high, tx_normal, tx_low],
+            barriers: Mutex::new(()),
+        },
+        PriorityLaneReceivers {
+            lanes: [rx_high, rx_normal, rx_low],
+            paused: [false; MessagePriority::NUM_LANES],
+            upgrade: None,
+        },
+    )
+}
+
+impl PriorityLanes {
+    /// Send a message on the lane for `priority`, returning the message if the
+    /// actor's message port is closed
+    pub(crate) fn send(
+        &self,
+        message: MuxedMessage,
+        priority: MessagePriority,
+    ) -> Result<(), Box<MuxedMessage>> {
+        self.lanes[priority.lane()]
+            .send(LaneMessage::Muxed(message))
+            .map_err(|e| match e.0 {
+                LaneMessage::Muxed(message) => Box::new(message),
+                LaneMessage::Barrier => unreachable!("Sent a message but got back a barrier"),
+            })
+    }
+
+    /// Send a handler upgrade request, placing a barrier on every lane. The upgrade is
+    /// received once all the messages sent ahead of it, on any lane, have been received.
+    pub(crate) fn send_upgrade(&self, upgrade: MuxedMessage) -> Result<(), Box<MuxedMessage>> {
+        let _guard = self.barriers.lock().unwrap_or_else(|e| e.into_inner());
+        for lane in self.lanes.iter().skip(1) {
+            if lane.send(LaneMessage::Barrier).is_err() {
+                return Err(Box::new(upgrade));
+            }
+        }
+        self.send(upgrade, MessagePriority::LANES[0])
+    }
+}
+
+impl PriorityLaneReceivers {
+    /// Receive the next message from the highest priority lane with one waiting, along with
+    /// the priority of its lane
+    ///
+    /// Returns [None] when the lanes are closed
+    pub(crate) async fn recv(&mut self) -> Option<(MessagePriority, MuxedMessage)> {
+        poll_fn(|cx| {
+            let mut pending = false;
+            for lane in 0..MessagePriority::NUM_LANES {
+                if self.paused[lane] {
+                    pending = true;
+                    continue;
+                }
+                match self.lanes[lane].poll_recv(cx) {
+                    Poll::Ready(Some(message)) => {
+                        if let Some(received) = self.received(lane, message) {
+                            return Poll::Ready(Some(received));
+                        }
+                        // the lane reached a barrier and is paused
+                        pending = true;
+                    }
+                    Poll::Ready(None) => {}
+                    Poll::Pending => pending = true,
+                }
+            }
+            if pending {
+                Poll::Pending
+            } else {
+                Poll::Ready(None)
+            }
+        })
+        .await
+    }
+
+    /// Receive the next message from the highest priority lane with one waiting, along with
+    /// the priority of its lane, without waiting for one
+    pub(crate) fn try_recv(&mut self) -> Option<(MessagePriority, MuxedMessage)> {
+        for lane in 0..MessagePriority::NUM_LANES {
+            if self.paused[lane] {
+                continue;
+            }
+            if let Ok(message) = self.lanes[lane].try_recv() {
+                if let Some(received) = self.received(lane, message) {
+                    return Some(received);
+                }
+            }
+        }
+        None
+    }
+
+    /// Handle a message taken off a lane, returning [None] if it's the barrier of an upgrade
+    /// which the other lanes haven't reached yet
+    fn received(
+        &mut self,
+        lane: usize,
+        message: LaneMessage,
+    ) -> Option<(MessagePriority, MuxedMessage)> {
+        let priority = MessagePriority::LANES[lane];
+        match message {
+            LaneMessage::Muxed(upgrade @ MuxedMessage::Upgrade(..)) => {
+                self.upgrade = Some(upgrade);
+            }
+            LaneMessage::Muxed(message) => return Some((priority, message)),
+            LaneMessage::Barrier => {}
+        }
+        self.paused[lane] = true;
+        if self.paused.iter().all(|paused| *paused) {
+            self.paused = [false; MessagePriority::NUM_LANES];
+            return self.upgrade.take().map(|upgrade| (priority, upgrade));
+        }
+        None
+    }
+
+    /// Close all the lanes, rejecting any further sends
+    pub(crate) fn close(&mut self) {
+        for lane in self.lanes.iter_mut() {
+            lane.close();
+        }
+    }
+
+    /// Flush any messages left in the lanes
+    pub(crate) fn flush(&mut self) {
+        for lane in self.lanes.iter_mut() {
+            while lane.try_recv().is_ok() {}
+        }
+        self.paused = [false; MessagePriority::NUM_LANES];
+        self.upgrade = None;
+    }
+}
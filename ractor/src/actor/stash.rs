@@ -0,0 +1,77 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Message stashing, allowing an actor to defer messages it can't handle in its current
+//! state and replay them later.
+//!
+//! Stashed messages are held aside until the actor calls [crate::ActorCell::unstash_all],
+//! at which point they're replayed in the order they were stashed, ahead of anything
+//! waiting in the actor's mailbox. Signals, stop requests, and supervision events keep
+//! their priority over replayed messages.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use crate::message::BoxedMessage;
+
+#[derive(Default)]
+struct StashQueues {
+    /// Messages which have been stashed and are waiting to be unstashed
+    stashed: VecDeque<BoxedMessage>,
+    /// Messages which have been unstashed and are waiting to be replayed to the actor
+    unstashed: VecDeque<BoxedMessage>,
+}
+
+/// The stash of an actor
+pub(crate) struct MessageStash {
+    capacity: usize,
+    queues: Mutex<StashQueues>,
+}
+
+impl MessageStash {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            queues: Mutex::new(StashQueues::default()),
+        }
+    }
+
+    /// Stash a message, returning it if the stash is full
+    pub(crate) fn stash(&self, message: BoxedMessage) -> Result<(), Box<BoxedMessage>> {
+        let mut queues = self.queues.lock().unwrap();
+        if queues.stashed.len() >= self.capacity {
+            return Err(Box::new(message));
+        }
+        queues.stashed.push_back(message);
+        Ok(())
+    }
+
+    /// Move all the stashed messages to the replay queue
+    pub(crate) fn unstash_all(&self) {
+        let mut queues = self.queues.lock().unwrap();
+        let stashed = std::mem::take(&mut queues.stashed);
+        queues.unstashed.extend(stashed);
+    }
+
+    /// The number of messages currently stashed (not including those unstashed but not yet replayed)
+    pub(crate) fn len(&self) -> usize {
+        self.queues.lock().unwrap().stashed.len()
+    }
+
+    pub(crate) fn has_unstashed(&self) -> bool {
+        !self.queues.lock().unwrap().unstashed.is_empty()
+    }
+
+    pub(crate) fn pop_unstashed(&self) -> Option<BoxedMessage> {
+        self.queues.lock().unwrap().unstashed.pop_front()
+    }
+
+    /// Drop all the stashed and unstashed messages
+    pub(crate) fn clear(&self) {
+        let queues = std::mem::take(&mut *self.queues.lock().unwrap());
+        // drop the messages outside of the lock
+        drop(queues);
+    }
+}
@@ -0,0 +1,214 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Statistics collection for actors.
+//!
+//! Every actor keeps a small set of counters about its mailbox and message handling,
+//! which can be read at any time with [crate::ActorCell::get_stats]. Additionally an
+//! [ActorStatsLayer] can be attached when spawning the actor (see [crate::SpawnOptions])
+//! to export these events to a metrics system as they happen.
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use once_cell::sync::OnceCell;
+
+use crate::concurrency::Duration;
+use crate::ActorCell;
+use crate::SupervisionEvent;
+
+/// A wrapper over whatever stats collection a user wishes to utilize
+/// for an actor. Attached to the actor with [crate::SpawnOptions].
+pub trait ActorStatsLayer: Send + Sync + 'static {
+    /// Called when the actor has handled a message
+    ///
+    /// * `actor` - The actor which handled the message
+    /// * `elapsed` - How long the actor's handler took
+    /// * `mailbox_depth` - The number of messages still waiting in the actor's mailbox
+    fn message_handled(&self, actor: &ActorCell, elapsed: Duration, mailbox_depth: usize);
+
+    /// Called when the actor drops a request which passed its deadline, without handling it
+    #[allow(unused_variables)]
+    fn message_expired(&self, actor: &ActorCell) {}
+
+    /// Called when the actor has handled a supervision event
+    #[allow(unused_variables)]
+    fn supervision_event(&self, actor: &ActorCell, event: &SupervisionEvent) {}
+
+    /// Called when a supervisor (e.g. [crate::supervisor::Supervisor]) restarts one
+    /// of its children
+    #[allow(unused_variables)]
+    fn child_restarted(&self, supervisor: &ActorCell, child_id: &str) {}
+}
+
+/// The upper bounds of the [LatencyHistogram] buckets, the last bucket is unbounded
+const LATENCY_BUCKETS: [Duration; 6] = [
+    Duration::from_micros(10),
+    Duration::from_micros(100),
+    Duration::from_millis(1),
+    Duration::from_millis(10),
+    Duration::from_millis(100),
+    Duration::from_secs(1),
+];
+
+/// A histogram of how long an actor's handler takes per message
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct LatencyHistogram {
+    counts: [u64; LATENCY_BUCKETS.len() + 1],
+    total: Duration,
+    max: Duration,
+}
+
+impl LatencyHistogram {
+    /// The buckets of the histogram, as (inclusive upper bound, count) pairs. The last
+    /// bucket has no upper bound.
+    pub fn buckets(&self) -> impl Iterator<Item = (Option<Duration>, u64)> + '_ {
+        LATENCY_BUCKETS
+            .iter()
+            .copied()
+            .map(Some)
+            .chain(std::iter::once(None))
+            .zip(self.counts.iter().copied())
+    }
+
+    /// The number of samples in the histogram
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// The mean latency, or [None] if there are no samples
+    pub fn mean(&self) -> Option<Duration> {
+        let count = self.count();
+        if count == 0 {
+            None
+        } else {
+            Some(Duration::from_nanos(
+                (self.total.as_nanos() / count as u128) as u64,
+            ))
+        }
+    }
+
+    /// The largest latency observed
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+}
+
+/// A point-in-time snapshot of an actor's statistics
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ActorStats {
+    /// The number of messages which have been accepted into the actor's mailbox
+    pub messages_received: u64,
+    /// The number of messages the actor has handled
+    pub messages_handled: u64,
+    /// The number of requests the actor dropped because they passed their deadline
+    pub messages_expired: u64,
+    /// The number of messages currently waiting in the actor's mailbox
+    pub mailbox_depth: usize,
+    /// The number of supervision events the actor has handled
+    pub supervision_events: u64,
+    /// The number of children this actor has restarted, if it's a supervisor
+    pub restarts: u64,
+    /// How long the actor's handler took per message
+    pub handle_latency: LatencyHistogram,
+}
+
+/// The live counters of an actor, shared between its [crate::ActorCell]s and its runtime
+#[derive(Default)]
+pub(crate) struct ActorStatsCollector {
+    received: AtomicU64,
+    handled: AtomicU64,
+    expired: AtomicU64,
+    depth: AtomicUsize,
+    supervision_events: AtomicU64,
+    restarts: AtomicU64,
+    latency_counts: [AtomicU64; LATENCY_BUCKETS.len() + 1],
+    latency_total_ns: AtomicU64,
+    latency_max_ns: AtomicU64,
+    layer: OnceCell<Arc<dyn ActorStatsLayer>>,
+}
+
+impl ActorStatsCollector {
+    /// Attach the stats layer. This can only happen once, before the actor starts.
+    pub(crate) fn set_layer(&self, layer: Arc<dyn ActorStatsLayer>) {
+        let _ = self.layer.set(layer);
+    }
+
+    /// A message is being put into the mailbox. This is recorded before the message is
+    /// sent, so that the actor can never observe it before it's counted.
+    pub(crate) fn message_enqueued(&self) {
+        self.received.fetch_add(1, Ordering::Relaxed);
+        self.depth.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A message recorded with [ActorStatsCollector::message_enqueued] failed to be sent
+    pub(crate) fn message_enqueue_failed(&self) {
+        self.received.fetch_sub(1, Ordering::Relaxed);
+        self.depth.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// A message was taken out of the mailbox
+    pub(crate) fn message_dequeued(&self) {
+        self.depth.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn message_handled(&self, actor: &ActorCell, elapsed: Duration) {
+        self.handled.fetch_add(1, Ordering::Relaxed);
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| elapsed <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.latency_counts[bucket].fetch_add(1, Ordering::Relaxed);
+        let nanos = elapsed.as_nanos().min(u64::MAX as u128) as u64;
+        self.latency_total_ns.fetch_add(nanos, Ordering::Relaxed);
+        self.latency_max_ns.fetch_max(nanos, Ordering::Relaxed);
+        if let Some(layer) = self.layer.get() {
+            layer.message_handled(actor, elapsed, self.depth.load(Ordering::Relaxed));
+        }
+    }
+
+    pub(crate) fn message_expired(&self, actor: &ActorCell) {
+        self.expired.fetch_add(1, Ordering::Relaxed);
+        if let Some(layer) = self.layer.get() {
+            layer.message_expired(actor);
+        }
+    }
+
+    pub(crate) fn supervision_event(&self, actor: &ActorCell, event: &SupervisionEvent) {
+        self.supervision_events.fetch_add(1, Ordering::Relaxed);
+        if let Some(layer) = self.layer.get() {
+            layer.supervision_event(actor, event);
+        }
+    }
+
+    pub(crate) fn child_restarted(&self, supervisor: &ActorCell, child_id: &str) {
+        self.restarts.fetch_add(1, Ordering::Relaxed);
+        if let Some(layer) = self.layer.get() {
+            layer.child_restarted(supervisor, child_id);
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> ActorStats {
+        let mut counts = [0u64; LATENCY_BUCKETS.len() + 1];
+        for (count, counter) in counts.iter_mut().zip(self.latency_counts.iter()) {
+            *count = counter.load(Ordering::Relaxed);
+        }
+        ActorStats {
+            messages_received: self.received.load(Ordering::Relaxed),
+            messages_handled: self.handled.load(Ordering::Relaxed),
+            messages_expired: self.expired.load(Ordering::Relaxed),
+            mailbox_depth: self.depth.load(Ordering::Relaxed),
+            supervision_events: self.supervision_events.load(Ordering::Relaxed),
+            restarts: self.restarts.load(Ordering::Relaxed),
+            handle_latency: LatencyHistogram {
+                counts,
+                total: Duration::from_nanos(self.latency_total_ns.load(Ordering::Relaxed)),
+                max: Duration::from_nanos(self.latency_max_ns.load(Ordering::Relaxed)),
+            },
+        }
+    }
+}
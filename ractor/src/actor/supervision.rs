@@ -84,6 +84,17 @@ impl SupervisionTree {
         }
     }
 
+    /// Return the ids of all the monitors of this supervision tree
+    #[cfg(feature = "monitors")]
+    pub(crate) fn get_monitors(&self) -> Vec<ActorId> {
+        let guard = self.monitors.lock().unwrap();
+        if let Some(map) = &*guard {
+            map.keys().cloned().collect()
+        } else {
+            vec![]
+        }
+    }
+
     /// Terminate all your supervised children and unlink them
     /// from the supervision tree since the supervisor is shutting down
     /// and can't deal with superivison events anyways
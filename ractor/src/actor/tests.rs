@@ -84,244 +84,256 @@ async fn test_error_on_start_captured() {
     assert!(matches!(actor_output, Err(SpawnErr::StartupFailed(_))));
 }
 
-#[crate::concurrency::test]
-#[cfg_attr(
-    not(all(target_arch = "wasm32", target_os = "unknown")),
-    tracing_test::traced_test
-)]
-async fn test_stop_higher_priority_over_messages() {
-    let message_counter = Arc::new(AtomicU8::new(0u8));
-
-    struct TestActor {
-        counter: Arc<AtomicU8>,
-    }
-
-    #[cfg_attr(feature = "async-trait", crate::async_trait)]
-    impl Actor for TestActor {
-        type Msg = EmptyMessage;
-        type Arguments = ();
-        type State = ();
-
-        async fn pre_start(
-            &self,
-            _this_actor: crate::ActorRef<Self::Msg>,
-            _: (),
-        ) -> Result<Self::State, ActorProcessingErr> {
-            Ok(())
+#[test]
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+#[tracing_test::traced_test]
+fn test_stop_higher_priority_over_messages() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        let message_counter = Arc::new(AtomicU8::new(0u8));
+
+        struct TestActor {
+            counter: Arc<AtomicU8>,
         }
 
-        async fn handle(
-            &self,
-            _myself: ActorRef<Self::Msg>,
-            _message: Self::Msg,
-            _state: &mut Self::State,
-        ) -> Result<(), ActorProcessingErr> {
-            self.counter.fetch_add(1, Ordering::Relaxed);
-            crate::concurrency::sleep(Duration::from_millis(100)).await;
-            Ok(())
+        #[cfg_attr(feature = "async-trait", crate::async_trait)]
+        impl Actor for TestActor {
+            type Msg = EmptyMessage;
+            type Arguments = ();
+            type State = ();
+
+            async fn pre_start(
+                &self,
+                _this_actor: crate::ActorRef<Self::Msg>,
+                _: (),
+            ) -> Result<Self::State, ActorProcessingErr> {
+                Ok(())
+            }
+
+            async fn handle(
+                &self,
+                _myself: ActorRef<Self::Msg>,
+                _message: Self::Msg,
+                _state: &mut Self::State,
+            ) -> Result<(), ActorProcessingErr> {
+                self.counter.fetch_add(1, Ordering::Relaxed);
+                crate::concurrency::sleep(Duration::from_millis(100)).await;
+                Ok(())
+            }
         }
-    }
 
-    let (actor, handle) = Actor::spawn(
-        None,
-        TestActor {
-            counter: message_counter.clone(),
-        },
-        (),
-    )
-    .await
-    .expect("Actor failed to start");
+        let (actor, handle) = Actor::spawn(
+            None,
+            TestActor {
+                counter: message_counter.clone(),
+            },
+            (),
+        )
+        .await
+        .expect("Actor failed to start");
 
-    #[cfg(feature = "cluster")]
-    assert!(!actor.supports_remoting());
+        #[cfg(feature = "cluster")]
+        assert!(!actor.supports_remoting());
 
-    // pump 10 messages on the queue
-    for _i in 0..10 {
-        actor
-            .send_message(EmptyMessage)
-            .expect("Failed to send message to actor");
-    }
+        // pump 10 messages on the queue
+        for _i in 0..10 {
+            actor
+                .send_message(EmptyMessage)
+                .expect("Failed to send message to actor");
+        }
 
-    // give some time to process the first message and start sleeping
-    crate::concurrency::sleep(Duration::from_millis(10)).await;
+        // give some time to process the first message and start sleeping
+        crate::testkit::advance(Duration::from_millis(10)).await;
 
-    // followed by the "stop" signal
-    actor.stop(None);
+        // followed by the "stop" signal
+        actor.stop(None);
 
-    // current async work should complete, so we're still "running" sleeping
-    // on the first message
-    crate::concurrency::sleep(Duration::from_millis(10)).await;
-    assert_eq!(ActorStatus::Running, actor.get_status());
-    assert!(!handle.is_finished());
+        // current async work should complete, so we're still "running" sleeping
+        // on the first message
+        crate::testkit::advance(Duration::from_millis(10)).await;
+        assert_eq!(ActorStatus::Running, actor.get_status());
+        assert!(!handle.is_finished());
 
-    // now wait enough time for the first iteration to complete
-    crate::concurrency::sleep(Duration::from_millis(150)).await;
+        // now wait enough time for the first iteration to complete
+        crate::testkit::advance(Duration::from_millis(150)).await;
 
-    tracing::info!("Counter: {}", message_counter.load(Ordering::Relaxed));
+        tracing::info!("Counter: {}", message_counter.load(Ordering::Relaxed));
 
-    // actor should have "stopped"
-    assert_eq!(ActorStatus::Stopped, actor.get_status());
-    assert!(handle.is_finished());
-    // counter should have bumped only a single time
-    assert_eq!(1, message_counter.load(Ordering::Relaxed));
+        // actor should have "stopped"
+        assert_eq!(ActorStatus::Stopped, actor.get_status());
+        assert!(handle.is_finished());
+        // counter should have bumped only a single time
+        assert_eq!(1, message_counter.load(Ordering::Relaxed));
+    });
 }
 
-#[crate::concurrency::test]
-#[cfg_attr(
-    not(all(target_arch = "wasm32", target_os = "unknown")),
-    tracing_test::traced_test
-)]
-async fn test_kill_terminates_work() {
-    struct TestActor;
-
-    #[cfg_attr(feature = "async-trait", crate::async_trait)]
-    impl Actor for TestActor {
-        type Msg = EmptyMessage;
-        type Arguments = ();
-        type State = ();
-
-        async fn pre_start(
-            &self,
-            _this_actor: crate::ActorRef<Self::Msg>,
-            _: (),
-        ) -> Result<Self::State, ActorProcessingErr> {
-            Ok(())
-        }
+#[test]
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+#[tracing_test::traced_test]
+fn test_kill_terminates_work() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        struct TestActor;
+
+        #[cfg_attr(feature = "async-trait", crate::async_trait)]
+        impl Actor for TestActor {
+            type Msg = EmptyMessage;
+            type Arguments = ();
+            type State = ();
+
+            async fn pre_start(
+                &self,
+                _this_actor: crate::ActorRef<Self::Msg>,
+                _: (),
+            ) -> Result<Self::State, ActorProcessingErr> {
+                Ok(())
+            }
 
-        async fn handle(
-            &self,
-            _myself: ActorRef<Self::Msg>,
-            _message: Self::Msg,
-            _state: &mut Self::State,
-        ) -> Result<(), ActorProcessingErr> {
-            crate::concurrency::sleep(Duration::from_secs(10)).await;
-            Ok(())
+            async fn handle(
+                &self,
+                _myself: ActorRef<Self::Msg>,
+                _message: Self::Msg,
+                _state: &mut Self::State,
+            ) -> Result<(), ActorProcessingErr> {
+                crate::concurrency::sleep(Duration::from_secs(10)).await;
+                Ok(())
+            }
         }
-    }
 
-    let (actor, handle) = Actor::spawn(None, TestActor, ())
-        .await
-        .expect("Actor failed to start");
+        let (actor, handle) = Actor::spawn(None, TestActor, ())
+            .await
+            .expect("Actor failed to start");
 
-    actor
-        .send_message(EmptyMessage)
-        .expect("Failed to send message to actor");
-    crate::concurrency::sleep(Duration::from_millis(10)).await;
+        actor
+            .send_message(EmptyMessage)
+            .expect("Failed to send message to actor");
+        crate::testkit::advance(Duration::from_millis(10)).await;
 
-    actor.kill();
-    crate::concurrency::sleep(Duration::from_millis(10)).await;
+        actor.kill();
+        crate::testkit::advance(Duration::from_millis(10)).await;
 
-    assert_eq!(ActorStatus::Stopped, actor.get_status());
-    assert!(handle.is_finished());
+        assert_eq!(ActorStatus::Stopped, actor.get_status());
+        assert!(handle.is_finished());
+    });
 }
 
-#[crate::concurrency::test]
-#[cfg_attr(
-    not(all(target_arch = "wasm32", target_os = "unknown")),
-    tracing_test::traced_test
-)]
-async fn test_stop_does_not_terminate_async_work() {
-    struct TestActor;
-
-    #[cfg_attr(feature = "async-trait", crate::async_trait)]
-    impl Actor for TestActor {
-        type Msg = EmptyMessage;
-        type Arguments = ();
-        type State = ();
-
-        async fn pre_start(
-            &self,
-            _this_actor: crate::ActorRef<Self::Msg>,
-            _: (),
-        ) -> Result<Self::State, ActorProcessingErr> {
-            Ok(())
-        }
+#[test]
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+#[tracing_test::traced_test]
+fn test_stop_does_not_terminate_async_work() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        struct TestActor;
+
+        #[cfg_attr(feature = "async-trait", crate::async_trait)]
+        impl Actor for TestActor {
+            type Msg = EmptyMessage;
+            type Arguments = ();
+            type State = ();
+
+            async fn pre_start(
+                &self,
+                _this_actor: crate::ActorRef<Self::Msg>,
+                _: (),
+            ) -> Result<Self::State, ActorProcessingErr> {
+                Ok(())
+            }
 
-        async fn handle(
-            &self,
-            _myself: ActorRef<Self::Msg>,
-            _message: Self::Msg,
-            _state: &mut Self::State,
-        ) -> Result<(), ActorProcessingErr> {
-            crate::concurrency::sleep(Duration::from_millis(100)).await;
-            Ok(())
+            async fn handle(
+                &self,
+                _myself: ActorRef<Self::Msg>,
+                _message: Self::Msg,
+                _state: &mut Self::State,
+            ) -> Result<(), ActorProcessingErr> {
+                crate::concurrency::sleep(Duration::from_millis(100)).await;
+                Ok(())
+            }
         }
-    }
 
-    let (actor, handle) = Actor::spawn(None, TestActor, ())
-        .await
-        .expect("Actor failed to start");
+        let (actor, handle) = Actor::spawn(None, TestActor, ())
+            .await
+            .expect("Actor failed to start");
 
-    // send a work message followed by a stop message
-    actor
-        .send_message(EmptyMessage)
-        .expect("Failed to send message to actor");
-    crate::concurrency::sleep(Duration::from_millis(10)).await;
-    actor.stop(None);
+        // send a work message followed by a stop message
+        actor
+            .send_message(EmptyMessage)
+            .expect("Failed to send message to actor");
+        crate::testkit::advance(Duration::from_millis(10)).await;
+        actor.stop(None);
 
-    // async work should complete, so we're still "running" sleeping
-    crate::concurrency::sleep(Duration::from_millis(10)).await;
-    assert_eq!(ActorStatus::Running, actor.get_status());
-    assert!(!handle.is_finished());
+        // async work should complete, so we're still "running" sleeping
+        crate::testkit::advance(Duration::from_millis(10)).await;
+        assert_eq!(ActorStatus::Running, actor.get_status());
+        assert!(!handle.is_finished());
 
-    periodic_check(
-        || ActorStatus::Stopped == actor.get_status(),
-        Duration::from_millis(500),
-    )
-    .await;
+        periodic_check(
+            || ActorStatus::Stopped == actor.get_status(),
+            Duration::from_millis(500),
+        )
+        .await;
 
-    assert!(handle.is_finished());
+        assert!(handle.is_finished());
+    });
 }
 
-#[crate::concurrency::test]
-#[cfg_attr(
-    not(all(target_arch = "wasm32", target_os = "unknown")),
-    tracing_test::traced_test
-)]
-async fn test_kill_terminates_supervision_work() {
-    struct TestActor;
-
-    #[cfg_attr(feature = "async-trait", crate::async_trait)]
-    impl Actor for TestActor {
-        type Msg = EmptyMessage;
-        type Arguments = ();
-        type State = ();
-
-        async fn pre_start(
-            &self,
-            _this_actor: crate::ActorRef<Self::Msg>,
-            _: (),
-        ) -> Result<Self::State, ActorProcessingErr> {
-            Ok(())
-        }
+#[test]
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+#[tracing_test::traced_test]
+fn test_kill_terminates_supervision_work() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        struct TestActor;
+
+        #[cfg_attr(feature = "async-trait", crate::async_trait)]
+        impl Actor for TestActor {
+            type Msg = EmptyMessage;
+            type Arguments = ();
+            type State = ();
+
+            async fn pre_start(
+                &self,
+                _this_actor: crate::ActorRef<Self::Msg>,
+                _: (),
+            ) -> Result<Self::State, ActorProcessingErr> {
+                Ok(())
+            }
 
-        async fn handle_supervisor_evt(
-            &self,
-            _myself: ActorRef<Self::Msg>,
-            _message: SupervisionEvent,
-            _state: &mut Self::State,
-        ) -> Result<(), ActorProcessingErr> {
-            crate::concurrency::sleep(Duration::from_millis(100)).await;
-            Ok(())
+            async fn handle_supervisor_evt(
+                &self,
+                _myself: ActorRef<Self::Msg>,
+                _message: SupervisionEvent,
+                _state: &mut Self::State,
+            ) -> Result<(), ActorProcessingErr> {
+                crate::concurrency::sleep(Duration::from_millis(100)).await;
+                Ok(())
+            }
         }
-    }
 
-    let (actor, handle) = Actor::spawn(None, TestActor, ())
-        .await
-        .expect("Actor failed to start");
+        let (actor, handle) = Actor::spawn(None, TestActor, ())
+            .await
+            .expect("Actor failed to start");
 
-    // send some dummy event to cause the supervision stuff to hang
-    let actor_cell: ActorCell = actor.clone().into();
-    actor
-        .send_supervisor_evt(SupervisionEvent::ActorStarted(actor_cell))
-        .expect("Failed to send message to actor");
-    crate::concurrency::sleep(Duration::from_millis(10)).await;
+        // send some dummy event to cause the supervision stuff to hang
+        let actor_cell: ActorCell = actor.clone().into();
+        actor
+            .send_supervisor_evt(SupervisionEvent::ActorStarted(actor_cell))
+            .expect("Failed to send message to actor");
+        crate::testkit::advance(Duration::from_millis(10)).await;
 
-    actor.kill();
-    crate::concurrency::sleep(Duration::from_millis(10)).await;
+        actor.kill();
+        crate::testkit::advance(Duration::from_millis(10)).await;
 
-    assert_eq!(ActorStatus::Stopped, actor.get_status());
-    assert!(handle.is_finished());
+        assert_eq!(ActorStatus::Stopped, actor.get_status());
+        assert!(handle.is_finished());
+    });
 }
 
 #[crate::concurrency::test]
@@ -1074,60 +1086,63 @@ async fn actor_failing_in_spawn_err_doesnt_poison_registries() {
 }
 
 /// https://github.com/slawlor/ractor/issues/254
-#[crate::concurrency::test]
-#[cfg_attr(
-    not(all(target_arch = "wasm32", target_os = "unknown")),
-    tracing_test::traced_test
-)]
-async fn actor_post_stop_executed_before_stop_and_wait_returns() {
-    struct TestActor {
-        signal: Arc<AtomicU8>,
-    }
-
-    #[cfg_attr(feature = "async-trait", crate::async_trait)]
-    impl Actor for TestActor {
-        type Msg = EmptyMessage;
-        type Arguments = ();
-        type State = ();
-
-        async fn pre_start(
-            &self,
-            _this_actor: crate::ActorRef<Self::Msg>,
-            _: (),
-        ) -> Result<Self::State, ActorProcessingErr> {
-            Ok(())
+#[test]
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+#[tracing_test::traced_test]
+fn actor_post_stop_executed_before_stop_and_wait_returns() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        struct TestActor {
+            signal: Arc<AtomicU8>,
         }
 
-        async fn post_stop(
-            &self,
-            _: ActorRef<Self::Msg>,
-            _: &mut Self::State,
-        ) -> Result<(), ActorProcessingErr> {
-            sleep(Duration::from_millis(1000)).await;
-            self.signal.store(1, Ordering::SeqCst);
-            Ok(())
-        }
-    }
+        #[cfg_attr(feature = "async-trait", crate::async_trait)]
+        impl Actor for TestActor {
+            type Msg = EmptyMessage;
+            type Arguments = ();
+            type State = ();
+
+            async fn pre_start(
+                &self,
+                _this_actor: crate::ActorRef<Self::Msg>,
+                _: (),
+            ) -> Result<Self::State, ActorProcessingErr> {
+                Ok(())
+            }
 
-    let signal = Arc::new(AtomicU8::new(0));
-    let (actor, handle) = Actor::spawn(
-        None,
-        TestActor {
-            signal: signal.clone(),
-        },
-        (),
-    )
-    .await
-    .expect("Failed to spawn test actor");
+            async fn post_stop(
+                &self,
+                _: ActorRef<Self::Msg>,
+                _: &mut Self::State,
+            ) -> Result<(), ActorProcessingErr> {
+                sleep(Duration::from_millis(1000)).await;
+                self.signal.store(1, Ordering::SeqCst);
+                Ok(())
+            }
+        }
 
-    actor
-        .stop_and_wait(None, None)
+        let signal = Arc::new(AtomicU8::new(0));
+        let (actor, handle) = Actor::spawn(
+            None,
+            TestActor {
+                signal: signal.clone(),
+            },
+            (),
+        )
         .await
-        .expect("Failed to stop and wait");
+        .expect("Failed to spawn test actor");
 
-    assert_eq!(1, signal.load(Ordering::SeqCst));
+        actor
+            .stop_and_wait(None, None)
+            .await
+            .expect("Failed to stop and wait");
 
-    handle.await.unwrap();
+        assert_eq!(1, signal.load(Ordering::SeqCst));
+
+        handle.await.unwrap();
+    });
 }
 
 #[crate::concurrency::test]
@@ -1466,3 +1481,1329 @@ async fn can_use_call_in_actor() {
 
     assert!(result == 42);
 }
+
+/// An actor with a mailbox bounded to 2 messages which records the messages it handles,
+/// and blocks in each handle until the test releases it.
+///
+/// `POLICY` maps to 0 = Reject, 1 = Block, 2 = DropOldest
+struct GatedMailboxActor<const POLICY: u8> {
+    gate: Arc<crate::concurrency::Semaphore>,
+    received: Arc<std::sync::Mutex<Vec<u32>>>,
+}
+
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+impl<const POLICY: u8> Actor for GatedMailboxActor<POLICY> {
+    type Msg = u32;
+    type Arguments = ();
+    type State = ();
+
+    const MAILBOX_CAPACITY: Option<usize> = Some(2);
+    const MAILBOX_OVERFLOW_POLICY: crate::MailboxOverflowPolicy = match POLICY {
+        0 => crate::MailboxOverflowPolicy::Reject,
+        1 => crate::MailboxOverflowPolicy::Block,
+        _ => crate::MailboxOverflowPolicy::DropOldest,
+    };
+
+    async fn pre_start(
+        &self,
+        _this_actor: crate::ActorRef<Self::Msg>,
+        _: (),
+    ) -> Result<Self::State, ActorProcessingErr> {
+        Ok(())
+    }
+
+    async fn handle(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        message: Self::Msg,
+        _state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        self.received.lock().unwrap().push(message);
+        self.gate.acquire().await?.forget();
+        Ok(())
+    }
+}
+
+/// Spawn a [GatedMailboxActor] and wait until it's blocked handling the message `0`,
+/// so that the mailbox is empty
+async fn spawn_gated_mailbox_actor<const POLICY: u8>() -> (
+    ActorRef<u32>,
+    crate::concurrency::JoinHandle<()>,
+    Arc<crate::concurrency::Semaphore>,
+    Arc<std::sync::Mutex<Vec<u32>>>,
+) {
+    let gate = Arc::new(crate::concurrency::Semaphore::new(0));
+    let received = Arc::new(std::sync::Mutex::new(vec![]));
+    let (actor, handle) = Actor::spawn(
+        None,
+        GatedMailboxActor::<POLICY> {
+            gate: gate.clone(),
+            received: received.clone(),
+        },
+        (),
+    )
+    .await
+    .expect("Failed to start actor");
+
+    actor.send_message(0).expect("Failed to send message");
+    let r = received.clone();
+    periodic_check(|| r.lock().unwrap().len() == 1, Duration::from_secs(1)).await;
+
+    (actor, handle, gate, received)
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_bounded_mailbox_rejects_when_full() {
+    let (actor, handle, gate, received) = spawn_gated_mailbox_actor::<0>().await;
+
+    actor.send_message(1).expect("Failed to send message");
+    actor.send_message(2).expect("Failed to send message");
+    let err = actor.send_message(3).expect_err("Mailbox should be full");
+    assert!(matches!(err, MessagingErr::MailboxFull(3)));
+    let err = actor
+        .send_message_wait(3)
+        .await
+        .expect_err("Mailbox should be full");
+    assert!(matches!(err, MessagingErr::MailboxFull(3)));
+
+    // free up the actor, which frees up the mailbox slots
+    gate.add_permits(3);
+    periodic_check(
+        || received.lock().unwrap().len() == 3,
+        Duration::from_secs(1),
+    )
+    .await;
+    actor.send_message(3).expect("Failed to send message");
+    gate.add_permits(1);
+    periodic_check(
+        || received.lock().unwrap().len() == 4,
+        Duration::from_secs(1),
+    )
+    .await;
+    assert_eq!(vec![0, 1, 2, 3], *received.lock().unwrap());
+
+    actor.stop(None);
+    handle.await.unwrap();
+}
+
+#[test]
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+#[tracing_test::traced_test]
+fn test_bounded_mailbox_blocks_when_full() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        let (actor, handle, gate, received) = spawn_gated_mailbox_actor::<1>().await;
+
+        actor.send_message(1).expect("Failed to send message");
+        actor
+            .send_message_wait(2)
+            .await
+            .expect("Failed to send message");
+        // the non-blocking send can't wait, so it's rejected
+        let err = actor.send_message(3).expect_err("Mailbox should be full");
+        assert!(matches!(err, MessagingErr::MailboxFull(3)));
+
+        let sender = actor.clone();
+        let blocked = crate::concurrency::spawn(async move { sender.send_message_wait(3).await });
+        crate::testkit::yield_rounds().await;
+        assert!(!blocked.is_finished());
+
+        // handling one message frees up a slot for the blocked sender
+        gate.add_permits(1);
+        blocked
+            .await
+            .unwrap()
+            .expect("Blocked sender should have been admitted");
+
+        gate.add_permits(3);
+        periodic_check(
+            || received.lock().unwrap().len() == 4,
+            Duration::from_secs(1),
+        )
+        .await;
+        assert_eq!(vec![0, 1, 2, 3], *received.lock().unwrap());
+
+        actor.stop(None);
+        handle.await.unwrap();
+    });
+}
+
+#[test]
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+#[tracing_test::traced_test]
+fn test_bounded_mailbox_blocked_sender_fails_on_exit() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        let (actor, handle, _gate, _received) = spawn_gated_mailbox_actor::<1>().await;
+
+        actor.send_message(1).expect("Failed to send message");
+        actor.send_message(2).expect("Failed to send message");
+
+        let sender = actor.clone();
+        let blocked = crate::concurrency::spawn(async move { sender.send_message_wait(3).await });
+        crate::testkit::yield_rounds().await;
+
+        actor.kill();
+        handle.await.unwrap();
+
+        let err = blocked
+            .await
+            .unwrap()
+            .expect_err("Send to a dead actor should fail");
+        assert!(matches!(err, MessagingErr::SendErr(3)));
+    });
+}
+
+#[test]
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+#[tracing_test::traced_test]
+fn test_bounded_mailbox_drops_oldest_when_full() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        let (actor, handle, gate, received) = spawn_gated_mailbox_actor::<2>().await;
+
+        for i in 1..=4 {
+            actor.send_message(i).expect("Failed to send message");
+        }
+
+        gate.add_permits(10);
+        periodic_check(
+            || received.lock().unwrap().len() == 3,
+            Duration::from_secs(1),
+        )
+        .await;
+        // give any erroneously retained messages a chance to be processed
+        crate::testkit::yield_rounds().await;
+        assert_eq!(vec![0, 3, 4], *received.lock().unwrap());
+
+        actor.stop(None);
+        handle.await.unwrap();
+    });
+}
+
+#[test]
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+#[tracing_test::traced_test]
+fn test_bounded_mailbox_drops_oldest_of_lowest_priority() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        let (actor, handle, gate, received) = spawn_gated_mailbox_actor::<2>().await;
+
+        actor
+            .send_message_with_priority(1, crate::MessagePriority::High)
+            .expect("Failed to send message");
+        actor
+            .send_message_with_priority(2, crate::MessagePriority::Low)
+            .expect("Failed to send message");
+        // displaces the low priority message, not the high priority one ahead of it
+        actor
+            .send_message_with_priority(3, crate::MessagePriority::Normal)
+            .expect("Failed to send message");
+        // displaces the normal priority message, the only one left below high
+        actor
+            .send_message_wait_with_priority(4, crate::MessagePriority::Low)
+            .await
+            .expect("Failed to send message");
+
+        gate.add_permits(10);
+        periodic_check(
+            || received.lock().unwrap().len() == 3,
+            Duration::from_secs(1),
+        )
+        .await;
+        // give any erroneously retained messages a chance to be processed
+        crate::testkit::yield_rounds().await;
+        assert_eq!(vec![0, 1, 4], *received.lock().unwrap());
+
+        actor.stop(None);
+        handle.await.unwrap();
+    });
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_stash_and_unstash_all() {
+    enum Msg {
+        Connect,
+        Work(u32),
+        Get(crate::RpcReplyPort<Vec<u32>>),
+    }
+    #[cfg(feature = "cluster")]
+    impl crate::Message for Msg {}
+
+    struct TestActor;
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for TestActor {
+        type Msg = Msg;
+        type Arguments = ();
+        type State = (bool, Vec<u32>);
+
+        async fn pre_start(
+            &self,
+            _this_actor: crate::ActorRef<Self::Msg>,
+            _: (),
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok((false, vec![]))
+        }
+
+        async fn handle(
+            &self,
+            myself: ActorRef<Self::Msg>,
+            message: Self::Msg,
+            (connected, handled): &mut Self::State,
+        ) -> Result<(), ActorProcessingErr> {
+            match message {
+                Msg::Connect => {
+                    *connected = true;
+                    myself.unstash_all();
+                }
+                Msg::Work(work) if *connected => handled.push(work),
+                work @ Msg::Work(_) => {
+                    myself.stash(work)?;
+                }
+                Msg::Get(reply) => {
+                    let _ = reply.send(handled.clone());
+                }
+            }
+            Ok(())
+        }
+    }
+
+    let (actor, handle) = Actor::spawn(None, TestActor, ())
+        .await
+        .expect("Failed to start actor");
+
+    actor.send_message(Msg::Work(1)).unwrap();
+    actor.send_message(Msg::Work(2)).unwrap();
+    let handled = crate::call!(actor, Msg::Get).unwrap();
+    assert!(handled.is_empty());
+    assert_eq!(2, actor.get_stash_len());
+
+    // the stashed messages are replayed ahead of the message already in the mailbox
+    actor.send_message(Msg::Connect).unwrap();
+    actor.send_message(Msg::Work(3)).unwrap();
+    let handled = crate::call!(actor, Msg::Get).unwrap();
+    assert_eq!(vec![1, 2, 3], handled);
+    assert_eq!(0, actor.get_stash_len());
+
+    actor.stop(None);
+    handle.await.unwrap();
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_stash_is_bounded() {
+    struct TestActor {
+        overflowed: Arc<AtomicU32>,
+    }
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for TestActor {
+        type Msg = u32;
+        type Arguments = ();
+        type State = ();
+
+        const STASH_CAPACITY: usize = 2;
+
+        async fn pre_start(
+            &self,
+            _this_actor: crate::ActorRef<Self::Msg>,
+            _: (),
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(())
+        }
+
+        async fn handle(
+            &self,
+            myself: ActorRef<Self::Msg>,
+            message: Self::Msg,
+            _state: &mut Self::State,
+        ) -> Result<(), ActorProcessingErr> {
+            if let Err(MessagingErr::StashFull(returned)) = myself.stash(message) {
+                assert_eq!(message, returned);
+                self.overflowed.fetch_add(1, Ordering::Relaxed);
+            }
+            Ok(())
+        }
+    }
+
+    let overflowed = Arc::new(AtomicU32::new(0));
+    let (actor, handle) = Actor::spawn(
+        None,
+        TestActor {
+            overflowed: overflowed.clone(),
+        },
+        (),
+    )
+    .await
+    .expect("Failed to start actor");
+
+    for i in 0..3 {
+        actor.send_message(i).unwrap();
+    }
+    periodic_check(
+        || overflowed.load(Ordering::Relaxed) == 1,
+        Duration::from_secs(1),
+    )
+    .await;
+    assert_eq!(2, actor.get_stash_len());
+
+    actor.stop(None);
+    handle.await.unwrap();
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_stop_takes_priority_over_unstashed_messages() {
+    struct TestActor {
+        handled: Arc<AtomicU32>,
+    }
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for TestActor {
+        type Msg = u32;
+        type Arguments = ();
+        type State = ();
+
+        async fn pre_start(
+            &self,
+            _this_actor: crate::ActorRef<Self::Msg>,
+            _: (),
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(())
+        }
+
+        async fn handle(
+            &self,
+            myself: ActorRef<Self::Msg>,
+            message: Self::Msg,
+            _state: &mut Self::State,
+        ) -> Result<(), ActorProcessingErr> {
+            if message == 0 {
+                for i in 1..=10 {
+                    myself.stash(i)?;
+                }
+                myself.unstash_all();
+                myself.stop(None);
+            } else {
+                self.handled.fetch_add(1, Ordering::Relaxed);
+            }
+            Ok(())
+        }
+    }
+
+    let handled = Arc::new(AtomicU32::new(0));
+    let (actor, handle) = Actor::spawn(
+        None,
+        TestActor {
+            handled: handled.clone(),
+        },
+        (),
+    )
+    .await
+    .expect("Failed to start actor");
+
+    actor.send_message(0).unwrap();
+    handle.await.unwrap();
+    assert_eq!(0, handled.load(Ordering::Relaxed));
+}
+
+enum UpgradeTestMessage {
+    Record(u32),
+    Get(crate::RpcReplyPort<Vec<String>>),
+}
+#[cfg(feature = "cluster")]
+impl crate::Message for UpgradeTestMessage {}
+
+struct UpgradableActor {
+    version: u32,
+    fail_upgrade: bool,
+}
+
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+impl Actor for UpgradableActor {
+    type Msg = UpgradeTestMessage;
+    type Arguments = ();
+    type State = Vec<String>;
+
+    async fn pre_start(
+        &self,
+        _this_actor: crate::ActorRef<Self::Msg>,
+        _: (),
+    ) -> Result<Self::State, ActorProcessingErr> {
+        Ok(vec![])
+    }
+
+    async fn handle(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        message: Self::Msg,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        match message {
+            UpgradeTestMessage::Record(value) => state.push(format!("v{}:{value}", self.version)),
+            UpgradeTestMessage::Get(reply) => {
+                let _ = reply.send(state.clone());
+            }
+        }
+        Ok(())
+    }
+
+    async fn code_change(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        assert_eq!(ActorStatus::Upgrading, myself.get_status());
+        if self.fail_upgrade {
+            return Err(From::from("failed to migrate"));
+        }
+        state.push(format!("upgrade:v{}", self.version));
+        Ok(())
+    }
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_hot_upgrade_swaps_handler() {
+    let name = "test_hot_upgrade_swaps_handler".to_string();
+    let (actor, handle) = Actor::spawn(
+        Some(name.clone()),
+        UpgradableActor {
+            version: 1,
+            fail_upgrade: false,
+        },
+        (),
+    )
+    .await
+    .expect("Failed to start actor");
+    let id = actor.get_id();
+
+    actor.send_message(UpgradeTestMessage::Record(1)).unwrap();
+    actor
+        .upgrade(UpgradableActor {
+            version: 2,
+            fail_upgrade: false,
+        })
+        .await
+        .expect("Failed to upgrade actor");
+    assert_eq!(ActorStatus::Running, actor.get_status());
+    actor.send_message(UpgradeTestMessage::Record(2)).unwrap();
+
+    let log = crate::call!(actor, UpgradeTestMessage::Get).unwrap();
+    assert_eq!(
+        vec![
+            "v1:1".to_string(),
+            "upgrade:v2".to_string(),
+            "v2:2".to_string()
+        ],
+        log
+    );
+
+    // identity and registration are preserved
+    let found = crate::registry::where_is(name).expect("Actor should still be registered");
+    assert_eq!(id, found.get_id());
+    assert_eq!(id, actor.get_id());
+
+    actor.stop(None);
+    handle.await.unwrap();
+}
+
+#[test]
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+#[tracing_test::traced_test]
+fn test_hot_upgrade_is_ordered_across_priorities() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        let (actor, handle) = Actor::spawn(
+            None,
+            UpgradableActor {
+                version: 1,
+                fail_upgrade: false,
+            },
+            (),
+        )
+        .await
+        .expect("Failed to start actor");
+
+        for (value, priority) in [
+            (1, crate::MessagePriority::Low),
+            (2, crate::MessagePriority::Normal),
+        ] {
+            actor
+                .send_message_with_priority(UpgradeTestMessage::Record(value), priority)
+                .unwrap();
+        }
+        let mut upgrade = Box::pin(actor.upgrade(UpgradableActor {
+            version: 2,
+            fail_upgrade: false,
+        }));
+        // request the upgrade, then send more normal priority messages while it's pending.
+        // The actor can't run in between on the single-threaded test runtime.
+        assert!(futures::poll!(&mut upgrade).is_pending());
+        for value in 3..=5 {
+            actor
+                .send_message(UpgradeTestMessage::Record(value))
+                .unwrap();
+        }
+        upgrade.await.expect("Failed to upgrade actor");
+
+        let mut log = crate::call!(actor, UpgradeTestMessage::Get).unwrap();
+        // the messages sent ahead of the upgrade may be handled in either order
+        log[..2].sort();
+        assert_eq!(
+            vec![
+                "v1:1".to_string(),
+                "v1:2".to_string(),
+                "upgrade:v2".to_string(),
+                "v2:3".to_string(),
+                "v2:4".to_string(),
+                "v2:5".to_string(),
+            ],
+            log
+        );
+
+        actor.stop(None);
+        handle.await.unwrap();
+    });
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_hot_upgrade_rejects_other_handler_types() {
+    struct OtherActor;
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for OtherActor {
+        type Msg = UpgradeTestMessage;
+        type Arguments = ();
+        type State = ();
+
+        async fn pre_start(
+            &self,
+            _this_actor: crate::ActorRef<Self::Msg>,
+            _: (),
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(())
+        }
+    }
+
+    struct UnitActor;
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for UnitActor {
+        type Msg = ();
+        type Arguments = ();
+        type State = ();
+
+        async fn pre_start(
+            &self,
+            _this_actor: crate::ActorRef<Self::Msg>,
+            _: (),
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(())
+        }
+    }
+
+    let (actor, handle) = Actor::spawn(
+        None,
+        UpgradableActor {
+            version: 1,
+            fail_upgrade: false,
+        },
+        (),
+    )
+    .await
+    .expect("Failed to start actor");
+
+    let err = actor
+        .upgrade(OtherActor)
+        .await
+        .expect_err("Upgrade to a different handler type should fail");
+    assert!(matches!(
+        err,
+        RactorErr::Messaging(MessagingErr::InvalidActorType)
+    ));
+    let err = actor
+        .upgrade(UnitActor)
+        .await
+        .expect_err("Upgrade to a different message type should fail");
+    assert!(matches!(
+        err,
+        RactorErr::Messaging(MessagingErr::InvalidActorType)
+    ));
+
+    // the actor is unaffected
+    actor.send_message(UpgradeTestMessage::Record(1)).unwrap();
+    let log = crate::call!(actor, UpgradeTestMessage::Get).unwrap();
+    assert_eq!(vec!["v1:1".to_string()], log);
+
+    actor.stop(None);
+    handle.await.unwrap();
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_hot_upgrade_failure_fails_actor() {
+    let (actor, handle) = Actor::spawn(
+        None,
+        UpgradableActor {
+            version: 1,
+            fail_upgrade: false,
+        },
+        (),
+    )
+    .await
+    .expect("Failed to start actor");
+
+    let err = actor
+        .upgrade(UpgradableActor {
+            version: 2,
+            fail_upgrade: true,
+        })
+        .await
+        .expect_err("Upgrade should fail");
+    assert!(matches!(err, RactorErr::Actor(_)));
+
+    handle.await.unwrap();
+    assert_eq!(ActorStatus::Stopped, actor.get_status());
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_messages_handled_by_priority() {
+    struct PriorityActor {
+        gate: Arc<crate::concurrency::Semaphore>,
+        received: Arc<std::sync::Mutex<Vec<u32>>>,
+    }
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for PriorityActor {
+        type Msg = u32;
+        type Arguments = ();
+        type State = ();
+
+        async fn pre_start(
+            &self,
+            _this_actor: crate::ActorRef<Self::Msg>,
+            _: (),
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(())
+        }
+
+        async fn handle(
+            &self,
+            _myself: ActorRef<Self::Msg>,
+            message: Self::Msg,
+            _state: &mut Self::State,
+        ) -> Result<(), ActorProcessingErr> {
+            self.received.lock().unwrap().push(message);
+            self.gate.acquire().await?.forget();
+            Ok(())
+        }
+    }
+
+    let gate = Arc::new(crate::concurrency::Semaphore::new(0));
+    let received = Arc::new(std::sync::Mutex::new(vec![]));
+    let (actor, handle) = Actor::spawn(
+        None,
+        PriorityActor {
+            gate: gate.clone(),
+            received: received.clone(),
+        },
+        (),
+    )
+    .await
+    .expect("Failed to start actor");
+
+    // block the actor so the rest of the messages queue up
+    actor.send_message(0).expect("Failed to send message");
+    let r = received.clone();
+    periodic_check(|| r.lock().unwrap().len() == 1, Duration::from_secs(1)).await;
+
+    for (message, priority) in [
+        (1, crate::MessagePriority::Low),
+        (2, crate::MessagePriority::Normal),
+        (3, crate::MessagePriority::High),
+        (4, crate::MessagePriority::Low),
+        (5, crate::MessagePriority::High),
+    ] {
+        actor
+            .send_message_with_priority(message, priority)
+            .expect("Failed to send message");
+    }
+    actor.send_message(6).expect("Failed to send message");
+    // the drain marker is handled after all the messages sent before it, regardless of priority
+    actor.drain().expect("Failed to drain actor");
+    assert!(actor
+        .send_message_with_priority(7, crate::MessagePriority::High)
+        .is_err());
+
+    gate.add_permits(10);
+    handle.await.unwrap();
+    assert_eq!(vec![0, 3, 5, 2, 6, 1, 4], *received.lock().unwrap());
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_message_interceptors() {
+    use crate::actor::interceptor::MessageInterceptor;
+
+    struct RecordingActor {
+        received: Arc<std::sync::Mutex<Vec<u32>>>,
+    }
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for RecordingActor {
+        type Msg = u32;
+        type Arguments = ();
+        type State = ();
+
+        async fn pre_start(
+            &self,
+            _this_actor: crate::ActorRef<Self::Msg>,
+            _: (),
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(())
+        }
+
+        async fn handle(
+            &self,
+            _myself: ActorRef<Self::Msg>,
+            message: Self::Msg,
+            _state: &mut Self::State,
+        ) -> Result<(), ActorProcessingErr> {
+            self.received.lock().unwrap().push(message);
+            Ok(())
+        }
+    }
+
+    /// Rejects zeros, and adds `add` to everything else, logging the hook calls
+    struct TestInterceptor {
+        label: &'static str,
+        add: u32,
+        log: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl MessageInterceptor<u32> for TestInterceptor {
+        fn before_handle(&self, _actor: &ActorCell, message: u32) -> Option<u32> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("before {} {message}", self.label));
+            if message == 0 {
+                None
+            } else {
+                Some(message + self.add)
+            }
+        }
+
+        fn after_handle(
+            &self,
+            _actor: &ActorCell,
+            result: &Result<(), ActorProcessingErr>,
+            _elapsed: Duration,
+        ) {
+            assert!(result.is_ok());
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("after {}", self.label));
+        }
+    }
+
+    let received = Arc::new(std::sync::Mutex::new(vec![]));
+    let log = Arc::new(std::sync::Mutex::new(vec![]));
+    let options = crate::SpawnOptions::builder()
+        .name("test_message_interceptors".to_string())
+        .interceptors(vec![
+            Arc::new(TestInterceptor {
+                label: "a",
+                add: 10,
+                log: log.clone(),
+            }) as Arc<dyn MessageInterceptor<u32>>,
+            Arc::new(TestInterceptor {
+                label: "b",
+                add: 100,
+                log: log.clone(),
+            }),
+        ])
+        .build();
+    let (actor, handle) = crate::ActorRuntime::spawn_with_options(
+        RecordingActor {
+            received: received.clone(),
+        },
+        (),
+        options,
+    )
+    .await
+    .expect("Failed to start actor");
+    assert_eq!(
+        Some(actor.get_id()),
+        crate::registry::where_is("test_message_interceptors".to_string()).map(|a| a.get_id())
+    );
+
+    actor.send_message(0).expect("Failed to send message");
+    actor.send_message(1).expect("Failed to send message");
+    let r = received.clone();
+    periodic_check(|| r.lock().unwrap().len() == 1, Duration::from_secs(1)).await;
+
+    assert_eq!(vec![111], *received.lock().unwrap());
+    assert_eq!(
+        vec![
+            "before a 0",
+            "before a 1",
+            "before b 11",
+            "after b",
+            "after a"
+        ],
+        *log.lock().unwrap()
+    );
+
+    actor.stop(None);
+    handle.await.unwrap();
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_actor_stats() {
+    use crate::actor::stats::ActorStatsLayer;
+
+    struct GatedActor {
+        gate: Arc<crate::concurrency::Semaphore>,
+    }
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for GatedActor {
+        type Msg = u32;
+        type Arguments = ();
+        type State = ();
+
+        async fn pre_start(
+            &self,
+            _this_actor: crate::ActorRef<Self::Msg>,
+            _: (),
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(())
+        }
+
+        async fn handle(
+            &self,
+            _myself: ActorRef<Self::Msg>,
+            _message: Self::Msg,
+            _state: &mut Self::State,
+        ) -> Result<(), ActorProcessingErr> {
+            self.gate.acquire().await?.forget();
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct CountingLayer {
+        handled: AtomicU32,
+        last_depth: AtomicU32,
+    }
+
+    impl ActorStatsLayer for CountingLayer {
+        fn message_handled(&self, _actor: &ActorCell, _elapsed: Duration, mailbox_depth: usize) {
+            self.handled.fetch_add(1, Ordering::SeqCst);
+            self.last_depth
+                .store(mailbox_depth as u32, Ordering::SeqCst);
+        }
+    }
+
+    let gate = Arc::new(crate::concurrency::Semaphore::new(0));
+    let layer = Arc::new(CountingLayer::default());
+    let (actor, handle) = crate::ActorRuntime::spawn_with_options(
+        GatedActor { gate: gate.clone() },
+        (),
+        crate::SpawnOptions::builder()
+            .stats(layer.clone() as Arc<dyn ActorStatsLayer>)
+            .build(),
+    )
+    .await
+    .expect("Failed to start actor");
+
+    for i in 0..3 {
+        actor.send_message(i).expect("Failed to send message");
+    }
+    // the first message is being handled, the other two are waiting in the mailbox
+    periodic_check(
+        || actor.get_stats().mailbox_depth == 2,
+        Duration::from_secs(1),
+    )
+    .await;
+    let stats = actor.get_stats();
+    assert_eq!(3, stats.messages_received);
+    assert_eq!(0, stats.messages_handled);
+
+    gate.add_permits(3);
+    periodic_check(
+        || actor.get_stats().messages_handled == 3,
+        Duration::from_secs(1),
+    )
+    .await;
+    let stats = actor.get_stats();
+    assert_eq!(0, stats.mailbox_depth);
+    assert_eq!(3, stats.handle_latency.count());
+    assert!(stats.handle_latency.mean().is_some());
+    assert_eq!(
+        3u64,
+        stats
+            .handle_latency
+            .buckets()
+            .map(|(_, count)| count)
+            .sum::<u64>()
+    );
+    assert_eq!(3, layer.handled.load(Ordering::SeqCst));
+    assert_eq!(0, layer.last_depth.load(Ordering::SeqCst));
+
+    actor.stop(None);
+    handle.await.unwrap();
+}
+
+/// Records the batches it handles, waiting on a gate after recording each one
+struct BatchingActor<const LINGER_MS: u64> {
+    gate: Arc<crate::concurrency::Semaphore>,
+    batches: Arc<std::sync::Mutex<Vec<Vec<u32>>>>,
+}
+
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+impl<const LINGER_MS: u64> Actor for BatchingActor<LINGER_MS> {
+    type Msg = u32;
+    type Arguments = ();
+    type State = ();
+
+    const MAX_BATCH_SIZE: usize = 3;
+    const BATCH_LINGER: Option<Duration> = if LINGER_MS > 0 {
+        Some(Duration::from_millis(LINGER_MS))
+    } else {
+        None
+    };
+
+    async fn pre_start(
+        &self,
+        _this_actor: crate::ActorRef<Self::Msg>,
+        _: (),
+    ) -> Result<Self::State, ActorProcessingErr> {
+        Ok(())
+    }
+
+    async fn handle_batch(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        messages: Vec<Self::Msg>,
+        _state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        self.batches.lock().unwrap().push(messages);
+        self.gate.acquire().await?.forget();
+        Ok(())
+    }
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_handle_batch() {
+    let gate = Arc::new(crate::concurrency::Semaphore::new(0));
+    let batches = Arc::new(std::sync::Mutex::new(vec![]));
+    let (actor, handle) = Actor::spawn(
+        None,
+        BatchingActor::<0> {
+            gate: gate.clone(),
+            batches: batches.clone(),
+        },
+        (),
+    )
+    .await
+    .expect("Failed to start actor");
+
+    // the first batch only holds the message which was waiting
+    actor.send_message(0).expect("Failed to send message");
+    periodic_check(
+        || batches.lock().unwrap().len() == 1,
+        Duration::from_secs(1),
+    )
+    .await;
+    for i in 1..=5 {
+        actor.send_message(i).expect("Failed to send message");
+    }
+    gate.add_permits(3);
+    periodic_check(
+        || batches.lock().unwrap().len() == 3,
+        Duration::from_secs(1),
+    )
+    .await;
+    assert_eq!(
+        vec![vec![0], vec![1, 2, 3], vec![4, 5]],
+        *batches.lock().unwrap()
+    );
+    assert_eq!(6, actor.get_stats().messages_handled);
+
+    actor.stop(None);
+    handle.await.unwrap();
+}
+
+#[test]
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+#[tracing_test::traced_test]
+fn test_handle_batch_lingers() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        let gate = Arc::new(crate::concurrency::Semaphore::new(10));
+        let batches = Arc::new(std::sync::Mutex::new(vec![]));
+        let (actor, handle) = Actor::spawn(
+            None,
+            BatchingActor::<200> {
+                gate: gate.clone(),
+                batches: batches.clone(),
+            },
+            (),
+        )
+        .await
+        .expect("Failed to start actor");
+
+        // the batch waits for more messages, until it's full
+        actor.send_message(0).expect("Failed to send message");
+        crate::testkit::advance(Duration::from_millis(20)).await;
+        actor.send_message(1).expect("Failed to send message");
+        actor.send_message(2).expect("Failed to send message");
+        periodic_check(
+            || batches.lock().unwrap().len() == 1,
+            Duration::from_secs(1),
+        )
+        .await;
+
+        // or until it lingered long enough
+        actor.send_message(3).expect("Failed to send message");
+        periodic_check(
+            || batches.lock().unwrap().len() == 2,
+            Duration::from_secs(1),
+        )
+        .await;
+        assert_eq!(vec![vec![0, 1, 2], vec![3]], *batches.lock().unwrap());
+
+        actor.stop(None);
+        handle.await.unwrap();
+    });
+}
+
+#[test]
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+#[tracing_test::traced_test]
+fn test_handle_batch_linger_ends_on_control_messages() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        let gate = Arc::new(crate::concurrency::Semaphore::new(10));
+        let batches = Arc::new(std::sync::Mutex::new(vec![]));
+        let (actor, handle) = Actor::spawn(
+            None,
+            BatchingActor::<60_000> {
+                gate: gate.clone(),
+                batches: batches.clone(),
+            },
+            (),
+        )
+        .await
+        .expect("Failed to start actor");
+
+        // a stop request ends the batch, and is handled right after it
+        actor.send_message(0).expect("Failed to send message");
+        crate::testkit::yield_rounds().await;
+        actor.stop(None);
+        crate::testkit::yield_rounds().await;
+        assert!(handle.is_finished());
+        assert_eq!(vec![vec![0]], *batches.lock().unwrap());
+
+        // a kill interrupts the batch
+        let batches = Arc::new(std::sync::Mutex::new(vec![]));
+        let (actor, handle) = Actor::spawn(
+            None,
+            BatchingActor::<60_000> {
+                gate: gate.clone(),
+                batches: batches.clone(),
+            },
+            (),
+        )
+        .await
+        .expect("Failed to start actor");
+        actor.send_message(0).expect("Failed to send message");
+        crate::testkit::yield_rounds().await;
+        actor.kill();
+        crate::testkit::yield_rounds().await;
+        assert!(handle.is_finished());
+        assert!(batches.lock().unwrap().is_empty());
+    });
+}
+
+#[test]
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+#[tracing_test::traced_test]
+fn test_handle_batch_interceptors_share_elapsed_time() {
+    use crate::actor::interceptor::MessageInterceptor;
+
+    struct Timing(Arc<std::sync::Mutex<Vec<Duration>>>);
+
+    impl MessageInterceptor<u32> for Timing {
+        fn after_handle(
+            &self,
+            _actor: &ActorCell,
+            _result: &Result<(), ActorProcessingErr>,
+            elapsed: Duration,
+        ) {
+            self.0.lock().unwrap().push(elapsed);
+        }
+    }
+
+    crate::testkit::TestRuntime::new().block_on(async {
+        let gate = Arc::new(crate::concurrency::Semaphore::new(0));
+        let batches = Arc::new(std::sync::Mutex::new(vec![]));
+        let timings = Arc::new(std::sync::Mutex::new(vec![]));
+        let options = crate::SpawnOptions::builder()
+            .interceptors(vec![
+                Arc::new(Timing(timings.clone())) as Arc<dyn MessageInterceptor<u32>>
+            ])
+            .build();
+        let (actor, handle) = crate::ActorRuntime::spawn_with_options(
+            BatchingActor::<0> {
+                gate: gate.clone(),
+                batches: batches.clone(),
+            },
+            (),
+            options,
+        )
+        .await
+        .expect("Failed to start actor");
+
+        actor.send_message(0).expect("Failed to send message");
+        crate::testkit::yield_rounds().await;
+        for i in 1..=3 {
+            actor.send_message(i).expect("Failed to send message");
+        }
+        gate.add_permits(1);
+        crate::testkit::yield_rounds().await;
+        // the batch of 3 messages takes 30ms, 10ms for each
+        crate::testkit::advance(Duration::from_millis(30)).await;
+        gate.add_permits(1);
+        crate::testkit::yield_rounds().await;
+
+        assert_eq!(vec![vec![0], vec![1, 2, 3]], *batches.lock().unwrap());
+        assert_eq!(
+            vec![Duration::from_millis(10); 3],
+            timings.lock().unwrap()[1..]
+        );
+
+        actor.stop(None);
+        handle.await.unwrap();
+    });
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_default_handle_batch_handles_each_message() {
+    struct TestActor {
+        received: Arc<std::sync::Mutex<Vec<u32>>>,
+    }
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for TestActor {
+        type Msg = u32;
+        type Arguments = ();
+        type State = ();
+
+        const MAX_BATCH_SIZE: usize = 4;
+
+        async fn pre_start(
+            &self,
+            _this_actor: crate::ActorRef<Self::Msg>,
+            _: (),
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(())
+        }
+
+        async fn handle(
+            &self,
+            _myself: ActorRef<Self::Msg>,
+            message: Self::Msg,
+            _state: &mut Self::State,
+        ) -> Result<(), ActorProcessingErr> {
+            self.received.lock().unwrap().push(message);
+            Ok(())
+        }
+    }
+
+    let received = Arc::new(std::sync::Mutex::new(vec![]));
+    let (actor, handle) = Actor::spawn(
+        None,
+        TestActor {
+            received: received.clone(),
+        },
+        (),
+    )
+    .await
+    .expect("Failed to start actor");
+
+    for i in 0..10 {
+        actor.send_message(i).expect("Failed to send message");
+    }
+    // the drain marker ends up behind a batch, and is handled once it's done
+    actor.drain().expect("Failed to drain actor");
+    handle.await.unwrap();
+
+    assert_eq!((0..10).collect::<Vec<_>>(), *received.lock().unwrap());
+    assert_eq!(ActorStatus::Stopped, actor.get_status());
+}
@@ -0,0 +1,258 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Timers bound to the lifecycle of an actor.
+//!
+//! The timers started through [crate::time] on an actor (e.g. [crate::ActorRef::send_interval],
+//! [crate::ActorRef::send_after], [crate::ActorRef::start_timer] or [crate::ActorRef::exit_after])
+//! are registered on the actor, and are cancelled once it stops, instead of lingering until
+//! their next tick. Timers can optionally be started
+//! with a key (e.g. [crate::ActorRef::send_interval_keyed]), in which case they can be listed
+//! with [crate::ActorCell::get_timers] and cancelled with [crate::ActorCell::cancel_timer].
+//! Starting a timer with the key of an active timer replaces it.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use futures::future::Either;
+
+use crate::concurrency::Duration;
+use crate::concurrency::JoinHandle;
+use crate::concurrency::Notify;
+use crate::time::TimerRef;
+use crate::time::TimerService;
+use crate::ActorCell;
+
+/// The key of an actor timer
+pub type TimerKey = String;
+
+struct TimerEntry {
+    id: u64,
+    cancel: Arc<Notify>,
+}
+
+/// How an anonymous timer is cancelled
+enum TimerCancel {
+    /// The timer runs on its own task, which is aborted once notified
+    Task(Arc<Notify>),
+    /// The timer is scheduled on a [TimerService]
+    Service(TimerRef),
+}
+
+impl TimerCancel {
+    fn cancel(&self) {
+        match self {
+            Self::Task(cancel) => cancel.notify_one(),
+            Self::Service(timer) => {
+                timer.cancel();
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct TimerTable {
+    next_id: u64,
+    stopped: bool,
+    keyed: HashMap<TimerKey, TimerEntry>,
+    anonymous: HashMap<u64, TimerCancel>,
+}
+
+/// The active timers of an actor
+#[derive(Default)]
+pub(crate) struct ActorTimers {
+    table: Mutex<TimerTable>,
+}
+
+impl ActorTimers {
+    /// Register a new timer, cancelling the active timer with the same key (if any).
+    ///
+    /// Returns the timer's id and the signal which cancels it, which is already
+    /// notified if the actor has stopped
+    fn register(&self, key: Option<TimerKey>) -> (u64, Arc<Notify>) {
+        let mut table = self.table.lock().unwrap();
+        let id = table.next_id;
+        table.next_id += 1;
+        let cancel = Arc::new(Notify::new());
+        if table.stopped {
+            cancel.notify_one();
+            return (id, cancel);
+        }
+        match key {
+            Some(key) => {
+                let entry = TimerEntry {
+                    id,
+                    cancel: cancel.clone(),
+                };
+                if let Some(replaced) = table.keyed.insert(key, entry) {
+                    replaced.cancel.notify_one();
+                }
+            }
+            None => {
+                table
+                    .anonymous
+                    .insert(id, TimerCancel::Task(cancel.clone()));
+            }
+        }
+        (id, cancel)
+    }
+
+    /// Start an anonymous timer on the service, which is cancelled if the actor stops
+    fn start_on<F>(
+        &self,
+        actor: &ActorCell,
+        service: &TimerService,
+        after: Duration,
+        action: F,
+    ) -> TimerRef
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        // the table stays locked until the timer is registered, so that a timer which
+        // fires right away can't unregister itself before that
+        let mut table = self.table.lock().unwrap();
+        let id = table.next_id;
+        table.next_id += 1;
+        let actor = actor.clone();
+        let timer = service.start(after, move || {
+            actor.inner.timers.unregister(None, id);
+            action();
+        });
+        if table.stopped {
+            timer.cancel();
+        } else {
+            table
+                .anonymous
+                .insert(id, TimerCancel::Service(timer.clone()));
+        }
+        timer
+    }
+
+    /// Remove a finished timer, if it hasn't been replaced since
+    fn unregister(&self, key: Option<&str>, id: u64) {
+        let mut table = self.table.lock().unwrap();
+        match key {
+            Some(key) => {
+                if matches!(table.keyed.get(key), Some(entry) if entry.id == id) {
+                    table.keyed.remove(key);
+                }
+            }
+            None => {
+                table.anonymous.remove(&id);
+            }
+        }
+    }
+
+    /// The keys of the active keyed timers, sorted
+    pub(crate) fn keys(&self) -> Vec<TimerKey> {
+        let mut keys = self
+            .table
+            .lock()
+            .unwrap()
+            .keyed
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys
+    }
+
+    /// Cancel the keyed timer, returning whether it was active
+    pub(crate) fn cancel(&self, key: &str) -> bool {
+        match self.table.lock().unwrap().keyed.remove(key) {
+            Some(entry) => {
+                entry.cancel.notify_one();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Cancel every timer, and any timer registered from now on. Called as the actor stops.
+    pub(crate) fn cancel_all(&self) {
+        let mut table = self.table.lock().unwrap();
+        table.stopped = true;
+        for (_, entry) in table.keyed.drain() {
+            entry.cancel.notify_one();
+        }
+        for (_, cancel) in table.anonymous.drain() {
+            cancel.cancel();
+        }
+    }
+}
+
+/// Bind the work of a timer to the actor, so that it's aborted if the timer is
+/// cancelled or the actor stops. The timer is registered right away, and runs once
+/// the returned future is polled.
+///
+/// * `actor` - The [ActorCell] the timer is bound to
+/// * `key` - The key of the timer, if it's keyed
+/// * `timer` - The timer's work
+///
+/// Returns the future of the timer, which resolves to [None] if the timer was aborted
+pub(crate) fn bind_actor_timer<F>(
+    actor: &ActorCell,
+    key: Option<TimerKey>,
+    timer: F,
+) -> impl Future<Output = Option<F::Output>> + Send + 'static
+where
+    F: Future + Send + 'static,
+    F::Output: Send,
+{
+    let (id, cancel) = actor.inner.timers.register(key.clone());
+    let actor = actor.clone();
+    async move {
+        let cancelled = Box::pin(cancel.notified());
+        match futures::future::select(Box::pin(timer), cancelled).await {
+            Either::Left((output, _)) => {
+                actor.inner.timers.unregister(key.as_deref(), id);
+                Some(output)
+            }
+            // cancelled timers have already been removed
+            Either::Right(_) => None,
+        }
+    }
+}
+
+/// Spawn the task of a timer bound to the actor, which is aborted if the timer is
+/// cancelled or the actor stops
+///
+/// * `actor` - The [ActorCell] the timer is bound to
+/// * `key` - The key of the timer, if it's keyed
+/// * `timer` - The timer's work
+pub(crate) fn spawn_actor_timer<F>(
+    actor: &ActorCell,
+    key: Option<TimerKey>,
+    timer: F,
+) -> JoinHandle<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let timer = bind_actor_timer(actor, key, timer);
+    crate::concurrency::spawn(async move {
+        timer.await;
+    })
+}
+
+/// Start a timer bound to the actor on a [TimerService], which is cancelled if the
+/// actor stops
+///
+/// * `actor` - The [ActorCell] the timer is bound to
+/// * `service` - The [TimerService] to schedule the timer on
+/// * `after` - The [Duration] to wait before running the action
+/// * `action` - The callback, which is run on the service's task
+pub(crate) fn start_actor_timer<F>(
+    actor: &ActorCell,
+    service: &TimerService,
+    after: Duration,
+    action: F,
+) -> TimerRef
+where
+    F: FnOnce() + Send + 'static,
+{
+    actor.inner.timers.start_on(actor, service, after, action)
+}
@@ -5,6 +5,10 @@
 
 //! Shared concurrency primitives utilized within the library for different frameworks (tokio, async-std, etc)
 
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::hash::Hasher;
+
 /// A timeout error
 #[derive(Debug)]
 pub struct Timeout;
@@ -18,6 +22,9 @@ impl std::fmt::Display for Timeout {
 /// A notification
 pub type Notify = tokio::sync::Notify;
 
+/// A counting semaphore
+pub type Semaphore = tokio::sync::Semaphore;
+
 /// A one-use sender
 pub type OneshotSender<T> = tokio::sync::oneshot::Sender<T>;
 /// A one-use receiver
@@ -100,3 +107,21 @@ mod target_specific {
 }
 
 pub(crate) use target_specific::SystemTime;
+
+/// A random number, for the randomized choices of the library (e.g. picking a random
+/// process group member). Under a `testkit::TestRuntime` it's drawn from the runtime's
+/// seeded generator instead, so that the choices are reproducible.
+pub(crate) fn random_u64() -> u64 {
+    #[cfg(any(
+        feature = "testkit",
+        all(
+            test,
+            not(feature = "async-std"),
+            not(all(target_arch = "wasm32", target_os = "unknown"))
+        )
+    ))]
+    if let Some(value) = crate::testkit::seeded_random_u64() {
+        return value;
+    }
+    RandomState::new().build_hasher().finish()
+}
@@ -164,6 +164,25 @@ pub async fn sleep(dur: super::Duration) {
     async_std::task::sleep(dur).await;
 }
 
+/// Identifies a runtime, see [current_runtime_id]. async-std only has its global runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct RuntimeId;
+
+/// The identifier of the runtime the caller is running on
+pub(crate) fn current_runtime_id() -> RuntimeId {
+    RuntimeId
+}
+
+/// Run blocking work (e.g. file I/O) on the runtime's blocking threads, so that it
+/// doesn't stall the tasks of the runtime
+pub(crate) async fn run_blocking<F, T>(work: F) -> T
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    async_std::task::spawn_blocking(work).await
+}
+
 /// Spawn a task on the executor runtime
 pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
 where
@@ -36,6 +36,27 @@ pub fn interval(dur: Duration) -> Interval {
 /// (first-completed, first-served)
 pub type JoinSet<T> = tokio::task::JoinSet<T>;
 
+/// Identifies a runtime, see [current_runtime_id]
+pub(crate) type RuntimeId = tokio::runtime::Id;
+
+/// The identifier of the runtime the caller is running on
+pub(crate) fn current_runtime_id() -> RuntimeId {
+    tokio::runtime::Handle::current().id()
+}
+
+/// Run blocking work (e.g. file I/O) on the runtime's blocking threads, so that it
+/// doesn't stall the tasks of the runtime. A panic of the work is resumed in the caller.
+pub(crate) async fn run_blocking<F, T>(work: F) -> T
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    match tokio::task::spawn_blocking(work).await {
+        Ok(output) => output,
+        Err(err) => std::panic::resume_unwind(err.into_panic()),
+    }
+}
+
 /// Spawn a task on the executor runtime
 pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
 where
@@ -42,6 +42,25 @@ pub fn interval(dur: Duration) -> Interval {
 /// (first-completed, first-served)
 pub type JoinSet<T> = tokio::task::JoinSet<T>;
 
+/// Identifies a runtime, see [current_runtime_id]. There's only ever one in the browser.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct RuntimeId;
+
+/// The identifier of the runtime the caller is running on
+pub(crate) fn current_runtime_id() -> RuntimeId {
+    RuntimeId
+}
+
+/// Run blocking work. There are no threads to move it to in the browser, so it runs in
+/// place.
+pub(crate) async fn run_blocking<F, T>(work: F) -> T
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    work()
+}
+
 /// Spawn a task on the executor runtime
 pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
 where
@@ -118,6 +118,17 @@ pub enum MessagingErr<T> {
     /// This happens if you have an [crate::ActorCell] which has the type id of its
     /// handler and you try to use an alternate handler to send a message
     InvalidActorType,
+
+    /// The actor's bounded mailbox is full and its overflow policy rejected the message
+    /// (see [crate::Actor::MAILBOX_CAPACITY]).
+    ///
+    /// Includes the message which failed to send so the caller can retry it later
+    MailboxFull(T),
+
+    /// The actor's stash is full (see [crate::Actor::STASH_CAPACITY]).
+    ///
+    /// Includes the message which failed to be stashed
+    StashFull(T),
 }
 
 impl<T> MessagingErr<T> {
@@ -131,6 +142,8 @@ impl<T> MessagingErr<T> {
             MessagingErr::SendErr(err) => MessagingErr::SendErr(mapper(err)),
             MessagingErr::ChannelClosed => MessagingErr::ChannelClosed,
             MessagingErr::InvalidActorType => MessagingErr::InvalidActorType,
+            MessagingErr::MailboxFull(err) => MessagingErr::MailboxFull(mapper(err)),
+            MessagingErr::StashFull(err) => MessagingErr::StashFull(mapper(err)),
         }
     }
 }
@@ -141,6 +154,8 @@ impl<T> std::fmt::Debug for MessagingErr<T> {
             Self::SendErr(_) => write!(f, "SendErr"),
             Self::ChannelClosed => write!(f, "RecvErr"),
             Self::InvalidActorType => write!(f, "InvalidActorType"),
+            Self::MailboxFull(_) => write!(f, "MailboxFull"),
+            Self::StashFull(_) => write!(f, "StashFull"),
         }
     }
 }
@@ -182,6 +197,18 @@ impl<T> Display for MessagingErr<T> {
             Self::SendErr(_) => {
                 write!(f, "Messaging failed to enqueue the message to the specified actor, the actor is likely terminated")
             }
+            Self::MailboxFull(_) => {
+                write!(
+                    f,
+                    "Messaging failed to enqueue the message because the actor's mailbox is full"
+                )
+            }
+            Self::StashFull(_) => {
+                write!(
+                    f,
+                    "Messaging failed to stash the message because the actor's stash is full"
+                )
+            }
         }
     }
 }
@@ -205,7 +232,14 @@ impl<T> RactorErr<T> {
     ///
     /// Returns [true] if the error contains a message payload of type `T`, [false] otherwise.
     pub fn has_message(&self) -> bool {
-        matches!(self, Self::Messaging(MessagingErr::SendErr(_)))
+        matches!(
+            self,
+            Self::Messaging(
+                MessagingErr::SendErr(_)
+                    | MessagingErr::MailboxFull(_)
+                    | MessagingErr::StashFull(_)
+            )
+        )
     }
     /// Try and extract the message payload from the contained error. This consumes the
     /// [RactorErr] instance in order to not have require cloning the message payload.
@@ -213,7 +247,12 @@ impl<T> RactorErr<T> {
     ///
     /// Returns [Some(`T`)] if there is a message payload, [None] otherwise.
     pub fn try_get_message(self) -> Option<T> {
-        if let Self::Messaging(MessagingErr::SendErr(msg)) = self {
+        if let Self::Messaging(
+            MessagingErr::SendErr(msg)
+            | MessagingErr::MailboxFull(msg)
+            | MessagingErr::StashFull(msg),
+        ) = self
+        {
             Some(msg)
         } else {
             None
@@ -932,6 +932,18 @@ where
         message: SupervisionEvent,
         state: &mut Self::State,
     ) -> Result<(), ActorProcessingErr> {
+        if let Some(who) = message.actor_cell() {
+            // workers which exit as part of a shutdown aren't replaced
+            if crate::shutdown::is_shutting_down(who) {
+                if matches!(
+                    message,
+                    SupervisionEvent::ActorTerminated(..) | SupervisionEvent::ActorFailed(..)
+                ) {
+                    crate::shutdown::take_shutting_down(who);
+                }
+                return Ok(());
+            }
+        }
         match message {
             SupervisionEvent::ActorTerminated(who, _, reason) => {
                 let wid = if let Some(worker) = state
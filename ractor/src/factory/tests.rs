@@ -7,11 +7,26 @@
 
 mod basic;
 mod draining_requests;
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+/// these tests run on the testkit's virtual time, which requires tokio
 mod dynamic_discarding;
 mod dynamic_pool;
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+/// these tests run on the testkit's virtual time, which requires tokio
 mod dynamic_settings;
 mod lifecycle;
 mod priority_queueing;
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+/// these tests run on the testkit's virtual time, which requires tokio
 mod ratelim;
 #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
 /// these tests use panic and are not supported on wasm because wasm is panic=abort
@@ -120,10 +120,18 @@ impl WorkerBuilder<TestWorker, ()> for SlowTestWorkerBuilder {
     }
 }
 
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
 struct InsanelySlowWorkerBuilder {
     counters: [Arc<AtomicU16>; NUM_TEST_WORKERS],
 }
 
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
 impl WorkerBuilder<TestWorker, ()> for InsanelySlowWorkerBuilder {
     fn build(&mut self, wid: usize) -> (TestWorker, ()) {
         (
@@ -531,99 +539,102 @@ async fn test_dispatch_sticky_queueing() {
     );
 }
 
-#[crate::concurrency::test]
-#[cfg_attr(
-    not(all(target_arch = "wasm32", target_os = "unknown")),
-    tracing_test::traced_test
-)]
-async fn test_discarding_old_records_on_queuer() {
-    let worker_counters: [_; NUM_TEST_WORKERS] = [
-        Arc::new(AtomicU16::new(0)),
-        Arc::new(AtomicU16::new(0)),
-        Arc::new(AtomicU16::new(0)),
-    ];
-    let discard_counter = Arc::new(AtomicU16::new(0));
-
-    struct TestDiscarder {
-        counter: Arc<AtomicU16>,
-    }
-    impl DiscardHandler<TestKey, TestMessage> for TestDiscarder {
-        fn discard(&self, _reason: DiscardReason, _job: &mut Job<TestKey, TestMessage>) {
-            let _ = self.counter.fetch_add(1, Ordering::Relaxed);
+#[test]
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+#[tracing_test::traced_test]
+fn test_discarding_old_records_on_queuer() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        let worker_counters: [_; NUM_TEST_WORKERS] = [
+            Arc::new(AtomicU16::new(0)),
+            Arc::new(AtomicU16::new(0)),
+            Arc::new(AtomicU16::new(0)),
+        ];
+        let discard_counter = Arc::new(AtomicU16::new(0));
+
+        struct TestDiscarder {
+            counter: Arc<AtomicU16>,
+        }
+        impl DiscardHandler<TestKey, TestMessage> for TestDiscarder {
+            fn discard(&self, _reason: DiscardReason, _job: &mut Job<TestKey, TestMessage>) {
+                let _ = self.counter.fetch_add(1, Ordering::Relaxed);
+            }
         }
-    }
 
-    let worker_builder = InsanelySlowWorkerBuilder {
-        counters: worker_counters.clone(),
-    };
-    let factory_definition = Factory::<
-        TestKey,
-        TestMessage,
-        (),
-        TestWorker,
-        routing::QueuerRouting<TestKey, TestMessage>,
-        DefaultQueue,
-    >::default();
-    let (factory, factory_handle) = Actor::spawn(
-        None,
-        factory_definition,
-        FactoryArguments {
-            num_initial_workers: NUM_TEST_WORKERS,
-            queue: DefaultQueue::default(),
-            router: Default::default(),
-            capacity_controller: None,
-            dead_mans_switch: None,
-            discard_handler: Some(Arc::new(TestDiscarder {
-                counter: discard_counter.clone(),
-            })),
-            discard_settings: DiscardSettings::Static {
-                limit: 5,
-                mode: DiscardMode::Oldest,
+        let worker_builder = InsanelySlowWorkerBuilder {
+            counters: worker_counters.clone(),
+        };
+        let factory_definition = Factory::<
+            TestKey,
+            TestMessage,
+            (),
+            TestWorker,
+            routing::QueuerRouting<TestKey, TestMessage>,
+            DefaultQueue,
+        >::default();
+        let (factory, factory_handle) = Actor::spawn(
+            None,
+            factory_definition,
+            FactoryArguments {
+                num_initial_workers: NUM_TEST_WORKERS,
+                queue: DefaultQueue::default(),
+                router: Default::default(),
+                capacity_controller: None,
+                dead_mans_switch: None,
+                discard_handler: Some(Arc::new(TestDiscarder {
+                    counter: discard_counter.clone(),
+                })),
+                discard_settings: DiscardSettings::Static {
+                    limit: 5,
+                    mode: DiscardMode::Oldest,
+                },
+                lifecycle_hooks: None,
+                worker_builder: Box::new(worker_builder),
+                stats: None,
             },
-            lifecycle_hooks: None,
-            worker_builder: Box::new(worker_builder),
-            stats: None,
-        },
-    )
-    .await
-    .expect("Failed to spawn factory");
+        )
+        .await
+        .expect("Failed to spawn factory");
 
-    for _ in 0..108 {
-        factory
-            .cast(FactoryMessage::Dispatch(Job {
-                key: TestKey { id: 1 },
-                msg: TestMessage::Ok,
-                options: JobOptions::default(),
-                accepted: None,
-            }))
-            .expect("Failed to send to factory");
-    }
+        for _ in 0..108 {
+            factory
+                .cast(FactoryMessage::Dispatch(Job {
+                    key: TestKey { id: 1 },
+                    msg: TestMessage::Ok,
+                    options: JobOptions::default(),
+                    accepted: None,
+                }))
+                .expect("Failed to send to factory");
+        }
 
-    // give some time to process all the messages
-    crate::concurrency::sleep(Duration::from_millis(250)).await;
+        // give some time to process all the messages
+        crate::testkit::advance(Duration::from_millis(250)).await;
 
-    println!(
-        "Counters: [{}] [{}] [{}]",
-        worker_counters[0].load(Ordering::Relaxed),
-        worker_counters[1].load(Ordering::Relaxed),
-        worker_counters[2].load(Ordering::Relaxed)
-    );
+        println!(
+            "Counters: [{}] [{}] [{}]",
+            worker_counters[0].load(Ordering::Relaxed),
+            worker_counters[1].load(Ordering::Relaxed),
+            worker_counters[2].load(Ordering::Relaxed)
+        );
 
-    // assert
+        // assert
 
-    // each worker only got 1 message, then "slept"
-    assert!(worker_counters
-        .iter()
-        .map(|c| c.load(Ordering::Relaxed))
-        .all(|count| count == 1));
+        // each worker only got 1 message, then "slept"
+        assert!(worker_counters
+            .iter()
+            .map(|c| c.load(Ordering::Relaxed))
+            .all(|count| count == 1));
 
-    // 5 messages should be left in the factory's queue, while the remaining should get "discarded"
-    // (3 in workers) + (5 in queue) + (100 discarded) = 108, the number of msgs we sent to the factory
-    assert_eq!(100, discard_counter.load(Ordering::Relaxed));
+        // 5 messages should be left in the factory's queue, while the remaining should get "discarded"
+        // (3 in workers) + (5 in queue) + (100 discarded) = 108, the number of msgs we sent to the factory
+        assert_eq!(100, discard_counter.load(Ordering::Relaxed));
 
-    // wait for factory termination
-    factory.stop(None);
-    factory_handle.await.unwrap();
+        // wait for factory termination
+        factory.stop(None);
+        factory_handle.await.unwrap();
+    });
 }
 
 struct StuckWorker {
@@ -675,200 +686,210 @@ impl Actor for StuckWorker {
     }
 }
 
-#[crate::concurrency::test]
-#[cfg_attr(
-    not(all(target_arch = "wasm32", target_os = "unknown")),
-    tracing_test::traced_test
-)]
-async fn test_stuck_workers() {
-    let worker_counters: [_; NUM_TEST_WORKERS] = [
-        Arc::new(AtomicU16::new(0)),
-        Arc::new(AtomicU16::new(0)),
-        Arc::new(AtomicU16::new(0)),
-    ];
-
-    struct StuckWorkerBuilder {
-        counters: [Arc<AtomicU16>; NUM_TEST_WORKERS],
-    }
-
-    impl WorkerBuilder<TestWorker, ()> for StuckWorkerBuilder {
-        fn build(&mut self, wid: usize) -> (TestWorker, ()) {
-            (
-                TestWorker {
-                    counter: self.counters[wid].clone(),
-                    slow: Some(10000),
-                },
-                (),
-            )
+#[test]
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+#[tracing_test::traced_test]
+fn test_stuck_workers() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        let worker_counters: [_; NUM_TEST_WORKERS] = [
+            Arc::new(AtomicU16::new(0)),
+            Arc::new(AtomicU16::new(0)),
+            Arc::new(AtomicU16::new(0)),
+        ];
+
+        struct StuckWorkerBuilder {
+            counters: [Arc<AtomicU16>; NUM_TEST_WORKERS],
         }
-    }
 
-    let worker_builder = StuckWorkerBuilder {
-        counters: worker_counters.clone(),
-    };
-    let factory_definition = Factory::<
-        TestKey,
-        TestMessage,
-        (),
-        TestWorker,
-        routing::RoundRobinRouting<TestKey, TestMessage>,
-        DefaultQueue,
-    >::default();
-    let dms = DeadMansSwitchConfiguration::builder()
-        .detection_timeout(Duration::from_millis(50))
-        .kill_worker(true)
-        .build();
-    tracing::debug!("DMS settings: {dms:?}");
-    let args = FactoryArguments::builder()
-        .num_initial_workers(NUM_TEST_WORKERS)
-        .queue(Default::default())
-        .router(Default::default())
-        .worker_builder(Box::new(worker_builder))
-        .dead_mans_switch(dms)
-        .build();
-    tracing::debug!("Factory args {args:?}");
-    let (factory, factory_handle) = Actor::spawn(None, factory_definition, args)
-        .await
-        .expect("Failed to spawn factory");
-
-    tracing::debug!(
-        "Actor node {}, pid {}",
-        factory.get_id().node(),
-        factory.get_id().pid()
-    );
+        impl WorkerBuilder<TestWorker, ()> for StuckWorkerBuilder {
+            fn build(&mut self, wid: usize) -> (TestWorker, ()) {
+                (
+                    TestWorker {
+                        counter: self.counters[wid].clone(),
+                        slow: Some(10000),
+                    },
+                    (),
+                )
+            }
+        }
 
-    for _ in 0..9 {
-        factory
-            .cast(FactoryMessage::Dispatch(
-                Job::builder()
-                    .key(TestKey { id: 1 })
-                    .msg(TestMessage::Ok)
-                    .build(),
-            ))
-            .expect("Failed to send to factory");
-    }
+        let worker_builder = StuckWorkerBuilder {
+            counters: worker_counters.clone(),
+        };
+        let factory_definition = Factory::<
+            TestKey,
+            TestMessage,
+            (),
+            TestWorker,
+            routing::RoundRobinRouting<TestKey, TestMessage>,
+            DefaultQueue,
+        >::default();
+        let dms = DeadMansSwitchConfiguration::builder()
+            .detection_timeout(Duration::from_millis(50))
+            .kill_worker(true)
+            .build();
+        tracing::debug!("DMS settings: {dms:?}");
+        let args = FactoryArguments::builder()
+            .num_initial_workers(NUM_TEST_WORKERS)
+            .queue(Default::default())
+            .router(Default::default())
+            .worker_builder(Box::new(worker_builder))
+            .dead_mans_switch(dms)
+            .build();
+        tracing::debug!("Factory args {args:?}");
+        let (factory, factory_handle) = Actor::spawn(None, factory_definition, args)
+            .await
+            .expect("Failed to spawn factory");
+
+        tracing::debug!(
+            "Actor node {}, pid {}",
+            factory.get_id().node(),
+            factory.get_id().pid()
+        );
+
+        for _ in 0..9 {
+            factory
+                .cast(FactoryMessage::Dispatch(
+                    Job::builder()
+                        .key(TestKey { id: 1 })
+                        .msg(TestMessage::Ok)
+                        .build(),
+                ))
+                .expect("Failed to send to factory");
+        }
 
-    // give some time to process all the messages
-    crate::concurrency::sleep(Duration::from_millis(500)).await;
+        // give some time to process all the messages
+        crate::testkit::advance(Duration::from_millis(500)).await;
 
-    // wait for factory termination
-    factory.stop(None);
-    factory_handle.await.unwrap();
+        // wait for factory termination
+        factory.stop(None);
+        factory_handle.await.unwrap();
 
-    println!(
-        "Counters: [{}] [{}] [{}]",
-        worker_counters[0].load(Ordering::Relaxed),
-        worker_counters[1].load(Ordering::Relaxed),
-        worker_counters[2].load(Ordering::Relaxed)
-    );
+        println!(
+            "Counters: [{}] [{}] [{}]",
+            worker_counters[0].load(Ordering::Relaxed),
+            worker_counters[1].load(Ordering::Relaxed),
+            worker_counters[2].load(Ordering::Relaxed)
+        );
 
-    // assert
+        // assert
 
-    // each worker only got 1 message, then "slept"
-    assert!(worker_counters
-        .iter()
-        .map(|c| c.load(Ordering::Relaxed))
-        .all(|count| count > 1));
+        // each worker only got 1 message, then "slept"
+        assert!(worker_counters
+            .iter()
+            .map(|c| c.load(Ordering::Relaxed))
+            .all(|count| count > 1));
+    });
 }
 
-#[crate::concurrency::test]
-async fn test_discarding_new_records_on_queuer() {
-    let worker_counters: [_; NUM_TEST_WORKERS] = [
-        Arc::new(AtomicU16::new(0)),
-        Arc::new(AtomicU16::new(0)),
-        Arc::new(AtomicU16::new(0)),
-    ];
-    let discard_counter = Arc::new(AtomicU16::new(0));
-
-    struct TestDiscarder {
-        counter: Arc<AtomicU16>,
-    }
-    impl DiscardHandler<TestKey, TestMessage> for TestDiscarder {
-        fn discard(&self, _reason: DiscardReason, job: &mut Job<TestKey, TestMessage>) {
-            if let TestMessage::Count(count) = job.msg {
-                let _ = self.counter.fetch_add(count, Ordering::Relaxed);
+#[test]
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+#[tracing_test::traced_test]
+fn test_discarding_new_records_on_queuer() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        let worker_counters: [_; NUM_TEST_WORKERS] = [
+            Arc::new(AtomicU16::new(0)),
+            Arc::new(AtomicU16::new(0)),
+            Arc::new(AtomicU16::new(0)),
+        ];
+        let discard_counter = Arc::new(AtomicU16::new(0));
+
+        struct TestDiscarder {
+            counter: Arc<AtomicU16>,
+        }
+        impl DiscardHandler<TestKey, TestMessage> for TestDiscarder {
+            fn discard(&self, _reason: DiscardReason, job: &mut Job<TestKey, TestMessage>) {
+                if let TestMessage::Count(count) = job.msg {
+                    let _ = self.counter.fetch_add(count, Ordering::Relaxed);
+                }
             }
         }
-    }
 
-    let worker_builder = InsanelySlowWorkerBuilder {
-        counters: worker_counters.clone(),
-    };
-    let factory_definition = Factory::<
-        TestKey,
-        TestMessage,
-        (),
-        TestWorker,
-        routing::QueuerRouting<TestKey, TestMessage>,
-        DefaultQueue,
-    >::default();
-    let (factory, factory_handle) = Actor::spawn(
-        None,
-        factory_definition,
-        FactoryArguments {
-            num_initial_workers: NUM_TEST_WORKERS,
-            queue: DefaultQueue::default(),
-            router: Default::default(),
-            capacity_controller: None,
-            dead_mans_switch: None,
-            discard_handler: Some(Arc::new(TestDiscarder {
-                counter: discard_counter.clone(),
-            })),
-            discard_settings: DiscardSettings::Static {
-                limit: 5,
-                mode: DiscardMode::Newest,
+        let worker_builder = InsanelySlowWorkerBuilder {
+            counters: worker_counters.clone(),
+        };
+        let factory_definition = Factory::<
+            TestKey,
+            TestMessage,
+            (),
+            TestWorker,
+            routing::QueuerRouting<TestKey, TestMessage>,
+            DefaultQueue,
+        >::default();
+        let (factory, factory_handle) = Actor::spawn(
+            None,
+            factory_definition,
+            FactoryArguments {
+                num_initial_workers: NUM_TEST_WORKERS,
+                queue: DefaultQueue::default(),
+                router: Default::default(),
+                capacity_controller: None,
+                dead_mans_switch: None,
+                discard_handler: Some(Arc::new(TestDiscarder {
+                    counter: discard_counter.clone(),
+                })),
+                discard_settings: DiscardSettings::Static {
+                    limit: 5,
+                    mode: DiscardMode::Newest,
+                },
+                lifecycle_hooks: None,
+                worker_builder: Box::new(worker_builder),
+                stats: None,
             },
-            lifecycle_hooks: None,
-            worker_builder: Box::new(worker_builder),
-            stats: None,
-        },
-    )
-    .await
-    .expect("Failed to spawn factory");
-
-    for i in 0..10 {
-        factory
-            .cast(FactoryMessage::Dispatch(Job {
-                key: TestKey { id: 1 },
-                msg: TestMessage::Count(i),
-                options: JobOptions::default(),
-                accepted: None,
-            }))
-            .expect("Failed to send to factory");
-    }
-
-    let active_requests = factory
-        .call(FactoryMessage::GetNumActiveWorkers, None)
+        )
         .await
-        .expect("Failed to send query to factory")
-        .expect("Failed to get result from factory");
-    assert!(active_requests > 0);
-
-    // give some time to process all the messages
-    crate::concurrency::sleep(Duration::from_millis(250)).await;
-
-    println!(
-        "Counters: [{}] [{}] [{}]",
-        worker_counters[0].load(Ordering::Relaxed),
-        worker_counters[1].load(Ordering::Relaxed),
-        worker_counters[2].load(Ordering::Relaxed)
-    );
-
-    // assert
-
-    // each worker only got 1 message, then "slept"
-    assert!(worker_counters
-        .iter()
-        .map(|c| c.load(Ordering::Relaxed))
-        .all(|count| count == 1));
+        .expect("Failed to spawn factory");
 
-    // 5 messages should be left in the factory's queue, while the remaining should get "discarded"
-    //
-    // The "newest" messages had values (8) and (9), respectively, which together should mean
-    // the discard counter is 17
-    assert_eq!(17, discard_counter.load(Ordering::Relaxed));
+        for i in 0..10 {
+            factory
+                .cast(FactoryMessage::Dispatch(Job {
+                    key: TestKey { id: 1 },
+                    msg: TestMessage::Count(i),
+                    options: JobOptions::default(),
+                    accepted: None,
+                }))
+                .expect("Failed to send to factory");
+        }
 
-    // wait for factory termination
-    factory.stop(None);
-    factory_handle.await.unwrap();
+        let active_requests = factory
+            .call(FactoryMessage::GetNumActiveWorkers, None)
+            .await
+            .expect("Failed to send query to factory")
+            .expect("Failed to get result from factory");
+        assert!(active_requests > 0);
+
+        // give some time to process all the messages
+        crate::testkit::advance(Duration::from_millis(250)).await;
+
+        println!(
+            "Counters: [{}] [{}] [{}]",
+            worker_counters[0].load(Ordering::Relaxed),
+            worker_counters[1].load(Ordering::Relaxed),
+            worker_counters[2].load(Ordering::Relaxed)
+        );
+
+        // assert
+
+        // each worker only got 1 message, then "slept"
+        assert!(worker_counters
+            .iter()
+            .map(|c| c.load(Ordering::Relaxed))
+            .all(|count| count == 1));
+
+        // 5 messages should be left in the factory's queue, while the remaining should get "discarded"
+        //
+        // The "newest" messages had values (8) and (9), respectively, which together should mean
+        // the discard counter is 17
+        assert_eq!(17, discard_counter.load(Ordering::Relaxed));
+
+        // wait for factory termination
+        factory.stop(None);
+        factory_handle.await.unwrap();
+    });
 }
@@ -136,104 +136,103 @@ impl DynamicDiscardController for DiscardController {
     }
 }
 
-#[crate::concurrency::test]
-#[cfg_attr(
-    not(all(target_arch = "wasm32", target_os = "unknown")),
-    tracing_test::traced_test
-)]
-async fn test_dynamic_dispatch_basic() {
-    // let handle = tokio::runtime::Handle::current();
-    // Setup
-    let worker_counters: [_; NUM_TEST_WORKERS] =
-        [Arc::new(AtomicU16::new(0)), Arc::new(AtomicU16::new(0))];
-    let discard_counter = Arc::new(AtomicU16::new(0));
-
-    let worker_builder = SlowTestWorkerBuilder {
-        counters: worker_counters.clone(),
-    };
-    let factory_definition = Factory::<
-        TestKey,
-        TestMessage,
-        (),
-        TestWorker,
-        routing::QueuerRouting<TestKey, TestMessage>,
-        queues::DefaultQueue<TestKey, TestMessage>,
-    >::default();
-    let (factory, factory_handle) = Actor::spawn(
-        None,
-        factory_definition,
-        FactoryArguments {
-            num_initial_workers: NUM_TEST_WORKERS,
-            queue: queues::DefaultQueue::default(),
-            router: Default::default(),
-            capacity_controller: None,
-            dead_mans_switch: None,
-            discard_handler: Some(Arc::new(TestDiscarder {
-                counter: discard_counter.clone(),
-            })),
-            discard_settings: DiscardSettings::Dynamic {
-                limit: 5,
-                mode: DiscardMode::Newest,
-                updater: Box::new(DiscardController {}),
+#[test]
+#[tracing_test::traced_test]
+fn test_dynamic_dispatch_basic() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        // let handle = tokio::runtime::Handle::current();
+        // Setup
+        let worker_counters: [_; NUM_TEST_WORKERS] =
+            [Arc::new(AtomicU16::new(0)), Arc::new(AtomicU16::new(0))];
+        let discard_counter = Arc::new(AtomicU16::new(0));
+
+        let worker_builder = SlowTestWorkerBuilder {
+            counters: worker_counters.clone(),
+        };
+        let factory_definition = Factory::<
+            TestKey,
+            TestMessage,
+            (),
+            TestWorker,
+            routing::QueuerRouting<TestKey, TestMessage>,
+            queues::DefaultQueue<TestKey, TestMessage>,
+        >::default();
+        let (factory, factory_handle) = Actor::spawn(
+            None,
+            factory_definition,
+            FactoryArguments {
+                num_initial_workers: NUM_TEST_WORKERS,
+                queue: queues::DefaultQueue::default(),
+                router: Default::default(),
+                capacity_controller: None,
+                dead_mans_switch: None,
+                discard_handler: Some(Arc::new(TestDiscarder {
+                    counter: discard_counter.clone(),
+                })),
+                discard_settings: DiscardSettings::Dynamic {
+                    limit: 5,
+                    mode: DiscardMode::Newest,
+                    updater: Box::new(DiscardController {}),
+                },
+                lifecycle_hooks: None,
+                worker_builder: Box::new(worker_builder),
+                stats: None,
             },
-            lifecycle_hooks: None,
-            worker_builder: Box::new(worker_builder),
-            stats: None,
-        },
-    )
-    .await
-    .expect("Failed to spawn factory");
-
-    // Act
-    for i in 0..10 {
-        factory
-            .cast(FactoryMessage::Dispatch(Job {
-                key: TestKey { id: 1 },
-                msg: TestMessage::Count(i),
-                options: JobOptions::default(),
-                accepted: None,
-            }))
-            .expect("Failed to send to factory");
-    }
-    // give some time to process all the messages (10ms/msg by 2 workers for 7 msgs)
-    crate::periodic_check(
-        || {
-            // Assert
-            // we should have shed the 3 newest messages, so 7, 8, 9
-            discard_counter.load(Ordering::Relaxed) == 24
-        },
-        Duration::from_secs(1),
-    )
-    .await;
-
-    // now we wait for the ping to change the discard threshold to 10
-    crate::concurrency::sleep(Duration::from_millis(300)).await;
-
-    // Act again
-    for i in 0..14 {
-        factory
-            .cast(FactoryMessage::Dispatch(Job {
-                key: TestKey { id: 1 },
-                msg: TestMessage::Count(i),
-                options: JobOptions::default(),
-                accepted: None,
-            }))
-            .expect("Failed to send to factory");
-    }
+        )
+        .await
+        .expect("Failed to spawn factory");
+
+        // Act
+        for i in 0..10 {
+            factory
+                .cast(FactoryMessage::Dispatch(Job {
+                    key: TestKey { id: 1 },
+                    msg: TestMessage::Count(i),
+                    options: JobOptions::default(),
+                    accepted: None,
+                }))
+                .expect("Failed to send to factory");
+        }
+        // give some time to process all the messages (10ms/msg by 2 workers for 7 msgs)
+        crate::periodic_check(
+            || {
+                // Assert
+                // we should have shed the 3 newest messages, so 7, 8, 9
+                discard_counter.load(Ordering::Relaxed) == 24
+            },
+            Duration::from_secs(1),
+        )
+        .await;
+
+        // now we wait for the ping to change the discard threshold to 10
+        crate::testkit::advance(Duration::from_millis(300)).await;
+
+        // Act again
+        for i in 0..14 {
+            factory
+                .cast(FactoryMessage::Dispatch(Job {
+                    key: TestKey { id: 1 },
+                    msg: TestMessage::Count(i),
+                    options: JobOptions::default(),
+                    accepted: None,
+                }))
+                .expect("Failed to send to factory");
+        }
+
+        // give some time to process all the messages (10ms/msg by 2 workers for 7 msgs)
+        crate::periodic_check(
+            || {
+                // Assert
+                // we should have shed the 2 newest messages, so 12 and 13 + original amount of 24
+                discard_counter.load(Ordering::Relaxed) == 49
+            },
+            Duration::from_secs(1),
+        )
+        .await;
 
-    // give some time to process all the messages (10ms/msg by 2 workers for 7 msgs)
-    crate::periodic_check(
-        || {
-            // Assert
-            // we should have shed the 2 newest messages, so 12 and 13 + original amount of 24
-            discard_counter.load(Ordering::Relaxed) == 49
-        },
-        Duration::from_secs(1),
-    )
-    .await;
-
-    // Cleanup
-    // wait for factory termination
-    factory.stop(None);
-    factory_handle.await.unwrap();
+        // Cleanup
+        // wait for factory termination
+        factory.stop(None);
+        factory_handle.await.unwrap();
+    });
 }
@@ -8,11 +8,6 @@
 
 use std::sync::Arc;
 
-#[cfg(not(feature = "async-trait"))]
-use futures::future::BoxFuture;
-#[cfg(not(feature = "async-trait"))]
-use futures::FutureExt;
-
 use crate::concurrency::Duration;
 use crate::factory::*;
 use crate::Actor;
@@ -187,110 +182,113 @@ async fn test_worker_pool_adjustment_manual() {
     factory_handle.await.unwrap();
 }
 
-#[crate::concurrency::test]
-#[cfg_attr(
-    not(all(target_arch = "wasm32", target_os = "unknown")),
-    tracing_test::traced_test
-)]
-async fn test_worker_pool_adjustment_automatic() {
-    // Setup
+#[test]
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+#[tracing_test::traced_test]
+fn test_worker_pool_adjustment_automatic() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        // Setup
+
+        struct DynamicWorkerController;
+
+        #[cfg_attr(feature = "async-trait", crate::async_trait)]
+        impl WorkerCapacityController for DynamicWorkerController {
+            #[cfg(feature = "async-trait")]
+            async fn get_pool_size(&mut self, _current: usize) -> usize {
+                10
+            }
+
+            #[cfg(not(feature = "async-trait"))]
+            fn get_pool_size(&mut self, _current: usize) -> futures::future::BoxFuture<'_, usize> {
+                futures::FutureExt::boxed(async { 10 })
+            }
+        }
 
-    struct DynamicWorkerController;
+        let id_map = Arc::new(dashmap::DashSet::new());
 
-    #[cfg_attr(feature = "async-trait", crate::async_trait)]
-    impl WorkerCapacityController for DynamicWorkerController {
-        #[cfg(feature = "async-trait")]
-        async fn get_pool_size(&mut self, _current: usize) -> usize {
-            10
+        let worker_builder = TestWorkerBuilder {
+            id_map: id_map.clone(),
+        };
+        let factory_definition = Factory::<
+            TestKey,
+            TestMessage,
+            (),
+            TestWorker,
+            routing::RoundRobinRouting<TestKey, TestMessage>,
+            queues::DefaultQueue<TestKey, TestMessage>,
+        >::default();
+        let (factory, factory_handle) = Actor::spawn(
+            None,
+            factory_definition,
+            FactoryArguments {
+                num_initial_workers: 4,
+                queue: queues::DefaultQueue::default(),
+                router: Default::default(),
+                capacity_controller: Some(Box::new(DynamicWorkerController)),
+                dead_mans_switch: None,
+                discard_handler: None,
+                discard_settings: DiscardSettings::None,
+                lifecycle_hooks: None,
+                worker_builder: Box::new(worker_builder),
+                stats: None,
+            },
+        )
+        .await
+        .expect("Failed to spawn factory");
+
+        // Act
+        for i in 0..50 {
+            factory
+                .cast(FactoryMessage::Dispatch(Job {
+                    key: TestKey { id: 1 },
+                    msg: TestMessage::Count(i),
+                    options: JobOptions::default(),
+                    accepted: None,
+                }))
+                .expect("Failed to send to factory");
         }
 
-        #[cfg(not(feature = "async-trait"))]
-        fn get_pool_size(&mut self, _current: usize) -> BoxFuture<'_, usize> {
-            async { 10 }.boxed()
+        crate::periodic_check(
+            || {
+                // The map should only have 4 entries, the id of each worker
+                id_map.len() == 4
+            },
+            Duration::from_millis(200),
+        )
+        .await;
+
+        // Setup new state
+        id_map.clear();
+        // now we wait for the ping to change the worker pool to 10
+        crate::testkit::advance(Duration::from_millis(300)).await;
+
+        // Act again
+        for i in 0..50 {
+            factory
+                .cast(FactoryMessage::Dispatch(Job {
+                    key: TestKey { id: 1 },
+                    msg: TestMessage::Count(i),
+                    options: JobOptions::default(),
+                    accepted: None,
+                }))
+                .expect("Failed to send to factory");
         }
-    }
-
-    let id_map = Arc::new(dashmap::DashSet::new());
 
-    let worker_builder = TestWorkerBuilder {
-        id_map: id_map.clone(),
-    };
-    let factory_definition = Factory::<
-        TestKey,
-        TestMessage,
-        (),
-        TestWorker,
-        routing::RoundRobinRouting<TestKey, TestMessage>,
-        queues::DefaultQueue<TestKey, TestMessage>,
-    >::default();
-    let (factory, factory_handle) = Actor::spawn(
-        None,
-        factory_definition,
-        FactoryArguments {
-            num_initial_workers: 4,
-            queue: queues::DefaultQueue::default(),
-            router: Default::default(),
-            capacity_controller: Some(Box::new(DynamicWorkerController)),
-            dead_mans_switch: None,
-            discard_handler: None,
-            discard_settings: DiscardSettings::None,
-            lifecycle_hooks: None,
-            worker_builder: Box::new(worker_builder),
-            stats: None,
-        },
-    )
-    .await
-    .expect("Failed to spawn factory");
-
-    // Act
-    for i in 0..50 {
-        factory
-            .cast(FactoryMessage::Dispatch(Job {
-                key: TestKey { id: 1 },
-                msg: TestMessage::Count(i),
-                options: JobOptions::default(),
-                accepted: None,
-            }))
-            .expect("Failed to send to factory");
-    }
-
-    crate::periodic_check(
-        || {
-            // The map should only have 4 entries, the id of each worker
-            id_map.len() == 4
-        },
-        Duration::from_millis(200),
-    )
-    .await;
-
-    // Setup new state
-    id_map.clear();
-    // now we wait for the ping to change the worker pool to 10
-    crate::concurrency::sleep(Duration::from_millis(300)).await;
-
-    // Act again
-    for i in 0..50 {
-        factory
-            .cast(FactoryMessage::Dispatch(Job {
-                key: TestKey { id: 1 },
-                msg: TestMessage::Count(i),
-                options: JobOptions::default(),
-                accepted: None,
-            }))
-            .expect("Failed to send to factory");
-    }
-
-    crate::periodic_check(
-        || {
-            // The map should have 10 entries, the id of each worker
-            id_map.len() == 10
-        },
-        Duration::from_millis(200),
-    )
-    .await;
+        crate::periodic_check(
+            || {
+                // The map should have 10 entries, the id of each worker
+                id_map.len() == 10
+            },
+            Duration::from_millis(200),
+        )
+        .await;
 
-    // Cleanup
-    // wait for factory termination
-    factory.stop(None);
-    factory_handle.await.unwrap();
+        // Cleanup
+        // wait for factory termination
+        factory.stop(None);
+        factory_handle.await.unwrap();
+    });
 }
@@ -131,9 +131,9 @@ async fn test_factory_rate_limiting_common<TRouter: Router<(), ()>>(router: TRou
             .expect("Failed to send message to factory");
     }
 
-    // sleep a little to let the worker be in the "ratelim" state
+    // advance the clock a little to let the worker be in the "ratelim" state
     // as it'll have routed the max allowable number of requests
-    sleep(Duration::from_millis(100)).await;
+    crate::testkit::advance(Duration::from_millis(100)).await;
     // send an additional request, which should be marked ratelimiting before
     // even being queued
     factory
@@ -160,122 +160,126 @@ async fn test_factory_rate_limiting_common<TRouter: Router<(), ()>>(router: TRou
     assert_eq!(5, discard_counter.load(Ordering::SeqCst));
 }
 
-#[crate::concurrency::test]
-#[cfg_attr(
-    not(all(target_arch = "wasm32", target_os = "unknown")),
-    tracing_test::traced_test
-)]
-async fn test_factory_rate_limiting_queuer() {
-    test_factory_rate_limiting_common::<QueuerRouting<(), ()>>(Default::default()).await
+#[test]
+#[tracing_test::traced_test]
+fn test_factory_rate_limiting_queuer() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        test_factory_rate_limiting_common::<QueuerRouting<(), ()>>(Default::default()).await
+    });
 }
 
-#[crate::concurrency::test]
-#[cfg_attr(
-    not(all(target_arch = "wasm32", target_os = "unknown")),
-    tracing_test::traced_test
-)]
-async fn test_factory_rate_limiting_sticky_queuer() {
-    test_factory_rate_limiting_common::<StickyQueuerRouting<(), ()>>(Default::default()).await
+#[test]
+#[tracing_test::traced_test]
+fn test_factory_rate_limiting_sticky_queuer() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        test_factory_rate_limiting_common::<StickyQueuerRouting<(), ()>>(Default::default()).await
+    });
 }
 
-#[crate::concurrency::test]
-#[cfg_attr(
-    not(all(target_arch = "wasm32", target_os = "unknown")),
-    tracing_test::traced_test
-)]
-async fn test_factory_rate_limiting_key_persistent() {
-    test_factory_rate_limiting_common::<KeyPersistentRouting<(), ()>>(Default::default()).await
+#[test]
+#[tracing_test::traced_test]
+fn test_factory_rate_limiting_key_persistent() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        test_factory_rate_limiting_common::<KeyPersistentRouting<(), ()>>(Default::default()).await
+    });
 }
 
-#[crate::concurrency::test]
-#[cfg_attr(
-    not(all(target_arch = "wasm32", target_os = "unknown")),
-    tracing_test::traced_test
-)]
-async fn test_factory_rate_limiting_round_robin() {
-    test_factory_rate_limiting_common::<RoundRobinRouting<(), ()>>(Default::default()).await
+#[test]
+#[tracing_test::traced_test]
+fn test_factory_rate_limiting_round_robin() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        test_factory_rate_limiting_common::<RoundRobinRouting<(), ()>>(Default::default()).await
+    });
 }
 
-#[crate::concurrency::test]
-#[cfg_attr(
-    not(all(target_arch = "wasm32", target_os = "unknown")),
-    tracing_test::traced_test
-)]
-async fn test_factory_rate_limiting_custom_hash() {
-    struct MyHasher;
-
-    impl CustomHashFunction<()> for MyHasher {
-        fn hash(&self, _key: &(), _worker_count: usize) -> usize {
-            0
+#[test]
+#[tracing_test::traced_test]
+fn test_factory_rate_limiting_custom_hash() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        struct MyHasher;
+
+        impl CustomHashFunction<()> for MyHasher {
+            fn hash(&self, _key: &(), _worker_count: usize) -> usize {
+                0
+            }
         }
-    }
 
-    let router = CustomRouting::new(MyHasher);
+        let router = CustomRouting::new(MyHasher);
 
-    test_factory_rate_limiting_common(router).await
+        test_factory_rate_limiting_common(router).await
+    });
 }
 
-#[crate::concurrency::test]
-#[cfg_attr(
-    not(all(target_arch = "wasm32", target_os = "unknown")),
-    tracing_test::traced_test
-)]
-async fn test_leaky_bucket_rate_limiting() {
-    // Setup
+#[test]
+#[tracing_test::traced_test]
+fn test_leaky_bucket_rate_limiting() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        // Setup
 
-    use crate::concurrency::sleep;
-    let discard_counter = Arc::new(AtomicU16::new(0));
+        let discard_counter = Arc::new(AtomicU16::new(0));
 
-    struct TestDiscarder {
-        counter: Arc<AtomicU16>,
-    }
-    impl DiscardHandler<(), ()> for TestDiscarder {
-        fn discard(&self, reason: DiscardReason, _job: &mut Job<(), ()>) {
-            tracing::debug!("Discarding job, reason {reason:?}");
-            if reason == DiscardReason::RateLimited {
-                let _ = self.counter.fetch_add(1, Ordering::SeqCst);
+        struct TestDiscarder {
+            counter: Arc<AtomicU16>,
+        }
+        impl DiscardHandler<(), ()> for TestDiscarder {
+            fn discard(&self, reason: DiscardReason, _job: &mut Job<(), ()>) {
+                tracing::debug!("Discarding job, reason {reason:?}");
+                if reason == DiscardReason::RateLimited {
+                    let _ = self.counter.fetch_add(1, Ordering::SeqCst);
+                }
             }
         }
-    }
-
-    let worker_builder = TestWorkerBuilder;
-
-    // Setup rate limited router
-    let limiter = LeakyBucketRateLimiter::builder()
-        .max(5)
-        .initial(5)
-        .refill(1)
-        .interval(Duration::from_millis(100))
-        .build();
-
-    let router = RateLimitedRouter::builder()
-        .router(routing::QueuerRouting::<(), ()>::default())
-        .rate_limiter(limiter)
-        .build();
-    let arguments = FactoryArguments::builder()
-        .num_initial_workers(1)
-        .queue(Default::default())
-        .router(router)
-        .worker_builder(Box::new(worker_builder))
-        .discard_handler(Arc::new(TestDiscarder {
-            counter: discard_counter.clone(),
-        }))
-        .build();
 
-    let factory_definition = Factory::<
-        (),
-        (),
-        (),
-        TestWorker,
-        RateLimitedRouter<routing::QueuerRouting<(), ()>, _>,
-        queues::DefaultQueue<(), ()>,
-    >::default();
-    let (factory, factory_handle) = Actor::spawn(None, factory_definition, arguments)
-        .await
-        .expect("Failed to spawn factory");
+        let worker_builder = TestWorkerBuilder;
+
+        // Setup rate limited router
+        let limiter = LeakyBucketRateLimiter::builder()
+            .max(5)
+            .initial(5)
+            .refill(1)
+            .interval(Duration::from_millis(100))
+            .build();
+
+        let router = RateLimitedRouter::builder()
+            .router(routing::QueuerRouting::<(), ()>::default())
+            .rate_limiter(limiter)
+            .build();
+        let arguments = FactoryArguments::builder()
+            .num_initial_workers(1)
+            .queue(Default::default())
+            .router(router)
+            .worker_builder(Box::new(worker_builder))
+            .discard_handler(Arc::new(TestDiscarder {
+                counter: discard_counter.clone(),
+            }))
+            .build();
+
+        let factory_definition = Factory::<
+            (),
+            (),
+            (),
+            TestWorker,
+            RateLimitedRouter<routing::QueuerRouting<(), ()>, _>,
+            queues::DefaultQueue<(), ()>,
+        >::default();
+        let (factory, factory_handle) = Actor::spawn(None, factory_definition, arguments)
+            .await
+            .expect("Failed to spawn factory");
+
+        // Test
+        for _ in 0..6 {
+            factory
+                .cast(FactoryMessage::Dispatch(Job {
+                    accepted: None,
+                    key: (),
+                    msg: (),
+                    options: JobOptions::default(),
+                }))
+                .expect("Failed to send message to factory");
+        }
 
-    // Test
-    for _ in 0..6 {
+        // advance >100ms and we should be able to push another job
+        crate::testkit::advance(Duration::from_millis(200)).await;
         factory
             .cast(FactoryMessage::Dispatch(Job {
                 accepted: None,
@@ -284,30 +288,19 @@ async fn test_leaky_bucket_rate_limiting() {
                 options: JobOptions::default(),
             }))
             .expect("Failed to send message to factory");
-    }
-
-    // sleep >100ms and we should be able to push another job
-    sleep(crate::concurrency::Duration::from_millis(200)).await;
-    factory
-        .cast(FactoryMessage::Dispatch(Job {
-            accepted: None,
-            key: (),
-            msg: (),
-            options: JobOptions::default(),
-        }))
-        .expect("Failed to send message to factory");
-
-    // Drain factory
-    factory
-        .cast(FactoryMessage::DrainRequests)
-        .expect("Failed to message factory");
-
-    // once the factory is stopped, the shutdown handler should have been called
-    crate::concurrency::timeout(Duration::from_secs(1), factory_handle)
-        .await
-        .expect("Failed to drain requests in 1s")
-        .expect("Failed to join factory handle");
 
-    // Check that we rate-limited only 1 message, from the first batch
-    assert_eq!(1, discard_counter.load(Ordering::SeqCst));
+        // Drain factory
+        factory
+            .cast(FactoryMessage::DrainRequests)
+            .expect("Failed to message factory");
+
+        // once the factory is stopped, the shutdown handler should have been called
+        crate::concurrency::timeout(Duration::from_secs(1), factory_handle)
+            .await
+            .expect("Failed to drain requests in 1s")
+            .expect("Failed to join factory handle");
+
+        // Check that we rate-limited only 1 message, from the first batch
+        assert_eq!(1, discard_counter.load(Ordering::SeqCst));
+    });
 }
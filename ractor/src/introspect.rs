@@ -0,0 +1,288 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Introspection of the live supervision tree, similar to Erlang's `observer`.
+//!
+//! [ActorNode::of] walks the supervision tree below an actor, capturing the hierarchy as a
+//! tree of [ActorNode]s. With the `cluster` feature, which maintains the
+//! [crate::registry::pid_registry], [snapshot] captures the trees of every local actor,
+//! starting from the actors which have no (local) supervisor. The trees can then be
+//! rendered as human-readable text with [render_text], or as JSON with [render_json].
+//! With the `serde` feature enabled, [ActorNode] additionally implements `serde::Serialize`.
+//!
+//! Note that the tree is captured actor-by-actor while the system keeps running, so it
+//! isn't an atomic view of the system.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use ractor::introspect::ActorNode;
+//! use ractor::ActorCell;
+//!
+//! fn print_tree(root: &ActorCell) {
+//!     let tree = ActorNode::of(root);
+//!     println!("{tree}");
+//!     println!("{}", ractor::introspect::render_json(&[tree]));
+//! }
+//! ```
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::ActorCell;
+use crate::ActorId;
+use crate::ActorName;
+use crate::ActorStatus;
+
+#[cfg(test)]
+mod tests;
+
+/// A point-in-time description of an actor and its supervised children
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ActorNode {
+    /// The actor's id
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_display"))]
+    pub id: ActorId,
+    /// The actor's registered name, if any
+    pub name: Option<ActorName>,
+    /// The actor's status
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_debug"))]
+    pub status: ActorStatus,
+    /// The name of the actor's type, see [ActorCell::get_type_name]
+    pub type_name: &'static str,
+    /// The number of messages waiting in the actor's mailbox
+    pub mailbox_len: usize,
+    /// The ids of the actors monitoring this actor. Always empty without the
+    /// `monitors` feature.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_display_vec"))]
+    pub monitors: Vec<ActorId>,
+    /// The actor's supervised children, ordered by id
+    pub children: Vec<ActorNode>,
+}
+
+impl ActorNode {
+    /// Capture the tree rooted at the provided actor
+    pub fn of(actor: &ActorCell) -> Self {
+        Self::capture(actor, &mut HashSet::new())
+    }
+
+    fn capture(actor: &ActorCell, visited: &mut HashSet<ActorId>) -> Self {
+        visited.insert(actor.get_id());
+
+        let mut children = actor.get_children();
+        children.sort_by_key(|child| child.get_id());
+        let mut nodes = Vec::with_capacity(children.len());
+        for child in children.iter() {
+            // guard against a child being re-linked mid-walk and showing up twice
+            if !visited.contains(&child.get_id()) {
+                nodes.push(Self::capture(child, visited));
+            }
+        }
+
+        #[cfg(feature = "monitors")]
+        let monitors = {
+            let mut monitors = actor.inner.tree.get_monitors();
+            monitors.sort();
+            monitors
+        };
+        #[cfg(not(feature = "monitors"))]
+        let monitors = vec![];
+
+        Self {
+            id: actor.get_id(),
+            name: actor.get_name(),
+            status: actor.get_status(),
+            type_name: actor.get_type_name(),
+            mailbox_len: actor.get_stats().mailbox_depth,
+            monitors,
+            children: nodes,
+        }
+    }
+
+    /// The total number of actors in this tree, including this one
+    pub fn num_actors(&self) -> usize {
+        1 + self
+            .children
+            .iter()
+            .map(ActorNode::num_actors)
+            .sum::<usize>()
+    }
+
+    /// Find the node of the actor with the provided id in this tree
+    pub fn find(&self, id: ActorId) -> Option<&ActorNode> {
+        if self.id == id {
+            Some(self)
+        } else {
+            self.children.iter().find_map(|child| child.find(id))
+        }
+    }
+
+    fn write_text(&self, out: &mut String, prefix: &str, connector: &str, child_prefix: &str) {
+        let _ = write!(out, "{prefix}{connector}{}", self.id);
+        if let Some(name) = &self.name {
+            let _ = write!(out, " \"{name}\"");
+        }
+        let _ = write!(
+            out,
+            " [{:?}] {} mailbox={}",
+            self.status, self.type_name, self.mailbox_len
+        );
+        if !self.monitors.is_empty() {
+            let monitors = self
+                .monitors
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            let _ = write!(out, " monitors=[{}]", monitors.join(", "));
+        }
+        out.push('\n');
+
+        let prefix = format!("{prefix}{child_prefix}");
+        for (i, child) in self.children.iter().enumerate() {
+            if i + 1 == self.children.len() {
+                child.write_text(out, &prefix, "└── ", "    ");
+            } else {
+                child.write_text(out, &prefix, "├── ", "│   ");
+            }
+        }
+    }
+
+    fn write_json(&self, out: &mut String) {
+        let _ = write!(out, "{{\"id\":\"{}\",\"name\":", self.id);
+        match &self.name {
+            Some(name) => write_json_string(out, name),
+            None => out.push_str("null"),
+        }
+        let _ = write!(out, ",\"status\":\"{:?}\",\"type_name\":", self.status);
+        write_json_string(out, self.type_name);
+        let _ = write!(out, ",\"mailbox_len\":{},\"monitors\":[", self.mailbox_len);
+        for (i, monitor) in self.monitors.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let _ = write!(out, "\"{monitor}\"");
+        }
+        out.push_str("],\"children\":[");
+        for (i, child) in self.children.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            child.write_json(out);
+        }
+        out.push_str("]}");
+    }
+}
+
+impl std::fmt::Display for ActorNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut out = String::new();
+        self.write_text(&mut out, "", "", "");
+        write!(f, "{}", out.trim_end())
+    }
+}
+
+/// Capture the supervision trees of all the local actors.
+///
+/// The roots are the actors without a supervisor, or whose supervisor isn't a local
+/// actor (i.e. it's remote or has already exited). They're ordered by id, as are the
+/// children of every node.
+#[cfg(feature = "cluster")]
+pub fn snapshot() -> Vec<ActorNode> {
+    let mut actors = crate::registry::get_all_pids();
+    actors.sort_by_key(|actor| actor.get_id());
+    let local = actors
+        .iter()
+        .map(|actor| actor.get_id())
+        .collect::<HashSet<_>>();
+
+    let mut visited = HashSet::new();
+    let mut roots = vec![];
+    for actor in actors.iter() {
+        let is_root = match actor.try_get_supervisor() {
+            Some(supervisor) => !local.contains(&supervisor.get_id()),
+            None => true,
+        };
+        if is_root && !visited.contains(&actor.get_id()) {
+            roots.push(ActorNode::capture(actor, &mut visited));
+        }
+    }
+    roots
+}
+
+/// Render supervision trees as human-readable text, one line per actor, e.g.
+///
+/// ```text
+/// 0.1 "root" [Running] my_crate::Root mailbox=0
+/// ├── 0.2 [Running] my_crate::Worker mailbox=3
+/// └── 0.3 [Stopping] my_crate::Worker mailbox=0
+/// ```
+pub fn render_text(trees: &[ActorNode]) -> String {
+    let mut out = String::new();
+    for tree in trees {
+        tree.write_text(&mut out, "", "", "");
+    }
+    out
+}
+
+/// Render supervision trees as a JSON array of nodes, with the same fields as
+/// [ActorNode]. Ids are rendered as strings (e.g. `"0.12"`) and statuses by their
+/// variant name (e.g. `"Running"`).
+pub fn render_json(trees: &[ActorNode]) -> String {
+    let mut out = String::from("[");
+    for (i, tree) in trees.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        tree.write_json(&mut out);
+    }
+    out.push(']');
+    out
+}
+
+fn write_json_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(feature = "serde")]
+fn serialize_display<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: std::fmt::Display,
+    S: serde::Serializer,
+{
+    serializer.collect_str(value)
+}
+
+#[cfg(feature = "serde")]
+fn serialize_debug<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: std::fmt::Debug,
+    S: serde::Serializer,
+{
+    serializer.collect_str(&format_args!("{value:?}"))
+}
+
+#[cfg(feature = "serde")]
+fn serialize_display_vec<T, S>(values: &[T], serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: std::fmt::Display,
+    S: serde::Serializer,
+{
+    serializer.collect_seq(values.iter().map(ToString::to_string))
+}
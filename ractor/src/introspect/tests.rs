@@ -0,0 +1,153 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Tests for supervision tree introspection
+
+use super::*;
+use crate::common_test::periodic_check;
+use crate::concurrency::Duration;
+use crate::Actor;
+use crate::ActorProcessingErr;
+use crate::ActorRef;
+
+struct Node;
+
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+impl Actor for Node {
+    type Msg = ();
+    type State = ();
+    type Arguments = ();
+
+    async fn pre_start(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        _args: Self::Arguments,
+    ) -> Result<Self::State, ActorProcessingErr> {
+        Ok(())
+    }
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_snapshot_of_supervision_tree() {
+    let (root, root_handle) = Actor::spawn(Some("introspect_root".to_string()), Node, ())
+        .await
+        .expect("Failed to start root");
+    let (a, _) = Actor::spawn_linked(None, Node, (), root.get_cell())
+        .await
+        .expect("Failed to start child");
+    let (b, _) = Actor::spawn_linked(None, Node, (), root.get_cell())
+        .await
+        .expect("Failed to start child");
+    let (c, _) = Actor::spawn_linked(
+        Some("introspect_\"grandchild\"".to_string()),
+        Node,
+        (),
+        a.get_cell(),
+    )
+    .await
+    .expect("Failed to start grandchild");
+
+    // spawning returns once the actors have started, but before they're marked as running
+    periodic_check(
+        || {
+            [root.get_cell(), a.get_cell(), b.get_cell(), c.get_cell()]
+                .iter()
+                .all(|actor| actor.get_status() == ActorStatus::Running)
+        },
+        Duration::from_secs(1),
+    )
+    .await;
+
+    let tree = &ActorNode::of(&root.get_cell());
+    assert_eq!(4, tree.num_actors());
+    assert_eq!(Some("introspect_root".to_string()), tree.name);
+    assert_eq!(ActorStatus::Running, tree.status);
+    assert!(tree.type_name.ends_with("introspect::tests::Node"));
+    assert_eq!(0, tree.mailbox_len);
+    assert!(tree.monitors.is_empty());
+    assert_eq!(
+        vec![a.get_id(), b.get_id()],
+        tree.children.iter().map(|c| c.id).collect::<Vec<_>>()
+    );
+    let grandchild = tree.find(c.get_id()).expect("Missing grandchild");
+    assert!(grandchild.children.is_empty());
+    assert_eq!(&ActorNode::of(&a.get_cell()), &tree.children[0]);
+
+    #[cfg(feature = "cluster")]
+    {
+        let trees = snapshot();
+        assert_eq!(
+            Some(tree),
+            trees.iter().find(|tree| tree.id == root.get_id())
+        );
+        // children aren't roots of their own
+        assert!(trees.iter().all(|tree| tree.id != a.get_id()));
+    }
+
+    let text = tree.to_string();
+    let lines = text.lines().collect::<Vec<_>>();
+    assert_eq!(4, lines.len());
+    assert!(lines[0].starts_with(&format!("{} \"introspect_root\" [Running]", root.get_id())));
+    assert!(lines[1].starts_with(&format!("├── {}", a.get_id())));
+    assert!(lines[2].starts_with(&format!("│   └── {}", c.get_id())));
+    assert!(lines[3].starts_with(&format!("└── {}", b.get_id())));
+    assert_eq!(format!("{text}\n"), render_text(std::slice::from_ref(tree)));
+
+    let json = render_json(std::slice::from_ref(tree));
+    assert!(json.starts_with(&format!(
+        "[{{\"id\":\"{}\",\"name\":\"introspect_root\",\"status\":\"Running\",",
+        root.get_id()
+    )));
+    assert!(json.contains("\"name\":\"introspect_\\\"grandchild\\\"\""));
+    assert!(json.contains("\"mailbox_len\":0,\"monitors\":[],\"children\":[{"));
+    assert!(json.ends_with("\"children\":[]}]}]"));
+
+    root.stop(None);
+    root_handle.await.unwrap();
+    #[cfg(feature = "cluster")]
+    {
+        // stopped actors leave the snapshot
+        periodic_check(
+            || {
+                snapshot()
+                    .iter()
+                    .all(|tree| tree.find(c.get_id()).is_none())
+            },
+            Duration::from_secs(1),
+        )
+        .await;
+    }
+}
+
+#[cfg(feature = "monitors")]
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_snapshot_includes_monitors() {
+    let (actor, handle) = Actor::spawn(None, Node, ())
+        .await
+        .expect("Failed to start actor");
+    let (monitor, monitor_handle) = Actor::spawn(None, Node, ())
+        .await
+        .expect("Failed to start monitor");
+    monitor.monitor(actor.get_cell());
+
+    let node = ActorNode::of(&actor.get_cell());
+    assert_eq!(vec![monitor.get_id()], node.monitors);
+    assert!(node
+        .to_string()
+        .ends_with(&format!("monitors=[{}]", monitor.get_id())));
+
+    actor.stop(None);
+    monitor.stop(None);
+    handle.await.unwrap();
+    monitor_handle.await.unwrap();
+}
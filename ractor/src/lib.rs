@@ -172,14 +172,28 @@ pub use common_test::*;
 pub mod concurrency;
 pub mod errors;
 pub mod factory;
+pub mod introspect;
 pub mod macros;
 pub mod message;
+pub mod persistence;
 pub mod pg;
 pub mod port;
 pub mod registry;
 pub mod rpc;
-#[cfg(feature = "cluster")]
 pub mod serialization;
+pub mod shutdown;
+pub mod state_machine;
+pub mod supervisor;
+// the crate's own tests use the testkit on tokio, whether or not the feature is enabled
+#[cfg(any(
+    feature = "testkit",
+    all(
+        test,
+        not(feature = "async-std"),
+        not(all(target_arch = "wasm32", target_os = "unknown"))
+    )
+))]
+pub mod testkit;
 pub mod thread_local;
 pub mod time;
 
@@ -197,10 +211,13 @@ pub use actor::actor_cell::ACTIVE_STATES;
 pub use actor::actor_id::ActorId;
 pub use actor::actor_ref::ActorRef;
 pub use actor::derived_actor::DerivedActorRef;
+pub use actor::mailbox::MailboxOverflowPolicy;
 pub use actor::messages::Signal;
 pub use actor::messages::SupervisionEvent;
+pub use actor::priority::MessagePriority;
 pub use actor::Actor;
 pub use actor::ActorRuntime;
+pub use actor::SpawnOptions;
 #[cfg(feature = "async-trait")]
 pub use async_trait::async_trait;
 #[cfg(test)]
@@ -218,7 +235,6 @@ pub use port::OutputPort;
 pub use port::RpcReplyPort;
 #[cfg(test)]
 use rand as _;
-#[cfg(feature = "cluster")]
 pub use serialization::BytesConvertable;
 #[cfg(test)]
 use tracing_glog as _;
@@ -65,6 +65,29 @@ pub struct BoxedMessage {
     #[cfg(feature = "cluster")]
     pub serialized_msg: Option<SerializedMessage>,
     pub(crate) span: Option<tracing::Span>,
+    /// The deadline of the request this message carries, if it's an RPC with a timeout.
+    /// Messages past their deadline are dropped by the actor without being handled.
+    pub(crate) deadline: Option<crate::concurrency::Instant>,
+    /// The receipt of this message in the actor's durable mailbox, if it's logged there,
+    /// see [crate::actor::durable_mailbox]
+    #[cfg(feature = "cluster")]
+    pub(crate) receipt: Option<crate::actor::durable_mailbox::DurableReceipt>,
+}
+
+impl BoxedMessage {
+    /// Determine if this message's deadline (if any) has passed
+    pub(crate) fn is_expired(&self) -> bool {
+        matches!(self.deadline, Some(deadline) if crate::concurrency::Instant::now() >= deadline)
+    }
+
+    /// Acknowledge this message in the actor's durable mailbox (if any), once it's been
+    /// handled or dropped, so it isn't replayed
+    pub(crate) fn acknowledge(&mut self) {
+        #[cfg(feature = "cluster")]
+        if let Some(receipt) = self.receipt.take() {
+            receipt.ack();
+        }
+    }
 }
 
 impl std::fmt::Debug for BoxedMessage {
@@ -150,12 +173,16 @@ pub trait Message: Any + Send + Sized + 'static {
                 msg: None,
                 serialized_msg: Some(self.serialize()?),
                 span: None,
+                deadline: None,
+                receipt: None,
             })
         } else if pid.is_local() {
             Ok(BoxedMessage {
                 msg: Some(Box::new(self)),
                 serialized_msg: None,
                 span,
+                deadline: None,
+                receipt: None,
             })
         } else {
             Err(BoxedDowncastErr)
@@ -179,6 +206,7 @@ pub trait Message: Any + Send + Sized + 'static {
         Ok(BoxedMessage {
             msg: Some(Box::new(self)),
             span,
+            deadline: None,
         })
     }
 
@@ -0,0 +1,518 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Event-sourced persistent actors, whose state survives restarts.
+//!
+//! A [PersistentActor] doesn't change its state directly. Instead, while handling a
+//! message it persists events with [PersistentState::persist], each of which is appended
+//! to a [Journal] before it's applied to the state with [PersistentActor::apply]. When the
+//! actor is (re-)started, its state is recovered in `pre_start` by applying the journaled
+//! events again, so a supervisor restarting the actor with the same [PersistenceConfig]
+//! picks up where it failed.
+//!
+//! To bound the recovery time, the state can be snapshot every so many events (see
+//! [PersistenceConfig]) or on demand with [PersistentState::save_snapshot]. Recovery then
+//! starts from the latest snapshot and only applies the events after it.
+//!
+//! Events and snapshots are encoded with [BytesConvertable], which is implemented for all
+//! `serde` types with the `blanket_serde` feature. Two journals are provided: the
+//! [InMemoryJournal], which survives restarts within the process, and the [FileJournal].
+//!
+//! A persistent actor is run by wrapping it in a [Persistent] actor, which is spawned as
+//! usual with [Actor::spawn] or [Actor::spawn_linked].
+//!
+//! ## Example
+//!
+//! ```rust
+//! use std::sync::Arc;
+//!
+//! use ractor::persistence::InMemoryJournal;
+//! use ractor::persistence::Persistent;
+//! use ractor::persistence::PersistenceConfig;
+//! use ractor::persistence::PersistentActor;
+//! use ractor::persistence::PersistentState;
+//! use ractor::Actor;
+//! use ractor::ActorProcessingErr;
+//! use ractor::ActorRef;
+//!
+//! struct Counter;
+//!
+//! #[cfg_attr(feature = "async-trait", ractor::async_trait)]
+//! impl PersistentActor for Counter {
+//!     type Msg = u64;
+//!     type Event = u64;
+//!     type State = u64;
+//!     type Arguments = ();
+//!
+//!     async fn pre_start(
+//!         &self,
+//!         _myself: ActorRef<Self::Msg>,
+//!         _: (),
+//!     ) -> Result<Self::State, ActorProcessingErr> {
+//!         Ok(0)
+//!     }
+//!
+//!     fn apply(&self, total: &mut u64, added: u64) {
+//!         *total += added;
+//!     }
+//!
+//!     async fn handle(
+//!         &self,
+//!         _myself: ActorRef<Self::Msg>,
+//!         message: u64,
+//!         state: &mut PersistentState<Self>,
+//!     ) -> Result<(), ActorProcessingErr> {
+//!         state.persist(message).await?;
+//!         Ok(())
+//!     }
+//! }
+//!
+//! async fn run() {
+//!     let config = PersistenceConfig::builder()
+//!         .persistence_id("counter".to_string())
+//!         .journal(Arc::new(InMemoryJournal::new()))
+//!         .snapshot_every(100)
+//!         .build();
+//!     let (counter, _) = Actor::spawn(None, Persistent::new(Counter, config), ())
+//!         .await
+//!         .expect("Failed to start the counter");
+//!     counter.cast(3).unwrap();
+//! }
+//! ```
+
+use std::fmt::Debug;
+#[cfg(not(feature = "async-trait"))]
+use std::future::Future;
+use std::sync::Arc;
+
+use crate::Actor;
+use crate::ActorProcessingErr;
+use crate::ActorRef;
+use crate::BytesConvertable;
+use crate::Message;
+use crate::SupervisionEvent;
+
+mod file;
+mod journal;
+
+pub use file::FileJournal;
+pub use journal::InMemoryJournal;
+pub use journal::Journal;
+pub use journal::JournalEntry;
+pub use journal::JournalErr;
+
+#[cfg(test)]
+mod tests;
+
+/// How a [Persistent] actor persists its events
+#[derive(Debug, Clone, bon::Builder)]
+pub struct PersistenceConfig {
+    /// The id the events and snapshots are stored under. It needs to be stable across
+    /// restarts, and unique among the actors sharing the journal.
+    pub persistence_id: String,
+    /// The journal to store the events and snapshots in
+    pub journal: Arc<dyn Journal>,
+    /// Snapshot the state after this many events were persisted since the last snapshot
+    ///
+    /// Default = [None], only snapshot with [PersistentState::save_snapshot]
+    pub snapshot_every: Option<u64>,
+    /// Delete the events included in a snapshot from the journal once it's saved
+    ///
+    /// Default = [false]
+    #[builder(default)]
+    pub delete_events_on_snapshot: bool,
+}
+
+/// An event-sourced actor, run by a [Persistent] actor, see the
+/// [module documentation](self)
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+pub trait PersistentActor: Sized + Send + Sync + 'static {
+    /// The message type of the actor
+    type Msg: Message;
+    /// The events which are persisted, and applied to the state
+    type Event: BytesConvertable + Clone + Send + 'static;
+    /// The state of the actor, which is recovered from its snapshot and events
+    type State: BytesConvertable + Clone + crate::State;
+    /// The startup arguments of the actor (use `()` to ignore)
+    type Arguments: crate::State;
+
+    /// Invoked when the actor is being started, to create its state before any events
+    /// were applied. When a snapshot is recovered it replaces this state.
+    ///
+    /// * `myself` - A handle to the [crate::ActorCell] representing this actor
+    /// * `args` - Arguments that are passed in the spawning of the actor
+    ///
+    /// Returns the empty state
+    #[cfg(not(feature = "async-trait"))]
+    fn pre_start(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        args: Self::Arguments,
+    ) -> impl Future<Output = Result<Self::State, ActorProcessingErr>> + Send;
+
+    /// Invoked when the actor is being started, to create its state before any events
+    /// were applied. When a snapshot is recovered it replaces this state.
+    ///
+    /// * `myself` - A handle to the [crate::ActorCell] representing this actor
+    /// * `args` - Arguments that are passed in the spawning of the actor
+    ///
+    /// Returns the empty state
+    #[cfg(feature = "async-trait")]
+    async fn pre_start(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        args: Self::Arguments,
+    ) -> Result<Self::State, ActorProcessingErr>;
+
+    /// Apply an event to the state, both while recovering and once it's persisted. It
+    /// should be deterministic, and not have side effects.
+    ///
+    /// * `state` - The state to update
+    /// * `event` - The event to apply
+    fn apply(&self, state: &mut Self::State, event: Self::Event);
+
+    /// Invoked after the actor's state was recovered and it has started
+    ///
+    /// * `myself` - A handle to the [crate::ActorCell] representing this actor
+    /// * `state` - The recovered state
+    #[allow(unused_variables)]
+    #[cfg(not(feature = "async-trait"))]
+    fn post_start(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        state: &mut PersistentState<Self>,
+    ) -> impl Future<Output = Result<(), ActorProcessingErr>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Invoked after the actor's state was recovered and it has started
+    ///
+    /// * `myself` - A handle to the [crate::ActorCell] representing this actor
+    /// * `state` - The recovered state
+    #[allow(unused_variables)]
+    #[cfg(feature = "async-trait")]
+    async fn post_start(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        state: &mut PersistentState<Self>,
+    ) -> Result<(), ActorProcessingErr> {
+        Ok(())
+    }
+
+    /// Handle a message, persisting the events which result from it
+    ///
+    /// Errors and panics follow the supervision strategy, as for [Actor::handle]. A failure
+    /// to persist an event is returned by [PersistentState::persist], and is best
+    /// propagated so the actor is restarted from the journal.
+    ///
+    /// * `myself` - A handle to the [crate::ActorCell] representing this actor
+    /// * `message` - The message to process
+    /// * `state` - The state, which events are persisted to
+    #[cfg(not(feature = "async-trait"))]
+    fn handle(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        message: Self::Msg,
+        state: &mut PersistentState<Self>,
+    ) -> impl Future<Output = Result<(), ActorProcessingErr>> + Send;
+
+    /// Handle a message, persisting the events which result from it
+    ///
+    /// Errors and panics follow the supervision strategy, as for [Actor::handle]. A failure
+    /// to persist an event is returned by [PersistentState::persist], and is best
+    /// propagated so the actor is restarted from the journal.
+    ///
+    /// * `myself` - A handle to the [crate::ActorCell] representing this actor
+    /// * `message` - The message to process
+    /// * `state` - The state, which events are persisted to
+    #[cfg(feature = "async-trait")]
+    async fn handle(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        message: Self::Msg,
+        state: &mut PersistentState<Self>,
+    ) -> Result<(), ActorProcessingErr>;
+
+    /// Handle a supervision event, as for [Actor::handle_supervisor_evt]. The default is
+    /// to stop the actor on any child exit.
+    ///
+    /// * `myself` - A handle to the [crate::ActorCell] representing this actor
+    /// * `message` - The supervision event
+    /// * `state` - The state
+    #[allow(unused_variables)]
+    #[cfg(not(feature = "async-trait"))]
+    fn handle_supervisor_evt(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        message: SupervisionEvent,
+        state: &mut PersistentState<Self>,
+    ) -> impl Future<Output = Result<(), ActorProcessingErr>> + Send {
+        async move {
+            match message {
+                SupervisionEvent::ActorTerminated(who, _, _)
+                | SupervisionEvent::ActorFailed(who, _) => {
+                    myself.stop(None);
+                }
+                _ => {}
+            }
+            Ok(())
+        }
+    }
+
+    /// Handle a supervision event, as for [Actor::handle_supervisor_evt]. The default is
+    /// to stop the actor on any child exit.
+    ///
+    /// * `myself` - A handle to the [crate::ActorCell] representing this actor
+    /// * `message` - The supervision event
+    /// * `state` - The state
+    #[allow(unused_variables)]
+    #[cfg(feature = "async-trait")]
+    async fn handle_supervisor_evt(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        message: SupervisionEvent,
+        state: &mut PersistentState<Self>,
+    ) -> Result<(), ActorProcessingErr> {
+        match message {
+            SupervisionEvent::ActorTerminated(who, _, _)
+            | SupervisionEvent::ActorFailed(who, _) => {
+                myself.stop(None);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Invoked after the actor has been stopped to perform final cleanup, as for
+    /// [Actor::post_stop]
+    ///
+    /// * `myself` - A handle to the [crate::ActorCell] representing this actor
+    /// * `state` - The state
+    #[allow(unused_variables)]
+    #[cfg(not(feature = "async-trait"))]
+    fn post_stop(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        state: &mut PersistentState<Self>,
+    ) -> impl Future<Output = Result<(), ActorProcessingErr>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Invoked after the actor has been stopped to perform final cleanup, as for
+    /// [Actor::post_stop]
+    ///
+    /// * `myself` - A handle to the [crate::ActorCell] representing this actor
+    /// * `state` - The state
+    #[allow(unused_variables)]
+    #[cfg(feature = "async-trait")]
+    async fn post_stop(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        state: &mut PersistentState<Self>,
+    ) -> Result<(), ActorProcessingErr> {
+        Ok(())
+    }
+}
+
+/// The state of a [PersistentActor], along with its progress through the journal
+pub struct PersistentState<TActor: PersistentActor> {
+    actor: Arc<TActor>,
+    config: PersistenceConfig,
+    state: TActor::State,
+    sequence_nr: u64,
+    snapshot_sequence_nr: u64,
+}
+
+impl<TActor: PersistentActor> Debug for PersistentState<TActor> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PersistentState")
+            .field("persistence_id", &self.config.persistence_id)
+            .field("sequence_nr", &self.sequence_nr)
+            .finish()
+    }
+}
+
+impl<TActor: PersistentActor> PersistentState<TActor> {
+    /// The current state
+    pub fn state(&self) -> &TActor::State {
+        &self.state
+    }
+
+    /// The persistence id the events are stored under
+    pub fn persistence_id(&self) -> &str {
+        &self.config.persistence_id
+    }
+
+    /// The sequence number of the last persisted event, or 0 if none
+    pub fn sequence_nr(&self) -> u64 {
+        self.sequence_nr
+    }
+
+    /// Persist an event to the journal, and then apply it to the state
+    ///
+    /// * `event` - The event to persist
+    ///
+    /// Returns [Err(JournalErr)] if the event couldn't be persisted, in which case it
+    /// isn't applied
+    pub async fn persist(&mut self, event: TActor::Event) -> Result<(), JournalErr> {
+        self.persist_all(vec![event]).await
+    }
+
+    /// Persist events to the journal atomically, and then apply them to the state in order
+    ///
+    /// * `events` - The events to persist
+    ///
+    /// Returns [Err(JournalErr)] if the events couldn't be persisted, in which case none
+    /// is applied. If the events trigger an automatic snapshot (see
+    /// [PersistenceConfig::snapshot_every]) which fails, the failure is logged and the
+    /// snapshot is attempted again after the next persisted events.
+    pub async fn persist_all(&mut self, events: Vec<TActor::Event>) -> Result<(), JournalErr> {
+        let entries = (self.sequence_nr + 1..)
+            .zip(&events)
+            .map(|(sequence_nr, event)| JournalEntry::new(sequence_nr, event.clone().into_bytes()))
+            .collect();
+        self.config
+            .journal
+            .append(&self.config.persistence_id, entries)
+            .await?;
+
+        for event in events {
+            self.actor.apply(&mut self.state, event);
+            self.sequence_nr += 1;
+        }
+        if let Some(every) = self.config.snapshot_every {
+            if self.sequence_nr - self.snapshot_sequence_nr >= every {
+                // the events are persisted and applied, so this isn't their failure
+                if let Err(err) = self.save_snapshot().await {
+                    tracing::warn!(
+                        "Failed to save the snapshot of '{}' at sequence number {}: {err}",
+                        self.config.persistence_id,
+                        self.sequence_nr
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Save a snapshot of the current state, from which the actor is then recovered
+    ///
+    /// Returns [Err(JournalErr)] if the snapshot couldn't be saved
+    pub async fn save_snapshot(&mut self) -> Result<(), JournalErr> {
+        let snapshot = JournalEntry::new(self.sequence_nr, self.state.clone().into_bytes());
+        let journal = &self.config.journal;
+        journal
+            .save_snapshot(&self.config.persistence_id, snapshot)
+            .await?;
+        self.snapshot_sequence_nr = self.sequence_nr;
+        if self.config.delete_events_on_snapshot {
+            journal
+                .delete_to(&self.config.persistence_id, self.sequence_nr)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// The [Actor] which runs a [PersistentActor], see the [module documentation](self)
+#[derive(Debug)]
+pub struct Persistent<TActor> {
+    actor: Arc<TActor>,
+    config: PersistenceConfig,
+}
+
+impl<TActor: PersistentActor> Persistent<TActor> {
+    /// Wrap a persistent actor into an actor, which can then be spawned
+    ///
+    /// * `actor` - The [PersistentActor] to run
+    /// * `config` - How its events are persisted
+    pub fn new(actor: TActor, config: PersistenceConfig) -> Self {
+        Self {
+            actor: Arc::new(actor),
+            config,
+        }
+    }
+}
+
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+impl<TActor> Actor for Persistent<TActor>
+where
+    TActor: PersistentActor,
+{
+    type Msg = TActor::Msg;
+    type State = PersistentState<TActor>;
+    type Arguments = TActor::Arguments;
+
+    async fn pre_start(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        args: Self::Arguments,
+    ) -> Result<Self::State, ActorProcessingErr> {
+        let mut state = self.actor.pre_start(myself, args).await?;
+        let journal = &self.config.journal;
+        let persistence_id = &self.config.persistence_id;
+
+        let mut snapshot_sequence_nr = 0;
+        if let Some(snapshot) = journal.load_snapshot(persistence_id).await? {
+            state = TActor::State::from_bytes(snapshot.payload);
+            snapshot_sequence_nr = snapshot.sequence_nr;
+        }
+        let events = journal
+            .read(persistence_id, snapshot_sequence_nr + 1)
+            .await?;
+        tracing::debug!(
+            "Recovering '{persistence_id}' from sequence number {snapshot_sequence_nr}, replaying {} events",
+            events.len()
+        );
+        for event in events {
+            self.actor
+                .apply(&mut state, TActor::Event::from_bytes(event.payload));
+        }
+
+        Ok(PersistentState {
+            actor: self.actor.clone(),
+            config: self.config.clone(),
+            state,
+            sequence_nr: journal.highest_sequence_nr(persistence_id).await?,
+            snapshot_sequence_nr,
+        })
+    }
+
+    async fn post_start(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        self.actor.post_start(myself, state).await
+    }
+
+    async fn post_stop(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        self.actor.post_stop(myself, state).await
+    }
+
+    async fn handle(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        message: Self::Msg,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        self.actor.handle(myself, message, state).await
+    }
+
+    async fn handle_supervisor_evt(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        message: SupervisionEvent,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        self.actor
+            .handle_supervisor_evt(myself, message, state)
+            .await
+    }
+}
@@ -0,0 +1,524 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! A [Journal] stored in files
+//!
+//! Every persistence id has an append-only `<id>.journal` file, which starts with a header
+//! holding the sequence number events were deleted up to, followed by the records of the
+//! events. A record is the sequence number, the payload's length, the payload and a
+//! checksum. A record which was only partially written when the process died is dropped
+//! when the file is next opened. The snapshot is kept in a `<id>.snapshot` file, which is
+//! replaced atomically.
+//!
+//! A journal file is parsed once, when it's first accessed, to index where its records are.
+//! Reads then only load the records they return. Deleting events only updates the header,
+//! until the deleted records take up more space than the others, at which point the file is
+//! rewritten without them.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+#[cfg(not(feature = "async-trait"))]
+use futures::future::BoxFuture;
+#[cfg(not(feature = "async-trait"))]
+use futures::FutureExt;
+
+use super::journal::check_sequence;
+use super::Journal;
+use super::JournalEntry;
+use super::JournalErr;
+use crate::concurrency::run_blocking;
+
+const JOURNAL_MAGIC: &[u8; 4] = b"RJNL";
+const SNAPSHOT_MAGIC: &[u8; 4] = b"RSNP";
+/// The magic number and the sequence number events were deleted up to
+const HEADER_LEN: usize = 12;
+/// The sequence number, length and checksum around the payload of a record
+const RECORD_OVERHEAD: usize = 16;
+
+/// A [Journal] stored in files in a directory, see the [module documentation](self)
+///
+/// The file I/O runs on the async runtime's blocking threads.
+#[derive(Debug, Clone)]
+pub struct FileJournal {
+    inner: Arc<FileJournalInner>,
+}
+
+#[derive(Debug)]
+struct FileJournalInner {
+    directory: PathBuf,
+    /// The index of the journal files accessed so far. The lock also serializes access to
+    /// the files.
+    streams: Mutex<HashMap<String, StreamIndex>>,
+}
+
+/// Where the records of a journal file are, so that events can be read and deleted
+/// without parsing the whole file again
+#[derive(Debug, Default)]
+struct StreamIndex {
+    /// The sequence number events were deleted up to. Deleted records are left in the file
+    /// until they take up more space than the others, and then compacted away.
+    deleted_to: u64,
+    /// The sequence number of the first record in the file
+    first: u64,
+    /// The offset of every record in the file, starting with the one of `first`
+    offsets: Vec<u64>,
+    /// The length of the file up to the end of the last record
+    len: u64,
+}
+
+impl StreamIndex {
+    fn highest(&self) -> u64 {
+        if self.offsets.is_empty() {
+            self.deleted_to
+        } else {
+            (self.first + self.offsets.len() as u64 - 1).max(self.deleted_to)
+        }
+    }
+
+    /// The offset of the first record with at least the sequence number, or the length of
+    /// the file if there's none
+    fn offset_of(&self, sequence_nr: u64) -> u64 {
+        let index = sequence_nr.saturating_sub(self.first) as usize;
+        self.offsets.get(index).copied().unwrap_or(self.len)
+    }
+}
+
+impl FileJournal {
+    /// Open a journal stored in a directory, creating the directory if needed
+    ///
+    /// * `directory` - The directory to store the files in
+    pub fn open(directory: impl Into<PathBuf>) -> Result<Self, JournalErr> {
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory)?;
+        Ok(Self {
+            inner: Arc::new(FileJournalInner {
+                directory,
+                streams: Mutex::new(HashMap::new()),
+            }),
+        })
+    }
+
+    /// The directory the files are stored in
+    pub fn directory(&self) -> &Path {
+        &self.inner.directory
+    }
+
+    /// Run an operation on the blocking threads
+    async fn run<F, T>(&self, persistence_id: &str, operation: F) -> Result<T, JournalErr>
+    where
+        F: FnOnce(&FileJournalInner, &str) -> Result<T, JournalErr> + Send + 'static,
+        T: Send + 'static,
+    {
+        let inner = self.inner.clone();
+        let persistence_id = persistence_id.to_string();
+        run_blocking(move || operation(&inner, &persistence_id)).await
+    }
+}
+
+impl FileJournalInner {
+    fn path(&self, persistence_id: &str, extension: &str) -> PathBuf {
+        // escape anything which could be a path separator or otherwise special
+        let mut stem = String::with_capacity(persistence_id.len());
+        for byte in persistence_id.bytes() {
+            if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' {
+                stem.push(byte as char);
+            } else {
+                stem.push_str(&format!("%{byte:02X}"));
+            }
+        }
+        self.directory.join(format!("{stem}.{extension}"))
+    }
+
+    /// The index of the persistence id's journal file, which is built the first time it's
+    /// accessed, dropping a partially written record from the end of the file
+    fn index<'a>(
+        &self,
+        streams: &'a mut HashMap<String, StreamIndex>,
+        persistence_id: &str,
+    ) -> Result<&'a mut StreamIndex, JournalErr> {
+        if !streams.contains_key(persistence_id) {
+            let path = self.path(persistence_id, "journal");
+            let (index, file_len) = index_journal(&path)?;
+            if index.len < file_len {
+                tracing::warn!(
+                    "Dropping a partially written record from the journal of '{persistence_id}'"
+                );
+                OpenOptions::new()
+                    .write(true)
+                    .open(&path)?
+                    .set_len(index.len)?;
+            }
+            streams.insert(persistence_id.to_string(), index);
+        }
+        Ok(streams
+            .get_mut(persistence_id)
+            .expect("The index was just inserted"))
+    }
+
+    fn append(&self, persistence_id: &str, events: Vec<JournalEntry>) -> Result<(), JournalErr> {
+        let mut streams = self.streams.lock().unwrap();
+        let index = self.index(&mut streams, persistence_id)?;
+        check_sequence(persistence_id, index.highest(), &events)?;
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path(persistence_id, "journal"))?;
+        let mut buffer = vec![];
+        if index.len == 0 {
+            encode_header(&mut buffer, JOURNAL_MAGIC, 0);
+        }
+        let mut offsets = Vec::with_capacity(events.len());
+        for event in &events {
+            offsets.push(index.len + buffer.len() as u64);
+            encode_record(&mut buffer, event);
+        }
+        file.write_all(&buffer)?;
+        file.sync_data()?;
+
+        if index.offsets.is_empty() {
+            index.first = events[0].sequence_nr;
+        }
+        index.offsets.extend(offsets);
+        index.len += buffer.len() as u64;
+        Ok(())
+    }
+
+    fn read(
+        &self,
+        persistence_id: &str,
+        from_sequence_nr: u64,
+    ) -> Result<Vec<JournalEntry>, JournalErr> {
+        let mut streams = self.streams.lock().unwrap();
+        let index = self.index(&mut streams, persistence_id)?;
+        let from = index.offset_of(from_sequence_nr.max(index.deleted_to + 1));
+        if from >= index.len {
+            return Ok(vec![]);
+        }
+
+        let path = self.path(persistence_id, "journal");
+        let bytes = read_range(&path, from, index.len)?;
+        let mut events = vec![];
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let (event, len) = decode_record(&bytes[offset..]).ok_or_else(|| {
+                JournalErr::Corrupted(format!("Invalid record in journal file {path:?}"))
+            })?;
+            events.push(event);
+            offset += len;
+        }
+        Ok(events)
+    }
+
+    fn highest_sequence_nr(&self, persistence_id: &str) -> Result<u64, JournalErr> {
+        let mut streams = self.streams.lock().unwrap();
+        Ok(self.index(&mut streams, persistence_id)?.highest())
+    }
+
+    fn delete_to(&self, persistence_id: &str, to_sequence_nr: u64) -> Result<(), JournalErr> {
+        let mut streams = self.streams.lock().unwrap();
+        let index = self.index(&mut streams, persistence_id)?;
+        let deleted_to = index.deleted_to.max(to_sequence_nr.min(index.highest()));
+        if index.len == 0 || deleted_to == index.deleted_to {
+            return Ok(());
+        }
+
+        let path = self.path(persistence_id, "journal");
+        let retained = index.offset_of(deleted_to + 1);
+        let garbage = retained - HEADER_LEN as u64;
+        if garbage <= index.len - retained {
+            // only mark the events as deleted, in the header
+            let mut file = OpenOptions::new().write(true).open(&path)?;
+            file.seek(SeekFrom::Start(JOURNAL_MAGIC.len() as u64))?;
+            file.write_all(&deleted_to.to_le_bytes())?;
+            file.sync_data()?;
+            index.deleted_to = deleted_to;
+            return Ok(());
+        }
+
+        // the deleted records take up more space than the others, so compact them away
+        let mut buffer = vec![];
+        encode_header(&mut buffer, JOURNAL_MAGIC, deleted_to);
+        buffer.extend(read_range(&path, retained, index.len)?);
+        replace_file(&path, &buffer)?;
+
+        let kept = (deleted_to + 1).saturating_sub(index.first) as usize;
+        let offsets = index.offsets.split_off(kept.min(index.offsets.len()));
+        index.offsets = offsets.into_iter().map(|offset| offset - garbage).collect();
+        index.first = deleted_to + 1;
+        index.deleted_to = deleted_to;
+        index.len = buffer.len() as u64;
+        Ok(())
+    }
+
+    fn save_snapshot(
+        &self,
+        persistence_id: &str,
+        snapshot: JournalEntry,
+    ) -> Result<(), JournalErr> {
+        let _guard = self.streams.lock().unwrap();
+        let mut buffer = vec![];
+        encode_header(&mut buffer, SNAPSHOT_MAGIC, 0);
+        encode_record(&mut buffer, &snapshot);
+        replace_file(&self.path(persistence_id, "snapshot"), &buffer)
+    }
+
+    fn load_snapshot(&self, persistence_id: &str) -> Result<Option<JournalEntry>, JournalErr> {
+        let _guard = self.streams.lock().unwrap();
+        let path = self.path(persistence_id, "snapshot");
+        let bytes = match std::fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let corrupted = || JournalErr::Corrupted(format!("Invalid snapshot file {path:?}"));
+        decode_header(&bytes, SNAPSHOT_MAGIC).ok_or_else(corrupted)?;
+        match decode_record(&bytes[HEADER_LEN..]) {
+            Some((snapshot, len)) if HEADER_LEN + len == bytes.len() => Ok(Some(snapshot)),
+            _ => Err(corrupted()),
+        }
+    }
+}
+
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+impl Journal for FileJournal {
+    #[cfg(feature = "async-trait")]
+    async fn append(
+        &self,
+        persistence_id: &str,
+        events: Vec<JournalEntry>,
+    ) -> Result<(), JournalErr> {
+        self.run(persistence_id, move |inner, id| inner.append(id, events))
+            .await
+    }
+
+    #[cfg(not(feature = "async-trait"))]
+    fn append<'a>(
+        &'a self,
+        persistence_id: &'a str,
+        events: Vec<JournalEntry>,
+    ) -> BoxFuture<'a, Result<(), JournalErr>> {
+        self.run(persistence_id, move |inner, id| inner.append(id, events))
+            .boxed()
+    }
+
+    #[cfg(feature = "async-trait")]
+    async fn read(
+        &self,
+        persistence_id: &str,
+        from_sequence_nr: u64,
+    ) -> Result<Vec<JournalEntry>, JournalErr> {
+        self.run(persistence_id, move |inner, id| {
+            inner.read(id, from_sequence_nr)
+        })
+        .await
+    }
+
+    #[cfg(not(feature = "async-trait"))]
+    fn read<'a>(
+        &'a self,
+        persistence_id: &'a str,
+        from_sequence_nr: u64,
+    ) -> BoxFuture<'a, Result<Vec<JournalEntry>, JournalErr>> {
+        self.run(persistence_id, move |inner, id| {
+            inner.read(id, from_sequence_nr)
+        })
+        .boxed()
+    }
+
+    #[cfg(feature = "async-trait")]
+    async fn highest_sequence_nr(&self, persistence_id: &str) -> Result<u64, JournalErr> {
+        self.run(persistence_id, |inner, id| inner.highest_sequence_nr(id))
+            .await
+    }
+
+    #[cfg(not(feature = "async-trait"))]
+    fn highest_sequence_nr<'a>(
+        &'a self,
+        persistence_id: &'a str,
+    ) -> BoxFuture<'a, Result<u64, JournalErr>> {
+        self.run(persistence_id, |inner, id| inner.highest_sequence_nr(id))
+            .boxed()
+    }
+
+    #[cfg(feature = "async-trait")]
+    async fn delete_to(&self, persistence_id: &str, to_sequence_nr: u64) -> Result<(), JournalErr> {
+        self.run(persistence_id, move |inner, id| {
+            inner.delete_to(id, to_sequence_nr)
+        })
+        .await
+    }
+
+    #[cfg(not(feature = "async-trait"))]
+    fn delete_to<'a>(
+        &'a self,
+        persistence_id: &'a str,
+        to_sequence_nr: u64,
+    ) -> BoxFuture<'a, Result<(), JournalErr>> {
+        self.run(persistence_id, move |inner, id| {
+            inner.delete_to(id, to_sequence_nr)
+        })
+        .boxed()
+    }
+
+    #[cfg(feature = "async-trait")]
+    async fn save_snapshot(
+        &self,
+        persistence_id: &str,
+        snapshot: JournalEntry,
+    ) -> Result<(), JournalErr> {
+        self.run(persistence_id, move |inner, id| {
+            inner.save_snapshot(id, snapshot)
+        })
+        .await
+    }
+
+    #[cfg(not(feature = "async-trait"))]
+    fn save_snapshot<'a>(
+        &'a self,
+        persistence_id: &'a str,
+        snapshot: JournalEntry,
+    ) -> BoxFuture<'a, Result<(), JournalErr>> {
+        self.run(persistence_id, move |inner, id| {
+            inner.save_snapshot(id, snapshot)
+        })
+        .boxed()
+    }
+
+    #[cfg(feature = "async-trait")]
+    async fn load_snapshot(
+        &self,
+        persistence_id: &str,
+    ) -> Result<Option<JournalEntry>, JournalErr> {
+        self.run(persistence_id, |inner, id| inner.load_snapshot(id))
+            .await
+    }
+
+    #[cfg(not(feature = "async-trait"))]
+    fn load_snapshot<'a>(
+        &'a self,
+        persistence_id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<JournalEntry>, JournalErr>> {
+        self.run(persistence_id, |inner, id| inner.load_snapshot(id))
+            .boxed()
+    }
+}
+
+/// Read and index a journal file, which is empty if it doesn't exist. Returns the index
+/// along with the length of the file, which is longer than the indexed records if the
+/// last one was only partially written.
+fn index_journal(path: &Path) -> Result<(StreamIndex, u64), JournalErr> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => vec![],
+        Err(err) => return Err(err.into()),
+    };
+    let mut index = StreamIndex::default();
+    if bytes.is_empty() {
+        return Ok((index, 0));
+    }
+    let corrupted = || JournalErr::Corrupted(format!("Invalid journal file {path:?}"));
+    index.deleted_to = decode_header(&bytes, JOURNAL_MAGIC).ok_or_else(corrupted)?;
+
+    let mut offset = HEADER_LEN;
+    while let Some((event, len)) = decode_record(&bytes[offset..]) {
+        if index.offsets.is_empty() {
+            index.first = event.sequence_nr;
+        } else if event.sequence_nr != index.first + index.offsets.len() as u64 {
+            return Err(corrupted());
+        }
+        index.offsets.push(offset as u64);
+        offset += len;
+    }
+    index.len = offset as u64;
+    Ok((index, bytes.len() as u64))
+}
+
+/// Read the bytes of a file from the start offset up to the end offset
+fn read_range(path: &Path, start: u64, end: u64) -> Result<Vec<u8>, JournalErr> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(start))?;
+    let mut bytes = vec![0; (end - start) as usize];
+    file.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Write a file next to the target, and then move it over the target, so the target is
+/// never partially written
+fn replace_file(path: &Path, contents: &[u8]) -> Result<(), JournalErr> {
+    let temporary = path.with_extension("tmp");
+    let mut file = File::create(&temporary)?;
+    file.write_all(contents)?;
+    file.sync_data()?;
+    std::fs::rename(&temporary, path)?;
+    Ok(())
+}
+
+fn encode_header(buffer: &mut Vec<u8>, magic: &[u8; 4], deleted_to: u64) {
+    buffer.extend_from_slice(magic);
+    buffer.extend_from_slice(&deleted_to.to_le_bytes());
+}
+
+/// Decode a header, returning the sequence number events were deleted up to
+fn decode_header(bytes: &[u8], magic: &[u8; 4]) -> Option<u64> {
+    if bytes.len() < HEADER_LEN || &bytes[..4] != magic {
+        return None;
+    }
+    Some(u64::from_le_bytes(bytes[4..HEADER_LEN].try_into().ok()?))
+}
+
+fn encode_record(buffer: &mut Vec<u8>, entry: &JournalEntry) {
+    let sequence_nr = entry.sequence_nr.to_le_bytes();
+    buffer.extend_from_slice(&sequence_nr);
+    buffer.extend_from_slice(&(entry.payload.len() as u32).to_le_bytes());
+    buffer.extend_from_slice(&entry.payload);
+    buffer.extend_from_slice(&checksum(&sequence_nr, &entry.payload).to_le_bytes());
+}
+
+/// Decode the record at the start of the bytes, returning it along with its length, or
+/// [None] if it's incomplete or its checksum doesn't match
+fn decode_record(bytes: &[u8]) -> Option<(JournalEntry, usize)> {
+    if bytes.len() < RECORD_OVERHEAD {
+        return None;
+    }
+    let sequence_nr: [u8; 8] = bytes[..8].try_into().ok()?;
+    let len = u32::from_le_bytes(bytes[8..12].try_into().ok()?) as usize;
+    let end = 12usize.checked_add(len)?;
+    if bytes.len() < end + 4 {
+        return None;
+    }
+    let payload = &bytes[12..end];
+    let expected = u32::from_le_bytes(bytes[end..end + 4].try_into().ok()?);
+    if checksum(&sequence_nr, payload) != expected {
+        return None;
+    }
+    Some((
+        JournalEntry::new(u64::from_le_bytes(sequence_nr), payload.to_vec()),
+        end + 4,
+    ))
+}
+
+/// The 32-bit FNV-1a hash of the sequence number and payload
+fn checksum(sequence_nr: &[u8], payload: &[u8]) -> u32 {
+    sequence_nr
+        .iter()
+        .chain(payload)
+        .fold(0x811c_9dc5, |hash, byte| {
+            (hash ^ u32::from(*byte)).wrapping_mul(0x0100_0193)
+        })
+}
@@ -0,0 +1,437 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! The [Journal] storage of persistent actors, along with an in-memory implementation
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Mutex;
+
+#[cfg(not(feature = "async-trait"))]
+use futures::future::BoxFuture;
+#[cfg(not(feature = "async-trait"))]
+use futures::FutureExt;
+
+/// An encoded event or snapshot, at its sequence number. The sequence number of a
+/// snapshot is the one of the last event it includes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalEntry {
+    /// The sequence number, starting at 1 for the first event of a persistence id
+    pub sequence_nr: u64,
+    /// The encoded event or snapshot
+    pub payload: Vec<u8>,
+}
+
+impl JournalEntry {
+    /// Create a new entry
+    ///
+    /// * `sequence_nr` - The sequence number of the entry
+    /// * `payload` - The encoded event or snapshot
+    pub fn new(sequence_nr: u64, payload: Vec<u8>) -> Self {
+        Self {
+            sequence_nr,
+            payload,
+        }
+    }
+}
+
+/// An error of a [Journal]
+#[derive(Debug)]
+pub enum JournalErr {
+    /// The journal's storage failed
+    Io(std::io::Error),
+    /// An appended event doesn't follow the highest sequence number of the persistence id,
+    /// e.g. because another actor with the same persistence id wrote to it meanwhile
+    SequenceConflict {
+        /// The persistence id the events were appended to
+        persistence_id: String,
+        /// The sequence number the next event needs to have
+        expected: u64,
+        /// The sequence number of the appended event
+        actual: u64,
+    },
+    /// The stored data is malformed
+    Corrupted(String),
+}
+
+impl std::fmt::Display for JournalErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "Journal I/O error: {err}"),
+            Self::SequenceConflict {
+                persistence_id,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Sequence conflict appending to '{persistence_id}': expected {expected}, got {actual}"
+            ),
+            Self::Corrupted(reason) => write!(f, "Corrupted journal: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for JournalErr {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for JournalErr {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// The storage of the events and snapshots of persistent actors, keyed by their
+/// persistence id
+///
+/// The journal is awaited by the persistent actor, so the actor doesn't handle other
+/// messages until a call completes. Implementations which do blocking I/O should move it
+/// off the async runtime's threads, as the [super::FileJournal] does.
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+pub trait Journal: Debug + Send + Sync + 'static {
+    /// Append events, atomically: either all of them are stored or none is
+    ///
+    /// * `persistence_id` - The persistence id to append to
+    /// * `events` - The events, whose sequence numbers need to follow the highest one of
+    ///   the persistence id consecutively, otherwise [JournalErr::SequenceConflict] is
+    ///   returned
+    #[cfg(feature = "async-trait")]
+    async fn append(
+        &self,
+        persistence_id: &str,
+        events: Vec<JournalEntry>,
+    ) -> Result<(), JournalErr>;
+
+    /// Append events, atomically: either all of them are stored or none is
+    ///
+    /// * `persistence_id` - The persistence id to append to
+    /// * `events` - The events, whose sequence numbers need to follow the highest one of
+    ///   the persistence id consecutively, otherwise [JournalErr::SequenceConflict] is
+    ///   returned
+    #[cfg(not(feature = "async-trait"))]
+    fn append<'a>(
+        &'a self,
+        persistence_id: &'a str,
+        events: Vec<JournalEntry>,
+    ) -> BoxFuture<'a, Result<(), JournalErr>>;
+
+    /// Read the stored events, in order
+    ///
+    /// * `persistence_id` - The persistence id to read
+    /// * `from_sequence_nr` - The lowest sequence number to read
+    #[cfg(feature = "async-trait")]
+    async fn read(
+        &self,
+        persistence_id: &str,
+        from_sequence_nr: u64,
+    ) -> Result<Vec<JournalEntry>, JournalErr>;
+
+    /// Read the stored events, in order
+    ///
+    /// * `persistence_id` - The persistence id to read
+    /// * `from_sequence_nr` - The lowest sequence number to read
+    #[cfg(not(feature = "async-trait"))]
+    fn read<'a>(
+        &'a self,
+        persistence_id: &'a str,
+        from_sequence_nr: u64,
+    ) -> BoxFuture<'a, Result<Vec<JournalEntry>, JournalErr>>;
+
+    /// The highest sequence number ever appended, including deleted events, or 0 if none
+    ///
+    /// * `persistence_id` - The persistence id
+    #[cfg(feature = "async-trait")]
+    async fn highest_sequence_nr(&self, persistence_id: &str) -> Result<u64, JournalErr>;
+
+    /// The highest sequence number ever appended, including deleted events, or 0 if none
+    ///
+    /// * `persistence_id` - The persistence id
+    #[cfg(not(feature = "async-trait"))]
+    fn highest_sequence_nr<'a>(
+        &'a self,
+        persistence_id: &'a str,
+    ) -> BoxFuture<'a, Result<u64, JournalErr>>;
+
+    /// Delete the events up to and including a sequence number, e.g. once a snapshot
+    /// includes them
+    ///
+    /// * `persistence_id` - The persistence id
+    /// * `to_sequence_nr` - The highest sequence number to delete
+    #[cfg(feature = "async-trait")]
+    async fn delete_to(&self, persistence_id: &str, to_sequence_nr: u64) -> Result<(), JournalErr>;
+
+    /// Delete the events up to and including a sequence number, e.g. once a snapshot
+    /// includes them
+    ///
+    /// * `persistence_id` - The persistence id
+    /// * `to_sequence_nr` - The highest sequence number to delete
+    #[cfg(not(feature = "async-trait"))]
+    fn delete_to<'a>(
+        &'a self,
+        persistence_id: &'a str,
+        to_sequence_nr: u64,
+    ) -> BoxFuture<'a, Result<(), JournalErr>>;
+
+    /// Store a snapshot, replacing the previous one
+    ///
+    /// * `persistence_id` - The persistence id
+    /// * `snapshot` - The snapshot
+    #[cfg(feature = "async-trait")]
+    async fn save_snapshot(
+        &self,
+        persistence_id: &str,
+        snapshot: JournalEntry,
+    ) -> Result<(), JournalErr>;
+
+    /// Store a snapshot, replacing the previous one
+    ///
+    /// * `persistence_id` - The persistence id
+    /// * `snapshot` - The snapshot
+    #[cfg(not(feature = "async-trait"))]
+    fn save_snapshot<'a>(
+        &'a self,
+        persistence_id: &'a str,
+        snapshot: JournalEntry,
+    ) -> BoxFuture<'a, Result<(), JournalErr>>;
+
+    /// Load the latest snapshot, if any
+    ///
+    /// * `persistence_id` - The persistence id
+    #[cfg(feature = "async-trait")]
+    async fn load_snapshot(&self, persistence_id: &str)
+        -> Result<Option<JournalEntry>, JournalErr>;
+
+    /// Load the latest snapshot, if any
+    ///
+    /// * `persistence_id` - The persistence id
+    #[cfg(not(feature = "async-trait"))]
+    fn load_snapshot<'a>(
+        &'a self,
+        persistence_id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<JournalEntry>, JournalErr>>;
+}
+
+/// Check that events follow the highest sequence number consecutively
+pub(crate) fn check_sequence(
+    persistence_id: &str,
+    highest: u64,
+    events: &[JournalEntry],
+) -> Result<(), JournalErr> {
+    for (expected, event) in (highest + 1..).zip(events) {
+        if event.sequence_nr != expected {
+            return Err(JournalErr::SequenceConflict {
+                persistence_id: persistence_id.to_string(),
+                expected,
+                actual: event.sequence_nr,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct InMemoryStream {
+    events: Vec<JournalEntry>,
+    highest: u64,
+    snapshot: Option<JournalEntry>,
+}
+
+/// A [Journal] which keeps everything in memory, so it lasts as long as the process.
+/// Useful for tests, and for actors which only need to survive being restarted by their
+/// supervisor.
+#[derive(Default)]
+pub struct InMemoryJournal {
+    streams: Mutex<HashMap<String, InMemoryStream>>,
+}
+
+impl Debug for InMemoryJournal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InMemoryJournal")
+            .field("persistence_ids", &self.streams.lock().unwrap().len())
+            .finish()
+    }
+}
+
+impl InMemoryJournal {
+    /// Create a new, empty journal
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn append_now(
+        &self,
+        persistence_id: &str,
+        events: Vec<JournalEntry>,
+    ) -> Result<(), JournalErr> {
+        let mut streams = self.streams.lock().unwrap();
+        let stream = streams.entry(persistence_id.to_string()).or_default();
+        check_sequence(persistence_id, stream.highest, &events)?;
+        stream.highest += events.len() as u64;
+        stream.events.extend(events);
+        Ok(())
+    }
+
+    fn read_now(
+        &self,
+        persistence_id: &str,
+        from_sequence_nr: u64,
+    ) -> Result<Vec<JournalEntry>, JournalErr> {
+        let streams = self.streams.lock().unwrap();
+        Ok(streams
+            .get(persistence_id)
+            .map(|stream| {
+                stream
+                    .events
+                    .iter()
+                    .filter(|event| event.sequence_nr >= from_sequence_nr)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    fn highest_sequence_nr_now(&self, persistence_id: &str) -> Result<u64, JournalErr> {
+        let streams = self.streams.lock().unwrap();
+        Ok(streams
+            .get(persistence_id)
+            .map(|stream| stream.highest)
+            .unwrap_or_default())
+    }
+
+    fn delete_to_now(&self, persistence_id: &str, to_sequence_nr: u64) -> Result<(), JournalErr> {
+        let mut streams = self.streams.lock().unwrap();
+        if let Some(stream) = streams.get_mut(persistence_id) {
+            stream
+                .events
+                .retain(|event| event.sequence_nr > to_sequence_nr);
+        }
+        Ok(())
+    }
+
+    fn save_snapshot_now(
+        &self,
+        persistence_id: &str,
+        snapshot: JournalEntry,
+    ) -> Result<(), JournalErr> {
+        let mut streams = self.streams.lock().unwrap();
+        streams
+            .entry(persistence_id.to_string())
+            .or_default()
+            .snapshot = Some(snapshot);
+        Ok(())
+    }
+
+    fn load_snapshot_now(&self, persistence_id: &str) -> Result<Option<JournalEntry>, JournalErr> {
+        let streams = self.streams.lock().unwrap();
+        Ok(streams
+            .get(persistence_id)
+            .and_then(|stream| stream.snapshot.clone()))
+    }
+}
+
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+impl Journal for InMemoryJournal {
+    #[cfg(feature = "async-trait")]
+    async fn append(
+        &self,
+        persistence_id: &str,
+        events: Vec<JournalEntry>,
+    ) -> Result<(), JournalErr> {
+        self.append_now(persistence_id, events)
+    }
+
+    #[cfg(not(feature = "async-trait"))]
+    fn append<'a>(
+        &'a self,
+        persistence_id: &'a str,
+        events: Vec<JournalEntry>,
+    ) -> BoxFuture<'a, Result<(), JournalErr>> {
+        futures::future::ready(self.append_now(persistence_id, events)).boxed()
+    }
+
+    #[cfg(feature = "async-trait")]
+    async fn read(
+        &self,
+        persistence_id: &str,
+        from_sequence_nr: u64,
+    ) -> Result<Vec<JournalEntry>, JournalErr> {
+        self.read_now(persistence_id, from_sequence_nr)
+    }
+
+    #[cfg(not(feature = "async-trait"))]
+    fn read<'a>(
+        &'a self,
+        persistence_id: &'a str,
+        from_sequence_nr: u64,
+    ) -> BoxFuture<'a, Result<Vec<JournalEntry>, JournalErr>> {
+        futures::future::ready(self.read_now(persistence_id, from_sequence_nr)).boxed()
+    }
+
+    #[cfg(feature = "async-trait")]
+    async fn highest_sequence_nr(&self, persistence_id: &str) -> Result<u64, JournalErr> {
+        self.highest_sequence_nr_now(persistence_id)
+    }
+
+    #[cfg(not(feature = "async-trait"))]
+    fn highest_sequence_nr<'a>(
+        &'a self,
+        persistence_id: &'a str,
+    ) -> BoxFuture<'a, Result<u64, JournalErr>> {
+        futures::future::ready(self.highest_sequence_nr_now(persistence_id)).boxed()
+    }
+
+    #[cfg(feature = "async-trait")]
+    async fn delete_to(&self, persistence_id: &str, to_sequence_nr: u64) -> Result<(), JournalErr> {
+        self.delete_to_now(persistence_id, to_sequence_nr)
+    }
+
+    #[cfg(not(feature = "async-trait"))]
+    fn delete_to<'a>(
+        &'a self,
+        persistence_id: &'a str,
+        to_sequence_nr: u64,
+    ) -> BoxFuture<'a, Result<(), JournalErr>> {
+        futures::future::ready(self.delete_to_now(persistence_id, to_sequence_nr)).boxed()
+    }
+
+    #[cfg(feature = "async-trait")]
+    async fn save_snapshot(
+        &self,
+        persistence_id: &str,
+        snapshot: JournalEntry,
+    ) -> Result<(), JournalErr> {
+        self.save_snapshot_now(persistence_id, snapshot)
+    }
+
+    #[cfg(not(feature = "async-trait"))]
+    fn save_snapshot<'a>(
+        &'a self,
+        persistence_id: &'a str,
+        snapshot: JournalEntry,
+    ) -> BoxFuture<'a, Result<(), JournalErr>> {
+        futures::future::ready(self.save_snapshot_now(persistence_id, snapshot)).boxed()
+    }
+
+    #[cfg(feature = "async-trait")]
+    async fn load_snapshot(
+        &self,
+        persistence_id: &str,
+    ) -> Result<Option<JournalEntry>, JournalErr> {
+        self.load_snapshot_now(persistence_id)
+    }
+
+    #[cfg(not(feature = "async-trait"))]
+    fn load_snapshot<'a>(
+        &'a self,
+        persistence_id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<JournalEntry>, JournalErr>> {
+        futures::future::ready(self.load_snapshot_now(persistence_id)).boxed()
+    }
+}
@@ -0,0 +1,313 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Tests of persistent actors and journals
+
+use super::*;
+use crate::common_test::periodic_check;
+use crate::concurrency::Duration;
+use crate::supervisor::ChildSpec;
+use crate::supervisor::Restart;
+use crate::supervisor::Supervisor;
+use crate::supervisor::SupervisorArguments;
+use crate::RpcReplyPort;
+
+enum CounterMsg {
+    Add(u64),
+    Snapshot,
+    Fail,
+    Get(RpcReplyPort<(u64, u64)>),
+}
+#[cfg(feature = "cluster")]
+impl Message for CounterMsg {}
+
+struct Counter;
+
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+impl PersistentActor for Counter {
+    type Msg = CounterMsg;
+    type Event = u64;
+    type State = u64;
+    type Arguments = ();
+
+    async fn pre_start(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        _: (),
+    ) -> Result<Self::State, ActorProcessingErr> {
+        Ok(0)
+    }
+
+    fn apply(&self, total: &mut u64, added: u64) {
+        *total += added;
+    }
+
+    async fn handle(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        message: Self::Msg,
+        state: &mut PersistentState<Self>,
+    ) -> Result<(), ActorProcessingErr> {
+        match message {
+            CounterMsg::Add(added) => state.persist(added).await?,
+            CounterMsg::Snapshot => state.save_snapshot().await?,
+            CounterMsg::Fail => return Err(From::from("boom")),
+            CounterMsg::Get(reply) => {
+                let _ = reply.send((*state.state(), state.sequence_nr()));
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn spawn_counter(config: PersistenceConfig) -> ActorRef<CounterMsg> {
+    let (counter, _) = Actor::spawn(None, Persistent::new(Counter, config), ())
+        .await
+        .expect("Failed to spawn persistent actor");
+    counter
+}
+
+async fn get(counter: &ActorRef<CounterMsg>) -> (u64, u64) {
+    crate::call_t!(counter, CounterMsg::Get, 500).expect("Failed to query counter")
+}
+
+fn entries(range: std::ops::RangeInclusive<u64>) -> Vec<JournalEntry> {
+    range
+        .map(|sequence_nr| JournalEntry::new(sequence_nr, vec![sequence_nr as u8; 3]))
+        .collect()
+}
+
+async fn check_journal(journal: &dyn Journal) {
+    assert_eq!(0, journal.highest_sequence_nr("a").await.unwrap());
+    journal.append("a", entries(1..=3)).await.unwrap();
+    journal.append("b", entries(1..=1)).await.unwrap();
+    assert_eq!(entries(2..=3), journal.read("a", 2).await.unwrap());
+
+    // appends need to follow the highest sequence number
+    let err = journal.append("a", entries(5..=5)).await.unwrap_err();
+    assert!(matches!(
+        err,
+        JournalErr::SequenceConflict {
+            expected: 4,
+            actual: 5,
+            ..
+        }
+    ));
+    assert_eq!(3, journal.highest_sequence_nr("a").await.unwrap());
+
+    assert_eq!(None, journal.load_snapshot("a").await.unwrap());
+    journal
+        .save_snapshot("a", JournalEntry::new(2, vec![42]))
+        .await
+        .unwrap();
+    journal.delete_to("a", 2).await.unwrap();
+    assert_eq!(entries(3..=3), journal.read("a", 0).await.unwrap());
+    assert_eq!(
+        Some(JournalEntry::new(2, vec![42])),
+        journal.load_snapshot("a").await.unwrap()
+    );
+
+    // deleting everything keeps the sequence numbers going
+    journal.delete_to("a", 3).await.unwrap();
+    assert!(journal.read("a", 0).await.unwrap().is_empty());
+    assert_eq!(3, journal.highest_sequence_nr("a").await.unwrap());
+    journal.append("a", entries(4..=4)).await.unwrap();
+
+    assert_eq!(entries(1..=1), journal.read("b", 0).await.unwrap());
+}
+
+fn temporary_directory(name: &str) -> std::path::PathBuf {
+    let directory = std::env::temp_dir().join(format!(
+        "ractor_{name}_{}_{}",
+        std::process::id(),
+        crate::concurrency::random_u64()
+    ));
+    let _ = std::fs::remove_dir_all(&directory);
+    directory
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_in_memory_journal() {
+    check_journal(&InMemoryJournal::new()).await;
+}
+
+#[crate::concurrency::test]
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+#[tracing_test::traced_test]
+async fn test_file_journal() {
+    let directory = temporary_directory("test_file_journal");
+    check_journal(&FileJournal::open(&directory).unwrap()).await;
+
+    // everything is read back by a new journal on the same directory
+    let journal = FileJournal::open(&directory).unwrap();
+    assert_eq!(4, journal.highest_sequence_nr("a").await.unwrap());
+    assert_eq!(entries(4..=4), journal.read("a", 0).await.unwrap());
+    assert_eq!(
+        Some(JournalEntry::new(2, vec![42])),
+        journal.load_snapshot("a").await.unwrap()
+    );
+
+    // a partially written record is dropped
+    let path = directory.join("b.journal");
+    let mut file = std::fs::OpenOptions::new().append(true).open(path).unwrap();
+    std::io::Write::write_all(&mut file, &[2, 0, 0]).unwrap();
+    drop(file);
+    let journal = FileJournal::open(&directory).unwrap();
+    assert_eq!(1, journal.highest_sequence_nr("b").await.unwrap());
+    journal.append("b", entries(2..=2)).await.unwrap();
+    assert_eq!(entries(1..=2), journal.read("b", 0).await.unwrap());
+
+    // persistence ids are escaped into file names
+    journal.append("../c/d", entries(1..=1)).await.unwrap();
+    assert!(directory.join("%2E%2E%2Fc%2Fd.journal").exists());
+
+    // deleted events are only compacted away once they take up most of the file
+    let path = directory.join("e.journal");
+    journal.append("e", entries(1..=10)).await.unwrap();
+    let len = std::fs::metadata(&path).unwrap().len();
+    journal.delete_to("e", 4).await.unwrap();
+    assert_eq!(len, std::fs::metadata(&path).unwrap().len());
+    journal.delete_to("e", 6).await.unwrap();
+    assert!(std::fs::metadata(&path).unwrap().len() < len);
+    assert_eq!(entries(7..=10), journal.read("e", 0).await.unwrap());
+    journal.append("e", entries(11..=11)).await.unwrap();
+    let journal = FileJournal::open(&directory).unwrap();
+    assert_eq!(entries(8..=11), journal.read("e", 8).await.unwrap());
+    assert_eq!(11, journal.highest_sequence_nr("e").await.unwrap());
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_recover_from_events() {
+    let journal: Arc<dyn Journal> = Arc::new(InMemoryJournal::new());
+    let config = PersistenceConfig::builder()
+        .persistence_id("counter".to_string())
+        .journal(journal.clone())
+        .build();
+
+    let counter = spawn_counter(config.clone()).await;
+    for added in 1..=4 {
+        counter.cast(CounterMsg::Add(added)).unwrap();
+    }
+    assert_eq!((10, 4), get(&counter).await);
+    counter.stop_and_wait(None, None).await.unwrap();
+
+    let counter = spawn_counter(config).await;
+    assert_eq!((10, 4), get(&counter).await);
+    counter.cast(CounterMsg::Add(5)).unwrap();
+    assert_eq!((15, 5), get(&counter).await);
+    counter.stop_and_wait(None, None).await.unwrap();
+
+    assert_eq!(5, journal.highest_sequence_nr("counter").await.unwrap());
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_recover_from_snapshot() {
+    let journal: Arc<dyn Journal> = Arc::new(InMemoryJournal::new());
+    let config = PersistenceConfig::builder()
+        .persistence_id("counter".to_string())
+        .journal(journal.clone())
+        .snapshot_every(3)
+        .delete_events_on_snapshot(true)
+        .build();
+
+    let counter = spawn_counter(config.clone()).await;
+    for added in 1..=7 {
+        counter.cast(CounterMsg::Add(added)).unwrap();
+    }
+    assert_eq!((28, 7), get(&counter).await);
+    counter.stop_and_wait(None, None).await.unwrap();
+
+    // the snapshot includes the first 6 events, which were deleted
+    assert_eq!(
+        Some(JournalEntry::new(6, 21u64.into_bytes())),
+        journal.load_snapshot("counter").await.unwrap()
+    );
+    assert_eq!(1, journal.read("counter", 0).await.unwrap().len());
+
+    let counter = spawn_counter(config).await;
+    assert_eq!((28, 7), get(&counter).await);
+    counter.cast(CounterMsg::Snapshot).unwrap();
+    assert_eq!((28, 7), get(&counter).await);
+    assert_eq!(
+        Some(JournalEntry::new(7, 28u64.into_bytes())),
+        journal.load_snapshot("counter").await.unwrap()
+    );
+    counter.stop_and_wait(None, None).await.unwrap();
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_supervised_restart() {
+    let config = PersistenceConfig::builder()
+        .persistence_id("supervised_counter".to_string())
+        .journal(Arc::new(InMemoryJournal::new()))
+        .build();
+    let args = SupervisorArguments::builder()
+        .children(vec![ChildSpec::new(
+            "counter",
+            Restart::Permanent,
+            move |supervisor| {
+                let config = config.clone();
+                async move {
+                    let (actor, _) = Actor::spawn_linked(
+                        Some("test_supervised_restart".to_string()),
+                        Persistent::new(Counter, config),
+                        (),
+                        supervisor,
+                    )
+                    .await?;
+                    Ok(actor.get_cell())
+                }
+            },
+        )])
+        .build();
+    let (supervisor, handle) = Actor::spawn(None, Supervisor, args)
+        .await
+        .expect("Failed to spawn supervisor");
+
+    let counter: ActorRef<CounterMsg> =
+        crate::registry::where_is("test_supervised_restart".to_string())
+            .expect("The counter isn't registered")
+            .into();
+    for added in 1..=3 {
+        counter.cast(CounterMsg::Add(added)).unwrap();
+    }
+    counter.cast(CounterMsg::Fail).unwrap();
+
+    periodic_check(
+        || {
+            crate::registry::where_is("test_supervised_restart".to_string())
+                .map(|cell| cell.get_id() != counter.get_id())
+                .unwrap_or(false)
+        },
+        Duration::from_secs(1),
+    )
+    .await;
+    let restarted: ActorRef<CounterMsg> =
+        crate::registry::where_is("test_supervised_restart".to_string())
+            .unwrap()
+            .into();
+    assert_eq!((6, 3), get(&restarted).await);
+
+    supervisor.stop(None);
+    handle.await.unwrap();
+}
@@ -11,6 +11,13 @@
 //! members to a strong-type'd actor then dispatch a message with [crate::call]
 //! or [crate::cast].
 //!
+//! The [dispatch] helpers cover the common fan-out patterns: [broadcast] a message to
+//! every member, [send_to_any] single member, or [multi_call] every member and collect
+//! their replies.
+//!
+//! Members can be tagged with [MemberMeta] when joining a group (see [join_with_meta]),
+//! which is returned alongside them by [get_members_with_meta].
+//!
 //! Process groups can also be monitored for changes with calling [monitor] to
 //! subscribe to changes and [demonitor] to unsubscribe. Subscribers will receive
 //! process group change notifications via a [SupervisionEvent] called on the
@@ -93,14 +100,25 @@ pub const ALL_SCOPES_NOTIFICATION: &str = "__world_scope__";
 /// Key to monitor all of the groups in a scope
 pub const ALL_GROUPS_NOTIFICATION: &str = "__world_group_";
 
+pub mod dispatch;
+pub mod meta;
+pub use dispatch::broadcast;
+pub use dispatch::multi_call;
+pub use dispatch::send_to_any;
+pub use dispatch::DispatchStrategy;
+pub use meta::MemberMeta;
+pub use meta::MetaValue;
+
 #[cfg(test)]
 mod tests;
 
 /// Represents a change in a process group's membership
 #[derive(Clone, Debug)]
 pub enum GroupChangeMessage {
-    /// Some actors joined a group
-    Join(ScopeName, GroupName, Vec<ActorCell>),
+    /// Some actors joined a group, with the metadata they joined with (see [join_with_meta]).
+    /// The metadata is empty for a plain [join], which keeps the metadata of actors which
+    /// were already members.
+    Join(ScopeName, GroupName, Vec<ActorCell>, MemberMeta),
     /// Some actors left a group
     Leave(ScopeName, GroupName, Vec<ActorCell>),
 }
@@ -109,7 +127,7 @@ impl GroupChangeMessage {
     /// Retrieve the group that changed
     pub fn get_group(&self) -> GroupName {
         match self {
-            Self::Join(_, name, _) => name.clone(),
+            Self::Join(_, name, _, _) => name.clone(),
             Self::Leave(_, name, _) => name.clone(),
         }
     }
@@ -117,7 +135,7 @@ impl GroupChangeMessage {
     /// Retrieve the name of the scope in which the group change took place
     pub fn get_scope(&self) -> ScopeName {
         match self {
-            Self::Join(scope, _, _) => scope.to_string(),
+            Self::Join(scope, _, _, _) => scope.to_string(),
             Self::Leave(scope, _, _) => scope.to_string(),
         }
     }
@@ -144,8 +162,14 @@ impl ScopeGroupKey {
     }
 }
 
+/// A member of a group, along with the metadata it joined with
+struct GroupMember {
+    cell: ActorCell,
+    meta: MemberMeta,
+}
+
 struct PgState {
-    map: Arc<DashMap<ScopeGroupKey, HashMap<ActorId, ActorCell>>>,
+    map: Arc<DashMap<ScopeGroupKey, HashMap<ActorId, GroupMember>>>,
     index: Arc<DashMap<ScopeName, Vec<GroupName>>>,
     listeners: Arc<DashMap<ScopeGroupKey, Vec<ActorCell>>>,
 }
@@ -160,7 +184,8 @@ fn get_monitor<'a>() -> &'a PgState {
     })
 }
 
-/// Join actors to the group `group` in the default scope
+/// Join actors to the group `group` in the default scope. Actors which are already
+/// members of the group keep the metadata they joined with, new members have none.
 ///
 /// * `group` - The named group. Will be created if first actors to join
 /// * `actors` - The list of [crate::Actor]s to add to the group
@@ -168,12 +193,49 @@ pub fn join(group: GroupName, actors: Vec<ActorCell>) {
     join_scoped(DEFAULT_SCOPE.to_owned(), group, actors);
 }
 
-/// Join actors to the group `group` within the scope `scope`
+/// Join actors to the group `group` within the scope `scope`. Actors which are already
+/// members of the group keep the metadata they joined with, new members have none.
 ///
 /// * `scope` - The named scope. Will be created if first actors to join
 /// * `group` - The named group. Will be created if first actors to join
 /// * `actors` - The list of [crate::Actor]s to add to the group
 pub fn join_scoped(scope: ScopeName, group: GroupName, actors: Vec<ActorCell>) {
+    join_members(scope, group, actors, None);
+}
+
+/// Join actors to the group `group` in the default scope, tagging them with metadata
+///
+/// * `group` - The named group. Will be created if first actors to join
+/// * `actors` - The list of [crate::Actor]s to add to the group
+/// * `meta` - The [MemberMeta] of the actors, which replaces any metadata they joined the
+///   group with before
+pub fn join_with_meta(group: GroupName, actors: Vec<ActorCell>, meta: MemberMeta) {
+    join_scoped_with_meta(DEFAULT_SCOPE.to_owned(), group, actors, meta);
+}
+
+/// Join actors to the group `group` within the scope `scope`, tagging them with metadata
+///
+/// * `scope` - The named scope. Will be created if first actors to join
+/// * `group` - The named group. Will be created if first actors to join
+/// * `actors` - The list of [crate::Actor]s to add to the group
+/// * `meta` - The [MemberMeta] of the actors, which replaces any metadata they joined the
+///   group with before
+pub fn join_scoped_with_meta(
+    scope: ScopeName,
+    group: GroupName,
+    actors: Vec<ActorCell>,
+    meta: MemberMeta,
+) {
+    join_members(scope, group, actors, Some(meta));
+}
+
+/// Join actors to a group, replacing their metadata if `meta` is provided
+fn join_members(
+    scope: ScopeName,
+    group: GroupName,
+    actors: Vec<ActorCell>,
+    meta: Option<MemberMeta>,
+) {
     let key = ScopeGroupKey {
         scope: scope.to_owned(),
         group: group.to_owned(),
@@ -189,7 +251,19 @@ pub fn join_scoped(scope: ScopeName, group: GroupName, actors: Vec<ActorCell>) {
         Occupied(mut occupied_map) => {
             let oref = occupied_map.get_mut();
             for actor in actors.iter() {
-                oref.insert(actor.get_id(), actor.clone());
+                match oref.entry(actor.get_id()) {
+                    std::collections::hash_map::Entry::Occupied(mut member) => {
+                        if let Some(meta) = &meta {
+                            member.get_mut().meta = meta.clone();
+                        }
+                    }
+                    std::collections::hash_map::Entry::Vacant(vacancy) => {
+                        vacancy.insert(GroupMember {
+                            cell: actor.clone(),
+                            meta: meta.clone().unwrap_or_default(),
+                        });
+                    }
+                }
             }
             match monitor_idx {
                 Occupied(mut occupied_idx) => {
@@ -206,7 +280,15 @@ pub fn join_scoped(scope: ScopeName, group: GroupName, actors: Vec<ActorCell>) {
         Vacant(vacancy) => {
             let map = actors
                 .iter()
-                .map(|a| (a.get_id(), a.clone()))
+                .map(|a| {
+                    (
+                        a.get_id(),
+                        GroupMember {
+                            cell: a.clone(),
+                            meta: meta.clone().unwrap_or_default(),
+                        },
+                    )
+                })
                 .collect::<HashMap<_, _>>();
             vacancy.insert(map);
             match monitor_idx {
@@ -224,10 +306,16 @@ pub fn join_scoped(scope: ScopeName, group: GroupName, actors: Vec<ActorCell>) {
     }
 
     // notify supervisors
+    let meta = meta.unwrap_or_default();
     if let Some(listeners) = monitor.listeners.get(&key) {
         for listener in listeners.value() {
             let _ = listener.send_supervisor_evt(SupervisionEvent::ProcessGroupChanged(
-                GroupChangeMessage::Join(scope.to_owned(), group.clone(), actors.clone()),
+                GroupChangeMessage::Join(
+                    scope.to_owned(),
+                    group.clone(),
+                    actors.clone(),
+                    meta.clone(),
+                ),
             ));
         }
     }
@@ -237,7 +325,12 @@ pub fn join_scoped(scope: ScopeName, group: GroupName, actors: Vec<ActorCell>) {
         if let Some(listeners) = monitor.listeners.get(&key) {
             for listener in listeners.value() {
                 let _ = listener.send_supervisor_evt(SupervisionEvent::ProcessGroupChanged(
-                    GroupChangeMessage::Join(scope.to_owned(), group.clone(), actors.clone()),
+                    GroupChangeMessage::Join(
+                        scope.to_owned(),
+                        group.clone(),
+                        actors.clone(),
+                        meta.clone(),
+                    ),
                 ));
             }
         }
@@ -327,8 +420,8 @@ pub(crate) fn leave_all(actor: ActorId) {
     let mut removal_events = HashMap::new();
 
     for mut kv in map.iter_mut() {
-        if let Some(actor_cell) = kv.value_mut().remove(&actor) {
-            removal_events.insert(kv.key().clone(), actor_cell);
+        if let Some(member) = kv.value_mut().remove(&actor) {
+            removal_events.insert(kv.key().clone(), member.cell);
         }
         if kv.value().is_empty() {
             empty_scope_group_keys.push(kv.key().clone());
@@ -403,8 +496,8 @@ pub fn get_scoped_local_members(scope: &ScopeName, group: &GroupName) -> Vec<Act
         actors
             .value()
             .values()
-            .filter(|a| a.get_id().is_local())
-            .cloned()
+            .filter(|a| a.cell.get_id().is_local())
+            .map(|a| a.cell.clone())
             .collect::<Vec<_>>()
     } else {
         vec![]
@@ -435,7 +528,48 @@ pub fn get_scoped_members(scope: &ScopeName, group: &GroupName) -> Vec<ActorCell
     };
     let monitor = get_monitor();
     if let Some(actors) = monitor.map.get(&key) {
-        actors.value().values().cloned().collect::<Vec<_>>()
+        actors
+            .value()
+            .values()
+            .map(|a| a.cell.clone())
+            .collect::<Vec<_>>()
+    } else {
+        vec![]
+    }
+}
+
+/// Returns all the actors running on any node in the group `group`
+/// in the default scope, along with their metadata.
+///
+/// * `group_name` - A named group
+///
+/// Returns a [`Vec<(ActorCell, MemberMeta)>`] with the member actors and their [MemberMeta]
+pub fn get_members_with_meta(group_name: &GroupName) -> Vec<(ActorCell, MemberMeta)> {
+    get_scoped_members_with_meta(&DEFAULT_SCOPE.to_owned(), group_name)
+}
+
+/// Returns all the actors running on any node in the group `group`
+/// in the scope `scope`, along with their metadata.
+///
+/// * `scope` - A named scope
+/// * `group` - A named group
+///
+/// Returns a [`Vec<(ActorCell, MemberMeta)>`] with the member actors and their [MemberMeta]
+pub fn get_scoped_members_with_meta(
+    scope: &ScopeName,
+    group: &GroupName,
+) -> Vec<(ActorCell, MemberMeta)> {
+    let key = ScopeGroupKey {
+        scope: scope.to_owned(),
+        group: group.to_owned(),
+    };
+    let monitor = get_monitor();
+    if let Some(actors) = monitor.map.get(&key) {
+        actors
+            .value()
+            .values()
+            .map(|a| (a.cell.clone(), a.meta.clone()))
+            .collect::<Vec<_>>()
     } else {
         vec![]
     }
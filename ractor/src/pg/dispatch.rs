@@ -0,0 +1,169 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Helpers to dispatch messages to the members of a process group, without having
+//! to fan out over [super::get_scoped_members] by hand.
+//!
+//! Members which don't handle the message type (and therefore can't be sent the message)
+//! are skipped. Remote members can't have their message type checked at runtime, so
+//! they're always assumed to match.
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use dashmap::DashMap;
+use once_cell::sync::OnceCell;
+
+use super::ScopeGroupKey;
+use crate::concurrency::Duration;
+use crate::rpc::CallResult;
+use crate::ActorCell;
+use crate::ActorRef;
+use crate::GroupName;
+use crate::Message;
+use crate::MessagingErr;
+use crate::RpcReplyPort;
+use crate::ScopeName;
+
+/// How [send_to_any] selects the member of the group to send to
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum DispatchStrategy {
+    /// A random member
+    #[default]
+    Random,
+    /// Members take turns, in the order of their ids. The turn is tracked per group.
+    RoundRobin,
+    /// The member with the fewest messages waiting in its mailbox (see
+    /// [crate::ActorCell::get_stats]). Remote members always appear to have an empty
+    /// mailbox.
+    LeastLoaded,
+}
+
+/// The round-robin position of each group
+static ROUND_ROBIN: OnceCell<DashMap<ScopeGroupKey, AtomicUsize>> = OnceCell::new();
+
+/// The members of the group which accept messages of type `TMessage`, ordered by id
+fn typed_members<TMessage>(scope: &ScopeName, group: &GroupName) -> Vec<ActorCell>
+where
+    TMessage: Message,
+{
+    let mut members = super::get_scoped_members(scope, group)
+        .into_iter()
+        .filter(|member| !matches!(member.is_message_type_of::<TMessage>(), Some(false)))
+        .collect::<Vec<_>>();
+    members.sort_by_key(|member| member.get_id());
+    members
+}
+
+/// Send a copy of a message to every member of a process group
+///
+/// * `scope` - The scope of the group
+/// * `group` - The group to broadcast to
+/// * `message` - The message to send
+///
+/// Returns the number of members the message was delivered to. Members which exited,
+/// whose mailbox is full, or which don't handle the message type are skipped.
+pub fn broadcast<TMessage>(scope: &ScopeName, group: &GroupName, message: TMessage) -> usize
+where
+    TMessage: Message + Clone,
+{
+    typed_members::<TMessage>(scope, group)
+        .iter()
+        .filter(|member| member.send_message(message.clone()).is_ok())
+        .count()
+}
+
+/// Send a message to a single member of a process group
+///
+/// * `scope` - The scope of the group
+/// * `group` - The group to send to
+/// * `message` - The message to send
+/// * `strategy` - How the member is selected, see [DispatchStrategy]
+///
+/// If the selected member can't accept the message (i.e. it has exited or its mailbox is
+/// full) the next member in order is tried, until one accepts it.
+///
+/// Returns the [ActorCell] of the member which received the message, or
+/// [MessagingErr::SendErr] with the message if no member accepted it
+pub fn send_to_any<TMessage>(
+    scope: &ScopeName,
+    group: &GroupName,
+    message: TMessage,
+    strategy: DispatchStrategy,
+) -> Result<ActorCell, MessagingErr<TMessage>>
+where
+    TMessage: Message,
+{
+    let members = typed_members::<TMessage>(scope, group);
+    if members.is_empty() {
+        return Err(MessagingErr::SendErr(message));
+    }
+
+    let start = match strategy {
+        DispatchStrategy::Random => {
+            (crate::concurrency::random_u64() % members.len() as u64) as usize
+        }
+        DispatchStrategy::RoundRobin => {
+            let key = ScopeGroupKey {
+                scope: scope.to_owned(),
+                group: group.to_owned(),
+            };
+            ROUND_ROBIN
+                .get_or_init(DashMap::new)
+                .entry(key)
+                .or_default()
+                .fetch_add(1, Ordering::Relaxed)
+                % members.len()
+        }
+        DispatchStrategy::LeastLoaded => members
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, member)| member.get_stats().mailbox_depth)
+            .map(|(i, _)| i)
+            .unwrap_or_default(),
+    };
+
+    let mut message = message;
+    for member in members.iter().cycle().skip(start).take(members.len()) {
+        match member.send_message(message) {
+            Ok(()) => return Ok(member.clone()),
+            Err(MessagingErr::SendErr(m)) | Err(MessagingErr::MailboxFull(m)) => message = m,
+            Err(other) => return Err(other),
+        }
+    }
+    Err(MessagingErr::SendErr(message))
+}
+
+/// Call every member of a process group, awaiting all of their replies with the
+/// specified timeout. See [crate::rpc::multi_call].
+///
+/// * `scope` - The scope of the group
+/// * `group` - The group to call
+/// * `msg_builder` - The [Fn] to construct the message for each member
+/// * `timeout_option` - An optional [Duration] which represents the amount of
+///   time until the operation times out
+///
+/// Returns [Ok] with each member paired with its [CallResult], in the order of the
+/// members' ids, or [Err(MessagingErr)] if sending to any member failed
+pub async fn multi_call<TMessage, TReply, TMsgBuilder>(
+    scope: &ScopeName,
+    group: &GroupName,
+    msg_builder: TMsgBuilder,
+    timeout_option: Option<Duration>,
+) -> Result<Vec<(ActorCell, CallResult<TReply>)>, MessagingErr<TMessage>>
+where
+    TMessage: Message,
+    TReply: Send + 'static,
+    TMsgBuilder: Fn(RpcReplyPort<TReply>) -> TMessage,
+{
+    let members = typed_members::<TMessage>(scope, group);
+    let actors = members
+        .iter()
+        .cloned()
+        .map(ActorRef::<TMessage>::from)
+        .collect::<Vec<_>>();
+    let results = crate::rpc::multi_call(&actors, msg_builder, timeout_option).await?;
+    Ok(members.into_iter().zip(results).collect())
+}
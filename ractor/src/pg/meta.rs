@@ -0,0 +1,182 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Metadata attached to the members of a process group, such as a shard id, version, or
+//! capacity, so that routing decisions can be made from the group membership alone.
+//!
+//! Metadata is provided when joining a group with [super::join_with_meta] or
+//! [super::join_scoped_with_meta], delivered to group monitors in
+//! [super::GroupChangeMessage::Join], and retrieved with [super::get_members_with_meta]
+//! or [super::get_scoped_members_with_meta].
+//!
+//! In a cluster, the metadata of members is synchronized to the other nodes along with
+//! their membership.
+
+use std::collections::BTreeMap;
+
+/// A typed metadata value
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MetaValue {
+    /// A string value
+    String(String),
+    /// A signed integer value
+    Int(i64),
+    /// An unsigned integer value
+    UInt(u64),
+    /// A boolean value
+    Bool(bool),
+}
+
+impl MetaValue {
+    /// Retrieve the value if it's a [MetaValue::String]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Retrieve the value if it's an integer which fits in an [i64]
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Int(value) => Some(*value),
+            Self::UInt(value) => i64::try_from(*value).ok(),
+            _ => None,
+        }
+    }
+
+    /// Retrieve the value if it's an integer which fits in a [u64]
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Self::Int(value) => u64::try_from(*value).ok(),
+            Self::UInt(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Retrieve the value if it's a [MetaValue::Bool]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for MetaValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::String(value) => write!(f, "{value}"),
+            Self::Int(value) => write!(f, "{value}"),
+            Self::UInt(value) => write!(f, "{value}"),
+            Self::Bool(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+impl From<String> for MetaValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<&str> for MetaValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<i64> for MetaValue {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<i32> for MetaValue {
+    fn from(value: i32) -> Self {
+        Self::Int(value.into())
+    }
+}
+
+impl From<u64> for MetaValue {
+    fn from(value: u64) -> Self {
+        Self::UInt(value)
+    }
+}
+
+impl From<u32> for MetaValue {
+    fn from(value: u32) -> Self {
+        Self::UInt(value.into())
+    }
+}
+
+impl From<bool> for MetaValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+/// The metadata of a group member, a map of keys to [MetaValue]s
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemberMeta {
+    entries: BTreeMap<String, MetaValue>,
+}
+
+impl MemberMeta {
+    /// Create empty metadata
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an entry to the metadata, builder-style
+    pub fn with(mut self, key: impl Into<String>, value: impl Into<MetaValue>) -> Self {
+        self.insert(key, value);
+        self
+    }
+
+    /// Add an entry to the metadata, returning the value it replaced, if any
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<MetaValue>,
+    ) -> Option<MetaValue> {
+        self.entries.insert(key.into(), value.into())
+    }
+
+    /// Retrieve the value of an entry
+    pub fn get(&self, key: &str) -> Option<&MetaValue> {
+        self.entries.get(key)
+    }
+
+    /// Iterate over the entries, ordered by key
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &MetaValue)> {
+        self.entries.iter()
+    }
+
+    /// The number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for MemberMeta
+where
+    K: Into<String>,
+    V: Into<MetaValue>,
+{
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        Self {
+            entries: iter
+                .into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        }
+    }
+}
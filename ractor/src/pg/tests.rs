@@ -580,7 +580,7 @@ async fn test_pg_monitoring() {
         ) -> Result<(), ActorProcessingErr> {
             if let SupervisionEvent::ProcessGroupChanged(change) = message {
                 match change {
-                    pg::GroupChangeMessage::Join(_scope, _which, who) => {
+                    pg::GroupChangeMessage::Join(_scope, _which, who, _meta) => {
                         self.counter.fetch_add(who.len() as u8, Ordering::Relaxed);
                     }
                     pg::GroupChangeMessage::Leave(_scope, _which, who) => {
@@ -695,7 +695,7 @@ async fn test_scope_monitoring() {
         ) -> Result<(), ActorProcessingErr> {
             if let SupervisionEvent::ProcessGroupChanged(change) = message {
                 match change {
-                    pg::GroupChangeMessage::Join(scope_name, _which, who) => {
+                    pg::GroupChangeMessage::Join(scope_name, _which, who, _meta) => {
                         // ensure this test can run concurrently to others
                         if scope_name == function_name!() {
                             self.counter.fetch_add(who.len() as u8, Ordering::Relaxed);
@@ -919,3 +919,412 @@ async fn local_vs_remote_pg_members_in_named_scopes() {
         handle.await.expect("Actor cleanup failed");
     }
 }
+
+struct CountingActor {
+    count: Arc<std::sync::atomic::AtomicU32>,
+}
+
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+impl Actor for CountingActor {
+    type Msg = u32;
+    type Arguments = ();
+    type State = ();
+
+    async fn pre_start(
+        &self,
+        _this_actor: crate::ActorRef<Self::Msg>,
+        _: (),
+    ) -> Result<Self::State, ActorProcessingErr> {
+        Ok(())
+    }
+
+    async fn handle(
+        &self,
+        _this_actor: crate::ActorRef<Self::Msg>,
+        message: Self::Msg,
+        _state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        self.count.fetch_add(message, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+async fn spawn_counters(
+    n: usize,
+) -> Vec<(
+    crate::ActorRef<u32>,
+    crate::concurrency::JoinHandle<()>,
+    Arc<std::sync::atomic::AtomicU32>,
+)> {
+    let mut counters = vec![];
+    for _ in 0..n {
+        let count = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let (actor, handle) = Actor::spawn(
+            None,
+            CountingActor {
+                count: count.clone(),
+            },
+            (),
+        )
+        .await
+        .expect("Failed to spawn test actor");
+        counters.push((actor, handle, count));
+    }
+    counters
+}
+
+#[named]
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_broadcast() {
+    let scope = function_name!().to_string();
+    let group = function_name!().to_string();
+
+    let counters = spawn_counters(3).await;
+    // a member of another type doesn't receive the broadcast
+    let (other, other_handle) = Actor::spawn(None, TestActor, ())
+        .await
+        .expect("Failed to spawn test actor");
+    let mut members = counters
+        .iter()
+        .map(|(actor, _, _)| actor.get_cell())
+        .collect::<Vec<_>>();
+    members.push(other.get_cell());
+    pg::join_scoped(scope.clone(), group.clone(), members);
+
+    assert_eq!(3, pg::broadcast(&scope, &group, 2u32));
+    periodic_check(
+        || {
+            counters
+                .iter()
+                .all(|(_, _, count)| count.load(Ordering::Relaxed) == 2)
+        },
+        Duration::from_secs(1),
+    )
+    .await;
+    assert_eq!(0, pg::broadcast(&scope, &"missing".to_string(), 2u32));
+
+    other.stop(None);
+    other_handle.await.unwrap();
+    for (actor, handle, _) in counters {
+        actor.stop(None);
+        handle.await.unwrap();
+    }
+}
+
+#[named]
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_send_to_any() {
+    let scope = function_name!().to_string();
+    let group = function_name!().to_string();
+
+    let err = pg::send_to_any(&scope, &group, 1u32, pg::DispatchStrategy::Random)
+        .expect_err("The group is empty");
+    assert!(matches!(err, crate::MessagingErr::SendErr(1)));
+
+    let counters = spawn_counters(3).await;
+    pg::join_scoped(
+        scope.clone(),
+        group.clone(),
+        counters
+            .iter()
+            .map(|(actor, _, _)| actor.get_cell())
+            .collect(),
+    );
+    let total = || {
+        counters
+            .iter()
+            .map(|(_, _, count)| count.load(Ordering::Relaxed))
+            .sum::<u32>()
+    };
+
+    // round robin visits every member in turn
+    let mut receivers = vec![];
+    for _ in 0..6 {
+        let member = pg::send_to_any(&scope, &group, 1u32, pg::DispatchStrategy::RoundRobin)
+            .expect("Failed to send message");
+        receivers.push(member.get_id());
+    }
+    assert_eq!(receivers[..3], receivers[3..]);
+    let mut first_round = receivers[..3].to_vec();
+    first_round.sort();
+    first_round.dedup();
+    assert_eq!(3, first_round.len());
+    periodic_check(|| total() == 6, Duration::from_secs(1)).await;
+    for (_, _, count) in counters.iter() {
+        assert_eq!(2, count.load(Ordering::Relaxed));
+    }
+
+    for strategy in [
+        pg::DispatchStrategy::Random,
+        pg::DispatchStrategy::LeastLoaded,
+    ] {
+        for _ in 0..5 {
+            pg::send_to_any(&scope, &group, 1u32, strategy).expect("Failed to send message");
+        }
+    }
+    periodic_check(|| total() == 16, Duration::from_secs(1)).await;
+
+    for (actor, handle, _) in counters {
+        actor.stop(None);
+        handle.await.unwrap();
+    }
+}
+
+#[named]
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_group_multi_call() {
+    use crate::rpc::CallResult;
+    use crate::RpcReplyPort;
+
+    let scope = function_name!().to_string();
+    let group = function_name!().to_string();
+
+    struct EchoActor;
+
+    enum EchoMessage {
+        WhoAreYou(RpcReplyPort<crate::ActorId>),
+    }
+
+    #[cfg(feature = "cluster")]
+    impl crate::Message for EchoMessage {}
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for EchoActor {
+        type Msg = EchoMessage;
+        type Arguments = ();
+        type State = ();
+
+        async fn pre_start(
+            &self,
+            _this_actor: crate::ActorRef<Self::Msg>,
+            _: (),
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(())
+        }
+
+        async fn handle(
+            &self,
+            myself: crate::ActorRef<Self::Msg>,
+            message: Self::Msg,
+            _state: &mut Self::State,
+        ) -> Result<(), ActorProcessingErr> {
+            let EchoMessage::WhoAreYou(reply) = message;
+            let _ = reply.send(myself.get_id());
+            Ok(())
+        }
+    }
+
+    let mut actors = vec![];
+    for _ in 0..3 {
+        actors.push(
+            Actor::spawn(None, EchoActor, ())
+                .await
+                .expect("Failed to spawn test actor"),
+        );
+    }
+    let (other, other_handle) = Actor::spawn(None, TestActor, ())
+        .await
+        .expect("Failed to spawn test actor");
+    let mut members = actors
+        .iter()
+        .map(|(actor, _)| actor.get_cell())
+        .collect::<Vec<_>>();
+    members.push(other.get_cell());
+    pg::join_scoped(scope.clone(), group.clone(), members);
+
+    let results = pg::multi_call(
+        &scope,
+        &group,
+        EchoMessage::WhoAreYou,
+        Some(Duration::from_secs(1)),
+    )
+    .await
+    .expect("Failed to call group");
+    // only the members handling the message are called
+    assert_eq!(3, results.len());
+    for (member, result) in results {
+        assert!(matches!(result, CallResult::Success(id) if id == member.get_id()));
+    }
+
+    other.stop(None);
+    other_handle.await.unwrap();
+    for (actor, handle) in actors {
+        actor.stop(None);
+        handle.await.unwrap();
+    }
+}
+
+#[named]
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_member_metadata() {
+    use std::sync::Mutex;
+
+    use crate::pg::MemberMeta;
+    use crate::pg::MetaValue;
+
+    let scope = function_name!().to_string();
+    let group = function_name!().to_string();
+
+    struct MetaMonitor {
+        scope: ScopeName,
+        group: GroupName,
+        joins: Arc<Mutex<Vec<MemberMeta>>>,
+    }
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for MetaMonitor {
+        type Msg = ();
+        type Arguments = ();
+        type State = ();
+
+        async fn pre_start(
+            &self,
+            myself: crate::ActorRef<Self::Msg>,
+            _: (),
+        ) -> Result<Self::State, ActorProcessingErr> {
+            pg::monitor_scope(self.scope.clone(), myself.into());
+            Ok(())
+        }
+
+        async fn handle_supervisor_evt(
+            &self,
+            _myself: crate::ActorRef<Self::Msg>,
+            message: SupervisionEvent,
+            _state: &mut Self::State,
+        ) -> Result<(), ActorProcessingErr> {
+            if let SupervisionEvent::ProcessGroupChanged(pg::GroupChangeMessage::Join(
+                _scope,
+                which,
+                _who,
+                meta,
+            )) = message
+            {
+                if which == self.group {
+                    self.joins.lock().unwrap().push(meta);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    let joins = Arc::new(Mutex::new(vec![]));
+    let (monitor, monitor_handle) = Actor::spawn(
+        None,
+        MetaMonitor {
+            scope: scope.clone(),
+            group: group.clone(),
+            joins: joins.clone(),
+        },
+        (),
+    )
+    .await
+    .expect("Failed to start monitor actor");
+
+    let (a, a_handle) = Actor::spawn(None, TestActor, ())
+        .await
+        .expect("Failed to spawn test actor");
+    let (b, b_handle) = Actor::spawn(None, TestActor, ())
+        .await
+        .expect("Failed to spawn test actor");
+
+    let meta = MemberMeta::new()
+        .with("shard", 3u32)
+        .with("version", "1.2.0")
+        .with("primary", true);
+    pg::join_scoped_with_meta(
+        scope.clone(),
+        group.clone(),
+        vec![a.get_cell()],
+        meta.clone(),
+    );
+    // members joined without metadata have none
+    pg::join_scoped(scope.clone(), group.clone(), vec![b.get_cell()]);
+
+    let meta_of = |id: crate::ActorId| {
+        pg::get_scoped_members_with_meta(&scope, &group)
+            .into_iter()
+            .find(|(member, _)| member.get_id() == id)
+            .map(|(_, meta)| meta)
+            .expect("Missing member")
+    };
+    assert_eq!(meta, meta_of(a.get_id()));
+    assert!(meta_of(b.get_id()).is_empty());
+    assert_eq!(
+        Some(3),
+        meta_of(a.get_id()).get("shard").and_then(MetaValue::as_u64)
+    );
+    assert_eq!(
+        Some("1.2.0"),
+        meta_of(a.get_id())
+            .get("version")
+            .and_then(MetaValue::as_str)
+    );
+    assert_eq!(
+        Some(true),
+        meta_of(a.get_id())
+            .get("primary")
+            .and_then(MetaValue::as_bool)
+    );
+    assert_eq!(
+        None,
+        meta_of(a.get_id()).get("shard").and_then(MetaValue::as_str)
+    );
+
+    // re-joining replaces the metadata
+    let updated = [("shard", 4u32)].into_iter().collect::<MemberMeta>();
+    pg::join_scoped_with_meta(
+        scope.clone(),
+        group.clone(),
+        vec![a.get_cell()],
+        updated.clone(),
+    );
+    assert_eq!(updated, meta_of(a.get_id()));
+    assert_eq!(2, pg::get_scoped_members(&scope, &group).len());
+
+    // re-joining without metadata keeps it
+    pg::join_scoped(scope.clone(), group.clone(), vec![a.get_cell()]);
+    assert_eq!(updated, meta_of(a.get_id()));
+
+    // monitors receive the metadata with the join
+    periodic_check(|| joins.lock().unwrap().len() == 4, Duration::from_secs(1)).await;
+    assert_eq!(
+        vec![meta, MemberMeta::default(), updated, MemberMeta::default()],
+        *joins.lock().unwrap()
+    );
+
+    // the default scope variants
+    pg::join_with_meta(
+        group.clone(),
+        vec![b.get_cell()],
+        MemberMeta::new().with("capacity", -1),
+    );
+    let members = pg::get_members_with_meta(&group);
+    assert_eq!(1, members.len());
+    assert_eq!(
+        Some(-1),
+        members[0].1.get("capacity").and_then(MetaValue::as_i64)
+    );
+
+    monitor.stop(None);
+    a.stop(None);
+    b.stop(None);
+    monitor_handle.await.unwrap();
+    a_handle.await.unwrap();
+    b_handle.await.unwrap();
+}
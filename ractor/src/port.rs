@@ -21,10 +21,17 @@ pub use output::*;
 
 /// A remote procedure call's reply port. Wrapper of [concurrency::OneshotSender] with a
 /// consistent error type
+///
+/// When the port is created with a timeout, the caller stops waiting on the reply once
+/// the timeout elapses. The actor receiving a request whose deadline has already passed
+/// drops it without handling it, and long-running handlers can check
+/// [RpcReplyPort::is_expired] or wait on [RpcReplyPort::closed] to abandon work the
+/// caller is no longer interested in.
 #[derive(Debug)]
 pub struct RpcReplyPort<TMsg> {
     port: concurrency::OneshotSender<TMsg>,
     timeout: Option<concurrency::Duration>,
+    deadline: Option<concurrency::Instant>,
 }
 
 impl<TMsg> RpcReplyPort<TMsg> {
@@ -35,6 +42,22 @@ impl<TMsg> RpcReplyPort<TMsg> {
         self.timeout
     }
 
+    /// Read the deadline of this RPC reply port, which is when the timeout elapses
+    ///
+    /// Returns [Some(concurrency::Instant)] if a timeout is set, [None] otherwise
+    pub fn get_deadline(&self) -> Option<concurrency::Instant> {
+        self.deadline
+    }
+
+    /// Determine if the caller has given up on the reply, either because the deadline
+    /// has passed or because the receiver has been dropped
+    ///
+    /// Returns [true] if sending a reply is pointless, [false] otherwise
+    pub fn is_expired(&self) -> bool {
+        matches!(self.deadline, Some(deadline) if concurrency::Instant::now() >= deadline)
+            || self.is_closed()
+    }
+
     /// Send a message to the Rpc reply port. This consumes the port
     ///
     /// * `msg` - The message to send
@@ -52,6 +75,22 @@ impl<TMsg> RpcReplyPort<TMsg> {
     pub fn is_closed(&self) -> bool {
         self.port.is_closed()
     }
+
+    /// Wait for the caller to give up on the reply, i.e. for the receiver to be dropped
+    /// or the deadline to pass. Useful to cancel long-running work in a handler by
+    /// racing it against this future (e.g. with `tokio::select!`)
+    pub async fn closed(&mut self) {
+        match self.deadline {
+            Some(deadline) => {
+                let _ = concurrency::timeout(
+                    deadline.saturating_duration_since(concurrency::Instant::now()),
+                    self.port.closed(),
+                )
+                .await;
+            }
+            None => self.port.closed().await,
+        }
+    }
 }
 
 impl<TMsg> From<concurrency::OneshotSender<TMsg>> for RpcReplyPort<TMsg> {
@@ -59,6 +98,7 @@ impl<TMsg> From<concurrency::OneshotSender<TMsg>> for RpcReplyPort<TMsg> {
         Self {
             port: value,
             timeout: None,
+            deadline: None,
         }
     }
 }
@@ -68,6 +108,7 @@ impl<TMsg> From<(concurrency::OneshotSender<TMsg>, concurrency::Duration)> for R
         Self {
             port: value,
             timeout: Some(timeout),
+            deadline: Some(concurrency::Instant::now() + timeout),
         }
     }
 }
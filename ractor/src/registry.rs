@@ -20,6 +20,17 @@
 //! or agents will runtime panic on message reception, and supervision
 //! processes would need to restart the actors.
 //!
+//! [where_is_typed] additionally checks the actor's message type, returning a strongly-typed
+//! [ActorRef]. Besides the name they're spawned with, actors can be registered under any
+//! number of aliases with [register_alias], and names can be released without stopping the
+//! actor with [unregister_name].
+//!
+//! Actors can also be addressed by their position in the supervision tree, see [path].
+//!
+//! Actors can watch names with [monitor_name], receiving a
+//! [SupervisionEvent::NameChanged] whenever an actor is registered or unregistered under
+//! the name. The registry's storage is pluggable, see [backend].
+//!
 //! ## Examples
 //!
 //! **Basic actor retrieval**
@@ -80,18 +91,29 @@
 //! }
 //! ```
 
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt::Debug;
 use std::sync::Arc;
 
-use dashmap::mapref::entry::Entry::Occupied;
-use dashmap::mapref::entry::Entry::Vacant;
 use dashmap::DashMap;
 use once_cell::sync::OnceCell;
 
 use crate::ActorCell;
+use crate::ActorId;
 use crate::ActorName;
+use crate::ActorRef;
+use crate::Message;
+use crate::SupervisionEvent;
 
+pub mod backend;
+pub mod path;
 #[cfg(feature = "cluster")]
 pub mod pid_registry;
+pub use backend::LocalRegistryBackend;
+pub use backend::RegistryBackend;
+pub use path::resolve_path;
+pub use path::ActorPath;
 #[cfg(feature = "cluster")]
 pub use pid_registry::get_all_pids;
 #[cfg(feature = "cluster")]
@@ -109,30 +131,205 @@ pub enum ActorRegistryErr {
     AlreadyRegistered(ActorName),
 }
 
+impl std::fmt::Display for ActorRegistryErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AlreadyRegistered(name) => {
+                write!(
+                    f,
+                    "Actor '{name}' is already registered in the actor registry"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ActorRegistryErr {}
+
+/// Errors looking up an actor with [where_is_typed]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum RegistryLookupErr {
+    /// No actor is registered under the name
+    NotFound(ActorName),
+    /// The actor registered under the name doesn't handle the requested message type
+    WrongMessageType(ActorName),
+}
+
+impl std::fmt::Display for RegistryLookupErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound(name) => write!(f, "No actor is registered as '{name}'"),
+            Self::WrongMessageType(name) => write!(
+                f,
+                "The actor registered as '{name}' doesn't handle the requested message type"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RegistryLookupErr {}
+
+/// Represents a change to a name in the registry, delivered to the actors watching the
+/// name with [monitor_name]
+#[derive(Clone)]
+pub enum NameChangeEvent {
+    /// An actor was registered under the name
+    Registered(ActorName, ActorCell),
+    /// The actor registered under the name was unregistered (e.g. because it exited)
+    Unregistered(ActorName, ActorCell),
+}
+
+impl NameChangeEvent {
+    /// Retrieve the name which changed
+    pub fn get_name(&self) -> &ActorName {
+        match self {
+            Self::Registered(name, _) | Self::Unregistered(name, _) => name,
+        }
+    }
+}
+
+impl Debug for NameChangeEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Registered(name, who) => {
+                write!(f, "Registered '{name}' to {}", who.get_id())
+            }
+            Self::Unregistered(name, who) => {
+                write!(f, "Unregistered '{name}' from {}", who.get_id())
+            }
+        }
+    }
+}
+
 /// The name'd actor registry
-static ACTOR_REGISTRY: OnceCell<Arc<DashMap<ActorName, ActorCell>>> = OnceCell::new();
+static ACTOR_REGISTRY: OnceCell<Arc<dyn RegistryBackend>> = OnceCell::new();
+/// The aliases of each actor, see [register_alias]
+static ACTOR_ALIASES: OnceCell<DashMap<ActorId, Vec<ActorName>>> = OnceCell::new();
+/// The watchers of each name, see [monitor_name]
+static NAME_WATCHERS: OnceCell<DashMap<ActorName, HashMap<ActorId, ActorCell>>> = OnceCell::new();
+/// The names each watcher watches, so they can be forgotten when it exits
+static WATCHED_NAMES: OnceCell<DashMap<ActorId, HashSet<ActorName>>> = OnceCell::new();
 
 /// Retrieve the named actor registry handle
-fn get_actor_registry<'a>() -> &'a Arc<DashMap<ActorName, ActorCell>> {
-    ACTOR_REGISTRY.get_or_init(|| Arc::new(DashMap::new()))
+fn get_actor_registry<'a>() -> &'a Arc<dyn RegistryBackend> {
+    ACTOR_REGISTRY.get_or_init(|| Arc::new(LocalRegistryBackend::default()))
+}
+
+fn get_actor_aliases<'a>() -> &'a DashMap<ActorId, Vec<ActorName>> {
+    ACTOR_ALIASES.get_or_init(DashMap::new)
+}
+
+fn get_name_watchers<'a>() -> &'a DashMap<ActorName, HashMap<ActorId, ActorCell>> {
+    NAME_WATCHERS.get_or_init(DashMap::new)
+}
+
+fn get_watched_names<'a>() -> &'a DashMap<ActorId, HashSet<ActorName>> {
+    WATCHED_NAMES.get_or_init(DashMap::new)
+}
+
+/// Forget that a watcher watches a name, in the index of the names it watches
+fn unindex_watched_name(watcher: ActorId, name: &str) {
+    if let Some(watched) = WATCHED_NAMES.get() {
+        if let Some(mut names) = watched.get_mut(&watcher) {
+            names.remove(name);
+        }
+        watched.remove_if(&watcher, |_, names| names.is_empty());
+    }
+}
+
+/// Install the [RegistryBackend] which stores the named actor registry.
+///
+/// This must happen before the registry is first used (i.e. before any actor is spawned
+/// with a name or looked up by name), as the registry can't be migrated between backends.
+///
+/// Returns the provided backend as an error if the registry's backend was already set
+pub fn set_backend(backend: Arc<dyn RegistryBackend>) -> Result<(), Arc<dyn RegistryBackend>> {
+    ACTOR_REGISTRY.set(backend)
+}
+
+/// Notify the watchers of a name that it changed, dropping any watchers which have exited
+fn notify_name_watchers(event: NameChangeEvent) {
+    if let Some(watchers) = NAME_WATCHERS.get() {
+        if let Some(mut watching) = watchers.get_mut(event.get_name()) {
+            watching.retain(|id, watcher| {
+                let sent = watcher
+                    .send_supervisor_evt(SupervisionEvent::NameChanged(event.clone()))
+                    .is_ok();
+                if !sent {
+                    unindex_watched_name(*id, event.get_name());
+                }
+                sent
+            });
+        }
+        watchers.remove_if(event.get_name(), |_, watching| watching.is_empty());
+    }
 }
 
 /// Put an actor into the registry
 pub(crate) fn register(name: ActorName, actor: ActorCell) -> Result<(), ActorRegistryErr> {
-    match get_actor_registry().entry(name.clone()) {
-        Occupied(_) => Err(ActorRegistryErr::AlreadyRegistered(name)),
-        Vacant(vacancy) => {
-            vacancy.insert(actor);
-            Ok(())
+    get_actor_registry().register(name.clone(), actor.clone())?;
+    notify_name_watchers(NameChangeEvent::Registered(name, actor));
+    Ok(())
+}
+
+/// Remove an actor from the registry given it's actor name, if the name is still
+/// registered to the actor
+pub(crate) fn unregister(name: ActorName, actor: ActorId) {
+    if let Some(cell) = get_actor_registry().unregister_if(&name, actor) {
+        notify_name_watchers(NameChangeEvent::Unregistered(name, cell));
+    }
+}
+
+/// Remove all the aliases of an actor from the registry
+pub(crate) fn unregister_aliases(actor: ActorId) {
+    if let Some(aliases) = ACTOR_ALIASES.get() {
+        if let Some((_, names)) = aliases.remove(&actor) {
+            for name in names {
+                unregister(name, actor);
+            }
         }
     }
 }
 
-/// Remove an actor from the registry given it's actor name
-pub(crate) fn unregister(name: ActorName) {
-    if let Some(reg) = ACTOR_REGISTRY.get() {
-        let _ = reg.remove(&name);
+/// Register an actor under an additional name. An actor can have any number of aliases
+/// besides the name it was spawned with, and they're all unregistered automatically when
+/// the actor exits.
+///
+/// * `name` - The alias to register
+/// * `actor` - The [ActorCell] to register under the alias
+///
+/// Returns [ActorRegistryErr::AlreadyRegistered] if the name is taken
+pub fn register_alias(name: ActorName, actor: ActorCell) -> Result<(), ActorRegistryErr> {
+    let id = actor.get_id();
+    register(name.clone(), actor.clone())?;
+    get_actor_aliases().entry(id).or_default().push(name);
+    // the actor may have exited before the alias was tracked, in which case
+    // nothing else will clean it up
+    if actor.get_status() >= crate::ActorStatus::Stopping {
+        unregister_aliases(id);
+    }
+    Ok(())
+}
+
+/// Remove a name (either the name an actor was spawned with, or an alias) from the
+/// registry, without stopping the actor registered under it.
+///
+/// * `name` - The name to unregister
+///
+/// Returns the [ActorCell] which was registered under the name, if any
+pub fn unregister_name(name: &str) -> Option<ActorCell> {
+    let actor = get_actor_registry().unregister(name)?;
+    if let Some(aliases) = ACTOR_ALIASES.get() {
+        if let Some(mut names) = aliases.get_mut(&actor.get_id()) {
+            names.retain(|alias| alias != name);
+        }
+        aliases.remove_if(&actor.get_id(), |_, names| names.is_empty());
     }
+    notify_name_watchers(NameChangeEvent::Unregistered(
+        name.to_string(),
+        actor.clone(),
+    ));
+    Some(actor)
 }
 
 /// Try and retrieve an actor from the registry
@@ -142,8 +339,28 @@ pub(crate) fn unregister(name: ActorName) {
 /// Returns: Some(actor) on successful identification of an actor, None if
 /// actor not registered
 pub fn where_is(name: ActorName) -> Option<ActorCell> {
-    let reg = get_actor_registry();
-    reg.get(&name).map(|v| v.value().clone())
+    get_actor_registry().where_is(&name)
+}
+
+/// Try and retrieve a strongly-typed actor from the registry
+///
+/// * `name` - The name of the actor to try and retrieve
+///
+/// Returns [Ok(ActorRef)] if an actor handling `TMessage` is registered under the name.
+/// Remote actors can't have their message type checked at runtime, so they're assumed to
+/// match. Returns [RegistryLookupErr::NotFound] if no actor is registered under the name
+/// and [RegistryLookupErr::WrongMessageType] if the actor handles another message type.
+pub fn where_is_typed<TMessage>(name: ActorName) -> Result<ActorRef<TMessage>, RegistryLookupErr>
+where
+    TMessage: Message,
+{
+    let Some(actor) = where_is(name.clone()) else {
+        return Err(RegistryLookupErr::NotFound(name));
+    };
+    match actor.is_message_type_of::<TMessage>() {
+        Some(false) => Err(RegistryLookupErr::WrongMessageType(name)),
+        Some(true) | None => Ok(actor.into()),
+    }
 }
 
 /// Returns a list of names that have been registered
@@ -151,6 +368,54 @@ pub fn where_is(name: ActorName) -> Option<ActorCell> {
 /// Returns: A [`Vec<String>`] of actor names which are registered
 /// currently
 pub fn registered() -> Vec<ActorName> {
-    let reg = get_actor_registry();
-    reg.iter().map(|kvp| kvp.key().clone()).collect::<Vec<_>>()
+    get_actor_registry().registered()
+}
+
+/// Watch a name in the registry. The watcher receives a [SupervisionEvent::NameChanged]
+/// whenever an actor is registered or unregistered under the name, until it calls
+/// [demonitor_name] or exits.
+///
+/// * `name` - The name to watch, which doesn't need to be registered yet
+/// * `watcher` - The [ActorCell] representing who will receive updates
+pub fn monitor_name(name: ActorName, watcher: ActorCell) {
+    get_watched_names()
+        .entry(watcher.get_id())
+        .or_default()
+        .insert(name.clone());
+    get_name_watchers()
+        .entry(name)
+        .or_default()
+        .insert(watcher.get_id(), watcher);
+}
+
+/// Stop watching a name in the registry
+///
+/// * `name` - The name which was watched
+/// * `watcher` - The id of the actor which was receiving updates
+pub fn demonitor_name(name: &str, watcher: ActorId) {
+    unindex_watched_name(watcher, name);
+    if let Some(watchers) = NAME_WATCHERS.get() {
+        if let Some(mut watching) = watchers.get_mut(name) {
+            watching.remove(&watcher);
+        }
+        watchers.remove_if(name, |_, watching| watching.is_empty());
+    }
+}
+
+/// Stop watching every name an actor watches. Used only during actor shutdown
+pub(crate) fn demonitor_all_names(watcher: ActorId) {
+    let Some((_, names)) = WATCHED_NAMES
+        .get()
+        .and_then(|watched| watched.remove(&watcher))
+    else {
+        return;
+    };
+    if let Some(watchers) = NAME_WATCHERS.get() {
+        for name in names {
+            if let Some(mut watching) = watchers.get_mut(&name) {
+                watching.remove(&watcher);
+            }
+            watchers.remove_if(&name, |_, watching| watching.is_empty());
+        }
+    }
 }
@@ -0,0 +1,90 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Pluggable storage for the named actor registry.
+//!
+//! By default names are kept in a process-local map ([LocalRegistryBackend]). To back names
+//! with another store (e.g. a cluster-wide or sharded registry), implement [RegistryBackend]
+//! and install it with [crate::registry::set_backend] before any actor is spawned with a name.
+//! All registry operations, including [crate::registry::where_is] and
+//! [crate::ActorRef::where_is], then go through the installed backend.
+
+use dashmap::mapref::entry::Entry::Occupied;
+use dashmap::mapref::entry::Entry::Vacant;
+use dashmap::DashMap;
+
+use super::ActorRegistryErr;
+use crate::ActorCell;
+use crate::ActorId;
+use crate::ActorName;
+
+/// The storage of the named actor registry
+///
+/// Name watchers (see [crate::registry::monitor_name]) are notified by the registry itself
+/// after a successful [RegistryBackend::register], [RegistryBackend::unregister], or
+/// [RegistryBackend::unregister_if], so backends don't need to track them.
+pub trait RegistryBackend: Send + Sync + 'static {
+    /// Register an actor under the provided name
+    ///
+    /// Returns [ActorRegistryErr::AlreadyRegistered] if the name is taken
+    fn register(&self, name: ActorName, actor: ActorCell) -> Result<(), ActorRegistryErr>;
+
+    /// Remove the registration of the provided name
+    ///
+    /// Returns the actor which was registered under the name, if any
+    fn unregister(&self, name: &str) -> Option<ActorCell>;
+
+    /// Remove the registration of the provided name, only if it's registered to the actor
+    /// with the provided id. The check and the removal must be atomic, so that a name which
+    /// has been re-registered by another actor in the meantime is left alone.
+    ///
+    /// Returns the actor which was registered under the name, if it was removed
+    fn unregister_if(&self, name: &str, actor: ActorId) -> Option<ActorCell>;
+
+    /// Retrieve the actor registered under the provided name
+    fn where_is(&self, name: &str) -> Option<ActorCell>;
+
+    /// Retrieve all the registered names
+    fn registered(&self) -> Vec<ActorName>;
+}
+
+/// The default [RegistryBackend], which keeps names in a process-local map
+#[derive(Debug, Default)]
+pub struct LocalRegistryBackend {
+    names: DashMap<ActorName, ActorCell>,
+}
+
+impl RegistryBackend for LocalRegistryBackend {
+    fn register(&self, name: ActorName, actor: ActorCell) -> Result<(), ActorRegistryErr> {
+        match self.names.entry(name.clone()) {
+            Occupied(_) => Err(ActorRegistryErr::AlreadyRegistered(name)),
+            Vacant(vacancy) => {
+                vacancy.insert(actor);
+                Ok(())
+            }
+        }
+    }
+
+    fn unregister(&self, name: &str) -> Option<ActorCell> {
+        self.names.remove(name).map(|(_, actor)| actor)
+    }
+
+    fn unregister_if(&self, name: &str, actor: ActorId) -> Option<ActorCell> {
+        self.names
+            .remove_if(name, |_, cell| cell.get_id() == actor)
+            .map(|(_, actor)| actor)
+    }
+
+    fn where_is(&self, name: &str) -> Option<ActorCell> {
+        self.names.get(name).map(|v| v.value().clone())
+    }
+
+    fn registered(&self) -> Vec<ActorName> {
+        self.names
+            .iter()
+            .map(|kvp| kvp.key().clone())
+            .collect::<Vec<_>>()
+    }
+}
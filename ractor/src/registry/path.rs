@@ -0,0 +1,151 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Hierarchical actor paths, derived from the supervision tree.
+//!
+//! An actor's [ActorPath] lists the actors from the root of its supervision tree down to
+//! the actor itself, e.g. `/user/sessions/42`. Each segment is the actor's name, or its
+//! [crate::ActorId] (e.g. `0.17`) if it's unnamed. Since paths follow the supervision tree,
+//! they change if an actor is re-linked to another supervisor.
+//!
+//! Paths can be resolved back to actors with [resolve_path], where a `*` in a segment
+//! matches any run of characters within that segment, e.g. `/user/sessions/*` or
+//! `/user/session-*/worker`. Resolution starts from named actors without a supervisor;
+//! with the `cluster` feature unnamed root actors are found through the
+//! [crate::registry::pid_registry] as well.
+//!
+//! Names containing a `/` can't be addressed by path.
+
+use std::collections::HashSet;
+use std::fmt::Display;
+
+use crate::ActorCell;
+
+/// The path of an actor in the supervision tree, see the [module documentation](self)
+#[derive(Debug, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct ActorPath {
+    segments: Vec<String>,
+}
+
+impl ActorPath {
+    /// Build the path of the provided actor by walking up its supervisors
+    pub fn of(actor: &ActorCell) -> Self {
+        let mut segments = vec![segment_of(actor)];
+        let mut visited = HashSet::from([actor.get_id()]);
+        let mut current = actor.try_get_supervisor();
+        while let Some(supervisor) = current {
+            // guard against the tree being re-linked mid-walk into a cycle
+            if !visited.insert(supervisor.get_id()) {
+                break;
+            }
+            segments.push(segment_of(&supervisor));
+            current = supervisor.try_get_supervisor();
+        }
+        segments.reverse();
+        Self { segments }
+    }
+
+    /// The segments of the path, from the root down to the actor
+    pub fn segments(&self) -> &[String] {
+        &self.segments
+    }
+
+    /// The path of the actor's supervisor, or [None] for a root actor
+    pub fn parent(&self) -> Option<ActorPath> {
+        if self.segments.len() > 1 {
+            Some(Self {
+                segments: self.segments[..self.segments.len() - 1].to_vec(),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl Display for ActorPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for segment in self.segments.iter() {
+            write!(f, "/{segment}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Resolve a path, which may contain `*` wildcards, to the actors at that position in the
+/// supervision tree. See the [module documentation](self) for the path format.
+///
+/// * `path` - The path to resolve, e.g. `/user/sessions/*`
+///
+/// Returns the matching actors ordered by id, which is empty if nothing matches
+pub fn resolve_path(path: &str) -> Vec<ActorCell> {
+    let patterns = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>();
+    let Some((first, rest)) = patterns.split_first() else {
+        return vec![];
+    };
+
+    let mut matches = roots()
+        .into_iter()
+        .filter(|root| glob_match(first, &segment_of(root)))
+        .collect::<Vec<_>>();
+    for pattern in rest {
+        matches = matches
+            .iter()
+            .flat_map(|actor| actor.get_children())
+            .filter(|child| glob_match(pattern, &segment_of(child)))
+            .collect();
+    }
+    matches.sort_by_key(|actor| actor.get_id());
+    matches.dedup_by_key(|actor| actor.get_id());
+    matches
+}
+
+/// The path segment of an actor
+fn segment_of(actor: &ActorCell) -> String {
+    actor
+        .get_name()
+        .unwrap_or_else(|| actor.get_id().to_string())
+}
+
+/// All the actors which resolution can start from
+fn roots() -> Vec<ActorCell> {
+    #[cfg(feature = "cluster")]
+    let actors = super::get_all_pids();
+    #[cfg(not(feature = "cluster"))]
+    let actors = super::registered()
+        .into_iter()
+        .filter_map(super::where_is)
+        .collect::<Vec<_>>();
+
+    actors
+        .into_iter()
+        .filter(|actor| actor.try_get_supervisor().is_none())
+        .collect()
+}
+
+/// Match a segment against a pattern where `*` matches any run of characters
+pub(crate) fn glob_match(pattern: &str, segment: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // the first part is anchored to the start of the segment
+    let Some(rest) = segment.strip_prefix(parts.next().unwrap_or_default()) else {
+        return false;
+    };
+    let mut parts = parts.collect::<Vec<_>>();
+    let Some(last) = parts.pop() else {
+        // no wildcard, so the pattern must match exactly
+        return rest.is_empty();
+    };
+    let mut rest = rest;
+    for part in parts {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    // the last part is anchored to the end of the segment
+    rest.ends_with(last)
+}
@@ -143,6 +143,444 @@ async fn test_actor_registry_unenrollment() {
     assert!(crate::registry::where_is("unenrollment".to_string()).is_none());
 }
 
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_name_monitoring() {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use crate::common_test::periodic_check;
+    use crate::registry::NameChangeEvent;
+    use crate::ActorId;
+    use crate::SupervisionEvent;
+
+    struct EmptyActor;
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for EmptyActor {
+        type Msg = ();
+        type Arguments = ();
+        type State = ();
+
+        async fn pre_start(
+            &self,
+            _this_actor: crate::ActorRef<Self::Msg>,
+            _: (),
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(())
+        }
+    }
+
+    type Events = Arc<Mutex<Vec<(bool, String, ActorId)>>>;
+
+    struct Watcher {
+        events: Events,
+    }
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for Watcher {
+        type Msg = ();
+        type Arguments = ();
+        type State = ();
+
+        async fn pre_start(
+            &self,
+            myself: crate::ActorRef<Self::Msg>,
+            _: (),
+        ) -> Result<Self::State, ActorProcessingErr> {
+            crate::registry::monitor_name("watched_name".to_string(), myself.get_cell());
+            crate::registry::monitor_name("unchanged_name".to_string(), myself.get_cell());
+            Ok(())
+        }
+
+        async fn handle_supervisor_evt(
+            &self,
+            _myself: crate::ActorRef<Self::Msg>,
+            message: SupervisionEvent,
+            _state: &mut Self::State,
+        ) -> Result<(), ActorProcessingErr> {
+            if let SupervisionEvent::NameChanged(change) = message {
+                let event = match change {
+                    NameChangeEvent::Registered(name, who) => (true, name, who.get_id()),
+                    NameChangeEvent::Unregistered(name, who) => (false, name, who.get_id()),
+                };
+                self.events.lock().unwrap().push(event);
+            }
+            Ok(())
+        }
+    }
+
+    let events: Events = Arc::new(Mutex::new(vec![]));
+    let (watcher, watcher_handle) = Actor::spawn(
+        None,
+        Watcher {
+            events: events.clone(),
+        },
+        (),
+    )
+    .await
+    .expect("Failed to start watcher");
+
+    // other names aren't reported
+    let (other, other_handle) = Actor::spawn(Some("unwatched_name".to_string()), EmptyActor, ())
+        .await
+        .expect("Actor failed to start");
+    let (actor, handle) = Actor::spawn(Some("watched_name".to_string()), EmptyActor, ())
+        .await
+        .expect("Actor failed to start");
+    periodic_check(|| events.lock().unwrap().len() == 1, Duration::from_secs(1)).await;
+    assert_eq!(
+        (true, "watched_name".to_string(), actor.get_id()),
+        events.lock().unwrap()[0]
+    );
+
+    actor.stop(None);
+    handle.await.unwrap();
+    periodic_check(|| events.lock().unwrap().len() == 2, Duration::from_secs(1)).await;
+    assert_eq!(
+        (false, "watched_name".to_string(), actor.get_id()),
+        events.lock().unwrap()[1]
+    );
+
+    // once demonitored, the watcher stops receiving updates
+    crate::registry::demonitor_name("watched_name", watcher.get_id());
+    let (actor, handle) = Actor::spawn(Some("watched_name".to_string()), EmptyActor, ())
+        .await
+        .expect("Actor failed to start");
+    actor.stop(None);
+    handle.await.unwrap();
+    crate::concurrency::sleep(Duration::from_millis(50)).await;
+    assert_eq!(2, events.lock().unwrap().len());
+
+    other.stop(None);
+    watcher.stop(None);
+    other_handle.await.unwrap();
+    watcher_handle.await.unwrap();
+
+    // an exited watcher is forgotten, even for names which never changed
+    assert!(!super::NAME_WATCHERS
+        .get()
+        .unwrap()
+        .contains_key("unchanged_name"));
+    assert!(!super::WATCHED_NAMES
+        .get()
+        .unwrap()
+        .contains_key(&watcher.get_id()));
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_local_registry_backend() {
+    use crate::registry::ActorRegistryErr;
+    use crate::registry::LocalRegistryBackend;
+    use crate::registry::RegistryBackend;
+
+    struct EmptyActor;
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for EmptyActor {
+        type Msg = ();
+        type Arguments = ();
+        type State = ();
+
+        async fn pre_start(
+            &self,
+            _this_actor: crate::ActorRef<Self::Msg>,
+            _: (),
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(())
+        }
+    }
+
+    let (actor, handle) = Actor::spawn(None, EmptyActor, ())
+        .await
+        .expect("Actor failed to start");
+
+    // backends are independent of the global registry
+    let backend = LocalRegistryBackend::default();
+    backend
+        .register("backend_name".to_string(), actor.get_cell())
+        .expect("Failed to register name");
+    assert!(crate::registry::where_is("backend_name".to_string()).is_none());
+    assert!(matches!(
+        backend.register("backend_name".to_string(), actor.get_cell()),
+        Err(ActorRegistryErr::AlreadyRegistered(name)) if name == "backend_name"
+    ));
+    assert_eq!(
+        Some(actor.get_id()),
+        backend.where_is("backend_name").map(|a| a.get_id())
+    );
+    assert_eq!(vec!["backend_name".to_string()], backend.registered());
+
+    // a name is only conditionally removed if it's registered to the expected actor
+    assert!(backend
+        .unregister_if("backend_name", crate::ActorId::Local(u64::MAX))
+        .is_none());
+    assert!(backend.where_is("backend_name").is_some());
+    assert_eq!(
+        Some(actor.get_id()),
+        backend
+            .unregister_if("backend_name", actor.get_id())
+            .map(|a| a.get_id())
+    );
+    backend
+        .register("backend_name".to_string(), actor.get_cell())
+        .expect("Failed to register name");
+
+    assert_eq!(
+        Some(actor.get_id()),
+        backend.unregister("backend_name").map(|a| a.get_id())
+    );
+    assert!(backend.unregister("backend_name").is_none());
+    assert!(backend.registered().is_empty());
+
+    actor.stop(None);
+    handle.await.unwrap();
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_typed_lookup() {
+    use crate::registry::RegistryLookupErr;
+
+    struct EmptyActor;
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for EmptyActor {
+        type Msg = ();
+        type Arguments = ();
+        type State = ();
+
+        async fn pre_start(
+            &self,
+            _this_actor: crate::ActorRef<Self::Msg>,
+            _: (),
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(())
+        }
+    }
+
+    let (actor, handle) = Actor::spawn(Some("typed_lookup".to_string()), EmptyActor, ())
+        .await
+        .expect("Actor failed to start");
+
+    let found = crate::registry::where_is_typed::<()>("typed_lookup".to_string())
+        .expect("Failed to find actor");
+    assert_eq!(actor.get_id(), found.get_id());
+
+    let err = crate::registry::where_is_typed::<String>("typed_lookup".to_string())
+        .expect_err("The actor doesn't handle strings");
+    assert_eq!(
+        RegistryLookupErr::WrongMessageType("typed_lookup".to_string()),
+        err
+    );
+    assert!(crate::ActorRef::<String>::where_is("typed_lookup".to_string()).is_none());
+
+    let err = crate::registry::where_is_typed::<()>("typed_lookup_missing".to_string())
+        .expect_err("No actor is registered under the name");
+    assert_eq!(
+        RegistryLookupErr::NotFound("typed_lookup_missing".to_string()),
+        err
+    );
+
+    actor.stop(None);
+    handle.await.unwrap();
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_aliases_and_explicit_unregistration() {
+    use crate::registry::register_alias;
+    use crate::registry::unregister_name;
+    use crate::registry::where_is;
+    use crate::registry::ActorRegistryErr;
+
+    struct EmptyActor;
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for EmptyActor {
+        type Msg = ();
+        type Arguments = ();
+        type State = ();
+
+        async fn pre_start(
+            &self,
+            _this_actor: crate::ActorRef<Self::Msg>,
+            _: (),
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(())
+        }
+    }
+
+    let (actor, handle) = Actor::spawn(Some("alias_primary".to_string()), EmptyActor, ())
+        .await
+        .expect("Actor failed to start");
+    let (other, other_handle) = Actor::spawn(None, EmptyActor, ())
+        .await
+        .expect("Actor failed to start");
+
+    register_alias("alias_a".to_string(), actor.get_cell()).expect("Failed to register alias");
+    register_alias("alias_b".to_string(), actor.get_cell()).expect("Failed to register alias");
+    assert!(matches!(
+        register_alias("alias_a".to_string(), other.get_cell()),
+        Err(ActorRegistryErr::AlreadyRegistered(_))
+    ));
+    for name in ["alias_primary", "alias_a", "alias_b"] {
+        assert_eq!(
+            Some(actor.get_id()),
+            where_is(name.to_string()).map(|a| a.get_id())
+        );
+    }
+
+    // names can be released without stopping the actor, and then taken by another
+    assert_eq!(
+        Some(actor.get_id()),
+        unregister_name("alias_primary").map(|a| a.get_id())
+    );
+    assert!(unregister_name("alias_primary").is_none());
+    assert_eq!(
+        Some(actor.get_id()),
+        unregister_name("alias_b").map(|a| a.get_id())
+    );
+    assert!(where_is("alias_b".to_string()).is_none());
+    assert!(crate::ACTIVE_STATES.contains(&actor.get_status()));
+    register_alias("alias_primary".to_string(), other.get_cell()).expect("The name should be free");
+
+    // exiting removes the remaining aliases, but not names since taken by others
+    actor.stop(None);
+    handle.await.unwrap();
+    assert!(where_is("alias_a".to_string()).is_none());
+    assert_eq!(
+        Some(other.get_id()),
+        where_is("alias_primary".to_string()).map(|a| a.get_id())
+    );
+
+    other.stop(None);
+    other_handle.await.unwrap();
+    assert!(where_is("alias_primary".to_string()).is_none());
+}
+
+#[test]
+fn test_path_glob_matching() {
+    use crate::registry::path::glob_match;
+
+    assert!(glob_match("sessions", "sessions"));
+    assert!(!glob_match("sessions", "session"));
+    assert!(!glob_match("session", "sessions"));
+    assert!(glob_match("*", "anything"));
+    assert!(glob_match("*", ""));
+    assert!(glob_match("session-*", "session-42"));
+    assert!(glob_match("session-*", "session-"));
+    assert!(!glob_match("session-*", "sessions"));
+    assert!(glob_match("*-worker", "pool-worker"));
+    assert!(glob_match("a*b*c", "aXXbYYc"));
+    assert!(glob_match("a*b*c", "abc"));
+    assert!(!glob_match("a*b*c", "acb"));
+    assert!(!glob_match("ab*ba", "aba"));
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_path_resolution() {
+    use crate::registry::resolve_path;
+
+    struct EmptyActor;
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for EmptyActor {
+        type Msg = ();
+        type Arguments = ();
+        type State = ();
+
+        async fn pre_start(
+            &self,
+            _this_actor: crate::ActorRef<Self::Msg>,
+            _: (),
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(())
+        }
+    }
+
+    let (root, root_handle) = Actor::spawn(Some("path_user".to_string()), EmptyActor, ())
+        .await
+        .expect("Actor failed to start");
+    let (sessions, _) = Actor::spawn_linked(
+        Some("path_sessions".to_string()),
+        EmptyActor,
+        (),
+        root.get_cell(),
+    )
+    .await
+    .expect("Actor failed to start");
+    let (session_a, _) = Actor::spawn_linked(
+        Some("path_session_a".to_string()),
+        EmptyActor,
+        (),
+        sessions.get_cell(),
+    )
+    .await
+    .expect("Actor failed to start");
+    let (session_b, _) = Actor::spawn_linked(None, EmptyActor, (), sessions.get_cell())
+        .await
+        .expect("Actor failed to start");
+
+    let path = session_a.get_path();
+    assert_eq!("/path_user/path_sessions/path_session_a", path.to_string());
+    assert_eq!(
+        "/path_user/path_sessions",
+        path.parent().unwrap().to_string()
+    );
+    assert_eq!(
+        format!("/path_user/path_sessions/{}", session_b.get_id()),
+        session_b.get_path().to_string()
+    );
+    assert_eq!("/path_user", root.get_path().to_string());
+    assert!(root.get_path().parent().is_none());
+
+    let ids = |path: &str| {
+        resolve_path(path)
+            .iter()
+            .map(|actor| actor.get_id())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        vec![session_a.get_id()],
+        ids("/path_user/path_sessions/path_session_a")
+    );
+    assert_eq!(
+        vec![session_b.get_id()],
+        ids(&session_b.get_path().to_string())
+    );
+    let mut all_sessions = vec![session_a.get_id(), session_b.get_id()];
+    all_sessions.sort();
+    assert_eq!(all_sessions, ids("/path_user/path_sessions/*"));
+    assert_eq!(all_sessions, ids("/path_*/*/*"));
+    assert_eq!(vec![session_a.get_id()], ids("/path_user/*/*_a"));
+    assert_eq!(vec![sessions.get_id()], ids("/path_user/path_sessions/"));
+    // children aren't roots
+    assert!(ids("/path_sessions").is_empty());
+    assert!(ids("/path_user/missing/*").is_empty());
+    assert!(ids("/").is_empty());
+
+    root.stop(None);
+    root_handle.await.unwrap();
+}
+
 #[cfg(feature = "cluster")]
 mod pid_registry_tests {
     use std::sync::Arc;
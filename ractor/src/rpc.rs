@@ -115,13 +115,41 @@ where
     sender(msg)
 }
 
+/// Wait for the reply to a request, up to its timeout. A reply port which is dropped once
+/// the deadline has passed (e.g. the actor discarding the expired request, or a handler
+/// giving up on it) is reported as a [CallResult::Timeout], as the caller's own timeout
+/// would have been.
+async fn await_reply<TReply>(
+    rx: concurrency::OneshotReceiver<TReply>,
+    timeout_option: Option<Duration>,
+    deadline: Option<concurrency::Instant>,
+) -> CallResult<TReply> {
+    if let Some(duration) = timeout_option {
+        match crate::concurrency::timeout(duration, rx).await {
+            Ok(Ok(result)) => CallResult::Success(result),
+            Ok(Err(_send_err))
+                if deadline.map_or(false, |deadline| concurrency::Instant::now() >= deadline) =>
+            {
+                CallResult::Timeout
+            }
+            Ok(Err(_send_err)) => CallResult::SenderError,
+            Err(_timeout_err) => CallResult::Timeout,
+        }
+    } else {
+        match rx.await {
+            Ok(result) => CallResult::Success(result),
+            Err(_send_err) => CallResult::SenderError,
+        }
+    }
+}
+
 fn internal_call<F, TMessage, TReply, TMsgBuilder>(
     sender: F,
     msg_builder: TMsgBuilder,
     timeout_option: Option<Duration>,
 ) -> impl std::future::Future<Output = Result<CallResult<TReply>, MessagingErr<TMessage>>> + Send
 where
-    F: Fn(TMessage) -> Result<(), MessagingErr<TMessage>>,
+    F: Fn(TMessage, Option<concurrency::Instant>) -> Result<(), MessagingErr<TMessage>>,
     TMessage: Message,
     TMsgBuilder: FnOnce(RpcReplyPort<TReply>) -> TMessage,
     TReply: Send + 'static,
@@ -131,23 +159,13 @@ where
         Some(duration) => (tx, duration).into(),
         None => tx.into(),
     };
-    let sent = sender(msg_builder(port));
+    let deadline = port.get_deadline();
+    let sent = sender(msg_builder(port), deadline);
 
     // wait for the reply
     async move {
         sent?;
-        Ok(if let Some(duration) = timeout_option {
-            match crate::concurrency::timeout(duration, rx).await {
-                Ok(Ok(result)) => CallResult::Success(result),
-                Ok(Err(_send_err)) => CallResult::SenderError,
-                Err(_timeout_err) => CallResult::Timeout,
-            }
-        } else {
-            match rx.await {
-                Ok(result) => CallResult::Success(result),
-                Err(_send_err) => CallResult::SenderError,
-            }
-        })
+        Ok(await_reply(rx, timeout_option, deadline).await)
     }
 }
 
@@ -185,7 +203,12 @@ where
     TMsgBuilder: FnOnce(RpcReplyPort<TReply>) -> TMessage,
     TReply: Send + 'static,
 {
-    internal_call(|m| actor.send_message(m), msg_builder, timeout_option).await
+    internal_call(
+        |m, deadline| actor.send_request(m, deadline),
+        msg_builder,
+        timeout_option,
+    )
+    .await
 }
 
 /// Sends an asynchronous request to the specified actors, building a one-time
@@ -217,35 +240,15 @@ where
             Some(duration) => (tx, duration).into(),
             None => tx.into(),
         };
-        actor.cast(msg_builder(port))?;
-        rx_ports.push(rx);
+        let deadline = port.get_deadline();
+        actor.get_cell().send_request(msg_builder(port), deadline)?;
+        rx_ports.push((rx, deadline));
     }
 
     let mut results = Vec::new();
     let mut join_set = crate::concurrency::JoinSet::new();
-    for (i, rx) in rx_ports.into_iter().enumerate() {
-        if let Some(duration) = timeout_option {
-            join_set.spawn(async move {
-                (
-                    i,
-                    match crate::concurrency::timeout(duration, rx).await {
-                        Ok(Ok(result)) => CallResult::Success(result),
-                        Ok(Err(_send_err)) => CallResult::SenderError,
-                        Err(_) => CallResult::Timeout,
-                    },
-                )
-            });
-        } else {
-            join_set.spawn(async move {
-                (
-                    i,
-                    match rx.await {
-                        Ok(result) => CallResult::Success(result),
-                        Err(_send_err) => CallResult::SenderError,
-                    },
-                )
-            });
-        }
+    for (i, (rx, deadline)) in rx_ports.into_iter().enumerate() {
+        join_set.spawn(async move { (i, await_reply(rx, timeout_option, deadline).await) });
     }
 
     // we threaded the index in order to maintain ordering from the originally called
@@ -296,23 +299,14 @@ where
         Some(duration) => (tx, duration).into(),
         None => tx.into(),
     };
-    actor.send_message::<TMessage>(msg_builder(port))?;
+    let deadline = port.get_deadline();
+    actor.send_request::<TMessage>(msg_builder(port), deadline)?;
 
     // wait for the reply
     Ok(crate::concurrency::spawn(async move {
-        if let Some(duration) = timeout_option {
-            match crate::concurrency::timeout(duration, rx).await {
-                Ok(Ok(result)) => CallResult::Success(result),
-                Ok(Err(_send_err)) => CallResult::SenderError,
-                Err(_timeout_err) => CallResult::Timeout,
-            }
-        } else {
-            match rx.await {
-                Ok(result) => CallResult::Success(result),
-                Err(_send_err) => CallResult::SenderError,
-            }
-        }
-        .map(|msg| response_forward.send_message::<TForwardMessage>(forward_mapping(msg)))
+        await_reply(rx, timeout_option, deadline)
+            .await
+            .map(|msg| response_forward.send_message::<TForwardMessage>(forward_mapping(msg)))
     }))
 }
 
@@ -385,6 +379,11 @@ where
         TMsgBuilder: FnOnce(RpcReplyPort<TReply>) -> TMessage,
         TReply: Send + 'static,
     {
-        internal_call(|m| self.send_message(m), msg_builder, timeout_option).await
+        internal_call(
+            |m, deadline| self.send_request(m, deadline),
+            msg_builder,
+            timeout_option,
+        )
+        .await
     }
 }
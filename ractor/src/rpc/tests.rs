@@ -442,3 +442,112 @@ async fn test_multi_call() {
         handle.await.unwrap();
     }
 }
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_expired_calls_are_not_handled() {
+    let handled = Arc::new(AtomicU8::new(0u8));
+    let cancelled = Arc::new(AtomicU8::new(0u8));
+
+    struct TestActor {
+        handled: Arc<AtomicU8>,
+        cancelled: Arc<AtomicU8>,
+    }
+    enum MessageFormat {
+        Block(Duration),
+        Rpc(rpc::RpcReplyPort<String>),
+        Watch(rpc::RpcReplyPort<String>),
+    }
+    #[cfg(feature = "cluster")]
+    impl crate::Message for MessageFormat {}
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for TestActor {
+        type Msg = MessageFormat;
+        type Arguments = ();
+        type State = ();
+
+        async fn pre_start(
+            &self,
+            _this_actor: ActorRef<Self::Msg>,
+            _: (),
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(())
+        }
+
+        async fn handle(
+            &self,
+            _this_actor: ActorRef<Self::Msg>,
+            message: Self::Msg,
+            _state: &mut Self::State,
+        ) -> Result<(), ActorProcessingErr> {
+            match message {
+                MessageFormat::Block(duration) => {
+                    crate::concurrency::sleep(duration).await;
+                }
+                MessageFormat::Rpc(reply) => {
+                    self.handled.fetch_add(1, Ordering::Relaxed);
+                    let _ = reply.send("howdy".to_string());
+                }
+                MessageFormat::Watch(mut reply) => {
+                    // wait for the caller to give up
+                    reply.closed().await;
+                    assert!(reply.is_expired());
+                    self.cancelled.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    let (actor, handle) = Actor::spawn(
+        None,
+        TestActor {
+            handled: handled.clone(),
+            cancelled: cancelled.clone(),
+        },
+        (),
+    )
+    .await
+    .expect("Failed to start test actor");
+
+    // the request's deadline passes while the actor is busy
+    cast!(actor, MessageFormat::Block(Duration::from_millis(100))).unwrap();
+    let result = call_t!(actor, MessageFormat::Rpc, 10);
+    assert!(matches!(result, Err(crate::RactorErr::Timeout)));
+    // an untimed request behind it is still handled
+    let result = call!(actor, MessageFormat::Rpc).expect("Failed to call actor");
+    assert_eq!("howdy", result);
+    assert_eq!(1, handled.load(Ordering::Relaxed));
+
+    // the same goes for requests through a derived actor ref
+    let derived: crate::DerivedActorRef<MessageFormat> = actor.get_derived();
+    derived
+        .cast(MessageFormat::Block(Duration::from_millis(100)))
+        .unwrap();
+    let result = derived
+        .call(MessageFormat::Rpc, Some(Duration::from_millis(10)))
+        .await
+        .expect("Failed to call actor");
+    assert!(matches!(result, rpc::CallResult::Timeout));
+    let result = derived
+        .call(MessageFormat::Rpc, None)
+        .await
+        .expect("Failed to call actor");
+    assert!(matches!(result, rpc::CallResult::Success(reply) if reply == "howdy"));
+    assert_eq!(2, handled.load(Ordering::Relaxed));
+
+    // handlers can wait on the caller giving up
+    let result = call_t!(actor, MessageFormat::Watch, 10);
+    assert!(matches!(result, Err(crate::RactorErr::Timeout)));
+    periodic_check(
+        || cancelled.load(Ordering::Relaxed) == 1,
+        Duration::from_secs(1),
+    )
+    .await;
+
+    actor.stop(None);
+    handle.await.unwrap();
+}
@@ -3,8 +3,9 @@
 // This source code is licensed under both the MIT license found in the
 // LICENSE-MIT file in the root directory of this source tree.
 
-//! Serialization definitions for `ractor_cluster` over-the-network message encoding. This
-//! contains helpful types for encoding and decoding messages from raw byte vectors
+//! Serialization definitions for `ractor_cluster` over-the-network message encoding, and for
+//! the events and snapshots of [crate::persistence]. This contains helpful types for encoding
+//! and decoding messages from raw byte vectors
 //!
 //! We implement the trait automatically for 8, 16, 32, 64, and 128 bit numerics but we specifically
 //! DO NOT implement it for arch-specific types [isize] or [usize] because the may encode at one size
@@ -201,7 +202,7 @@ mod impls {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "cluster"))]
 mod tests {
     use rand::distributions::Alphanumeric;
     use rand::thread_rng;
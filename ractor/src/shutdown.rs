@@ -0,0 +1,406 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Graceful, system-wide shutdown.
+//!
+//! A [ShutdownCoordinator] runs a list of [ShutdownPhase]s in order, each with its own
+//! timeout, e.g. when the process receives a SIGTERM. A phase is either a task (flushing
+//! buffers, deregistering from service discovery, closing connections, ...) or stopping
+//! actors.
+//!
+//! Actors are stopped in reverse dependency order, following the supervision tree: the
+//! leaves are stopped first, and a supervisor is only stopped once all of its children
+//! have been. [ShutdownPhase::stop_actors] stops every actor of the system (all the local
+//! actors of the [pid registry](crate::registry::get_all_pids) with the `cluster` feature,
+//! otherwise the [named](crate::registry::registered) actors and all of their
+//! descendants), while [ShutdownPhase::stop_tree] stops a single supervision tree.
+//!
+//! While its actors are being stopped, [is_shutting_down] returns `true` for them, and
+//! supervisors ([crate::supervisor::Supervisor], [crate::supervisor::dynamic::DynamicSupervisor]
+//! and factories) don't restart them when they exit. Custom supervisors should check it
+//! too. An actor stays marked until its supervisor has handled its exit, even when the
+//! supervisor isn't part of the stopped tree, or once the supervisor stops if it's a
+//! custom one.
+//!
+//! Running the coordinator returns a [ShutdownReport] with the outcome of every phase,
+//! including the actors which failed to stop in time. A failed phase doesn't prevent the
+//! next ones from running.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use ractor::concurrency::Duration;
+//! use ractor::shutdown::ShutdownCoordinator;
+//! use ractor::shutdown::ShutdownPhase;
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     // ... start the actors, and wait for a SIGTERM ...
+//!
+//!     let report = ShutdownCoordinator::new()
+//!         .with_phase(ShutdownPhase::task(
+//!             "stop-accepting-requests",
+//!             Duration::from_secs(1),
+//!             || async { Ok(()) },
+//!         ))
+//!         .with_phase(ShutdownPhase::stop_actors(
+//!             "stop-actors",
+//!             Duration::from_secs(10),
+//!         ))
+//!         .run()
+//!         .await;
+//!     for actor in report.unstopped_actors() {
+//!         tracing::error!("Actor {:?} failed to stop in time", actor.get_id());
+//!     }
+//!     if !report.is_clean() {
+//!         std::process::exit(1);
+//!     }
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::Mutex;
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use once_cell::sync::OnceCell;
+
+use crate::concurrency::Duration;
+use crate::concurrency::Instant;
+use crate::ActorCell;
+use crate::ActorId;
+use crate::ActorProcessingErr;
+use crate::ActorStatus;
+
+#[cfg(test)]
+mod tests;
+
+/// The reason actors are stopped with by a [ShutdownCoordinator]
+pub const SHUTDOWN_REASON: &str = "shutdown";
+
+/// The actors which are being stopped by a [ShutdownCoordinator], along with their
+/// supervisor (if any), which handles their exit
+type ShutdownMarks = HashMap<ActorId, (ActorCell, Option<ActorCell>)>;
+
+static SHUTTING_DOWN: OnceCell<Mutex<ShutdownMarks>> = OnceCell::new();
+
+fn shutting_down() -> &'static Mutex<ShutdownMarks> {
+    SHUTTING_DOWN.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Determine if an actor is being stopped by a [ShutdownCoordinator], in which case it
+/// shouldn't be restarted by its supervisor when it exits
+///
+/// * `actor` - The actor to check
+pub fn is_shutting_down(actor: &ActorCell) -> bool {
+    shutting_down()
+        .lock()
+        .unwrap()
+        .contains_key(&actor.get_id())
+}
+
+/// Determine if an actor which exited was stopped by a [ShutdownCoordinator], clearing
+/// its mark since its supervisor is now handling the exit
+///
+/// * `actor` - The actor which exited
+pub(crate) fn take_shutting_down(actor: &ActorCell) -> bool {
+    shutting_down()
+        .lock()
+        .unwrap()
+        .remove(&actor.get_id())
+        .is_some()
+}
+
+/// Forget the marks which no supervisor is left to clear, of the actors which stopped
+/// and whose supervisor (if any) has stopped as well
+fn purge_marks(marks: &mut ShutdownMarks) {
+    let stopped = |actor: &ActorCell| actor.get_status() == ActorStatus::Stopped;
+    marks.retain(|_, (actor, supervisor)| {
+        !stopped(actor) || matches!(supervisor, Some(supervisor) if !stopped(supervisor))
+    });
+}
+
+type ShutdownTask = Box<dyn FnOnce() -> BoxFuture<'static, Result<(), ActorProcessingErr>> + Send>;
+
+enum PhaseAction {
+    Task(ShutdownTask),
+    /// Stop the supervision tree rooted at an actor, or every actor if [None]
+    StopActors(Option<ActorCell>),
+}
+
+/// A step of a shutdown, see the [module documentation](self)
+pub struct ShutdownPhase {
+    name: String,
+    timeout: Duration,
+    action: PhaseAction,
+}
+
+impl std::fmt::Debug for ShutdownPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let action = match &self.action {
+            PhaseAction::Task(_) => "Task".to_string(),
+            PhaseAction::StopActors(None) => "StopActors".to_string(),
+            PhaseAction::StopActors(Some(root)) => format!("StopTree({:?})", root.get_id()),
+        };
+        f.debug_struct("ShutdownPhase")
+            .field("name", &self.name)
+            .field("timeout", &self.timeout)
+            .field("action", &action)
+            .finish()
+    }
+}
+
+impl ShutdownPhase {
+    /// A phase which runs a task
+    ///
+    /// * `name` - The name of the phase, for the [ShutdownReport]
+    /// * `timeout` - How long the task can run for, before it's abandoned
+    /// * `task` - The task
+    pub fn task<F, Fut>(name: impl Into<String>, timeout: Duration, task: F) -> Self
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), ActorProcessingErr>> + Send + 'static,
+    {
+        Self {
+            name: name.into(),
+            timeout,
+            action: PhaseAction::Task(Box::new(move || task().boxed())),
+        }
+    }
+
+    /// A phase which stops every actor of the system, leaves of the supervision tree first
+    ///
+    /// * `name` - The name of the phase, for the [ShutdownReport]
+    /// * `timeout` - How long the actors have to stop
+    pub fn stop_actors(name: impl Into<String>, timeout: Duration) -> Self {
+        Self {
+            name: name.into(),
+            timeout,
+            action: PhaseAction::StopActors(None),
+        }
+    }
+
+    /// A phase which stops an actor along with all of its descendants in the supervision
+    /// tree, leaves first
+    ///
+    /// * `name` - The name of the phase, for the [ShutdownReport]
+    /// * `root` - The root of the supervision tree to stop
+    /// * `timeout` - How long the actors have to stop
+    pub fn stop_tree(name: impl Into<String>, root: ActorCell, timeout: Duration) -> Self {
+        Self {
+            name: name.into(),
+            timeout,
+            action: PhaseAction::StopActors(Some(root)),
+        }
+    }
+}
+
+/// How a [ShutdownPhase] went
+#[derive(Debug)]
+pub enum PhaseOutcome {
+    /// The phase completed in time
+    Completed,
+    /// The phase's task returned an error
+    Failed(ActorProcessingErr),
+    /// The phase's task didn't complete in time
+    TimedOut,
+    /// Some of the phase's actors didn't stop in time. They're still running.
+    ActorsNotStopped(Vec<ActorCell>),
+}
+
+/// The outcome of a [ShutdownPhase]
+#[derive(Debug)]
+pub struct PhaseReport {
+    /// The name of the phase
+    pub name: String,
+    /// How the phase went
+    pub outcome: PhaseOutcome,
+    /// How long the phase took
+    pub elapsed: Duration,
+}
+
+/// The outcome of a shutdown, with a [PhaseReport] per phase in the order they ran
+#[derive(Debug)]
+pub struct ShutdownReport {
+    /// The outcomes of the phases
+    pub phases: Vec<PhaseReport>,
+}
+
+impl ShutdownReport {
+    /// Determine if every phase completed in time
+    pub fn is_clean(&self) -> bool {
+        self.phases
+            .iter()
+            .all(|phase| matches!(phase.outcome, PhaseOutcome::Completed))
+    }
+
+    /// The actors which didn't stop in time, across all the phases
+    pub fn unstopped_actors(&self) -> impl Iterator<Item = &ActorCell> {
+        self.phases
+            .iter()
+            .filter_map(|phase| match &phase.outcome {
+                PhaseOutcome::ActorsNotStopped(actors) => Some(actors.iter()),
+                _ => None,
+            })
+            .flatten()
+    }
+}
+
+/// Runs the [ShutdownPhase]s of a shutdown, see the [module documentation](self)
+#[derive(Debug, Default)]
+pub struct ShutdownCoordinator {
+    phases: Vec<ShutdownPhase>,
+}
+
+impl ShutdownCoordinator {
+    /// Create a new coordinator, without any phase
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a phase, which runs after the ones added before it
+    ///
+    /// * `phase` - The phase
+    pub fn with_phase(mut self, phase: ShutdownPhase) -> Self {
+        self.phases.push(phase);
+        self
+    }
+
+    /// Run the phases in order, returning how each of them went
+    pub async fn run(self) -> ShutdownReport {
+        let mut phases = Vec::with_capacity(self.phases.len());
+        for phase in self.phases {
+            tracing::info!("Running shutdown phase '{}'", phase.name);
+            let start = Instant::now();
+            let outcome = match phase.action {
+                PhaseAction::Task(task) => {
+                    match crate::concurrency::timeout(phase.timeout, task()).await {
+                        Ok(Ok(())) => PhaseOutcome::Completed,
+                        Ok(Err(err)) => PhaseOutcome::Failed(err),
+                        Err(_) => PhaseOutcome::TimedOut,
+                    }
+                }
+                PhaseAction::StopActors(root) => {
+                    let roots = root.map(|root| vec![root]).unwrap_or_else(system_actors);
+                    let unstopped = stop_actors(roots, phase.timeout).await;
+                    if unstopped.is_empty() {
+                        PhaseOutcome::Completed
+                    } else {
+                        PhaseOutcome::ActorsNotStopped(unstopped)
+                    }
+                }
+            };
+            if !matches!(outcome, PhaseOutcome::Completed) {
+                tracing::warn!(
+                    "Shutdown phase '{}' didn't complete: {outcome:?}",
+                    phase.name
+                );
+            }
+            phases.push(PhaseReport {
+                name: phase.name,
+                outcome,
+                elapsed: start.elapsed(),
+            });
+        }
+        ShutdownReport { phases }
+    }
+}
+
+/// All the actors of the system which are known, from which the whole supervision tree is
+/// reachable
+fn system_actors() -> Vec<ActorCell> {
+    #[cfg(feature = "cluster")]
+    {
+        crate::registry::get_all_pids()
+            .into_iter()
+            .filter(|actor| actor.get_id().is_local())
+            .collect()
+    }
+    #[cfg(not(feature = "cluster"))]
+    {
+        crate::registry::registered()
+            .into_iter()
+            .filter_map(crate::registry::where_is)
+            .collect()
+    }
+}
+
+/// Group the actors of the supervision trees rooted at the given actors by their height in
+/// the tree, i.e. the leaves first, then their supervisors, and so on
+fn levels(roots: Vec<ActorCell>) -> Vec<Vec<ActorCell>> {
+    fn height(
+        actor: &ActorCell,
+        heights: &mut HashMap<ActorId, (ActorCell, usize)>,
+        visiting: &mut HashSet<ActorId>,
+    ) -> usize {
+        if let Some((_, height)) = heights.get(&actor.get_id()) {
+            return *height;
+        }
+        visiting.insert(actor.get_id());
+        let mut max = 0;
+        for child in actor.get_children() {
+            if !visiting.contains(&child.get_id()) {
+                max = max.max(height(&child, heights, visiting) + 1);
+            }
+        }
+        visiting.remove(&actor.get_id());
+        heights.insert(actor.get_id(), (actor.clone(), max));
+        max
+    }
+
+    let mut heights = HashMap::new();
+    for root in &roots {
+        height(root, &mut heights, &mut HashSet::new());
+    }
+    let mut levels: Vec<Vec<ActorCell>> = vec![];
+    for (actor, height) in heights.into_values() {
+        if levels.len() <= height {
+            levels.resize_with(height + 1, Vec::new);
+        }
+        levels[height].push(actor);
+    }
+    levels
+}
+
+/// Stop the supervision trees rooted at the given actors, leaves first, returning the actors
+/// which didn't stop in time
+async fn stop_actors(roots: Vec<ActorCell>, timeout: Duration) -> Vec<ActorCell> {
+    let deadline = Instant::now() + timeout;
+    let levels = levels(roots);
+    {
+        let mut marks = shutting_down().lock().unwrap();
+        purge_marks(&mut marks);
+        marks.extend(levels.iter().flatten().map(|actor| {
+            let mark = (actor.clone(), actor.try_get_supervisor());
+            (actor.get_id(), mark)
+        }));
+    }
+
+    let mut unstopped = vec![];
+    for level in levels {
+        for actor in &level {
+            actor.stop(Some(SHUTDOWN_REASON.to_string()));
+        }
+        let waits = level.into_iter().map(|actor| async move {
+            if actor.get_status() == ActorStatus::Stopped {
+                return None;
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match actor.wait(Some(remaining)).await {
+                Ok(()) => None,
+                // the exit may have been consumed by another waiter, so check the status again
+                Err(_) => (actor.get_status() != ActorStatus::Stopped).then_some(actor),
+            }
+        });
+        unstopped.extend(futures::future::join_all(waits).await.into_iter().flatten());
+    }
+
+    // the actors stay marked until their supervisor has handled their exit, and the ones
+    // which are still running so they're not restarted if they eventually exit
+    purge_marks(&mut shutting_down().lock().unwrap());
+    unstopped
+}
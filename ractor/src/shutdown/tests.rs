@@ -0,0 +1,370 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Tests of the shutdown coordinator
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use super::*;
+use crate::common_test::periodic_check;
+use crate::supervisor::ChildSpec;
+use crate::supervisor::Restart;
+use crate::supervisor::Supervisor;
+use crate::supervisor::SupervisorArguments;
+use crate::Actor;
+use crate::ActorRef;
+use crate::SupervisionEvent;
+
+/// Records its name when it stops. A stuck node never finishes handling a message.
+struct Node {
+    name: &'static str,
+    log: Arc<Mutex<Vec<String>>>,
+    stuck: bool,
+}
+
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+impl Actor for Node {
+    type Msg = ();
+    type State = ();
+    type Arguments = ();
+
+    async fn pre_start(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        _: (),
+    ) -> Result<Self::State, ActorProcessingErr> {
+        Ok(())
+    }
+
+    async fn handle(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        _message: Self::Msg,
+        _state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        if self.stuck {
+            crate::concurrency::sleep(Duration::from_secs(10)).await;
+        }
+        Ok(())
+    }
+
+    async fn post_stop(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        _state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        self.log.lock().unwrap().push(self.name.to_string());
+        Ok(())
+    }
+
+    // keep running when a child exits, so it's up to the shutdown to stop the parent
+    async fn handle_supervisor_evt(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        _message: SupervisionEvent,
+        _state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        Ok(())
+    }
+}
+
+async fn spawn_node(
+    name: &'static str,
+    log: &Arc<Mutex<Vec<String>>>,
+    supervisor: Option<&ActorCell>,
+) -> ActorRef<()> {
+    let node = Node {
+        name,
+        log: log.clone(),
+        stuck: false,
+    };
+    let (actor, _) = match supervisor {
+        Some(supervisor) => Actor::spawn_linked(None, node, (), supervisor.clone()).await,
+        None => Actor::spawn(None, node, ()).await,
+    }
+    .expect("Failed to spawn node");
+    actor
+}
+
+fn task_logging(
+    name: &'static str,
+    log: &Arc<Mutex<Vec<String>>>,
+) -> impl FnOnce() -> BoxFuture<'static, Result<(), ActorProcessingErr>> {
+    let log = log.clone();
+    move || {
+        async move {
+            log.lock().unwrap().push(name.to_string());
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_stop_tree_leaves_first() {
+    let log = Arc::new(Mutex::new(vec![]));
+    let root = spawn_node("root", &log, None).await;
+    let mid = spawn_node("mid", &log, Some(&root.get_cell())).await;
+    spawn_node("leaf1", &log, Some(&mid.get_cell())).await;
+    spawn_node("leaf2", &log, Some(&mid.get_cell())).await;
+    spawn_node("leaf3", &log, Some(&root.get_cell())).await;
+
+    let report = ShutdownCoordinator::new()
+        .with_phase(ShutdownPhase::task(
+            "before",
+            Duration::from_secs(1),
+            task_logging("before", &log),
+        ))
+        .with_phase(ShutdownPhase::stop_tree(
+            "stop",
+            root.get_cell(),
+            Duration::from_secs(1),
+        ))
+        .with_phase(ShutdownPhase::task(
+            "after",
+            Duration::from_secs(1),
+            task_logging("after", &log),
+        ))
+        .run()
+        .await;
+
+    assert!(report.is_clean(), "{report:?}");
+    assert_eq!(
+        vec!["before", "stop", "after"],
+        report
+            .phases
+            .iter()
+            .map(|phase| phase.name.as_str())
+            .collect::<Vec<_>>()
+    );
+    let mut log = log.lock().unwrap().clone();
+    log[1..4].sort();
+    assert_eq!(
+        vec!["before", "leaf1", "leaf2", "leaf3", "mid", "root", "after"],
+        log
+    );
+    assert_eq!(ActorStatus::Stopped, root.get_status());
+    assert!(!is_shutting_down(&root.get_cell()));
+}
+
+#[test]
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+#[tracing_test::traced_test]
+fn test_shutdown_reports_failures() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        let log = Arc::new(Mutex::new(vec![]));
+        let (stuck, handle) = Actor::spawn(
+            None,
+            Node {
+                name: "stuck",
+                log: log.clone(),
+                stuck: true,
+            },
+            (),
+        )
+        .await
+        .expect("Failed to spawn node");
+        stuck.cast(()).unwrap();
+
+        let report = ShutdownCoordinator::new()
+            .with_phase(ShutdownPhase::task(
+                "failing",
+                Duration::from_secs(1),
+                || async { Err(From::from("boom")) },
+            ))
+            .with_phase(ShutdownPhase::task(
+                "slow",
+                Duration::from_millis(50),
+                || async {
+                    crate::concurrency::sleep(Duration::from_secs(10)).await;
+                    Ok(())
+                },
+            ))
+            .with_phase(ShutdownPhase::stop_tree(
+                "stop",
+                stuck.get_cell(),
+                Duration::from_millis(100),
+            ))
+            .run()
+            .await;
+
+        assert!(!report.is_clean());
+        assert!(matches!(report.phases[0].outcome, PhaseOutcome::Failed(_)));
+        assert!(matches!(report.phases[1].outcome, PhaseOutcome::TimedOut));
+        assert_eq!(
+            vec![stuck.get_id()],
+            report
+                .unstopped_actors()
+                .map(|actor| actor.get_id())
+                .collect::<Vec<_>>()
+        );
+        // the actor stays marked, so it isn't restarted when it eventually stops
+        assert!(is_shutting_down(&stuck.get_cell()));
+
+        stuck.kill();
+        handle.await.unwrap();
+    });
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_supervisor_does_not_restart_during_shutdown() {
+    let log = Arc::new(Mutex::new(vec![]));
+    let spawns = Arc::new(AtomicUsize::new(0));
+    let child_log = log.clone();
+    let child_spawns = spawns.clone();
+    let args = SupervisorArguments::builder()
+        .children(vec![ChildSpec::new(
+            "child",
+            Restart::Permanent,
+            move |supervisor| {
+                let log = child_log.clone();
+                child_spawns.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    let node = Node {
+                        name: "child",
+                        log,
+                        stuck: false,
+                    };
+                    let (actor, _) = Actor::spawn_linked(None, node, (), supervisor).await?;
+                    Ok(actor.get_cell())
+                }
+            },
+        )])
+        .build();
+    let (supervisor, handle) = Actor::spawn(None, Supervisor, args)
+        .await
+        .expect("Failed to spawn supervisor");
+    periodic_check(
+        || supervisor.get_children().len() == 1,
+        Duration::from_secs(1),
+    )
+    .await;
+
+    let report = ShutdownCoordinator::new()
+        .with_phase(ShutdownPhase::stop_tree(
+            "stop",
+            supervisor.get_cell(),
+            Duration::from_secs(1),
+        ))
+        .run()
+        .await;
+    handle.await.unwrap();
+
+    assert!(report.is_clean(), "{report:?}");
+    assert_eq!(1, spawns.load(Ordering::SeqCst));
+    assert_eq!(vec!["child"], *log.lock().unwrap());
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_supervisor_does_not_restart_stopped_subtree() {
+    let log = Arc::new(Mutex::new(vec![]));
+    let spawns = Arc::new(AtomicUsize::new(0));
+    let child_log = log.clone();
+    let child_spawns = spawns.clone();
+    let args = SupervisorArguments::builder()
+        .children(vec![ChildSpec::new(
+            "child",
+            Restart::Permanent,
+            move |supervisor| {
+                let log = child_log.clone();
+                child_spawns.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    let node = Node {
+                        name: "child",
+                        log: log.clone(),
+                        stuck: false,
+                    };
+                    let (actor, _) = Actor::spawn_linked(None, node, (), supervisor).await?;
+                    spawn_node("grandchild", &log, Some(&actor.get_cell())).await;
+                    Ok(actor.get_cell())
+                }
+            },
+        )])
+        .build();
+    let (supervisor, handle) = Actor::spawn(None, Supervisor, args)
+        .await
+        .expect("Failed to spawn supervisor");
+    periodic_check(
+        || supervisor.get_children().len() == 1,
+        Duration::from_secs(1),
+    )
+    .await;
+    let child = supervisor.get_children().pop().unwrap();
+
+    // only the child's subtree is stopped, the supervisor keeps running
+    let report = ShutdownCoordinator::new()
+        .with_phase(ShutdownPhase::stop_tree(
+            "stop",
+            child.clone(),
+            Duration::from_secs(1),
+        ))
+        .run()
+        .await;
+    assert!(report.is_clean(), "{report:?}");
+    assert_eq!(vec!["grandchild", "child"], *log.lock().unwrap());
+
+    // the child stays marked until the supervisor has handled its exit
+    periodic_check(|| !is_shutting_down(&child), Duration::from_secs(1)).await;
+    assert_eq!(ActorStatus::Running, supervisor.get_status());
+    assert!(supervisor.get_children().is_empty());
+    assert_eq!(1, spawns.load(Ordering::SeqCst));
+
+    supervisor.stop(None);
+    handle.await.unwrap();
+}
+
+#[test]
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+#[tracing_test::traced_test]
+fn test_stop_tree_on_multi_thread_runtime() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(4)
+        .enable_all()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        // the waiters are woken on other threads than the ones stopping the actors, so
+        // they need to observe the stopped status right away
+        for _ in 0..20 {
+            let log = Arc::new(Mutex::new(vec![]));
+            let root = spawn_node("root", &log, None).await;
+            for _ in 0..4 {
+                let mid = spawn_node("mid", &log, Some(&root.get_cell())).await;
+                spawn_node("leaf", &log, Some(&mid.get_cell())).await;
+            }
+
+            let report = ShutdownCoordinator::new()
+                .with_phase(ShutdownPhase::stop_tree(
+                    "stop",
+                    root.get_cell(),
+                    Duration::from_secs(1),
+                ))
+                .run()
+                .await;
+            assert!(report.is_clean(), "{report:?}");
+            assert_eq!(9, log.lock().unwrap().len());
+        }
+    });
+}
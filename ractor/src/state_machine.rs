@@ -0,0 +1,674 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Finite state machine actors, similar to Erlang's `gen_statem`.
+//!
+//! A [StateMachineActor] has an explicit state, typically an enum, alongside its data. The
+//! state type implements [StateHandler], so every event is dispatched to the handler of the
+//! current state, which answers with a [Transition]: keep the state, move to the next state,
+//! or stop. A transition can additionally
+//!
+//! * Postpone the event, deferring it until the next state change, after which postponed
+//!   events are handled again (in the order they arrived) before any newer event.
+//! * Start a state timeout, which delivers [StateMachineEvent::StateTimeout] unless the
+//!   state changes first. Starting another state timeout replaces the running one.
+//! * Start an event timeout, which delivers [StateMachineEvent::EventTimeout] unless
+//!   another event arrives first.
+//!
+//! Moving to a state equal to the current one is not a state change, so it neither
+//! replays postponed events nor cancels the state timeout.
+//!
+//! A state machine is run by wrapping it in a [StateMachine], which is a regular [Actor].
+//! It's spawned with [Actor::spawn] or [Actor::spawn_linked], so supervision, the registry,
+//! and process groups apply as for any other actor. Its message type is
+//! [StateMachineMessage], which events convert into with [From].
+//!
+//! ## Example
+//!
+//! ```rust
+//! use ractor::concurrency::Duration;
+//! use ractor::state_machine::StateHandler;
+//! use ractor::state_machine::StateMachine;
+//! use ractor::state_machine::StateMachineActor;
+//! use ractor::state_machine::StateMachineEvent;
+//! use ractor::state_machine::StateMachineMessage;
+//! use ractor::state_machine::Transition;
+//! use ractor::Actor;
+//! use ractor::ActorProcessingErr;
+//! use ractor::ActorRef;
+//!
+//! #[derive(Debug, Clone, PartialEq)]
+//! enum Door {
+//!     Locked,
+//!     Open,
+//! }
+//!
+//! enum DoorMsg {
+//!     Unlock(u32),
+//!     Push,
+//! }
+//! #[cfg(feature = "cluster")]
+//! impl ractor::Message for DoorMsg {}
+//!
+//! struct DoorLock;
+//!
+//! #[cfg_attr(feature = "async-trait", ractor::async_trait)]
+//! impl StateMachineActor for DoorLock {
+//!     type Msg = DoorMsg;
+//!     type State = Door;
+//!     type Data = u32;
+//!     type Arguments = u32;
+//!
+//!     async fn init(
+//!         &self,
+//!         _myself: ActorRef<StateMachineMessage<DoorMsg>>,
+//!         code: u32,
+//!     ) -> Result<(Door, u32), ActorProcessingErr> {
+//!         Ok((Door::Locked, code))
+//!     }
+//! }
+//!
+//! #[cfg_attr(feature = "async-trait", ractor::async_trait)]
+//! impl StateHandler<DoorLock> for Door {
+//!     async fn handle_event(
+//!         &self,
+//!         _machine: &DoorLock,
+//!         _myself: ActorRef<StateMachineMessage<DoorMsg>>,
+//!         event: StateMachineEvent<DoorMsg>,
+//!         code: &mut u32,
+//!     ) -> Result<Transition<Door, DoorMsg>, ActorProcessingErr> {
+//!         Ok(match self {
+//!             Door::Locked => locked(event, *code),
+//!             Door::Open => open(event),
+//!         })
+//!     }
+//! }
+//!
+//! fn locked(event: StateMachineEvent<DoorMsg>, code: u32) -> Transition<Door, DoorMsg> {
+//!     match event {
+//!         StateMachineEvent::Message(DoorMsg::Unlock(guess)) if guess == code => {
+//!             // relock the door after a while
+//!             Transition::next(Door::Open).with_state_timeout(Duration::from_secs(10))
+//!         }
+//!         // wait for the door to be unlocked before going through
+//!         StateMachineEvent::Message(DoorMsg::Push) => Transition::keep().postpone(DoorMsg::Push),
+//!         _ => Transition::keep(),
+//!     }
+//! }
+//!
+//! fn open(event: StateMachineEvent<DoorMsg>) -> Transition<Door, DoorMsg> {
+//!     match event {
+//!         StateMachineEvent::StateTimeout => Transition::next(Door::Locked),
+//!         _ => Transition::keep(),
+//!     }
+//! }
+//!
+//! async fn run() {
+//!     let (door, _) = Actor::spawn(None, StateMachine::new(DoorLock), 1234)
+//!         .await
+//!         .expect("Failed to start the door");
+//!     door.cast(DoorMsg::Push.into()).unwrap();
+//!     door.cast(DoorMsg::Unlock(1234).into()).unwrap();
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt::Debug;
+#[cfg(not(feature = "async-trait"))]
+use std::future::Future;
+
+use crate::concurrency::Duration;
+use crate::time::TimerRef;
+use crate::time::TimerService;
+use crate::Actor;
+use crate::ActorProcessingErr;
+use crate::ActorRef;
+use crate::Message;
+use crate::SupervisionEvent;
+
+#[cfg(test)]
+mod tests;
+
+/// A finite state machine, run as an actor by a [StateMachine], see the
+/// [module documentation](self)
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+pub trait StateMachineActor: Sized + Send + Sync + 'static {
+    /// The events the state machine receives as messages
+    type Msg: Message;
+    /// The states of the machine, typically an enum, which handle the events with a
+    /// [StateHandler]
+    type State: StateHandler<Self> + Debug + PartialEq + Send + Sync + 'static;
+    /// The data of the machine, which is kept across states
+    type Data: crate::State;
+    /// The startup arguments of the machine (use `()` to ignore)
+    type Arguments: crate::State;
+
+    /// Invoked when the state machine is being started, to create its initial state and
+    /// data. [StateMachineActor::on_enter] is then invoked for the initial state.
+    ///
+    /// * `myself` - A handle to the [crate::ActorCell] representing this state machine
+    /// * `args` - Arguments that are passed in the spawning of the state machine
+    ///
+    /// Returns the initial state and data of the machine
+    #[cfg(not(feature = "async-trait"))]
+    fn init(
+        &self,
+        myself: ActorRef<StateMachineMessage<Self::Msg>>,
+        args: Self::Arguments,
+    ) -> impl Future<Output = Result<(Self::State, Self::Data), ActorProcessingErr>> + Send;
+
+    /// Invoked when the state machine is being started, to create its initial state and
+    /// data. [StateMachineActor::on_enter] is then invoked for the initial state.
+    ///
+    /// * `myself` - A handle to the [crate::ActorCell] representing this state machine
+    /// * `args` - Arguments that are passed in the spawning of the state machine
+    ///
+    /// Returns the initial state and data of the machine
+    #[cfg(feature = "async-trait")]
+    async fn init(
+        &self,
+        myself: ActorRef<StateMachineMessage<Self::Msg>>,
+        args: Self::Arguments,
+    ) -> Result<(Self::State, Self::Data), ActorProcessingErr>;
+
+    /// Invoked when the machine enters a state, i.e. for the initial state and after every
+    /// state change, before any postponed events are handled again
+    ///
+    /// * `myself` - A handle to the [crate::ActorCell] representing this state machine
+    /// * `state` - The state which was entered
+    /// * `data` - The data of the machine
+    #[allow(unused_variables)]
+    #[cfg(not(feature = "async-trait"))]
+    fn on_enter(
+        &self,
+        myself: ActorRef<StateMachineMessage<Self::Msg>>,
+        state: &Self::State,
+        data: &mut Self::Data,
+    ) -> impl Future<Output = Result<(), ActorProcessingErr>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Invoked when the machine enters a state, i.e. for the initial state and after every
+    /// state change, before any postponed events are handled again
+    ///
+    /// * `myself` - A handle to the [crate::ActorCell] representing this state machine
+    /// * `state` - The state which was entered
+    /// * `data` - The data of the machine
+    #[allow(unused_variables)]
+    #[cfg(feature = "async-trait")]
+    async fn on_enter(
+        &self,
+        myself: ActorRef<StateMachineMessage<Self::Msg>>,
+        state: &Self::State,
+        data: &mut Self::Data,
+    ) -> Result<(), ActorProcessingErr> {
+        Ok(())
+    }
+
+    /// Handle a supervision event, as for [Actor::handle_supervisor_evt]. The default
+    /// is to stop the state machine on any child exit.
+    ///
+    /// * `myself` - A handle to the [crate::ActorCell] representing this state machine
+    /// * `message` - The supervision event
+    /// * `state` - The current state
+    /// * `data` - The data of the machine
+    #[allow(unused_variables)]
+    #[cfg(not(feature = "async-trait"))]
+    fn handle_supervisor_evt(
+        &self,
+        myself: ActorRef<StateMachineMessage<Self::Msg>>,
+        message: SupervisionEvent,
+        state: &Self::State,
+        data: &mut Self::Data,
+    ) -> impl Future<Output = Result<(), ActorProcessingErr>> + Send {
+        async move {
+            match message {
+                SupervisionEvent::ActorTerminated(who, _, _)
+                | SupervisionEvent::ActorFailed(who, _) => {
+                    myself.stop(None);
+                }
+                _ => {}
+            }
+            Ok(())
+        }
+    }
+
+    /// Handle a supervision event, as for [Actor::handle_supervisor_evt]. The default
+    /// is to stop the state machine on any child exit.
+    ///
+    /// * `myself` - A handle to the [crate::ActorCell] representing this state machine
+    /// * `message` - The supervision event
+    /// * `state` - The current state
+    /// * `data` - The data of the machine
+    #[allow(unused_variables)]
+    #[cfg(feature = "async-trait")]
+    async fn handle_supervisor_evt(
+        &self,
+        myself: ActorRef<StateMachineMessage<Self::Msg>>,
+        message: SupervisionEvent,
+        state: &Self::State,
+        data: &mut Self::Data,
+    ) -> Result<(), ActorProcessingErr> {
+        match message {
+            SupervisionEvent::ActorTerminated(who, _, _)
+            | SupervisionEvent::ActorFailed(who, _) => {
+                myself.stop(None);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Invoked after the state machine has been stopped to perform final cleanup, as for
+    /// [Actor::post_stop]
+    ///
+    /// * `myself` - A handle to the [crate::ActorCell] representing this state machine
+    /// * `state` - The state the machine stopped in
+    /// * `data` - The data of the machine
+    #[allow(unused_variables)]
+    #[cfg(not(feature = "async-trait"))]
+    fn terminate(
+        &self,
+        myself: ActorRef<StateMachineMessage<Self::Msg>>,
+        state: &Self::State,
+        data: &mut Self::Data,
+    ) -> impl Future<Output = Result<(), ActorProcessingErr>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Invoked after the state machine has been stopped to perform final cleanup, as for
+    /// [Actor::post_stop]
+    ///
+    /// * `myself` - A handle to the [crate::ActorCell] representing this state machine
+    /// * `state` - The state the machine stopped in
+    /// * `data` - The data of the machine
+    #[allow(unused_variables)]
+    #[cfg(feature = "async-trait")]
+    async fn terminate(
+        &self,
+        myself: ActorRef<StateMachineMessage<Self::Msg>>,
+        state: &Self::State,
+        data: &mut Self::Data,
+    ) -> Result<(), ActorProcessingErr> {
+        Ok(())
+    }
+}
+
+/// The handling of events in a state of a [StateMachineActor]. It's implemented by the
+/// state type, so every state dispatches its events to its own handler.
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+pub trait StateHandler<TMachine: StateMachineActor>: Sized {
+    /// Handle an event in this state
+    ///
+    /// Errors and panics follow the supervision strategy, as for [Actor::handle].
+    ///
+    /// * `machine` - The state machine
+    /// * `myself` - A handle to the [crate::ActorCell] representing the state machine
+    /// * `event` - The event to handle
+    /// * `data` - The data of the machine
+    ///
+    /// Returns the [Transition] to take
+    #[cfg(not(feature = "async-trait"))]
+    fn handle_event(
+        &self,
+        machine: &TMachine,
+        myself: ActorRef<StateMachineMessage<TMachine::Msg>>,
+        event: StateMachineEvent<TMachine::Msg>,
+        data: &mut TMachine::Data,
+    ) -> impl Future<Output = Result<Transition<TMachine::State, TMachine::Msg>, ActorProcessingErr>>
+           + Send;
+
+    /// Handle an event in this state
+    ///
+    /// Errors and panics follow the supervision strategy, as for [Actor::handle].
+    ///
+    /// * `machine` - The state machine
+    /// * `myself` - A handle to the [crate::ActorCell] representing the state machine
+    /// * `event` - The event to handle
+    /// * `data` - The data of the machine
+    ///
+    /// Returns the [Transition] to take
+    #[cfg(feature = "async-trait")]
+    async fn handle_event(
+        &self,
+        machine: &TMachine,
+        myself: ActorRef<StateMachineMessage<TMachine::Msg>>,
+        event: StateMachineEvent<TMachine::Msg>,
+        data: &mut TMachine::Data,
+    ) -> Result<Transition<TMachine::State, TMachine::Msg>, ActorProcessingErr>;
+}
+
+/// An event handled by a [StateMachineActor]
+#[derive(Debug)]
+pub enum StateMachineEvent<TMsg> {
+    /// A message sent to the state machine
+    Message(TMsg),
+    /// The state timeout of a [Transition] expired without the state changing
+    StateTimeout,
+    /// The event timeout of a [Transition] expired without another event arriving
+    EventTimeout,
+}
+
+/// The message type of a [StateMachine] actor
+///
+/// The events of the machine convert into it with [From], so they can be sent with
+/// `actor.cast(event.into())`.
+#[derive(Debug)]
+pub enum StateMachineMessage<TMsg> {
+    /// An event for the state machine
+    Event(TMsg),
+    /// An expired timeout, which is only meaningful to the state machine itself
+    Timeout(StateMachineTimeout),
+}
+
+impl<TMsg> From<TMsg> for StateMachineMessage<TMsg> {
+    fn from(msg: TMsg) -> Self {
+        Self::Event(msg)
+    }
+}
+
+#[cfg(feature = "cluster")]
+impl<TMsg: Message> Message for StateMachineMessage<TMsg> {}
+
+/// An expired timeout of a [StateMachine]. Timeouts which were cancelled or replaced
+/// before they were handled are ignored.
+#[derive(Debug)]
+pub struct StateMachineTimeout {
+    kind: TimeoutKind,
+    id: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TimeoutKind {
+    State,
+    Event,
+}
+
+/// The outcome of handling an event, see [StateHandler::handle_event]
+#[derive(Debug)]
+pub struct Transition<TState, TMsg> {
+    next: Option<TState>,
+    postponed: Option<TMsg>,
+    stop: Option<Option<String>>,
+    state_timeout: Option<Duration>,
+    event_timeout: Option<Duration>,
+}
+
+impl<TState, TMsg> Transition<TState, TMsg> {
+    fn new(next: Option<TState>, stop: Option<Option<String>>) -> Self {
+        Self {
+            next,
+            postponed: None,
+            stop,
+            state_timeout: None,
+            event_timeout: None,
+        }
+    }
+
+    /// Keep the current state
+    pub fn keep() -> Self {
+        Self::new(None, None)
+    }
+
+    /// Move to the next state, which is a state change unless it equals the current state
+    pub fn next(state: TState) -> Self {
+        Self::new(Some(state), None)
+    }
+
+    /// Stop the state machine, as with [crate::ActorCell::stop]. Postponed events are
+    /// dropped.
+    ///
+    /// * `reason` - The optional reason the state machine stopped
+    pub fn stop(reason: Option<String>) -> Self {
+        Self::new(None, Some(reason))
+    }
+
+    /// Postpone the event until after the next state change
+    ///
+    /// * `message` - The message of the event, which is handed back to be handled again
+    pub fn postpone(mut self, message: TMsg) -> Self {
+        self.postponed = Some(message);
+        self
+    }
+
+    /// Start a state timeout, replacing the running one, if any
+    ///
+    /// * `timeout` - The [Duration] after which [StateMachineEvent::StateTimeout] is
+    ///   delivered, unless the state changes first
+    pub fn with_state_timeout(mut self, timeout: Duration) -> Self {
+        self.state_timeout = Some(timeout);
+        self
+    }
+
+    /// Start an event timeout
+    ///
+    /// * `timeout` - The [Duration] after which [StateMachineEvent::EventTimeout] is
+    ///   delivered, unless another event arrives first
+    pub fn with_event_timeout(mut self, timeout: Duration) -> Self {
+        self.event_timeout = Some(timeout);
+        self
+    }
+}
+
+/// The [Actor] which runs a [StateMachineActor], see the [module documentation](self)
+#[derive(Debug)]
+pub struct StateMachine<TMachine> {
+    machine: TMachine,
+}
+
+impl<TMachine> StateMachine<TMachine>
+where
+    TMachine: StateMachineActor,
+{
+    /// Wrap a state machine into an actor, which can then be spawned
+    ///
+    /// * `machine` - The [StateMachineActor] to run
+    pub fn new(machine: TMachine) -> Self {
+        Self { machine }
+    }
+
+    /// The state machine which is run
+    pub fn machine(&self) -> &TMachine {
+        &self.machine
+    }
+
+    /// Handle an event, followed by the postponed events it releases
+    async fn process(
+        &self,
+        myself: &ActorRef<StateMachineMessage<TMachine::Msg>>,
+        event: StateMachineEvent<TMachine::Msg>,
+        state: &mut StateMachineState<TMachine>,
+    ) -> Result<(), ActorProcessingErr> {
+        let mut pending = VecDeque::new();
+        let mut next_event = Some(event);
+        while let Some(event) = next_event
+            .take()
+            .or_else(|| pending.pop_front().map(StateMachineEvent::Message))
+        {
+            state.cancel_timeout(TimeoutKind::Event);
+            let transition = state
+                .state
+                .handle_event(&self.machine, myself.clone(), event, &mut state.data)
+                .await?;
+
+            if let Some(message) = transition.postponed {
+                state.postponed.push_back(message);
+            }
+            if let Some(reason) = transition.stop {
+                myself.stop(reason);
+                return Ok(());
+            }
+            if let Some(next) = transition.next {
+                if next != state.state {
+                    tracing::trace!(
+                        "State machine {} moving from {:?} to {:?}",
+                        myself.get_id(),
+                        state.state,
+                        next
+                    );
+                    state.state = next;
+                    state.cancel_timeout(TimeoutKind::State);
+                    self.machine
+                        .on_enter(myself.clone(), &state.state, &mut state.data)
+                        .await?;
+                    // postponed events go ahead of the events released earlier
+                    let mut released = std::mem::take(&mut state.postponed);
+                    released.append(&mut pending);
+                    pending = released;
+                }
+            }
+            if let Some(timeout) = transition.state_timeout {
+                state.start_timeout(myself, TimeoutKind::State, timeout);
+            }
+            if let Some(timeout) = transition.event_timeout {
+                state.start_timeout(myself, TimeoutKind::Event, timeout);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The state of a [StateMachine] actor: the state and data of its machine, along with
+/// the postponed events and running timeouts
+pub struct StateMachineState<TMachine: StateMachineActor> {
+    state: TMachine::State,
+    data: TMachine::Data,
+    postponed: VecDeque<TMachine::Msg>,
+    state_timeout: Option<(u64, TimerRef)>,
+    event_timeout: Option<(u64, TimerRef)>,
+    next_timeout_id: u64,
+}
+
+impl<TMachine: StateMachineActor> Debug for StateMachineState<TMachine> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StateMachineState")
+            .field("state", &self.state)
+            .field("postponed", &self.postponed.len())
+            .finish()
+    }
+}
+
+impl<TMachine: StateMachineActor> StateMachineState<TMachine> {
+    /// The current state of the machine
+    pub fn state(&self) -> &TMachine::State {
+        &self.state
+    }
+
+    /// The data of the machine
+    pub fn data(&self) -> &TMachine::Data {
+        &self.data
+    }
+
+    fn timeout(&mut self, kind: TimeoutKind) -> &mut Option<(u64, TimerRef)> {
+        match kind {
+            TimeoutKind::State => &mut self.state_timeout,
+            TimeoutKind::Event => &mut self.event_timeout,
+        }
+    }
+
+    fn start_timeout(
+        &mut self,
+        myself: &ActorRef<StateMachineMessage<TMachine::Msg>>,
+        kind: TimeoutKind,
+        after: Duration,
+    ) {
+        self.cancel_timeout(kind);
+        let id = self.next_timeout_id;
+        self.next_timeout_id += 1;
+        let timer = TimerService::global().start_timer(after, myself.get_cell(), move || {
+            StateMachineMessage::<TMachine::Msg>::Timeout(StateMachineTimeout { kind, id })
+        });
+        *self.timeout(kind) = Some((id, timer));
+    }
+
+    fn cancel_timeout(&mut self, kind: TimeoutKind) {
+        if let Some((_, timer)) = self.timeout(kind).take() {
+            timer.cancel();
+        }
+    }
+
+    /// Clear the timeout if it's the one which expired, returning whether it was
+    fn expire_timeout(&mut self, timeout: &StateMachineTimeout) -> bool {
+        let running = self.timeout(timeout.kind);
+        if matches!(running, Some((id, _)) if *id == timeout.id) {
+            *running = None;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+impl<TMachine> Actor for StateMachine<TMachine>
+where
+    TMachine: StateMachineActor,
+{
+    type Msg = StateMachineMessage<TMachine::Msg>;
+    type State = StateMachineState<TMachine>;
+    type Arguments = TMachine::Arguments;
+
+    async fn pre_start(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        args: Self::Arguments,
+    ) -> Result<Self::State, ActorProcessingErr> {
+        let (state, mut data) = self.machine.init(myself.clone(), args).await?;
+        self.machine.on_enter(myself, &state, &mut data).await?;
+        Ok(StateMachineState {
+            state,
+            data,
+            postponed: VecDeque::new(),
+            state_timeout: None,
+            event_timeout: None,
+            next_timeout_id: 0,
+        })
+    }
+
+    async fn post_stop(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        state.cancel_timeout(TimeoutKind::State);
+        state.cancel_timeout(TimeoutKind::Event);
+        self.machine
+            .terminate(myself, &state.state, &mut state.data)
+            .await
+    }
+
+    async fn handle(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        message: Self::Msg,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        let event = match message {
+            StateMachineMessage::Event(msg) => StateMachineEvent::Message(msg),
+            StateMachineMessage::Timeout(timeout) => {
+                if !state.expire_timeout(&timeout) {
+                    // cancelled or replaced after it had already been sent
+                    return Ok(());
+                }
+                match timeout.kind {
+                    TimeoutKind::State => StateMachineEvent::StateTimeout,
+                    TimeoutKind::Event => StateMachineEvent::EventTimeout,
+                }
+            }
+        };
+        self.process(&myself, event, state).await
+    }
+
+    async fn handle_supervisor_evt(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        message: SupervisionEvent,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        self.machine
+            .handle_supervisor_evt(myself, message, &state.state, &mut state.data)
+            .await
+    }
+}
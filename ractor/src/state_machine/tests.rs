@@ -0,0 +1,344 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Tests of state machine actors
+
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use super::*;
+use crate::common_test::periodic_check;
+use crate::ActorStatus;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Door {
+    Locked,
+    Open,
+}
+
+// some events are only sent by the tests which run on the testkit, i.e. on tokio
+#[cfg_attr(
+    any(
+        feature = "async-std",
+        all(target_arch = "wasm32", target_os = "unknown")
+    ),
+    allow(dead_code)
+)]
+enum DoorMsg {
+    Unlock(u32),
+    Lock,
+    Push,
+    Idle(Duration),
+    Fail,
+    Stop,
+}
+#[cfg(feature = "cluster")]
+impl Message for DoorMsg {}
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+struct DoorLock {
+    code: u32,
+    open_for: Duration,
+}
+
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+impl StateMachineActor for DoorLock {
+    type Msg = DoorMsg;
+    type State = Door;
+    type Data = Log;
+    type Arguments = Log;
+
+    async fn init(
+        &self,
+        _myself: ActorRef<StateMachineMessage<DoorMsg>>,
+        log: Log,
+    ) -> Result<(Door, Log), ActorProcessingErr> {
+        Ok((Door::Locked, log))
+    }
+
+    async fn on_enter(
+        &self,
+        _myself: ActorRef<StateMachineMessage<DoorMsg>>,
+        state: &Door,
+        log: &mut Log,
+    ) -> Result<(), ActorProcessingErr> {
+        log.lock().unwrap().push(format!("enter {state:?}"));
+        Ok(())
+    }
+
+    async fn terminate(
+        &self,
+        _myself: ActorRef<StateMachineMessage<DoorMsg>>,
+        state: &Door,
+        log: &mut Log,
+    ) -> Result<(), ActorProcessingErr> {
+        log.lock().unwrap().push(format!("terminate {state:?}"));
+        Ok(())
+    }
+}
+
+impl DoorLock {
+    fn locked(&self, event: StateMachineEvent<DoorMsg>) -> Transition<Door, DoorMsg> {
+        match event {
+            StateMachineEvent::Message(DoorMsg::Unlock(code)) if code == self.code => {
+                Transition::next(Door::Open).with_state_timeout(self.open_for)
+            }
+            StateMachineEvent::Message(DoorMsg::Push) => Transition::keep().postpone(DoorMsg::Push),
+            _ => Transition::keep(),
+        }
+    }
+
+    fn open(&self, event: StateMachineEvent<DoorMsg>, log: &Log) -> Transition<Door, DoorMsg> {
+        match event {
+            StateMachineEvent::Message(DoorMsg::Push) => {
+                log.lock().unwrap().push("push".to_string());
+                Transition::keep()
+            }
+            StateMachineEvent::Message(DoorMsg::Lock) => Transition::next(Door::Locked),
+            StateMachineEvent::StateTimeout => {
+                log.lock().unwrap().push("relock".to_string());
+                Transition::next(Door::Locked)
+            }
+            _ => Transition::keep(),
+        }
+    }
+}
+
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+impl StateHandler<DoorLock> for Door {
+    async fn handle_event(
+        &self,
+        machine: &DoorLock,
+        _myself: ActorRef<StateMachineMessage<DoorMsg>>,
+        event: StateMachineEvent<DoorMsg>,
+        log: &mut Log,
+    ) -> Result<Transition<Door, DoorMsg>, ActorProcessingErr> {
+        // events handled the same way in every state
+        let event = match event {
+            StateMachineEvent::Message(DoorMsg::Idle(timeout)) => {
+                return Ok(Transition::keep().with_event_timeout(timeout));
+            }
+            StateMachineEvent::Message(DoorMsg::Fail) => return Err(From::from("boom")),
+            StateMachineEvent::Message(DoorMsg::Stop) => {
+                return Ok(Transition::stop(Some("stopped".to_string())));
+            }
+            StateMachineEvent::EventTimeout => {
+                log.lock().unwrap().push("idle".to_string());
+                return Ok(Transition::keep());
+            }
+            event => event,
+        };
+        Ok(match self {
+            Door::Locked => machine.locked(event),
+            Door::Open => machine.open(event, log),
+        })
+    }
+}
+
+async fn spawn_door(
+    open_for: Duration,
+) -> (
+    ActorRef<StateMachineMessage<DoorMsg>>,
+    crate::concurrency::JoinHandle<()>,
+    Log,
+) {
+    let log = Log::default();
+    let (door, handle) = Actor::spawn(
+        None,
+        StateMachine::new(DoorLock {
+            code: 1234,
+            open_for,
+        }),
+        log.clone(),
+    )
+    .await
+    .expect("Failed to spawn state machine");
+    (door, handle, log)
+}
+
+fn entries(log: &Log) -> Vec<String> {
+    log.lock().unwrap().clone()
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_postponed_events() {
+    let (door, handle, log) = spawn_door(Duration::from_secs(10)).await;
+
+    door.cast(DoorMsg::Push.into()).unwrap();
+    door.cast(DoorMsg::Push.into()).unwrap();
+    // a wrong code doesn't change the state, so the pushes stay postponed
+    door.cast(DoorMsg::Unlock(1).into()).unwrap();
+    door.cast(DoorMsg::Unlock(1234).into()).unwrap();
+    door.cast(DoorMsg::Push.into()).unwrap();
+
+    periodic_check(|| entries(&log).len() == 5, Duration::from_secs(1)).await;
+    assert_eq!(
+        vec!["enter Locked", "enter Open", "push", "push", "push"],
+        entries(&log)
+    );
+
+    door.stop(None);
+    handle.await.unwrap();
+}
+
+#[test]
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+#[tracing_test::traced_test]
+fn test_state_timeout() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        let (door, handle, log) = spawn_door(Duration::from_millis(50)).await;
+
+        door.cast(DoorMsg::Unlock(1234).into()).unwrap();
+        periodic_check(
+            || entries(&log).contains(&"relock".to_string()),
+            Duration::from_secs(1),
+        )
+        .await;
+        assert_eq!(
+            vec!["enter Locked", "enter Open", "relock", "enter Locked"],
+            entries(&log)
+        );
+
+        // changing the state cancels the state timeout
+        door.cast(DoorMsg::Unlock(1234).into()).unwrap();
+        door.cast(DoorMsg::Lock.into()).unwrap();
+        crate::testkit::advance(Duration::from_millis(200)).await;
+        assert_eq!(1, entries(&log).iter().filter(|e| *e == "relock").count());
+        assert_eq!(
+            Some("enter Locked"),
+            entries(&log).last().map(String::as_str)
+        );
+
+        door.stop(None);
+        handle.await.unwrap();
+    });
+}
+
+#[test]
+#[cfg(not(any(
+    feature = "async-std",
+    all(target_arch = "wasm32", target_os = "unknown")
+)))]
+#[tracing_test::traced_test]
+fn test_event_timeout() {
+    crate::testkit::TestRuntime::new().block_on(async {
+        let (door, handle, log) = spawn_door(Duration::from_secs(10)).await;
+
+        // any event cancels the event timeout
+        door.cast(DoorMsg::Idle(Duration::from_millis(50)).into())
+            .unwrap();
+        door.cast(DoorMsg::Lock.into()).unwrap();
+        crate::testkit::advance(Duration::from_millis(200)).await;
+        assert!(!entries(&log).contains(&"idle".to_string()));
+
+        door.cast(DoorMsg::Idle(Duration::from_millis(50)).into())
+            .unwrap();
+        periodic_check(
+            || entries(&log).contains(&"idle".to_string()),
+            Duration::from_secs(1),
+        )
+        .await;
+
+        door.stop(None);
+        handle.await.unwrap();
+    });
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_stop_transition() {
+    let (door, handle, log) = spawn_door(Duration::from_millis(50)).await;
+
+    door.cast(DoorMsg::Unlock(1234).into()).unwrap();
+    door.cast(DoorMsg::Stop.into()).unwrap();
+    handle.await.unwrap();
+
+    assert_eq!(ActorStatus::Stopped, door.get_status());
+    assert_eq!(
+        vec!["enter Locked", "enter Open", "terminate Open"],
+        entries(&log)
+    );
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_state_machine_supervision() {
+    struct Supervisor {
+        failed: Arc<Mutex<bool>>,
+    }
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for Supervisor {
+        type Msg = ();
+        type State = ();
+        type Arguments = ();
+        async fn pre_start(
+            &self,
+            _myself: ActorRef<Self::Msg>,
+            _: (),
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(())
+        }
+        async fn handle_supervisor_evt(
+            &self,
+            _myself: ActorRef<Self::Msg>,
+            event: SupervisionEvent,
+            _state: &mut Self::State,
+        ) -> Result<(), ActorProcessingErr> {
+            if let SupervisionEvent::ActorFailed(..) = event {
+                *self.failed.lock().unwrap() = true;
+            }
+            Ok(())
+        }
+    }
+
+    let failed = Arc::new(Mutex::new(false));
+    let (supervisor, supervisor_handle) = Actor::spawn(
+        None,
+        Supervisor {
+            failed: failed.clone(),
+        },
+        (),
+    )
+    .await
+    .expect("Failed to spawn supervisor");
+    let (door, handle) = Actor::spawn_linked(
+        Some("test_state_machine_supervision".to_string()),
+        StateMachine::new(DoorLock {
+            code: 1234,
+            open_for: Duration::from_secs(10),
+        }),
+        Log::default(),
+        supervisor.get_cell(),
+    )
+    .await
+    .expect("Failed to spawn state machine");
+
+    // the state machine is registered like any other actor
+    let registered = crate::registry::where_is("test_state_machine_supervision".to_string())
+        .expect("The state machine isn't registered");
+    assert_eq!(door.get_id(), registered.get_id());
+
+    door.cast(DoorMsg::Fail.into()).unwrap();
+    handle.await.unwrap();
+    periodic_check(|| *failed.lock().unwrap(), Duration::from_secs(1)).await;
+
+    supervisor.stop(None);
+    supervisor_handle.await.unwrap();
+}
@@ -0,0 +1,508 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Declarative supervision of child actors.
+//!
+//! A [Supervisor] is an actor which starts a static list of children, described by
+//! [ChildSpec]s, and restarts them when they exit according to their [Restart] type
+//! and the supervisor's [SupervisorStrategy]. This removes the need to hand-code
+//! restarts in [Actor::handle_supervisor_evt].
+//!
+//! To protect against restart loops, the supervisor tracks a maximum restart intensity:
+//! if more than `max_restarts` restarts occur within `max_window`, the supervisor stops
+//! all of its children and fails, escalating to its own supervisor.
+//!
+//! Inspired from [Erlang's `supervisor` behaviour](https://www.erlang.org/doc/man/supervisor.html)
+//!
+//! For children which are started on-demand rather than up-front, see
+//! [dynamic::DynamicSupervisor].
+//!
+//! ## Examples
+//!
+//! ```rust
+//! use ractor::supervisor::ChildSpec;
+//! use ractor::supervisor::Restart;
+//! use ractor::supervisor::Supervisor;
+//! use ractor::supervisor::SupervisorArguments;
+//! use ractor::supervisor::SupervisorStrategy;
+//! use ractor::Actor;
+//! use ractor::ActorProcessingErr;
+//! use ractor::ActorRef;
+//!
+//! struct ExampleActor;
+//!
+//! #[cfg_attr(feature = "async-trait", ractor::async_trait)]
+//! impl Actor for ExampleActor {
+//!     type Msg = ();
+//!     type State = ();
+//!     type Arguments = ();
+//!
+//!     async fn pre_start(
+//!         &self,
+//!         _myself: ActorRef<Self::Msg>,
+//!         _args: Self::Arguments,
+//!     ) -> Result<Self::State, ActorProcessingErr> {
+//!         Ok(())
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let args = SupervisorArguments::builder()
+//!         .children(vec![ChildSpec::new(
+//!             "worker",
+//!             Restart::Permanent,
+//!             |supervisor| async move {
+//!                 let (actor, _) =
+//!                     Actor::spawn_linked(None, ExampleActor, (), supervisor).await?;
+//!                 Ok(actor.get_cell())
+//!             },
+//!         )])
+//!         .strategy(SupervisorStrategy::OneForOne)
+//!         .max_restarts(3)
+//!         .build();
+//!
+//!     let (supervisor, handle) = Actor::spawn(None, Supervisor, args)
+//!         .await
+//!         .expect("Failed to start supervisor");
+//!
+//!     supervisor.stop(None);
+//!     handle.await.unwrap();
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::future::Future;
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+
+use crate::concurrency::Duration;
+use crate::concurrency::Instant;
+use crate::Actor;
+use crate::ActorCell;
+use crate::ActorId;
+use crate::ActorProcessingErr;
+use crate::ActorRef;
+use crate::ActorStatus;
+use crate::RpcReplyPort;
+use crate::SpawnErr;
+use crate::SupervisionEvent;
+
+pub mod dynamic;
+
+#[cfg(test)]
+mod tests;
+
+/// The function which spawns a child. It receives the supervisor's [ActorCell],
+/// which the child should be linked to (i.e. via [Actor::spawn_linked])
+pub type ChildSpawnFn =
+    Arc<dyn Fn(ActorCell) -> BoxFuture<'static, Result<ActorCell, SpawnErr>> + Send + Sync>;
+
+/// Controls when a child is restarted after it exits
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Restart {
+    /// The child is always restarted
+    Permanent,
+    /// The child is only restarted if it exits abnormally, meaning it failed
+    /// ([SupervisionEvent::ActorFailed]). A child which stops gracefully
+    /// ([SupervisionEvent::ActorTerminated]) is not restarted.
+    Transient,
+    /// The child is never restarted
+    Temporary,
+}
+
+impl Restart {
+    /// Determine if a child should be restarted, given whether it exited abnormally
+    pub fn should_restart(&self, abnormal: bool) -> bool {
+        match self {
+            Self::Permanent => true,
+            Self::Transient => abnormal,
+            Self::Temporary => false,
+        }
+    }
+}
+
+/// Controls which children are restarted when a child needs a restart
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SupervisorStrategy {
+    /// Only the child which exited is restarted
+    OneForOne,
+    /// All of the children are stopped, and then all restarted
+    OneForAll,
+    /// The child which exited, and all the children started after it, are stopped
+    /// and restarted
+    RestForOne,
+}
+
+/// The specification of a supervised child
+#[derive(Clone)]
+pub struct ChildSpec {
+    /// The identifier of the child, which is stable across restarts
+    pub id: String,
+    /// The restart type of the child
+    pub restart: Restart,
+    /// The function which spawns the child
+    pub spawn_fn: ChildSpawnFn,
+}
+
+impl Debug for ChildSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChildSpec")
+            .field("id", &self.id)
+            .field("restart", &self.restart)
+            .finish()
+    }
+}
+
+impl ChildSpec {
+    /// Create a new [ChildSpec]
+    ///
+    /// * `id` - The identifier of the child within the supervisor
+    /// * `restart` - The [Restart] type of the child
+    /// * `spawn_fn` - A function which spawns the child linked to the provided supervisor,
+    ///   returning the child's [ActorCell]
+    pub fn new<F, Fut>(id: impl Into<String>, restart: Restart, spawn_fn: F) -> Self
+    where
+        F: Fn(ActorCell) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<ActorCell, SpawnErr>> + Send + 'static,
+    {
+        Self {
+            id: id.into(),
+            restart,
+            spawn_fn: Arc::new(move |supervisor| spawn_fn(supervisor).boxed()),
+        }
+    }
+
+    pub(crate) async fn spawn(&self, supervisor: ActorCell) -> Result<ActorCell, SpawnErr> {
+        (self.spawn_fn)(supervisor).await
+    }
+}
+
+/// Tracks restarts within a sliding window, to compute the restart intensity
+#[derive(Debug)]
+pub(crate) struct RestartIntensity {
+    max_restarts: usize,
+    max_window: Duration,
+    restarts: VecDeque<Instant>,
+}
+
+impl RestartIntensity {
+    pub(crate) fn new(max_restarts: usize, max_window: Duration) -> Self {
+        Self {
+            max_restarts,
+            max_window,
+            restarts: VecDeque::new(),
+        }
+    }
+
+    /// Record a restart.
+    ///
+    /// Returns [true] if the restart is allowed, [false] if the maximum restart intensity
+    /// has been exceeded
+    pub(crate) fn record(&mut self) -> bool {
+        let now = Instant::now();
+        while let Some(front) = self.restarts.front() {
+            if now.duration_since(*front) > self.max_window {
+                self.restarts.pop_front();
+            } else {
+                break;
+            }
+        }
+        self.restarts.push_back(now);
+        self.restarts.len() <= self.max_restarts
+    }
+}
+
+/// Arguments for starting a [Supervisor]
+#[derive(bon::Builder, Debug)]
+pub struct SupervisorArguments {
+    /// The children of the supervisor. They're started in order, and stopped
+    /// in reverse order.
+    pub children: Vec<ChildSpec>,
+    /// The restart strategy
+    ///
+    /// Default is [SupervisorStrategy::OneForOne]
+    #[builder(default = SupervisorStrategy::OneForOne)]
+    pub strategy: SupervisorStrategy,
+    /// The maximum number of restarts allowed within `max_window` before the supervisor
+    /// gives up and fails
+    ///
+    /// Default is `1`
+    #[builder(default = 1)]
+    pub max_restarts: usize,
+    /// The window over which restarts are counted
+    ///
+    /// Default is 5 seconds
+    #[builder(default = Duration::from_secs(5))]
+    pub max_window: Duration,
+}
+
+/// Messages supported by the [Supervisor]
+#[derive(Debug)]
+pub enum SupervisorMessage {
+    /// Retrieve the id and [ActorCell] of each currently running child, in start order
+    WhichChildren(RpcReplyPort<Vec<(String, ActorCell)>>),
+}
+
+#[cfg(feature = "cluster")]
+impl crate::Message for SupervisorMessage {}
+
+/// A supervised child, and its running instance (if any)
+#[derive(Debug)]
+struct Child {
+    spec: ChildSpec,
+    cell: Option<ActorCell>,
+}
+
+/// The state of a [Supervisor]
+#[derive(Debug)]
+pub struct SupervisorState {
+    children: Vec<Child>,
+    strategy: SupervisorStrategy,
+    intensity: RestartIntensity,
+}
+
+/// A supervisor actor, which starts and restarts its children according to a
+/// [SupervisorStrategy]. See the [module-level documentation](self) for details.
+#[derive(Debug, Default)]
+pub struct Supervisor;
+
+/// Stop a child gracefully, waiting for it to exit
+pub(crate) async fn stop_child(cell: &ActorCell) {
+    if cell.get_status() == ActorStatus::Stopped {
+        return;
+    }
+    let _ = cell
+        .stop_and_wait(Some("supervisor_stop".to_string()), None)
+        .await;
+}
+
+/// Wait for an exited child to finish its cleanup, so that things like its
+/// name registration are released before it's restarted
+pub(crate) async fn wait_for_exit(cell: &ActorCell) {
+    if cell.get_status() != ActorStatus::Stopped {
+        let _ = cell.wait(None).await;
+    }
+}
+
+impl SupervisorState {
+    fn find_child(&self, id: ActorId) -> Option<usize> {
+        self.children
+            .iter()
+            .position(|child| child.cell.as_ref().map(|c| c.get_id()) == Some(id))
+    }
+
+    /// Stop all the running children, in reverse start order
+    async fn stop_all_children(&mut self) {
+        for child in self.children.iter_mut().rev() {
+            if let Some(cell) = child.cell.take() {
+                stop_child(&cell).await;
+            }
+        }
+    }
+
+    /// Record a restart against the restart intensity. If the maximum intensity is exceeded,
+    /// all the children are stopped and an error is returned, failing the supervisor.
+    async fn record_restart(&mut self, myself: &ActorCell) -> Result<(), ActorProcessingErr> {
+        if self.intensity.record() {
+            return Ok(());
+        }
+        self.stop_all_children().await;
+        Err(From::from(format!(
+            "Supervisor {:?} reached its maximum restart intensity",
+            myself.get_id()
+        )))
+    }
+
+    /// Spawn the child at `index`, retrying spawn failures while within the
+    /// restart intensity
+    async fn restart_child(
+        &mut self,
+        myself: &ActorCell,
+        index: usize,
+    ) -> Result<(), ActorProcessingErr> {
+        loop {
+            let child = &mut self.children[index];
+            match child.spec.spawn(myself.clone()).await {
+                Ok(cell) => {
+                    tracing::debug!("Supervisor restarted child '{}'", child.spec.id);
+                    myself.inner.stats.child_restarted(myself, &child.spec.id);
+                    child.cell = Some(cell);
+                    return Ok(());
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        "Supervisor failed to restart child '{}': {err}",
+                        child.spec.id
+                    );
+                }
+            }
+            // a failed restart counts towards the restart intensity
+            self.record_restart(myself).await?;
+        }
+    }
+
+    async fn handle_child_exit(
+        &mut self,
+        myself: &ActorCell,
+        cell: ActorCell,
+        abnormal: bool,
+    ) -> Result<(), ActorProcessingErr> {
+        // an exit from a child we don't know about (i.e. one we stopped ourselves as part
+        // of a restart) is ignored
+        let Some(index) = self.find_child(cell.get_id()) else {
+            return Ok(());
+        };
+        self.children[index].cell = None;
+
+        if crate::shutdown::take_shutting_down(&cell) {
+            tracing::debug!(
+                "Supervisor child '{}' exited during shutdown and won't be restarted",
+                self.children[index].spec.id
+            );
+            self.children.remove(index);
+            return Ok(());
+        }
+        if !self.children[index].spec.restart.should_restart(abnormal) {
+            tracing::debug!(
+                "Supervisor child '{}' exited and won't be restarted",
+                self.children[index].spec.id
+            );
+            self.children.remove(index);
+            return Ok(());
+        }
+        self.record_restart(myself).await?;
+
+        // the children which need to be stopped, and restarted, alongside the exited child
+        let affected = match self.strategy {
+            SupervisorStrategy::OneForOne => index..index + 1,
+            SupervisorStrategy::OneForAll => 0..self.children.len(),
+            SupervisorStrategy::RestForOne => index..self.children.len(),
+        };
+        for sibling in self.children[affected.clone()].iter_mut().rev() {
+            if let Some(sibling) = sibling.cell.take() {
+                stop_child(&sibling).await;
+            }
+        }
+        wait_for_exit(&cell).await;
+
+        // temporary siblings which were stopped are not restarted
+        let mut restart = vec![];
+        let mut retained = Vec::with_capacity(self.children.len());
+        for (i, child) in std::mem::take(&mut self.children).into_iter().enumerate() {
+            if affected.contains(&i) && i != index && child.spec.restart == Restart::Temporary {
+                continue;
+            }
+            if affected.contains(&i) {
+                restart.push(retained.len());
+            }
+            retained.push(child);
+        }
+        self.children = retained;
+
+        for index in restart {
+            self.restart_child(myself, index).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+impl Actor for Supervisor {
+    type Msg = SupervisorMessage;
+    type State = SupervisorState;
+    type Arguments = SupervisorArguments;
+
+    async fn pre_start(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        args: Self::Arguments,
+    ) -> Result<Self::State, ActorProcessingErr> {
+        let mut children: Vec<Child> = Vec::with_capacity(args.children.len());
+        for spec in args.children {
+            match spec.spawn(myself.get_cell()).await {
+                Ok(cell) => children.push(Child {
+                    spec,
+                    cell: Some(cell),
+                }),
+                Err(err) => {
+                    // startup failed, so tear down what we've started so far
+                    for child in children.iter().rev() {
+                        if let Some(cell) = &child.cell {
+                            stop_child(cell).await;
+                        }
+                    }
+                    return Err(From::from(format!(
+                        "Supervisor failed to start child '{}': {err}",
+                        spec.id
+                    )));
+                }
+            }
+        }
+        Ok(SupervisorState {
+            children,
+            strategy: args.strategy,
+            intensity: RestartIntensity::new(args.max_restarts, args.max_window),
+        })
+    }
+
+    async fn post_stop(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        state.stop_all_children().await;
+        Ok(())
+    }
+
+    async fn handle(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        message: Self::Msg,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        match message {
+            SupervisorMessage::WhichChildren(reply) => {
+                let children = state
+                    .children
+                    .iter()
+                    .filter_map(|child| {
+                        child
+                            .cell
+                            .as_ref()
+                            .map(|cell| (child.spec.id.clone(), cell.clone()))
+                    })
+                    .collect();
+                let _ = reply.send(children);
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_supervisor_evt(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        message: SupervisionEvent,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        match message {
+            SupervisionEvent::ActorTerminated(cell, _, _) => {
+                state
+                    .handle_child_exit(&myself.get_cell(), cell, false)
+                    .await
+            }
+            SupervisionEvent::ActorFailed(cell, err) => {
+                tracing::warn!("Supervisor child {:?} failed: {err}", cell.get_id());
+                state
+                    .handle_child_exit(&myself.get_cell(), cell, true)
+                    .await
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,489 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! A supervisor for children which are started on-demand.
+//!
+//! A [DynamicSupervisor] starts with no children. Children are added at runtime with
+//! [DynamicSupervisor::start_child] and removed with [DynamicSupervisor::terminate_child].
+//! Every child is supervised independently (i.e. one-for-one), and restarted according to
+//! its [Restart] type while within the supervisor's maximum restart intensity.
+//!
+//! Children must be linked to the supervisor by their spawn function (i.e. with
+//! [Actor::spawn_linked]) which enrolls them in the supervisor's supervision tree, so
+//! that their exits are reported back to it.
+//!
+//! Inspired from [Elixir's `DynamicSupervisor`](https://hexdocs.pm/elixir/DynamicSupervisor.html)
+//!
+//! ## Examples
+//!
+//! ```rust
+//! use ractor::supervisor::dynamic::DynamicSupervisor;
+//! use ractor::supervisor::dynamic::DynamicSupervisorArguments;
+//! use ractor::supervisor::ChildSpec;
+//! use ractor::supervisor::Restart;
+//! use ractor::Actor;
+//! use ractor::ActorProcessingErr;
+//! use ractor::ActorRef;
+//!
+//! struct Session;
+//!
+//! #[cfg_attr(feature = "async-trait", ractor::async_trait)]
+//! impl Actor for Session {
+//!     type Msg = ();
+//!     type State = ();
+//!     type Arguments = ();
+//!
+//!     async fn pre_start(
+//!         &self,
+//!         _myself: ActorRef<Self::Msg>,
+//!         _args: Self::Arguments,
+//!     ) -> Result<Self::State, ActorProcessingErr> {
+//!         Ok(())
+//!     }
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let args = DynamicSupervisorArguments::builder()
+//!         .max_children(100)
+//!         .build();
+//!     let (supervisor, handle) = Actor::spawn(None, DynamicSupervisor, args)
+//!         .await
+//!         .expect("Failed to start supervisor");
+//!
+//!     let spec = ChildSpec::new("session-1", Restart::Transient, |supervisor| async move {
+//!         let (actor, _) = Actor::spawn_linked(None, Session, (), supervisor).await?;
+//!         Ok(actor.get_cell())
+//!     });
+//!     DynamicSupervisor::start_child(&supervisor, spec)
+//!         .await
+//!         .expect("Failed to start child");
+//!     assert_eq!(
+//!         1,
+//!         DynamicSupervisor::count_children(&supervisor)
+//!             .await
+//!             .unwrap()
+//!             .active
+//!     );
+//!
+//!     supervisor.stop(None);
+//!     handle.await.unwrap();
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use super::stop_child;
+use super::wait_for_exit;
+use super::ChildSpec;
+use super::Restart;
+use super::RestartIntensity;
+use crate::concurrency::Duration;
+use crate::Actor;
+use crate::ActorCell;
+use crate::ActorId;
+use crate::ActorProcessingErr;
+use crate::ActorRef;
+use crate::RactorErr;
+use crate::RpcReplyPort;
+use crate::SpawnErr;
+use crate::SupervisionEvent;
+
+#[cfg(test)]
+mod tests;
+
+/// Errors from the [DynamicSupervisor]'s operations
+#[derive(Debug)]
+pub enum DynamicSupervisorErr {
+    /// The supervisor already has its maximum number of children
+    MaxChildren(usize),
+    /// A child with the provided id is already supervised
+    AlreadyStarted(String),
+    /// No child with the provided id is supervised
+    NotFound(String),
+    /// The child failed to start
+    Spawn(SpawnErr),
+    /// The request to the supervisor failed
+    Rpc(RactorErr<()>),
+}
+
+impl std::error::Error for DynamicSupervisorErr {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self {
+            Self::Spawn(inner) => Some(inner),
+            Self::Rpc(inner) => Some(inner),
+            _ => None,
+        }
+    }
+}
+
+impl Display for DynamicSupervisorErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MaxChildren(max) => {
+                write!(f, "The supervisor is at its maximum of {max} children")
+            }
+            Self::AlreadyStarted(id) => write!(f, "Child '{id}' is already started"),
+            Self::NotFound(id) => write!(f, "Child '{id}' was not found"),
+            Self::Spawn(err) => write!(f, "Child failed to start: {err}"),
+            Self::Rpc(err) => write!(f, "Failed to reach the supervisor: {err}"),
+        }
+    }
+}
+
+impl From<SpawnErr> for DynamicSupervisorErr {
+    fn from(value: SpawnErr) -> Self {
+        Self::Spawn(value)
+    }
+}
+
+impl<T> From<RactorErr<T>> for DynamicSupervisorErr {
+    fn from(value: RactorErr<T>) -> Self {
+        Self::Rpc(value.map(|_| ()))
+    }
+}
+
+/// Information about a running child of a [DynamicSupervisor]
+#[derive(Debug, Clone)]
+pub struct DynamicChildInfo {
+    /// The child's id
+    pub id: String,
+    /// The child's current instance
+    pub cell: ActorCell,
+    /// The child's restart type
+    pub restart: Restart,
+    /// The number of times the child has been restarted
+    pub restarts: usize,
+}
+
+/// Child counts of a [DynamicSupervisor]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ChildCount {
+    /// The number of supervised children
+    pub specs: usize,
+    /// The number of supervised children which are currently alive
+    pub active: usize,
+}
+
+/// Arguments for starting a [DynamicSupervisor]
+#[derive(bon::Builder, Debug)]
+pub struct DynamicSupervisorArguments {
+    /// The maximum number of children the supervisor allows. Starting a child past
+    /// this limit fails with [DynamicSupervisorErr::MaxChildren]
+    ///
+    /// Default is [None], meaning unlimited
+    pub max_children: Option<usize>,
+    /// The maximum number of restarts allowed within `max_window` before the supervisor
+    /// gives up and fails
+    ///
+    /// Default is `1`
+    #[builder(default = 1)]
+    pub max_restarts: usize,
+    /// The window over which restarts are counted
+    ///
+    /// Default is 5 seconds
+    #[builder(default = Duration::from_secs(5))]
+    pub max_window: Duration,
+}
+
+/// Messages supported by the [DynamicSupervisor]
+#[derive(Debug)]
+pub enum DynamicSupervisorMessage {
+    /// Start a new child
+    StartChild(
+        ChildSpec,
+        RpcReplyPort<Result<ActorCell, DynamicSupervisorErr>>,
+    ),
+    /// Stop a child, without restarting it
+    TerminateChild(String, RpcReplyPort<Result<(), DynamicSupervisorErr>>),
+    /// Retrieve the currently running children
+    WhichChildren(RpcReplyPort<Vec<DynamicChildInfo>>),
+    /// Retrieve the child counts
+    CountChildren(RpcReplyPort<ChildCount>),
+}
+
+#[cfg(feature = "cluster")]
+impl crate::Message for DynamicSupervisorMessage {}
+
+#[derive(Debug)]
+struct DynamicChild {
+    spec: ChildSpec,
+    cell: Option<ActorCell>,
+    restarts: usize,
+}
+
+/// The state of a [DynamicSupervisor]
+#[derive(Debug)]
+pub struct DynamicSupervisorState {
+    children: HashMap<String, DynamicChild>,
+    /// Index of the running children's actor ids to their child id
+    ids: HashMap<ActorId, String>,
+    max_children: Option<usize>,
+    intensity: RestartIntensity,
+}
+
+impl DynamicSupervisorState {
+    async fn start_child(
+        &mut self,
+        myself: &ActorCell,
+        spec: ChildSpec,
+    ) -> Result<ActorCell, DynamicSupervisorErr> {
+        if let Some(max) = self.max_children {
+            if self.children.len() >= max {
+                return Err(DynamicSupervisorErr::MaxChildren(max));
+            }
+        }
+        if self.children.contains_key(&spec.id) {
+            return Err(DynamicSupervisorErr::AlreadyStarted(spec.id));
+        }
+        let cell = spec.spawn(myself.clone()).await?;
+        self.ids.insert(cell.get_id(), spec.id.clone());
+        self.children.insert(
+            spec.id.clone(),
+            DynamicChild {
+                spec,
+                cell: Some(cell.clone()),
+                restarts: 0,
+            },
+        );
+        Ok(cell)
+    }
+
+    async fn terminate_child(&mut self, id: &str) -> Result<(), DynamicSupervisorErr> {
+        let child = self
+            .children
+            .remove(id)
+            .ok_or_else(|| DynamicSupervisorErr::NotFound(id.to_string()))?;
+        if let Some(cell) = child.cell {
+            // the exit notification for this child will be ignored, as it's no longer indexed
+            self.ids.remove(&cell.get_id());
+            stop_child(&cell).await;
+        }
+        Ok(())
+    }
+
+    async fn stop_all_children(&mut self) {
+        self.ids.clear();
+        for (_, child) in self.children.drain() {
+            if let Some(cell) = child.cell {
+                stop_child(&cell).await;
+            }
+        }
+    }
+
+    /// Record a restart against the restart intensity. If the maximum intensity is exceeded,
+    /// all the children are stopped and an error is returned, failing the supervisor.
+    async fn record_restart(&mut self, myself: &ActorCell) -> Result<(), ActorProcessingErr> {
+        if self.intensity.record() {
+            return Ok(());
+        }
+        self.stop_all_children().await;
+        Err(From::from(format!(
+            "DynamicSupervisor {:?} reached its maximum restart intensity",
+            myself.get_id()
+        )))
+    }
+
+    async fn handle_child_exit(
+        &mut self,
+        myself: &ActorCell,
+        cell: ActorCell,
+        abnormal: bool,
+    ) -> Result<(), ActorProcessingErr> {
+        let Some(id) = self.ids.remove(&cell.get_id()) else {
+            return Ok(());
+        };
+        let Some(child) = self.children.get_mut(&id) else {
+            return Ok(());
+        };
+        child.cell = None;
+        if crate::shutdown::take_shutting_down(&cell) {
+            tracing::debug!(
+                "DynamicSupervisor child '{id}' exited during shutdown and won't be restarted"
+            );
+            self.children.remove(&id);
+            return Ok(());
+        }
+        if !child.spec.restart.should_restart(abnormal) {
+            tracing::debug!("DynamicSupervisor child '{id}' exited and won't be restarted");
+            self.children.remove(&id);
+            return Ok(());
+        }
+
+        self.record_restart(myself).await?;
+        wait_for_exit(&cell).await;
+        loop {
+            let Some(child) = self.children.get_mut(&id) else {
+                return Ok(());
+            };
+            match child.spec.spawn(myself.clone()).await {
+                Ok(new_cell) => {
+                    tracing::debug!("DynamicSupervisor restarted child '{id}'");
+                    child.restarts += 1;
+                    myself.inner.stats.child_restarted(myself, &id);
+                    self.ids.insert(new_cell.get_id(), id);
+                    child.cell = Some(new_cell);
+                    return Ok(());
+                }
+                Err(err) => {
+                    tracing::warn!("DynamicSupervisor failed to restart child '{id}': {err}");
+                }
+            }
+            // a failed restart counts towards the restart intensity
+            self.record_restart(myself).await?;
+        }
+    }
+}
+
+/// A supervisor for children started on-demand. See the [module-level documentation](self)
+/// for details.
+#[derive(Debug, Default)]
+pub struct DynamicSupervisor;
+
+impl DynamicSupervisor {
+    /// Start a new child under the supervisor
+    ///
+    /// * `supervisor` - The [DynamicSupervisor]
+    /// * `spec` - The [ChildSpec] of the child. Its id must be unique within the supervisor
+    ///
+    /// Returns the [ActorCell] of the started child
+    pub async fn start_child(
+        supervisor: &ActorRef<DynamicSupervisorMessage>,
+        spec: ChildSpec,
+    ) -> Result<ActorCell, DynamicSupervisorErr> {
+        crate::call!(supervisor, DynamicSupervisorMessage::StartChild, spec)?
+    }
+
+    /// Stop a child of the supervisor, without restarting it
+    ///
+    /// * `supervisor` - The [DynamicSupervisor]
+    /// * `id` - The id of the child to stop
+    pub async fn terminate_child(
+        supervisor: &ActorRef<DynamicSupervisorMessage>,
+        id: impl Into<String>,
+    ) -> Result<(), DynamicSupervisorErr> {
+        crate::call!(
+            supervisor,
+            DynamicSupervisorMessage::TerminateChild,
+            id.into()
+        )?
+    }
+
+    /// Retrieve information about the running children of the supervisor, in no particular order
+    ///
+    /// * `supervisor` - The [DynamicSupervisor]
+    pub async fn which_children(
+        supervisor: &ActorRef<DynamicSupervisorMessage>,
+    ) -> Result<Vec<DynamicChildInfo>, DynamicSupervisorErr> {
+        Ok(crate::call!(
+            supervisor,
+            DynamicSupervisorMessage::WhichChildren
+        )?)
+    }
+
+    /// Retrieve the child counts of the supervisor
+    ///
+    /// * `supervisor` - The [DynamicSupervisor]
+    pub async fn count_children(
+        supervisor: &ActorRef<DynamicSupervisorMessage>,
+    ) -> Result<ChildCount, DynamicSupervisorErr> {
+        Ok(crate::call!(
+            supervisor,
+            DynamicSupervisorMessage::CountChildren
+        )?)
+    }
+}
+
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+impl Actor for DynamicSupervisor {
+    type Msg = DynamicSupervisorMessage;
+    type State = DynamicSupervisorState;
+    type Arguments = DynamicSupervisorArguments;
+
+    async fn pre_start(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        args: Self::Arguments,
+    ) -> Result<Self::State, ActorProcessingErr> {
+        Ok(DynamicSupervisorState {
+            children: HashMap::new(),
+            ids: HashMap::new(),
+            max_children: args.max_children,
+            intensity: RestartIntensity::new(args.max_restarts, args.max_window),
+        })
+    }
+
+    async fn post_stop(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        state.stop_all_children().await;
+        Ok(())
+    }
+
+    async fn handle(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        message: Self::Msg,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        match message {
+            DynamicSupervisorMessage::StartChild(spec, reply) => {
+                let result = state.start_child(&myself.get_cell(), spec).await;
+                let _ = reply.send(result);
+            }
+            DynamicSupervisorMessage::TerminateChild(id, reply) => {
+                let result = state.terminate_child(&id).await;
+                let _ = reply.send(result);
+            }
+            DynamicSupervisorMessage::WhichChildren(reply) => {
+                let children = state
+                    .children
+                    .values()
+                    .filter_map(|child| {
+                        child.cell.as_ref().map(|cell| DynamicChildInfo {
+                            id: child.spec.id.clone(),
+                            cell: cell.clone(),
+                            restart: child.spec.restart,
+                            restarts: child.restarts,
+                        })
+                    })
+                    .collect();
+                let _ = reply.send(children);
+            }
+            DynamicSupervisorMessage::CountChildren(reply) => {
+                let _ = reply.send(ChildCount {
+                    specs: state.children.len(),
+                    active: state.ids.len(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_supervisor_evt(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        message: SupervisionEvent,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        match message {
+            SupervisionEvent::ActorTerminated(cell, _, _) => {
+                state
+                    .handle_child_exit(&myself.get_cell(), cell, false)
+                    .await
+            }
+            SupervisionEvent::ActorFailed(cell, err) => {
+                tracing::warn!("DynamicSupervisor child {:?} failed: {err}", cell.get_id());
+                state
+                    .handle_child_exit(&myself.get_cell(), cell, true)
+                    .await
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,286 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Tests for the dynamic supervisor
+
+use super::*;
+use crate::common_test::periodic_async_check;
+use crate::concurrency::Duration;
+use crate::ActorStatus;
+
+enum ChildMessage {
+    Fail,
+    Stop,
+}
+
+#[cfg(feature = "cluster")]
+impl crate::Message for ChildMessage {}
+
+struct TestChild;
+
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+impl Actor for TestChild {
+    type Msg = ChildMessage;
+    type State = ();
+    type Arguments = ();
+
+    async fn pre_start(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        _args: Self::Arguments,
+    ) -> Result<Self::State, ActorProcessingErr> {
+        Ok(())
+    }
+
+    async fn handle(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        message: Self::Msg,
+        _state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        match message {
+            ChildMessage::Fail => Err(From::from("boom")),
+            ChildMessage::Stop => {
+                myself.stop(None);
+                Ok(())
+            }
+        }
+    }
+}
+
+fn test_child(id: &str, restart: Restart) -> ChildSpec {
+    ChildSpec::new(id, restart, |supervisor| async move {
+        let (actor, _) = Actor::spawn_linked(None, TestChild, (), supervisor).await?;
+        Ok(actor.get_cell())
+    })
+}
+
+async fn spawn_supervisor(
+    args: DynamicSupervisorArguments,
+) -> (
+    ActorRef<DynamicSupervisorMessage>,
+    crate::concurrency::JoinHandle<()>,
+) {
+    Actor::spawn(None, DynamicSupervisor, args)
+        .await
+        .expect("Failed to start supervisor")
+}
+
+async fn get_child(
+    supervisor: &ActorRef<DynamicSupervisorMessage>,
+    id: &str,
+) -> Option<DynamicChildInfo> {
+    DynamicSupervisor::which_children(supervisor)
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|child| child.id == id)
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_start_and_terminate_children() {
+    let (supervisor, handle) =
+        spawn_supervisor(DynamicSupervisorArguments::builder().build()).await;
+    assert_eq!(
+        ChildCount {
+            specs: 0,
+            active: 0
+        },
+        DynamicSupervisor::count_children(&supervisor)
+            .await
+            .unwrap()
+    );
+
+    let a = DynamicSupervisor::start_child(&supervisor, test_child("a", Restart::Permanent))
+        .await
+        .expect("Failed to start child");
+    let b = DynamicSupervisor::start_child(&supervisor, test_child("b", Restart::Permanent))
+        .await
+        .expect("Failed to start child");
+    assert_eq!(
+        ChildCount {
+            specs: 2,
+            active: 2
+        },
+        DynamicSupervisor::count_children(&supervisor)
+            .await
+            .unwrap()
+    );
+    // the children are enrolled in the supervisor's supervision tree
+    assert_eq!(2, supervisor.get_children().len());
+
+    let err = DynamicSupervisor::start_child(&supervisor, test_child("a", Restart::Permanent))
+        .await
+        .expect_err("Duplicate child ids should be rejected");
+    assert!(matches!(err, DynamicSupervisorErr::AlreadyStarted(id) if id == "a"));
+
+    DynamicSupervisor::terminate_child(&supervisor, "a")
+        .await
+        .expect("Failed to terminate child");
+    assert_eq!(ActorStatus::Stopped, a.get_status());
+    let children = DynamicSupervisor::which_children(&supervisor)
+        .await
+        .unwrap();
+    assert_eq!(1, children.len());
+    assert_eq!(b.get_id(), children[0].cell.get_id());
+
+    let err = DynamicSupervisor::terminate_child(&supervisor, "a")
+        .await
+        .expect_err("Child was already terminated");
+    assert!(matches!(err, DynamicSupervisorErr::NotFound(_)));
+
+    supervisor.stop(None);
+    handle.await.unwrap();
+    assert_eq!(ActorStatus::Stopped, b.get_status());
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_max_children() {
+    let (supervisor, handle) = spawn_supervisor(
+        DynamicSupervisorArguments::builder()
+            .max_children(2)
+            .build(),
+    )
+    .await;
+
+    for id in ["a", "b"] {
+        DynamicSupervisor::start_child(&supervisor, test_child(id, Restart::Permanent))
+            .await
+            .expect("Failed to start child");
+    }
+    let err = DynamicSupervisor::start_child(&supervisor, test_child("c", Restart::Permanent))
+        .await
+        .expect_err("Supervisor should be full");
+    assert!(matches!(err, DynamicSupervisorErr::MaxChildren(2)));
+
+    // terminating a child frees up room
+    DynamicSupervisor::terminate_child(&supervisor, "a")
+        .await
+        .unwrap();
+    DynamicSupervisor::start_child(&supervisor, test_child("c", Restart::Permanent))
+        .await
+        .expect("Failed to start child");
+
+    supervisor.stop(None);
+    handle.await.unwrap();
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_restarts_are_tracked() {
+    let (supervisor, handle) = spawn_supervisor(
+        DynamicSupervisorArguments::builder()
+            .max_restarts(10)
+            .build(),
+    )
+    .await;
+
+    DynamicSupervisor::start_child(&supervisor, test_child("permanent", Restart::Permanent))
+        .await
+        .unwrap();
+    DynamicSupervisor::start_child(&supervisor, test_child("transient", Restart::Transient))
+        .await
+        .unwrap();
+    DynamicSupervisor::start_child(&supervisor, test_child("temporary", Restart::Temporary))
+        .await
+        .unwrap();
+
+    for _ in 0..2 {
+        let child = get_child(&supervisor, "permanent").await.unwrap();
+        let actor: ActorRef<ChildMessage> = child.cell.clone().into();
+        actor.send_message(ChildMessage::Fail).unwrap();
+        let sup = supervisor.clone();
+        periodic_async_check(
+            || {
+                let sup = sup.clone();
+                let restarts = child.restarts;
+                async move {
+                    get_child(&sup, "permanent")
+                        .await
+                        .is_some_and(|c| c.restarts == restarts + 1)
+                }
+            },
+            Duration::from_secs(1),
+        )
+        .await;
+    }
+
+    // a transient child which stops normally is removed
+    let transient: ActorRef<ChildMessage> = get_child(&supervisor, "transient")
+        .await
+        .unwrap()
+        .cell
+        .into();
+    transient.send_message(ChildMessage::Stop).unwrap();
+    // a temporary child which fails is removed
+    let temporary: ActorRef<ChildMessage> = get_child(&supervisor, "temporary")
+        .await
+        .unwrap()
+        .cell
+        .into();
+    temporary.send_message(ChildMessage::Fail).unwrap();
+
+    let sup = supervisor.clone();
+    periodic_async_check(
+        || {
+            let sup = sup.clone();
+            async move {
+                DynamicSupervisor::count_children(&sup).await.unwrap()
+                    == ChildCount {
+                        specs: 1,
+                        active: 1,
+                    }
+            }
+        },
+        Duration::from_secs(1),
+    )
+    .await;
+    assert_eq!(
+        2,
+        get_child(&supervisor, "permanent").await.unwrap().restarts
+    );
+
+    supervisor.stop(None);
+    handle.await.unwrap();
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_max_restart_intensity_fails_supervisor() {
+    let (supervisor, handle) = spawn_supervisor(
+        DynamicSupervisorArguments::builder()
+            .max_restarts(0)
+            .build(),
+    )
+    .await;
+
+    let a = DynamicSupervisor::start_child(&supervisor, test_child("a", Restart::Permanent))
+        .await
+        .unwrap();
+    let b = DynamicSupervisor::start_child(&supervisor, test_child("b", Restart::Permanent))
+        .await
+        .unwrap();
+
+    let a: ActorRef<ChildMessage> = a.into();
+    a.send_message(ChildMessage::Fail).unwrap();
+    handle.await.unwrap();
+
+    assert_eq!(ActorStatus::Stopped, supervisor.get_status());
+    assert_eq!(ActorStatus::Stopped, b.get_status());
+}
@@ -0,0 +1,397 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Tests for the declarative supervisor
+
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use super::*;
+use crate::common_test::periodic_async_check;
+use crate::common_test::periodic_check;
+use crate::concurrency::Duration;
+
+enum ChildMessage {
+    Fail,
+    Stop,
+}
+
+#[cfg(feature = "cluster")]
+impl crate::Message for ChildMessage {}
+
+struct TestChild;
+
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+impl Actor for TestChild {
+    type Msg = ChildMessage;
+    type State = ();
+    type Arguments = ();
+
+    async fn pre_start(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        _args: Self::Arguments,
+    ) -> Result<Self::State, ActorProcessingErr> {
+        Ok(())
+    }
+
+    async fn handle(
+        &self,
+        myself: ActorRef<Self::Msg>,
+        message: Self::Msg,
+        _state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        match message {
+            ChildMessage::Fail => Err(From::from("boom")),
+            ChildMessage::Stop => {
+                myself.stop(None);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Build a [ChildSpec] for a [TestChild], counting the number of times it's started
+fn test_child(id: &str, restart: Restart, starts: Arc<AtomicU32>) -> ChildSpec {
+    ChildSpec::new(id, restart, move |supervisor| {
+        let starts = starts.clone();
+        async move {
+            let (actor, _) = Actor::spawn_linked(None, TestChild, (), supervisor).await?;
+            starts.fetch_add(1, Ordering::SeqCst);
+            Ok(actor.get_cell())
+        }
+    })
+}
+
+async fn which_children(supervisor: &ActorRef<SupervisorMessage>) -> Vec<(String, ActorCell)> {
+    crate::call!(supervisor, SupervisorMessage::WhichChildren)
+        .expect("Failed to query supervisor children")
+}
+
+async fn get_child(supervisor: &ActorRef<SupervisorMessage>, id: &str) -> ActorRef<ChildMessage> {
+    which_children(supervisor)
+        .await
+        .into_iter()
+        .find(|(child_id, _)| child_id == id)
+        .map(|(_, cell)| cell.into())
+        .expect("Child not found")
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_one_for_one_restarts_only_failed_child() {
+    let a_starts = Arc::new(AtomicU32::new(0));
+    let b_starts = Arc::new(AtomicU32::new(0));
+    let args = SupervisorArguments::builder()
+        .children(vec![
+            test_child("a", Restart::Permanent, a_starts.clone()),
+            test_child("b", Restart::Permanent, b_starts.clone()),
+        ])
+        .strategy(SupervisorStrategy::OneForOne)
+        .max_restarts(5)
+        .build();
+    let (supervisor, handle) = Actor::spawn(None, Supervisor, args)
+        .await
+        .expect("Failed to start supervisor");
+
+    let a = get_child(&supervisor, "a").await;
+    let b = get_child(&supervisor, "b").await;
+    a.send_message(ChildMessage::Fail).unwrap();
+
+    periodic_check(
+        || a_starts.load(Ordering::SeqCst) == 2,
+        Duration::from_secs(1),
+    )
+    .await;
+    assert_eq!(1, b_starts.load(Ordering::SeqCst));
+    assert_eq!(ActorStatus::Running, b.get_status());
+
+    let children = which_children(&supervisor).await;
+    assert_eq!(
+        vec!["a".to_string(), "b".to_string()],
+        children
+            .iter()
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>()
+    );
+    assert_ne!(a.get_id(), children[0].1.get_id());
+    assert_eq!(1, supervisor.get_stats().restarts);
+
+    supervisor.stop(None);
+    handle.await.unwrap();
+    // children are stopped alongside the supervisor
+    assert_eq!(ActorStatus::Stopped, children[0].1.get_status());
+    assert_eq!(ActorStatus::Stopped, b.get_status());
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_one_for_all_restarts_all_children() {
+    let a_starts = Arc::new(AtomicU32::new(0));
+    let b_starts = Arc::new(AtomicU32::new(0));
+    let c_starts = Arc::new(AtomicU32::new(0));
+    let args = SupervisorArguments::builder()
+        .children(vec![
+            test_child("a", Restart::Permanent, a_starts.clone()),
+            test_child("b", Restart::Permanent, b_starts.clone()),
+            test_child("c", Restart::Permanent, c_starts.clone()),
+        ])
+        .strategy(SupervisorStrategy::OneForAll)
+        .max_restarts(5)
+        .build();
+    let (supervisor, handle) = Actor::spawn(None, Supervisor, args)
+        .await
+        .expect("Failed to start supervisor");
+
+    let a = get_child(&supervisor, "a").await;
+    let b = get_child(&supervisor, "b").await;
+    b.send_message(ChildMessage::Fail).unwrap();
+
+    periodic_check(
+        || {
+            a_starts.load(Ordering::SeqCst) == 2
+                && b_starts.load(Ordering::SeqCst) == 2
+                && c_starts.load(Ordering::SeqCst) == 2
+        },
+        Duration::from_secs(1),
+    )
+    .await;
+    assert_eq!(ActorStatus::Stopped, a.get_status());
+    assert_eq!(3, which_children(&supervisor).await.len());
+
+    supervisor.stop(None);
+    handle.await.unwrap();
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_rest_for_one_restarts_later_children() {
+    let a_starts = Arc::new(AtomicU32::new(0));
+    let b_starts = Arc::new(AtomicU32::new(0));
+    let c_starts = Arc::new(AtomicU32::new(0));
+    let args = SupervisorArguments::builder()
+        .children(vec![
+            test_child("a", Restart::Permanent, a_starts.clone()),
+            test_child("b", Restart::Permanent, b_starts.clone()),
+            test_child("c", Restart::Permanent, c_starts.clone()),
+        ])
+        .strategy(SupervisorStrategy::RestForOne)
+        .max_restarts(5)
+        .build();
+    let (supervisor, handle) = Actor::spawn(None, Supervisor, args)
+        .await
+        .expect("Failed to start supervisor");
+
+    let b = get_child(&supervisor, "b").await;
+    b.send_message(ChildMessage::Fail).unwrap();
+
+    periodic_check(
+        || b_starts.load(Ordering::SeqCst) == 2 && c_starts.load(Ordering::SeqCst) == 2,
+        Duration::from_secs(1),
+    )
+    .await;
+    assert_eq!(1, a_starts.load(Ordering::SeqCst));
+
+    // start order is preserved across restarts
+    let children = which_children(&supervisor).await;
+    assert_eq!(
+        vec!["a".to_string(), "b".to_string(), "c".to_string()],
+        children
+            .iter()
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>()
+    );
+
+    supervisor.stop(None);
+    handle.await.unwrap();
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_restart_types() {
+    let transient_starts = Arc::new(AtomicU32::new(0));
+    let temporary_starts = Arc::new(AtomicU32::new(0));
+    let args = SupervisorArguments::builder()
+        .children(vec![
+            test_child("transient", Restart::Transient, transient_starts.clone()),
+            test_child("temporary", Restart::Temporary, temporary_starts.clone()),
+        ])
+        .max_restarts(5)
+        .build();
+    let (supervisor, handle) = Actor::spawn(None, Supervisor, args)
+        .await
+        .expect("Failed to start supervisor");
+
+    // a transient child is restarted after failing
+    let transient = get_child(&supervisor, "transient").await;
+    transient.send_message(ChildMessage::Fail).unwrap();
+    periodic_check(
+        || transient_starts.load(Ordering::SeqCst) == 2,
+        Duration::from_secs(1),
+    )
+    .await;
+
+    // but not after stopping normally
+    let transient = get_child(&supervisor, "transient").await;
+    transient.send_message(ChildMessage::Stop).unwrap();
+    let sup = supervisor.clone();
+    periodic_async_check(
+        || {
+            let sup = sup.clone();
+            async move {
+                !which_children(&sup)
+                    .await
+                    .iter()
+                    .any(|(id, _)| id == "transient")
+            }
+        },
+        Duration::from_secs(1),
+    )
+    .await;
+
+    // a temporary child is never restarted
+    let temporary = get_child(&supervisor, "temporary").await;
+    temporary.send_message(ChildMessage::Fail).unwrap();
+    let sup = supervisor.clone();
+    periodic_async_check(
+        || {
+            let sup = sup.clone();
+            async move { which_children(&sup).await.is_empty() }
+        },
+        Duration::from_secs(1),
+    )
+    .await;
+    assert_eq!(2, transient_starts.load(Ordering::SeqCst));
+    assert_eq!(1, temporary_starts.load(Ordering::SeqCst));
+
+    supervisor.stop(None);
+    handle.await.unwrap();
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_max_restart_intensity_escalates() {
+    struct Parent {
+        failures: Arc<AtomicU32>,
+    }
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for Parent {
+        type Msg = ();
+        type State = ();
+        type Arguments = ();
+
+        async fn pre_start(
+            &self,
+            _myself: ActorRef<Self::Msg>,
+            _args: Self::Arguments,
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(())
+        }
+
+        async fn handle_supervisor_evt(
+            &self,
+            _myself: ActorRef<Self::Msg>,
+            message: SupervisionEvent,
+            _state: &mut Self::State,
+        ) -> Result<(), ActorProcessingErr> {
+            if let SupervisionEvent::ActorFailed(_, _) = message {
+                self.failures.fetch_add(1, Ordering::SeqCst);
+            }
+            Ok(())
+        }
+    }
+
+    let failures = Arc::new(AtomicU32::new(0));
+    let (parent, parent_handle) = Actor::spawn(
+        None,
+        Parent {
+            failures: failures.clone(),
+        },
+        (),
+    )
+    .await
+    .expect("Failed to start parent");
+
+    let a_starts = Arc::new(AtomicU32::new(0));
+    let b_starts = Arc::new(AtomicU32::new(0));
+    let args = SupervisorArguments::builder()
+        .children(vec![
+            test_child("a", Restart::Permanent, a_starts.clone()),
+            test_child("b", Restart::Permanent, b_starts.clone()),
+        ])
+        .max_restarts(1)
+        .max_window(Duration::from_secs(10))
+        .build();
+    let (supervisor, handle) = Supervisor::spawn_linked(None, Supervisor, args, parent.get_cell())
+        .await
+        .expect("Failed to start supervisor");
+
+    // the first failure is within the intensity
+    get_child(&supervisor, "a")
+        .await
+        .send_message(ChildMessage::Fail)
+        .unwrap();
+    periodic_check(
+        || a_starts.load(Ordering::SeqCst) == 2,
+        Duration::from_secs(1),
+    )
+    .await;
+    let b = get_child(&supervisor, "b").await;
+
+    // the second exceeds it, failing the supervisor
+    get_child(&supervisor, "a")
+        .await
+        .send_message(ChildMessage::Fail)
+        .unwrap();
+    handle.await.unwrap();
+
+    periodic_check(
+        || failures.load(Ordering::SeqCst) == 1,
+        Duration::from_secs(1),
+    )
+    .await;
+    assert_eq!(ActorStatus::Stopped, b.get_status());
+    assert_eq!(2, a_starts.load(Ordering::SeqCst));
+
+    parent.stop(None);
+    parent_handle.await.unwrap();
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_supervisor_startup_failure() {
+    let a_starts = Arc::new(AtomicU32::new(0));
+    let args = SupervisorArguments::builder()
+        .children(vec![
+            test_child("a", Restart::Permanent, a_starts.clone()),
+            ChildSpec::new("b", Restart::Permanent, |_supervisor| async move {
+                Err(SpawnErr::StartupFailed(From::from("nope")))
+            }),
+        ])
+        .build();
+    let result = Actor::spawn(None, Supervisor, args).await;
+    assert!(matches!(result, Err(SpawnErr::StartupFailed(_))));
+    assert_eq!(1, a_starts.load(Ordering::SeqCst));
+}
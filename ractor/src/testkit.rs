@@ -0,0 +1,157 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Deterministic testing of actor systems, with virtual time. Requires the `testkit` feature.
+//!
+//! A [TestRuntime] runs actors on a single-threaded executor whose clock is paused, so
+//! tests don't depend on real time passing:
+//!
+//! * Time only moves when the test [advance]s it, or when every task is idle and waiting
+//!   on a timer, in which case the clock jumps straight to the next timer. A `sleep` of an
+//!   hour (or an actor's `send_after` timer) completes instantly, and in order.
+//! * Tasks are scheduled in a reproducible order. The library's randomized choices (e.g.
+//!   [crate::pg::DispatchStrategy::Random] and schedule jitter) are drawn from a generator
+//!   seeded by the runtime's seed. When built with `--cfg tokio_unstable` the seed is
+//!   also passed to tokio's own random number generator.
+//!
+//! A [Recorder] actor captures the messages it receives, to assert on them with
+//! [Recorder::assert_received]. A [TestProbe] additionally lets a test await the messages
+//! and supervision events it receives, see [probe].
+//!
+//! ## Example
+//!
+//! ```rust
+//! use ractor::concurrency::Duration;
+//! use ractor::testkit::Recorder;
+//! use ractor::testkit::TestRuntime;
+//!
+//! TestRuntime::with_seed(42).block_on(async {
+//!     let (actor, recorder) = Recorder::<u32>::spawn().await.expect("Failed to spawn");
+//!     actor.send_after(Duration::from_secs(3600), || 1);
+//!
+//!     ractor::testkit::advance(Duration::from_secs(3599)).await;
+//!     assert!(recorder.is_empty());
+//!     ractor::testkit::advance(Duration::from_secs(1)).await;
+//!     recorder.assert_received(&[1]);
+//! });
+//! ```
+
+use std::cell::Cell;
+use std::future::Future;
+
+use crate::concurrency::Duration;
+use crate::concurrency::Instant;
+
+pub mod probe;
+mod recorder;
+
+pub use probe::TestProbe;
+pub use recorder::Recorder;
+
+#[cfg(test)]
+mod tests;
+
+/// How many rounds of scheduling [yield_rounds] lets the other tasks run for
+const YIELD_ROUNDS: usize = 64;
+
+thread_local! {
+    /// The state of the seeded generator of the [TestRuntime] running on this thread
+    static SEEDED_RANDOM: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// Draw from the seeded generator of the [TestRuntime] running on this thread, if any
+pub(crate) fn seeded_random_u64() -> Option<u64> {
+    SEEDED_RANDOM.with(|state| {
+        let current = state.get()?;
+        // https://prng.di.unimi.it/splitmix64.c
+        let next = current.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        state.set(Some(next));
+        let mut z = next;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        Some(z ^ (z >> 31))
+    })
+}
+
+/// A single-threaded runtime with a paused clock, see the [module documentation](self)
+pub struct TestRuntime {
+    runtime: tokio::runtime::Runtime,
+    seed: u64,
+}
+
+impl std::fmt::Debug for TestRuntime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TestRuntime")
+            .field("seed", &self.seed)
+            .finish()
+    }
+}
+
+impl Default for TestRuntime {
+    fn default() -> Self {
+        Self::with_seed(0)
+    }
+}
+
+impl TestRuntime {
+    /// Create a new runtime with a seed of 0
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new runtime with the provided seed
+    ///
+    /// Panics if the runtime can't be created
+    pub fn with_seed(seed: u64) -> Self {
+        let mut builder = tokio::runtime::Builder::new_current_thread();
+        builder.enable_time().start_paused(true);
+        #[cfg(tokio_unstable)]
+        builder.rng_seed(tokio::runtime::RngSeed::from_bytes(&seed.to_le_bytes()));
+        let runtime = builder.build().expect("Failed to build the test runtime");
+        Self { runtime, seed }
+    }
+
+    /// The seed of the runtime
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Run a future to completion on the runtime, along with any actors and tasks it spawns
+    ///
+    /// Every call restarts the seeded generator from the runtime's seed.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        let previous = SEEDED_RANDOM.with(|state| state.replace(Some(self.seed)));
+        let output = self.runtime.block_on(future);
+        SEEDED_RANDOM.with(|state| state.set(previous));
+        output
+    }
+}
+
+/// The current (virtual) time of the [TestRuntime]
+pub fn now() -> Instant {
+    Instant::now()
+}
+
+/// Advance the (virtual) time of the [TestRuntime], firing the timers which come due in
+/// order, and then [yield_rounds]
+///
+/// * `duration` - The [Duration] to advance the clock by
+pub async fn advance(duration: Duration) {
+    crate::concurrency::sleep(duration).await;
+    yield_rounds().await;
+}
+
+/// Let the other tasks of the [TestRuntime] run for a fixed number of scheduling rounds
+/// (64), without advancing time, so that the messages sent so far get handled
+///
+/// Every round runs each task which is ready, so a chain of up to 64 messages (or other
+/// wake-ups) passed between actors settles within a call. It doesn't wait for the
+/// runtime to be idle: a longer chain, or a task which never stops being ready (e.g. it
+/// busy-loops), may still have work left when it returns.
+pub async fn yield_rounds() {
+    for _ in 0..YIELD_ROUNDS {
+        tokio::task::yield_now().await;
+    }
+}
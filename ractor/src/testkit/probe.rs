@@ -0,0 +1,253 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Test probes, actors whose received messages and supervision events can be awaited
+//! and asserted on from a test.
+//!
+//! A [TestProbe] dereferences to its [ActorRef], so it can be handed to the actor under
+//! test wherever it expects an actor to reply or forward to. To observe the supervision
+//! events of an actor, link it to the probe with [TestProbe::supervise] (or spawn it with
+//! the probe as its supervisor), or with the `monitors` feature, monitor it with
+//! [TestProbe::monitor].
+//!
+//! The `expect_*` methods panic when the expectation isn't met, so they read as assertions.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use ractor::concurrency::Duration;
+//! use ractor::testkit::TestProbe;
+//! use ractor::testkit::TestRuntime;
+//!
+//! TestRuntime::new().block_on(async {
+//!     let mut probe = TestProbe::<u32>::spawn().await.expect("Failed to spawn");
+//!     probe.send_after(Duration::from_secs(1), || 42);
+//!
+//!     probe.expect_no_msg(Duration::from_millis(900)).await;
+//!     assert_eq!(42, probe.expect_msg(Duration::from_millis(200)).await);
+//! });
+//! ```
+
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use crate::concurrency::mpsc_unbounded;
+use crate::concurrency::Duration;
+use crate::concurrency::MpscUnboundedReceiver;
+use crate::concurrency::MpscUnboundedSender;
+use crate::Actor;
+use crate::ActorCell;
+use crate::ActorProcessingErr;
+use crate::ActorRef;
+use crate::Message;
+use crate::SpawnErr;
+use crate::SupervisionEvent;
+
+/// A probe actor, see the [module documentation](self)
+pub struct TestProbe<TMessage> {
+    actor: ActorRef<TMessage>,
+    messages: MpscUnboundedReceiver<TMessage>,
+    events: MpscUnboundedReceiver<SupervisionEvent>,
+}
+
+impl<TMessage> Debug for TestProbe<TMessage> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TestProbe")
+            .field("actor", &self.actor.get_id())
+            .finish()
+    }
+}
+
+impl<TMessage> std::ops::Deref for TestProbe<TMessage> {
+    type Target = ActorRef<TMessage>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.actor
+    }
+}
+
+struct ProbeActor<TMessage> {
+    _message: PhantomData<fn() -> TMessage>,
+}
+
+struct ProbeState<TMessage> {
+    messages: MpscUnboundedSender<TMessage>,
+    events: MpscUnboundedSender<SupervisionEvent>,
+}
+
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+impl<TMessage> Actor for ProbeActor<TMessage>
+where
+    TMessage: Message,
+{
+    type Msg = TMessage;
+    type State = ProbeState<TMessage>;
+    type Arguments = ProbeState<TMessage>;
+
+    async fn pre_start(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        state: ProbeState<TMessage>,
+    ) -> Result<Self::State, ActorProcessingErr> {
+        Ok(state)
+    }
+
+    async fn handle(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        message: Self::Msg,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        // the probe may have been dropped by the test, in which case nobody's listening
+        let _ = state.messages.send(message);
+        Ok(())
+    }
+
+    async fn handle_supervisor_evt(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        event: SupervisionEvent,
+        state: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        // unlike the default, the probe keeps running when a child exits
+        let _ = state.events.send(event);
+        Ok(())
+    }
+}
+
+impl<TMessage> TestProbe<TMessage>
+where
+    TMessage: Message,
+{
+    /// Spawn a new probe actor
+    pub async fn spawn() -> Result<Self, SpawnErr> {
+        let (messages_tx, messages) = mpsc_unbounded();
+        let (events_tx, events) = mpsc_unbounded();
+        let state = ProbeState {
+            messages: messages_tx,
+            events: events_tx,
+        };
+        let (actor, _) = Actor::spawn(
+            None,
+            ProbeActor {
+                _message: PhantomData,
+            },
+            state,
+        )
+        .await?;
+        Ok(Self {
+            actor,
+            messages,
+            events,
+        })
+    }
+
+    /// The [ActorRef] of the probe
+    pub fn actor(&self) -> &ActorRef<TMessage> {
+        &self.actor
+    }
+
+    /// Link the actor to the probe as its supervisor, to receive its [SupervisionEvent]s
+    pub fn supervise(&self, actor: &ActorCell) {
+        actor.link(self.actor.get_cell());
+    }
+
+    /// Monitor the actor, to receive copies of its [SupervisionEvent]s
+    #[cfg(feature = "monitors")]
+    pub fn monitor(&self, actor: &ActorCell) {
+        self.actor.monitor(actor.clone());
+    }
+
+    /// Wait for the next message received by the probe
+    ///
+    /// Panics if no message is received within the timeout
+    pub async fn expect_msg(&mut self, timeout: Duration) -> TMessage {
+        match crate::concurrency::timeout(timeout, self.messages.recv()).await {
+            Ok(Some(message)) => message,
+            _ => panic!("The probe didn't receive a message within {timeout:?}"),
+        }
+    }
+
+    /// Wait for the timeout, asserting that the probe receives no message meanwhile
+    ///
+    /// Panics if a message is received
+    pub async fn expect_no_msg(&mut self, timeout: Duration)
+    where
+        TMessage: Debug,
+    {
+        if let Ok(Some(message)) = crate::concurrency::timeout(timeout, self.messages.recv()).await
+        {
+            panic!("The probe received an unexpected message {message:?}");
+        }
+    }
+
+    /// Wait for a message received by the probe which matches the predicate, discarding
+    /// the messages which don't
+    ///
+    /// Panics if no matching message is received within the timeout
+    pub async fn fish_for_msg<F>(&mut self, timeout: Duration, mut predicate: F) -> TMessage
+    where
+        F: FnMut(&TMessage) -> bool,
+    {
+        let fishing = async {
+            while let Some(message) = self.messages.recv().await {
+                if predicate(&message) {
+                    return Some(message);
+                }
+            }
+            None
+        };
+        match crate::concurrency::timeout(timeout, fishing).await {
+            Ok(Some(message)) => message,
+            _ => panic!("The probe didn't receive a matching message within {timeout:?}"),
+        }
+    }
+
+    /// Wait for the next [SupervisionEvent] received by the probe
+    ///
+    /// Panics if no event is received within the timeout
+    pub async fn expect_event(&mut self, timeout: Duration) -> SupervisionEvent {
+        match crate::concurrency::timeout(timeout, self.events.recv()).await {
+            Ok(Some(event)) => event,
+            _ => panic!("The probe didn't receive a supervision event within {timeout:?}"),
+        }
+    }
+
+    /// Wait for the timeout, asserting that the probe receives no [SupervisionEvent]
+    /// meanwhile
+    ///
+    /// Panics if an event is received
+    pub async fn expect_no_event(&mut self, timeout: Duration) {
+        if let Ok(Some(event)) = crate::concurrency::timeout(timeout, self.events.recv()).await {
+            panic!("The probe received an unexpected supervision event {event}");
+        }
+    }
+
+    /// Wait for a [SupervisionEvent] received by the probe which matches the predicate,
+    /// discarding the events which don't
+    ///
+    /// Panics if no matching event is received within the timeout
+    pub async fn fish_for_event<F>(
+        &mut self,
+        timeout: Duration,
+        mut predicate: F,
+    ) -> SupervisionEvent
+    where
+        F: FnMut(&SupervisionEvent) -> bool,
+    {
+        let fishing = async {
+            while let Some(event) = self.events.recv().await {
+                if predicate(&event) {
+                    return Some(event);
+                }
+            }
+            None
+        };
+        match crate::concurrency::timeout(timeout, fishing).await {
+            Ok(Some(event)) => event,
+            _ => panic!("The probe didn't receive a matching supervision event within {timeout:?}"),
+        }
+    }
+}
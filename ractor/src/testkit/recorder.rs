@@ -0,0 +1,157 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! An actor which records the messages it receives
+
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use crate::Actor;
+use crate::ActorProcessingErr;
+use crate::ActorRef;
+use crate::Message;
+use crate::SpawnErr;
+
+/// The messages received by a recording actor, in the order they were received
+///
+/// Cloning the recorder is cheap, and clones share the same messages.
+pub struct Recorder<TMessage> {
+    messages: Arc<Mutex<Vec<TMessage>>>,
+}
+
+impl<TMessage> Clone for Recorder<TMessage> {
+    fn clone(&self) -> Self {
+        Self {
+            messages: self.messages.clone(),
+        }
+    }
+}
+
+impl<TMessage> Debug for Recorder<TMessage> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Recorder")
+            .field("len", &self.messages.lock().unwrap().len())
+            .finish()
+    }
+}
+
+struct RecorderActor<TMessage> {
+    _message: PhantomData<fn() -> TMessage>,
+}
+
+#[cfg_attr(feature = "async-trait", crate::async_trait)]
+impl<TMessage> Actor for RecorderActor<TMessage>
+where
+    TMessage: Message,
+{
+    type Msg = TMessage;
+    type State = Recorder<TMessage>;
+    type Arguments = Recorder<TMessage>;
+
+    async fn pre_start(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        recorder: Recorder<TMessage>,
+    ) -> Result<Self::State, ActorProcessingErr> {
+        Ok(recorder)
+    }
+
+    async fn handle(
+        &self,
+        _myself: ActorRef<Self::Msg>,
+        message: Self::Msg,
+        recorder: &mut Self::State,
+    ) -> Result<(), ActorProcessingErr> {
+        recorder.messages.lock().unwrap().push(message);
+        Ok(())
+    }
+}
+
+impl<TMessage> Recorder<TMessage>
+where
+    TMessage: Message,
+{
+    /// Spawn an actor which records the messages it receives
+    ///
+    /// Returns the [ActorRef] of the recording actor, and the [Recorder] of its messages
+    pub async fn spawn() -> Result<(ActorRef<TMessage>, Self), SpawnErr> {
+        let recorder = Self {
+            messages: Arc::new(Mutex::new(vec![])),
+        };
+        let (actor, _) = Actor::spawn(
+            None,
+            RecorderActor {
+                _message: PhantomData,
+            },
+            recorder.clone(),
+        )
+        .await?;
+        Ok((actor, recorder))
+    }
+
+    /// The number of messages received
+    pub fn len(&self) -> usize {
+        self.messages.lock().unwrap().len()
+    }
+
+    /// Whether no messages were received
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Take the messages received so far, clearing the recorder
+    pub fn take(&self) -> Vec<TMessage> {
+        std::mem::take(&mut *self.messages.lock().unwrap())
+    }
+
+    /// Whether any received message matches the predicate
+    pub fn any<F>(&self, predicate: F) -> bool
+    where
+        F: Fn(&TMessage) -> bool,
+    {
+        self.messages.lock().unwrap().iter().any(predicate)
+    }
+
+    /// A copy of the messages received so far
+    pub fn received(&self) -> Vec<TMessage>
+    where
+        TMessage: Clone,
+    {
+        self.messages.lock().unwrap().clone()
+    }
+
+    /// Assert that exactly the expected messages were received, in order
+    ///
+    /// Panics with the received messages otherwise
+    #[track_caller]
+    pub fn assert_received(&self, expected: &[TMessage])
+    where
+        TMessage: PartialEq + Debug,
+    {
+        let messages = self.messages.lock().unwrap();
+        assert_eq!(
+            expected,
+            &messages[..],
+            "The recorder didn't receive the expected messages"
+        );
+    }
+
+    /// Assert that a received message matches the predicate
+    ///
+    /// Panics with the number of received messages otherwise
+    #[track_caller]
+    pub fn assert_received_matching<F>(&self, predicate: F)
+    where
+        F: Fn(&TMessage) -> bool,
+    {
+        assert!(
+            self.any(predicate),
+            "None of the {} messages received by the recorder matched",
+            self.len()
+        );
+    }
+}
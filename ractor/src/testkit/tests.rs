@@ -0,0 +1,213 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Tests of the testkit
+
+use super::*;
+use crate::Actor;
+use crate::ActorProcessingErr;
+use crate::ActorRef;
+use crate::SupervisionEvent;
+
+#[test]
+fn test_virtual_time() {
+    TestRuntime::new().block_on(async {
+        let (actor, recorder) = Recorder::<u32>::spawn()
+            .await
+            .expect("Failed to spawn recorder");
+        let start = now();
+
+        actor.send_after(Duration::from_secs(3600), || 2);
+        actor.send_after(Duration::from_secs(60), || 1);
+        actor.send_interval(Duration::from_secs(600), || 0);
+
+        advance(Duration::from_secs(59)).await;
+        assert!(recorder.is_empty());
+        advance(Duration::from_secs(1)).await;
+        recorder.assert_received(&[1]);
+
+        // the interval ticks at 10m, 20m, ..., 60m and the last timer fires at 60m
+        advance(Duration::from_secs(3540)).await;
+        assert_eq!(Duration::from_secs(3600), now() - start);
+        assert_eq!(8, recorder.len());
+        assert_eq!(6, recorder.received().iter().filter(|m| **m == 0).count());
+        recorder.assert_received_matching(|m| *m == 2);
+
+        // sleeping jumps straight to the deadline
+        crate::concurrency::sleep(Duration::from_secs(86_400)).await;
+        assert_eq!(Duration::from_secs(90_000), now() - start);
+
+        actor.stop(None);
+    });
+}
+
+#[test]
+fn test_yield_rounds() {
+    struct Forwarder;
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for Forwarder {
+        type Msg = u32;
+        type State = ActorRef<u32>;
+        type Arguments = ActorRef<u32>;
+        async fn pre_start(
+            &self,
+            _this_actor: ActorRef<Self::Msg>,
+            next: ActorRef<u32>,
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(next)
+        }
+        async fn handle(
+            &self,
+            _this_actor: ActorRef<Self::Msg>,
+            message: Self::Msg,
+            next: &mut Self::State,
+        ) -> Result<(), ActorProcessingErr> {
+            next.cast(message + 1)?;
+            Ok(())
+        }
+    }
+
+    TestRuntime::new().block_on(async {
+        let (last, recorder) = Recorder::<u32>::spawn()
+            .await
+            .expect("Failed to spawn recorder");
+        let mut next = last.clone();
+        for _ in 0..5 {
+            let (forwarder, _) = Actor::spawn(None, Forwarder, next)
+                .await
+                .expect("Failed to spawn forwarder");
+            next = forwarder;
+        }
+        let start = now();
+
+        next.cast(0).expect("Failed to send message");
+        yield_rounds().await;
+        recorder.assert_received(&[5]);
+        assert_eq!(start, now());
+    });
+}
+
+#[test]
+fn test_seeded_random() {
+    let draw = |seed: u64| {
+        TestRuntime::with_seed(seed).block_on(async {
+            (0..8)
+                .map(|_| crate::concurrency::random_u64())
+                .collect::<Vec<_>>()
+        })
+    };
+
+    assert_eq!(draw(7), draw(7));
+    assert_ne!(draw(7), draw(8));
+    // the generator is only seeded within the runtime
+    assert_eq!(None, seeded_random_u64());
+}
+
+#[test]
+fn test_probe_messages() {
+    TestRuntime::new().block_on(async {
+        let mut probe = TestProbe::<u32>::spawn()
+            .await
+            .expect("Failed to spawn probe");
+
+        probe.cast(1).expect("Failed to send message");
+        assert_eq!(1, probe.expect_msg(Duration::from_millis(10)).await);
+        probe.expect_no_msg(Duration::from_secs(1)).await;
+
+        for i in 2..6 {
+            probe.cast(i).expect("Failed to send message");
+        }
+        assert_eq!(
+            4,
+            probe
+                .fish_for_msg(Duration::from_millis(10), |m| *m % 4 == 0)
+                .await
+        );
+        // the messages before the match were discarded
+        assert_eq!(5, probe.expect_msg(Duration::from_millis(10)).await);
+
+        probe.stop(None);
+    });
+}
+
+#[test]
+#[should_panic(expected = "unexpected message 3")]
+fn test_probe_unexpected_message() {
+    TestRuntime::new().block_on(async {
+        let mut probe = TestProbe::<u32>::spawn()
+            .await
+            .expect("Failed to spawn probe");
+        probe.send_after(Duration::from_millis(500), || 3);
+        probe.expect_no_msg(Duration::from_secs(1)).await;
+    });
+}
+
+#[test]
+fn test_probe_supervision_events() {
+    struct Child;
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for Child {
+        type Msg = ();
+        type State = ();
+        type Arguments = ();
+        async fn pre_start(
+            &self,
+            _this_actor: ActorRef<Self::Msg>,
+            _: (),
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(())
+        }
+        async fn handle(
+            &self,
+            _this_actor: ActorRef<Self::Msg>,
+            _message: Self::Msg,
+            _state: &mut Self::State,
+        ) -> Result<(), ActorProcessingErr> {
+            Err(From::from("boom"))
+        }
+    }
+
+    TestRuntime::new().block_on(async {
+        let mut probe = TestProbe::<()>::spawn()
+            .await
+            .expect("Failed to spawn probe");
+
+        let (linked, _) = Actor::spawn_linked(None, Child, (), probe.get_cell())
+            .await
+            .expect("Failed to spawn child");
+        let (unlinked, _) = Actor::spawn(None, Child, ())
+            .await
+            .expect("Failed to spawn child");
+        probe.supervise(&unlinked.get_cell());
+
+        linked.cast(()).expect("Failed to send message");
+        let event = probe
+            .fish_for_event(Duration::from_millis(10), |event| {
+                matches!(event, SupervisionEvent::ActorFailed(..))
+            })
+            .await;
+        assert!(
+            matches!(event, SupervisionEvent::ActorFailed(who, _) if who.get_id() == linked.get_id())
+        );
+
+        unlinked.stop(None);
+        let event = probe
+            .fish_for_event(Duration::from_millis(10), |event| {
+                matches!(event, SupervisionEvent::ActorTerminated(..))
+            })
+            .await;
+        assert!(matches!(
+            event,
+            SupervisionEvent::ActorTerminated(who, _, _) if who.get_id() == unlinked.get_id()
+        ));
+        probe.expect_no_event(Duration::from_secs(1)).await;
+
+        // the probe outlives its children
+        assert_eq!(crate::ActorStatus::Running, probe.get_status());
+        probe.stop(None);
+    });
+}
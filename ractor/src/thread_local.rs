@@ -17,6 +17,7 @@ use crate::ActorCell;
 use crate::ActorName;
 use crate::ActorProcessingErr;
 use crate::ActorRef;
+use crate::MailboxOverflowPolicy;
 use crate::Message;
 use crate::RpcReplyPort;
 use crate::SpawnErr;
@@ -119,6 +120,18 @@ pub trait ThreadLocalActor: Default + Sized + 'static {
     /// NOT need to be [Send] and neither does the actor instance.
     type Arguments: State;
 
+    /// The maximum number of messages which can be waiting in this actor's mailbox.
+    /// See [SendActor::MAILBOX_CAPACITY]
+    const MAILBOX_CAPACITY: Option<usize> = None;
+
+    /// The policy applied when a message is sent to this actor while its bounded mailbox
+    /// is full. See [SendActor::MAILBOX_OVERFLOW_POLICY]
+    const MAILBOX_OVERFLOW_POLICY: MailboxOverflowPolicy = MailboxOverflowPolicy::Reject;
+
+    /// The maximum number of messages this actor can hold in its stash.
+    /// See [SendActor::STASH_CAPACITY]
+    const STASH_CAPACITY: usize = 1000;
+
     /// Invoked when an actor is being started by the system.
     ///
     /// Any initialization inherent to the actor's role should be
@@ -22,7 +22,9 @@ use crate::actor::actor_cell::ActorPortSet;
 use crate::actor::actor_properties::ActorProperties;
 use crate::actor::actor_properties::MuxedMessage;
 use crate::actor::get_panic_string;
+use crate::actor::mailbox::BoundedMailbox;
 use crate::actor::messages::StopMessage;
+use crate::actor::stash::MessageStash;
 use crate::actor::ActorLoopResult;
 use crate::concurrency as mpsc;
 use crate::concurrency::JoinHandle;
@@ -65,6 +67,9 @@ impl ActorCell {
             crate::registry::register(r_name, cell.clone())?;
         }
 
+        let mailbox = cell.inner.mailbox.clone();
+        let stash = cell.inner.stash.clone();
+        let stats = cell.inner.stats.clone();
         Ok((
             cell,
             ActorPortSet {
@@ -72,6 +77,10 @@ impl ActorCell {
                 stop_rx: rx2,
                 supervisor_rx: rx3,
                 message_rx: rx4,
+                mailbox,
+                stash,
+                stats,
+                deferred: None,
             },
         ))
     }
@@ -85,7 +94,7 @@ impl ActorProperties {
         OneshotReceiver<Signal>,
         OneshotReceiver<StopMessage>,
         mpsc::MpscUnboundedReceiver<SupervisionEvent>,
-        mpsc::MpscUnboundedReceiver<MuxedMessage>,
+        crate::actor::priority::PriorityLaneReceivers,
     )
     where
         TActor: crate::thread_local::ThreadLocalActor,
@@ -94,7 +103,7 @@ impl ActorProperties {
         let (tx_signal, rx_signal) = mpsc::oneshot();
         let (tx_stop, rx_stop) = mpsc::oneshot();
         let (tx_supervision, rx_supervision) = mpsc::mpsc_unbounded();
-        let (tx_message, rx_message) = mpsc::mpsc_unbounded();
+        let (tx_message, rx_message) = crate::actor::priority::priority_lanes();
         (
             Self {
                 id,
@@ -107,8 +116,20 @@ impl ActorProperties {
                 message: tx_message,
                 tree: Default::default(),
                 type_id: std::any::TypeId::of::<TActor::Msg>(),
+                type_name: std::any::type_name::<TActor>(),
                 #[cfg(feature = "cluster")]
                 supports_remoting: TActor::Msg::serializable(),
+                mailbox: TActor::MAILBOX_CAPACITY.map(|capacity| {
+                    Arc::new(BoundedMailbox::new(
+                        capacity,
+                        TActor::MAILBOX_OVERFLOW_POLICY,
+                    ))
+                }),
+                stash: Arc::new(MessageStash::new(TActor::STASH_CAPACITY)),
+                stats: Default::default(),
+                timers: Default::default(),
+                #[cfg(feature = "cluster")]
+                durable_mailbox: Default::default(),
             },
             rx_signal,
             rx_stop,
@@ -413,6 +434,7 @@ impl<TActor: ThreadLocalActor> ThreadLocalActorRuntime<TActor> {
                     Ok(ActorLoopResult::stop(exit_reason))
                 }
                 actor_cell::ActorPortMessage::Supervision(supervision) => {
+                    ports.stats.supervision_event(&myself, &supervision);
                     let future = Self::handle_supervision_message(
                         myself.clone(),
                         state,
@@ -428,8 +450,22 @@ impl<TActor: ThreadLocalActor> ThreadLocalActorRuntime<TActor> {
                     }
                 }
                 actor_cell::ActorPortMessage::Message(MuxedMessage::Message(msg)) => {
+                    if msg.is_expired() {
+                        // the caller has given up on this request, don't bother handling it
+                        tracing::debug!(
+                            "Actor {:?} dropped a request which passed its deadline",
+                            myself.get_id()
+                        );
+                        ports.stats.message_expired(&myself);
+                        return Ok(ActorLoopResult::ok());
+                    }
+                    let start = crate::concurrency::Instant::now();
                     let future = Self::handle_message(myself.clone(), state, handler, msg);
-                    match ports.run_with_signal(future).await {
+                    let result = ports.run_with_signal(future).await;
+                    if result.is_ok() {
+                        ports.stats.message_handled(&myself, start.elapsed());
+                    }
+                    match result {
                         Ok(Ok(())) => Ok(ActorLoopResult::ok()),
                         Ok(Err(internal_err)) => Err(internal_err),
                         Err(signal) => {
@@ -442,6 +478,14 @@ impl<TActor: ThreadLocalActor> ThreadLocalActorRuntime<TActor> {
                     // all the messages and we want the actor to die now
                     Ok(ActorLoopResult::stop(Some("Drained".to_string())))
                 }
+                actor_cell::ActorPortMessage::Message(MuxedMessage::Upgrade(_, reply)) => {
+                    // Hot upgrades require a `Send` handler, so they're never applicable to
+                    // thread-local actors
+                    let _ = reply.send(Err(crate::RactorErr::Messaging(
+                        MessagingErr::InvalidActorType,
+                    )));
+                    Ok(ActorLoopResult::ok())
+                }
             },
             Err(MessagingErr::ChannelClosed) => {
                 // one of the channels is closed, this means
@@ -460,7 +504,11 @@ impl<TActor: ThreadLocalActor> ThreadLocalActorRuntime<TActor> {
                     Signal::Kill,
                 )))
             }
-            Err(MessagingErr::SendErr(_)) => {
+            Err(
+                MessagingErr::SendErr(_)
+                | MessagingErr::MailboxFull(_)
+                | MessagingErr::StashFull(_),
+            ) => {
                 // not possible. Treat like a channel closed
                 Ok(ActorLoopResult::signal(Self::handle_signal(
                     myself,
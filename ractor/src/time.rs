@@ -14,6 +14,16 @@
 //! 3. Stop after a delay
 //! 4. Kill after a delay
 //!
+//! Timers started on an actor are bound to it, and are cancelled once it stops. They
+//! can also be keyed, to list and cancel them by key (see [crate::actor::timers]).
+//!
+//! Sends can also follow a [Schedule] with [send_on_schedule], such as a cron expression,
+//! a fixed rate or delay, or an exponential backoff, with optional random jitter.
+//!
+//! Each of the above spawns a task per timer. For many (or frequently re-armed) timers,
+//! [start_timer] instead schedules the timer on a shared [TimerService], which drives all
+//! of its timers from a single task and returns a [TimerRef] to cancel or reset the timer.
+//!
 //! ## Examples
 //!
 //! ```rust
@@ -85,20 +95,37 @@
 //! }
 //! ```
 
+use crate::actor::timers::bind_actor_timer;
+use crate::actor::timers::spawn_actor_timer;
+use crate::actor::timers::start_actor_timer;
+pub use crate::actor::timers::TimerKey;
 use crate::concurrency::Duration;
+use crate::concurrency::Instant;
 use crate::concurrency::JoinHandle;
 use crate::ActorCell;
 use crate::Message;
 use crate::MessagingErr;
 use crate::ACTIVE_STATES;
 
+mod cron;
+mod schedule;
+mod service;
+mod wheel;
+
+pub use cron::CronParseErr;
+pub use cron::CronSchedule;
+pub use schedule::Schedule;
+pub use schedule::ScheduleKind;
+pub use service::TimerRef;
+pub use service::TimerService;
+pub use service::DEFAULT_TIMER_RESOLUTION;
+
 #[cfg(test)]
 mod tests;
 
 /// Sends a message to a given actor repeatedly after a specified time
 /// using the provided message generation function. The task will exit
-/// once the channel is closed (meaning the underlying [crate::Actor]
-/// has terminated)
+/// once the underlying [crate::Actor] stops (see [crate::actor::timers])
 ///
 /// * `period` - The [Duration] representing the period for the send interval
 /// * `actor` - The [ActorCell] representing the [crate::Actor] to communicate with
@@ -108,6 +135,44 @@ mod tests;
 /// Returns: The [JoinHandle] which represents the backgrounded work (can be ignored to
 /// "fire and forget")
 pub fn send_interval<TMessage, F>(period: Duration, actor: ActorCell, msg: F) -> JoinHandle<()>
+where
+    TMessage: Message,
+    F: Fn() -> TMessage + Send + 'static,
+{
+    spawn_interval(None, period, actor, msg)
+}
+
+/// Sends a message to a given actor repeatedly after a specified time, like
+/// [send_interval], as a keyed timer of the actor. Starting a timer with the key of an
+/// active timer replaces it. See [crate::actor::timers].
+///
+/// * `key` - The [TimerKey] to list and cancel the timer by
+/// * `period` - The [Duration] representing the period for the send interval
+/// * `actor` - The [ActorCell] representing the [crate::Actor] to communicate with
+/// * `msg` - The [Fn] message builder which is called to generate a message for each send
+///   operation.
+///
+/// Returns: The [JoinHandle] which represents the backgrounded work (can be ignored to
+/// "fire and forget")
+pub fn send_interval_keyed<TMessage, F>(
+    key: TimerKey,
+    period: Duration,
+    actor: ActorCell,
+    msg: F,
+) -> JoinHandle<()>
+where
+    TMessage: Message,
+    F: Fn() -> TMessage + Send + 'static,
+{
+    spawn_interval(Some(key), period, actor, msg)
+}
+
+fn spawn_interval<TMessage, F>(
+    key: Option<TimerKey>,
+    period: Duration,
+    actor: ActorCell,
+    msg: F,
+) -> JoinHandle<()>
 where
     TMessage: Message,
     F: Fn() -> TMessage + Send + 'static,
@@ -116,7 +181,7 @@ where
     // Tokio and our internal version for `async_std` provide an interval timer which
     // accounts for execution time to send a message and changes in polling to wake
     // the task to assure that the period doesn't drift over long runtimes.
-    crate::concurrency::spawn(async move {
+    spawn_actor_timer(&actor.clone(), key, async move {
         let mut timer = crate::concurrency::interval(period);
         // timer tick's immediately the first time
         timer.tick().await;
@@ -131,8 +196,39 @@ where
     })
 }
 
+/// Sends a message to a given actor on a [Schedule], e.g. on a fixed cadence, at the times
+/// matching a cron expression, or with an exponential backoff, optionally with random
+/// jitter. The task will exit once the schedule is over or the underlying [crate::Actor]
+/// stops (see [crate::actor::timers]).
+///
+/// * `schedule` - The [Schedule] of the sends
+/// * `actor` - The [ActorCell] representing the [crate::Actor] to communicate with
+/// * `msg` - The [Fn] message builder which is called to generate a message for each send
+///   operation.
+///
+/// Returns: The [JoinHandle] which represents the backgrounded work. To cancel the
+/// schedule, you can abort the handle
+pub fn send_on_schedule<TMessage, F>(schedule: Schedule, actor: ActorCell, msg: F) -> JoinHandle<()>
+where
+    TMessage: Message,
+    F: Fn() -> TMessage + Send + 'static,
+{
+    spawn_actor_timer(&actor.clone(), None, async move {
+        let mut schedule = schedule.start();
+        while let Some(deadline) = schedule.next_deadline(Instant::now()) {
+            crate::concurrency::sleep(deadline.saturating_duration_since(Instant::now())).await;
+            if !ACTIVE_STATES.contains(&actor.get_status())
+                || actor.send_message::<TMessage>(msg()).is_err()
+            {
+                break;
+            }
+        }
+    })
+}
+
 /// Sends a message after a given period to the specified actor. The task terminates
-/// once the send has completed
+/// once the send has completed, or once the underlying [crate::Actor] stops (see
+/// [crate::actor::timers])
 ///
 /// * `period` - The [Duration] representing the time to delay before sending
 /// * `actor` - The [ActorCell] representing the [crate::Actor] to communicate with
@@ -140,7 +236,8 @@ where
 ///   operation
 ///
 /// Returns: The [JoinHandle<Result<(), MessagingErr>>] which represents the backgrounded work.
-/// Awaiting the handle will yield the result of the send operation. Can be safely ignored to
+/// Awaiting the handle will yield the result of the send operation, which is
+/// [MessagingErr::ChannelClosed] if the actor stopped first. Can be safely ignored to
 /// "fire and forget"
 pub fn send_after<TMessage, F>(
     period: Duration,
@@ -151,9 +248,40 @@ where
     TMessage: Message,
     F: FnOnce() -> TMessage + Send + 'static,
 {
-    crate::concurrency::spawn(async move {
+    let timer = bind_actor_timer(&actor.clone(), None, async move {
         crate::concurrency::sleep(period).await;
         actor.send_message::<TMessage>(msg())
+    });
+    crate::concurrency::spawn(
+        async move { timer.await.unwrap_or(Err(MessagingErr::ChannelClosed)) },
+    )
+}
+
+/// Sends a message after a given period to the specified actor, like [send_after], as a
+/// keyed timer of the actor. Starting a timer with the key of an active timer replaces it.
+/// See [crate::actor::timers].
+///
+/// * `key` - The [TimerKey] to list and cancel the timer by
+/// * `period` - The [Duration] representing the time to delay before sending
+/// * `actor` - The [ActorCell] representing the [crate::Actor] to communicate with
+/// * `msg` - The [FnOnce] message builder which is called to generate a message for the send
+///   operation
+///
+/// Returns: The [JoinHandle] which represents the backgrounded work (can be ignored to
+/// "fire and forget")
+pub fn send_after_keyed<TMessage, F>(
+    key: TimerKey,
+    period: Duration,
+    actor: ActorCell,
+    msg: F,
+) -> JoinHandle<()>
+where
+    TMessage: Message,
+    F: FnOnce() -> TMessage + Send + 'static,
+{
+    spawn_actor_timer(&actor.clone(), Some(key), async move {
+        crate::concurrency::sleep(period).await;
+        let _ = actor.send_message::<TMessage>(msg());
     })
 }
 
@@ -164,9 +292,10 @@ where
 /// * `actor` - The [ActorCell] representing the [crate::Actor] to exit after the duration
 ///
 /// Returns: The [JoinHandle] which denotes the backgrounded operation. To cancel the
-/// exit operation, you can abort the handle
+/// exit operation, you can abort the handle. It's cancelled automatically if the actor
+/// stops first.
 pub fn exit_after(period: Duration, actor: ActorCell) -> JoinHandle<()> {
-    crate::concurrency::spawn(async move {
+    spawn_actor_timer(&actor.clone(), None, async move {
         crate::concurrency::sleep(period).await;
         actor.stop(Some(format!("Exit after {}ms", period.as_millis())))
     })
@@ -178,14 +307,54 @@ pub fn exit_after(period: Duration, actor: ActorCell) -> JoinHandle<()> {
 /// * `actor` - The [ActorCell] representing the [crate::Actor] to kill after the duration
 ///
 /// Returns: The [JoinHandle] which denotes the backgrounded operation. To cancel the
-/// kill operation, you can abort the handle
+/// kill operation, you can abort the handle. It's cancelled automatically if the actor
+/// stops first.
 pub fn kill_after(period: Duration, actor: ActorCell) -> JoinHandle<()> {
-    crate::concurrency::spawn(async move {
+    spawn_actor_timer(&actor.clone(), None, async move {
         crate::concurrency::sleep(period).await;
         actor.kill()
     })
 }
 
+/// Sends a message after a given period to the specified actor, scheduled on the global
+/// [TimerService] rather than a dedicated task. Equivalent of Erlang's `erlang:start_timer/3`.
+/// The timer is cancelled if the underlying [crate::Actor] stops first (see
+/// [crate::actor::timers]).
+///
+/// * `period` - The [Duration] representing the time to delay before sending
+/// * `actor` - The [ActorCell] representing the [crate::Actor] to communicate with
+/// * `msg` - The [FnOnce] message builder which is called to generate a message for the send
+///   operation
+///
+/// Returns: The [TimerRef] which can cancel the timer (see [cancel_timer]) or re-arm it
+/// (see [reset_timer]) before it fires. Can be safely ignored to "fire and forget"
+pub fn start_timer<TMessage, F>(period: Duration, actor: ActorCell, msg: F) -> TimerRef
+where
+    TMessage: Message,
+    F: FnOnce() -> TMessage + Send + 'static,
+{
+    TimerService::global().start_timer(period, actor, msg)
+}
+
+/// Cancels a timer, see [TimerRef::cancel]. Equivalent of Erlang's `erlang:cancel_timer/1`.
+///
+/// * `timer` - The [TimerRef] of the timer to cancel
+///
+/// Returns: true if the timer was pending and won't fire
+pub fn cancel_timer(timer: &TimerRef) -> bool {
+    timer.cancel()
+}
+
+/// Re-arms a pending timer to fire after the given period from now, see [TimerRef::reset]
+///
+/// * `timer` - The [TimerRef] of the timer to re-arm
+/// * `period` - The [Duration] representing the new delay, starting now
+///
+/// Returns: true if the timer was pending and has been re-armed
+pub fn reset_timer(timer: &TimerRef, period: Duration) -> bool {
+    timer.reset(period)
+}
+
 /// Add the timing functionality on top of the [crate::ActorRef]
 impl<TMessage> crate::ActorRef<TMessage>
 where
@@ -199,6 +368,22 @@ where
         send_interval::<TMessage, F>(period, self.get_cell(), msg)
     }
 
+    /// Alias of [send_interval_keyed]
+    pub fn send_interval_keyed<F>(&self, key: TimerKey, period: Duration, msg: F) -> JoinHandle<()>
+    where
+        F: Fn() -> TMessage + Send + 'static,
+    {
+        send_interval_keyed::<TMessage, F>(key, period, self.get_cell(), msg)
+    }
+
+    /// Alias of [send_on_schedule]
+    pub fn send_on_schedule<F>(&self, schedule: Schedule, msg: F) -> JoinHandle<()>
+    where
+        F: Fn() -> TMessage + Send + 'static,
+    {
+        send_on_schedule::<TMessage, F>(schedule, self.get_cell(), msg)
+    }
+
     /// Alias of [send_after]
     pub fn send_after<F>(
         &self,
@@ -211,6 +396,22 @@ where
         send_after::<TMessage, F>(period, self.get_cell(), msg)
     }
 
+    /// Alias of [send_after_keyed]
+    pub fn send_after_keyed<F>(&self, key: TimerKey, period: Duration, msg: F) -> JoinHandle<()>
+    where
+        F: FnOnce() -> TMessage + Send + 'static,
+    {
+        send_after_keyed::<TMessage, F>(key, period, self.get_cell(), msg)
+    }
+
+    /// Alias of [start_timer]
+    pub fn start_timer<F>(&self, period: Duration, msg: F) -> TimerRef
+    where
+        F: FnOnce() -> TMessage + Send + 'static,
+    {
+        start_timer::<TMessage, F>(period, self.get_cell(), msg)
+    }
+
     /// Alias of [exit_after]
     pub fn exit_after(&self, period: Duration) -> JoinHandle<()> {
         exit_after(period, self.get_cell())
@@ -235,7 +436,7 @@ where
         // IMPORTANT: See notes on `send_interval` above for important implementation
         // notes
         let self_clone = self.clone();
-        crate::concurrency::spawn(async move {
+        spawn_actor_timer(&self.get_cell(), None, async move {
             let mut timer = crate::concurrency::interval(period);
             // timer tick's immediately the first time
             timer.tick().await;
@@ -260,11 +461,30 @@ where
         F: FnOnce() -> TMessage + Send + 'static,
     {
         let self_clone = self.clone();
-        crate::concurrency::spawn(async move {
+        let timer = bind_actor_timer(&self.get_cell(), None, async move {
             crate::concurrency::sleep(period).await;
             let msg = msg();
             self_clone.send_message(msg)
-        })
+        });
+        crate::concurrency::spawn(
+            async move { timer.await.unwrap_or(Err(MessagingErr::ChannelClosed)) },
+        )
+    }
+
+    /// Alias of [start_timer]
+    pub fn start_timer<F>(&self, period: Duration, msg: F) -> TimerRef
+    where
+        F: FnOnce() -> TMessage + Send + 'static,
+    {
+        let self_clone = self.clone();
+        start_actor_timer(
+            &self.get_cell(),
+            &TimerService::global(),
+            period,
+            move || {
+                let _ = self_clone.send_message(msg());
+            },
+        )
     }
 
     /// Alias of [exit_after]
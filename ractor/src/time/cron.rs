@@ -0,0 +1,270 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Parsing and evaluation of cron expressions, in UTC.
+//!
+//! An expression has 5 fields (`minute hour day-of-month month day-of-week`), or 6 with a
+//! leading `second` field. Each field is `*`, a value, a range `a-b`, or a step `*/n` or
+//! `a-b/n`, or a comma-separated list of those. Months (`JAN`-`DEC`) and days of the week
+//! (`SUN`-`SAT`, where both 0 and 7 are Sunday) can be given by name. As in standard cron,
+//! when both the day-of-month and the day-of-week are restricted, a day matching either
+//! one matches. The macros `@yearly`, `@annually`, `@monthly`, `@weekly`, `@daily`,
+//! `@midnight`, and `@hourly` are supported as well.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+const MONTHS: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEKDAYS: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// How far ahead to search for a matching time, before deciding that an expression
+/// never matches (e.g. February 30th)
+const SEARCH_YEARS: i64 = 8;
+
+/// An error parsing a cron expression
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum CronParseErr {
+    /// The expression doesn't have 5 or 6 fields, or is an unknown macro
+    InvalidFormat(String),
+    /// A field has an invalid value, range, or step
+    InvalidField {
+        /// The name of the field
+        field: &'static str,
+        /// The invalid value of the field
+        value: String,
+    },
+}
+
+impl Display for CronParseErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidFormat(expression) => write!(
+                f,
+                "Invalid cron expression '{expression}', expected 5 or 6 fields"
+            ),
+            Self::InvalidField { field, value } => {
+                write!(f, "Invalid value '{value}' of the cron {field} field")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CronParseErr {}
+
+/// A parsed cron expression, see the [module documentation](self) for the syntax
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CronSchedule {
+    seconds: u64,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    day_of_month_restricted: bool,
+    day_of_week_restricted: bool,
+}
+
+impl FromStr for CronSchedule {
+    type Err = CronParseErr;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let expanded = match expression.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other => other,
+        };
+        let fields = expanded.split_whitespace().collect::<Vec<_>>();
+        let (second, rest) = match fields.len() {
+            5 => ("0", &fields[..]),
+            6 => (fields[0], &fields[1..]),
+            _ => return Err(CronParseErr::InvalidFormat(expression.to_string())),
+        };
+
+        let mut days_of_week = parse_field("day-of-week", rest[4], 0, 7, &WEEKDAYS, 0)?;
+        // 7 is an alias of Sunday
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            seconds: parse_field("second", second, 0, 59, &[], 0)?,
+            minutes: parse_field("minute", rest[0], 0, 59, &[], 0)?,
+            hours: parse_field("hour", rest[1], 0, 23, &[], 0)?,
+            days_of_month: parse_field("day-of-month", rest[2], 1, 31, &[], 0)?,
+            months: parse_field("month", rest[3], 1, 12, &MONTHS, 1)?,
+            days_of_week,
+            day_of_month_restricted: !is_wildcard(rest[2]),
+            day_of_week_restricted: !is_wildcard(rest[4]),
+        })
+    }
+}
+
+impl CronSchedule {
+    /// Parse a cron expression, see the [module documentation](self) for the syntax
+    pub fn parse(expression: &str) -> Result<Self, CronParseErr> {
+        expression.parse()
+    }
+
+    /// The first time matching the expression strictly after the provided time, in
+    /// seconds since the UNIX epoch. [None] if nothing matches within the next years.
+    pub fn next_after(&self, unix_secs: u64) -> Option<u64> {
+        let start = unix_secs as i64 + 1;
+        let limit_year = DateTime::from_unix(start).year + SEARCH_YEARS;
+        let mut t = start;
+        loop {
+            let dt = DateTime::from_unix(t);
+            if dt.year > limit_year {
+                return None;
+            }
+            if !matches(self.months, dt.month) {
+                t = if dt.month == 12 {
+                    DateTime::to_unix(dt.year + 1, 1, 1)
+                } else {
+                    DateTime::to_unix(dt.year, dt.month + 1, 1)
+                };
+            } else if !self.day_matches(&dt) {
+                t = DateTime::to_unix(dt.year, dt.month, dt.day) + 86_400;
+            } else if !matches(self.hours, dt.hour) {
+                t = t - t % 3_600 + 3_600;
+            } else if !matches(self.minutes, dt.minute) {
+                t = t - t % 60 + 60;
+            } else if !matches(self.seconds, dt.second) {
+                t += 1;
+            } else {
+                return Some(t as u64);
+            }
+        }
+    }
+
+    fn day_matches(&self, dt: &DateTime) -> bool {
+        let day_of_month = matches(self.days_of_month, dt.day);
+        let day_of_week = matches(self.days_of_week, dt.weekday);
+        match (self.day_of_month_restricted, self.day_of_week_restricted) {
+            (true, true) => day_of_month || day_of_week,
+            _ => day_of_month && day_of_week,
+        }
+    }
+}
+
+fn matches(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+fn is_wildcard(field: &str) -> bool {
+    field == "*" || field == "?"
+}
+
+/// Parse a field into a bitmask of the matching values
+fn parse_field(
+    field: &'static str,
+    value: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+    first_name: u32,
+) -> Result<u64, CronParseErr> {
+    let invalid = || CronParseErr::InvalidField {
+        field,
+        value: value.to_string(),
+    };
+    let parse_value = |part: &str| -> Result<u32, CronParseErr> {
+        let parsed = match names
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(part))
+        {
+            Some(index) => index as u32 + first_name,
+            None => part.parse::<u32>().map_err(|_| invalid())?,
+        };
+        if parsed < min || parsed > max {
+            return Err(invalid());
+        }
+        Ok(parsed)
+    };
+
+    let mut mask = 0u64;
+    for item in value.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => {
+                let step = step.parse::<u32>().map_err(|_| invalid())?;
+                if step == 0 {
+                    return Err(invalid());
+                }
+                (range, step)
+            }
+            None => (item, 1),
+        };
+        let (start, end) = if is_wildcard(range) {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_value(start)?, parse_value(end)?)
+        } else {
+            let start = parse_value(range)?;
+            // `a/n` runs from `a` to the end of the field
+            (start, if item.contains('/') { max } else { start })
+        };
+        if start > end {
+            return Err(invalid());
+        }
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+/// A UTC date and time
+struct DateTime {
+    year: i64,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+    /// The day of the week, where 0 is Sunday
+    weekday: u32,
+}
+
+impl DateTime {
+    fn from_unix(secs: i64) -> Self {
+        let days = secs.div_euclid(86_400);
+        let secs_of_day = secs.rem_euclid(86_400) as u32;
+        // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        Self {
+            year,
+            month,
+            day,
+            hour: secs_of_day / 3_600,
+            minute: secs_of_day / 60 % 60,
+            second: secs_of_day % 60,
+            // 1970-01-01 was a Thursday
+            weekday: (days + 4).rem_euclid(7) as u32,
+        }
+    }
+
+    /// The seconds since the UNIX epoch at the start of the provided day
+    fn to_unix(year: i64, month: u32, day: u32) -> i64 {
+        // http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+        let y = if month <= 2 { year - 1 } else { year };
+        let era = y.div_euclid(400);
+        let yoe = y.rem_euclid(400);
+        let mp = (i64::from(month) + 9) % 12;
+        let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        (era * 146_097 + doe - 719_468) * 86_400
+    }
+}
@@ -0,0 +1,200 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! Schedules for [super::send_on_schedule]
+
+use super::cron::CronParseErr;
+use super::cron::CronSchedule;
+use crate::concurrency::Duration;
+use crate::concurrency::Instant;
+use crate::concurrency::SystemTime;
+
+/// When the sends of a schedule happen
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScheduleKind {
+    /// Send every period, on a cadence anchored at the start of the schedule. If the
+    /// schedule falls behind (e.g. the runtime was blocked), it sends once immediately and
+    /// then resumes the original cadence, rather than sending a burst of missed messages.
+    FixedRate(Duration),
+    /// Send with the period between consecutive sends, measured from the previous send.
+    /// Delays in sending push back the later sends.
+    FixedDelay(Duration),
+    /// Send at the (UTC) times matching a cron expression. The wall clock is read when the
+    /// schedule starts, and followed on [crate::concurrency::Instant] from then on.
+    Cron(CronSchedule),
+    /// Send with exponentially growing delays between the sends, measured from the
+    /// previous send
+    ExponentialBackoff {
+        /// The delay before the first send
+        initial: Duration,
+        /// The cap on the delay
+        max: Duration,
+        /// The factor the delay grows by after each send
+        factor: f64,
+    },
+}
+
+/// A schedule of sends, see [super::send_on_schedule]
+///
+/// ```rust
+/// use ractor::concurrency::Duration;
+/// use ractor::time::Schedule;
+///
+/// // every 15 minutes during working hours, spread out over up to 30s
+/// let schedule = Schedule::cron("*/15 9-17 * * MON-FRI")
+///     .expect("Invalid cron expression")
+///     .with_jitter(Duration::from_secs(30));
+///
+/// // retry after 100ms, 200ms, 400ms, ... up to 10s, at most 20 times
+/// let retries = Schedule::exponential_backoff(Duration::from_millis(100), Duration::from_secs(10))
+///     .with_limit(20);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    kind: ScheduleKind,
+    jitter: Duration,
+    limit: Option<usize>,
+}
+
+impl From<ScheduleKind> for Schedule {
+    fn from(kind: ScheduleKind) -> Self {
+        Self {
+            kind,
+            jitter: Duration::ZERO,
+            limit: None,
+        }
+    }
+}
+
+impl Schedule {
+    /// Send on a fixed cadence, see [ScheduleKind::FixedRate]
+    pub fn fixed_rate(period: Duration) -> Self {
+        ScheduleKind::FixedRate(period).into()
+    }
+
+    /// Send with a fixed delay between sends, see [ScheduleKind::FixedDelay]
+    pub fn fixed_delay(delay: Duration) -> Self {
+        ScheduleKind::FixedDelay(delay).into()
+    }
+
+    /// Send at the times matching a cron expression, see [CronSchedule] for the syntax
+    pub fn cron(expression: &str) -> Result<Self, CronParseErr> {
+        Ok(ScheduleKind::Cron(expression.parse()?).into())
+    }
+
+    /// Send with delays doubling from `initial` up to `max`, see
+    /// [ScheduleKind::ExponentialBackoff]
+    pub fn exponential_backoff(initial: Duration, max: Duration) -> Self {
+        ScheduleKind::ExponentialBackoff {
+            initial,
+            max,
+            factor: 2.0,
+        }
+        .into()
+    }
+
+    /// Delay every send by a random duration of up to `jitter`, to spread out the sends
+    /// of many actors on the same schedule
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Stop the schedule after this many sends
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// When the sends happen
+    pub fn kind(&self) -> &ScheduleKind {
+        &self.kind
+    }
+
+    /// Start tracking the schedule from now
+    pub(crate) fn start(self) -> ScheduleState {
+        let delay = match &self.kind {
+            ScheduleKind::ExponentialBackoff { initial, .. } => *initial,
+            _ => Duration::ZERO,
+        };
+        ScheduleState {
+            schedule: self,
+            start: Instant::now(),
+            wall_start: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default(),
+            sent: 0,
+            ticks: 0,
+            delay,
+        }
+    }
+}
+
+/// The progress through a [Schedule]
+pub(crate) struct ScheduleState {
+    schedule: Schedule,
+    start: Instant,
+    /// The wall clock time (since the unix epoch) at `start`. Cron times are tracked
+    /// relative to it, on the same clock as the deadlines, so that they follow a paused or
+    /// advanced clock.
+    wall_start: Duration,
+    sent: usize,
+    /// The cadence points of a fixed rate which have passed
+    ticks: u128,
+    /// The next delay of an exponential backoff
+    delay: Duration,
+}
+
+impl ScheduleState {
+    /// The deadline of the next send, or [None] if the schedule is over. Called after
+    /// each send, with the current time.
+    pub(crate) fn next_deadline(&mut self, now: Instant) -> Option<Instant> {
+        if matches!(self.schedule.limit, Some(limit) if self.sent >= limit) {
+            return None;
+        }
+        self.sent += 1;
+
+        let deadline = match &self.schedule.kind {
+            ScheduleKind::FixedRate(period) => {
+                let period = period.as_nanos().max(1);
+                self.ticks += 1;
+                let due = self.start + Duration::from_nanos((self.ticks * period) as u64);
+                if due < now {
+                    // fell behind, so send immediately and realign with the cadence
+                    self.ticks = now.saturating_duration_since(self.start).as_nanos() / period;
+                    now
+                } else {
+                    due
+                }
+            }
+            ScheduleKind::FixedDelay(delay) => now + *delay,
+            ScheduleKind::Cron(cron) => {
+                let wall = self.wall_start + now.saturating_duration_since(self.start);
+                let next = cron.next_after(wall.as_secs())?;
+                now + Duration::from_secs(next).saturating_sub(wall)
+            }
+            ScheduleKind::ExponentialBackoff { max, factor, .. } => {
+                let delay = self.delay.min(*max);
+                let next = delay.as_secs_f64() * factor.max(1.0);
+                self.delay = if next.is_finite() && next < max.as_secs_f64() {
+                    Duration::from_secs_f64(next)
+                } else {
+                    *max
+                };
+                now + delay
+            }
+        };
+        Some(deadline + random_jitter(self.schedule.jitter))
+    }
+}
+
+/// A random duration of up to `jitter`
+fn random_jitter(jitter: Duration) -> Duration {
+    let max = jitter.as_nanos() as u64;
+    if max == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_nanos(crate::concurrency::random_u64() % (max + 1))
+}
@@ -0,0 +1,286 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! A shared timer service, which drives every timer from a single background task.
+//!
+//! Where [super::send_after] spawns a task per timer, a [TimerService] keeps its timers in a
+//! hierarchical timer wheel and wakes up only when the next timer is
+//! due. Timers are addressed by a [TimerRef], which can cancel the timer or re-arm it before
+//! it fires, similar to Erlang's `erlang:start_timer/3`, `erlang:cancel_timer/1`, and
+//! re-starting a timer with the same reference.
+//!
+//! Timers fire at the granularity of the service's resolution, and never early. Timer
+//! callbacks run on the service's task, so they should be quick (e.g. sending a message).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::Weak;
+
+use once_cell::sync::Lazy;
+use once_cell::sync::OnceCell;
+
+use super::wheel::TimerId;
+use super::wheel::TimerWheel;
+use crate::concurrency::Duration;
+use crate::concurrency::Instant;
+use crate::concurrency::JoinHandle;
+use crate::concurrency::Notify;
+use crate::concurrency::RuntimeId;
+use crate::ActorCell;
+use crate::Message;
+
+/// The resolution of the [TimerService::global] service
+pub const DEFAULT_TIMER_RESOLUTION: Duration = Duration::from_millis(1);
+
+/// The global timer service of each runtime, see [TimerService::global]
+static GLOBAL_TIMER_SERVICES: Lazy<Mutex<HashMap<RuntimeId, TimerService>>> =
+    Lazy::new(Default::default);
+
+type TimerAction = Box<dyn FnOnce() + Send>;
+
+struct TimerServiceInner {
+    wheel: Mutex<TimerWheel<TimerAction>>,
+    start: Instant,
+    resolution: Duration,
+    wake: Arc<Notify>,
+    driver: OnceCell<JoinHandle<()>>,
+}
+
+impl TimerServiceInner {
+    /// The tick of the wheel at the provided instant, rounded down
+    fn tick_at(&self, instant: Instant) -> u64 {
+        (instant.saturating_duration_since(self.start).as_nanos() / self.resolution.as_nanos())
+            as u64
+    }
+
+    /// The tick of the wheel a timer started now with the provided delay expires at,
+    /// rounded up so that it never fires early
+    fn expiry_after(&self, after: Duration) -> u64 {
+        let elapsed = (Instant::now().saturating_duration_since(self.start) + after).as_nanos();
+        let resolution = self.resolution.as_nanos();
+        ((elapsed + resolution - 1) / resolution) as u64
+    }
+
+    /// The instant the provided tick of the wheel starts at
+    fn instant_of(&self, tick: u64) -> Instant {
+        let nanos = (self.resolution.as_nanos() as u64).saturating_mul(tick);
+        self.start + Duration::from_nanos(nanos)
+    }
+
+    /// Fire the expired timers, and return how long until the next timer is due
+    fn fire_expired(&self) -> Option<Duration> {
+        let now = self.tick_at(Instant::now());
+        let fired = self.wheel.lock().unwrap().advance(now);
+        // run the callbacks outside the lock, as they may (re-)start timers
+        for (_, action) in fired {
+            action();
+        }
+        let next = self.wheel.lock().unwrap().next_tick()?;
+        Some(
+            self.instant_of(next)
+                .saturating_duration_since(Instant::now()),
+        )
+    }
+}
+
+impl Drop for TimerServiceInner {
+    fn drop(&mut self) {
+        // let the driver task observe that the service is gone and exit
+        self.wake.notify_one();
+    }
+}
+
+/// A shared timer service, see the [module documentation](self)
+///
+/// Cloning the service is cheap, and clones share the same timers. The service stops
+/// once every clone has been dropped, and its pending timers never fire.
+#[derive(Clone)]
+pub struct TimerService {
+    inner: Arc<TimerServiceInner>,
+}
+
+impl std::fmt::Debug for TimerService {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TimerService")
+            .field("resolution", &self.inner.resolution)
+            .field("pending", &self.len())
+            .finish()
+    }
+}
+
+impl TimerService {
+    /// Start a new timer service on the current runtime
+    ///
+    /// * `resolution` - The granularity at which timers fire. Delays are rounded up to
+    ///   the next multiple of the resolution.
+    pub fn new(resolution: Duration) -> Self {
+        let wake = Arc::new(Notify::new());
+        let inner = Arc::new(TimerServiceInner {
+            wheel: Mutex::new(TimerWheel::new()),
+            start: Instant::now(),
+            resolution: resolution.max(Duration::from_nanos(1)),
+            wake: wake.clone(),
+            driver: OnceCell::new(),
+        });
+
+        let weak = Arc::downgrade(&inner);
+        let driver = crate::concurrency::spawn(async move {
+            loop {
+                let next = match weak.upgrade() {
+                    Some(inner) => inner.fire_expired(),
+                    None => break,
+                };
+                match next {
+                    Some(delay) => {
+                        let _ = crate::concurrency::timeout(delay, wake.notified()).await;
+                    }
+                    None => wake.notified().await,
+                }
+            }
+        });
+        let _ = inner.driver.set(driver);
+
+        Self { inner }
+    }
+
+    /// Retrieve the global timer service of the current runtime (with a resolution of
+    /// [DEFAULT_TIMER_RESOLUTION]), starting it if it isn't running. Every runtime has its
+    /// own, so the timers started on one runtime don't depend on another one staying up.
+    pub fn global() -> Self {
+        let mut services = GLOBAL_TIMER_SERVICES.lock().unwrap();
+        // forget the services of runtimes which have shut down
+        services.retain(|_, service| service.is_running());
+        services
+            .entry(crate::concurrency::current_runtime_id())
+            .or_insert_with(|| Self::new(DEFAULT_TIMER_RESOLUTION))
+            .clone()
+    }
+
+    /// Whether the service's background task is still running. It stops if the runtime
+    /// it was started on shuts down.
+    pub fn is_running(&self) -> bool {
+        matches!(self.inner.driver.get(), Some(driver) if !driver.is_finished())
+    }
+
+    /// The number of pending timers
+    pub fn len(&self) -> usize {
+        self.inner.wheel.lock().unwrap().len()
+    }
+
+    /// Whether there are no pending timers
+    pub fn is_empty(&self) -> bool {
+        self.inner.wheel.lock().unwrap().is_empty()
+    }
+
+    /// Start a timer which runs the provided callback once the delay has passed
+    ///
+    /// * `after` - The [Duration] to wait before running the callback
+    /// * `action` - The callback, which is run on the service's task
+    ///
+    /// Returns the [TimerRef] which can cancel or reset the timer
+    pub fn start<F>(&self, after: Duration, action: F) -> TimerRef
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let expiry = self.inner.expiry_after(after);
+        let id = self
+            .inner
+            .wheel
+            .lock()
+            .unwrap()
+            .insert(expiry, Box::new(action));
+        self.inner.wake.notify_one();
+        TimerRef {
+            id,
+            service: Arc::downgrade(&self.inner),
+        }
+    }
+
+    /// Start a timer which sends a message to the actor once the delay has passed. The
+    /// timer is bound to the actor, and is cancelled if the actor stops first (see
+    /// [crate::actor::timers]).
+    ///
+    /// * `after` - The [Duration] to wait before sending
+    /// * `actor` - The [ActorCell] representing the [crate::Actor] to send to
+    /// * `msg` - The [FnOnce] message builder, which is called when the timer fires
+    ///
+    /// Returns the [TimerRef] which can cancel or reset the timer
+    pub fn start_timer<TMessage, F>(&self, after: Duration, actor: ActorCell, msg: F) -> TimerRef
+    where
+        TMessage: Message,
+        F: FnOnce() -> TMessage + Send + 'static,
+    {
+        let cell = actor.clone();
+        crate::actor::timers::start_actor_timer(&cell, self, after, move || {
+            let _ = actor.send_message::<TMessage>(msg());
+        })
+    }
+}
+
+/// A reference to a timer started on a [TimerService]
+#[derive(Clone)]
+pub struct TimerRef {
+    id: TimerId,
+    service: Weak<TimerServiceInner>,
+}
+
+impl std::fmt::Debug for TimerRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TimerRef")
+            .field("id", &self.id)
+            .field("active", &self.is_active())
+            .finish()
+    }
+}
+
+impl TimerRef {
+    /// Whether the timer is still pending, i.e. it hasn't fired or been cancelled
+    pub fn is_active(&self) -> bool {
+        match self.service.upgrade() {
+            Some(service) => service.wheel.lock().unwrap().contains(self.id),
+            None => false,
+        }
+    }
+
+    /// The time left until the timer fires, or [None] if it isn't pending
+    pub fn remaining(&self) -> Option<Duration> {
+        let service = self.service.upgrade()?;
+        let expiry = service.wheel.lock().unwrap().expiry(self.id)?;
+        Some(
+            service
+                .instant_of(expiry)
+                .saturating_duration_since(Instant::now()),
+        )
+    }
+
+    /// Cancel the timer
+    ///
+    /// Returns true if the timer was pending and won't fire, false if it already fired
+    /// or was cancelled
+    pub fn cancel(&self) -> bool {
+        match self.service.upgrade() {
+            Some(service) => service.wheel.lock().unwrap().remove(self.id).is_some(),
+            None => false,
+        }
+    }
+
+    /// Re-arm the timer to fire after the provided delay from now, instead of when it
+    /// was due
+    ///
+    /// Returns true if the timer was pending and has been re-armed, false if it already
+    /// fired or was cancelled
+    pub fn reset(&self, after: Duration) -> bool {
+        let Some(service) = self.service.upgrade() else {
+            return false;
+        };
+        let expiry = service.expiry_after(after);
+        let reset = service.wheel.lock().unwrap().reset(self.id, expiry);
+        if reset {
+            service.wake.notify_one();
+        }
+        reset
+    }
+}
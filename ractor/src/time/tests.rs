@@ -9,6 +9,10 @@ use std::sync::atomic::AtomicU8;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
+use super::wheel::TimerWheel;
+use super::CronParseErr;
+use super::CronSchedule;
+use super::Schedule;
 use crate::common_test::periodic_check;
 use crate::concurrency::Duration;
 use crate::Actor;
@@ -234,3 +238,482 @@ async fn test_kill_after() {
     )
     .await;
 }
+
+#[test]
+fn test_timer_wheel_fires_at_expiry() {
+    let mut wheel = TimerWheel::new();
+    // spread the expiries over every level, and past the horizon of the wheel
+    let expiries = [
+        1u64, 5, 63, 64, 65, 200, 4_095, 4_096, 70_000, 300_000, 20_000_000,
+    ];
+    for expiry in expiries.iter().rev() {
+        wheel.insert(*expiry, *expiry);
+    }
+    assert_eq!(expiries.len(), wheel.len());
+
+    let mut fired = vec![];
+    let mut tick = 0;
+    while !wheel.is_empty() {
+        let next = wheel.next_tick().expect("Pending timers, but no next tick");
+        assert!(next > tick);
+        tick = next;
+        for (_, expiry) in wheel.advance(tick) {
+            // never early, and never late
+            assert_eq!(expiry, tick);
+            fired.push(expiry);
+        }
+    }
+    assert_eq!(expiries.to_vec(), fired);
+    assert_eq!(None, wheel.next_tick());
+}
+
+#[test]
+fn test_timer_wheel_cancel_and_reset() {
+    let mut wheel = TimerWheel::new();
+    let cancelled = wheel.insert(10, "cancelled");
+    let delayed = wheel.insert(10, "delayed");
+    let hastened = wheel.insert(5_000, "hastened");
+    let kept = wheel.insert(10, "kept");
+
+    assert_eq!(Some("cancelled"), wheel.remove(cancelled));
+    assert_eq!(None, wheel.remove(cancelled));
+    assert!(wheel.reset(delayed, 100));
+    assert!(wheel.reset(hastened, 20));
+    assert!(!wheel.reset(cancelled, 20));
+
+    let fired = wheel.advance(10);
+    assert_eq!(vec![(kept, "kept")], fired);
+    assert!(!wheel.contains(kept));
+
+    assert_eq!(vec![(hastened, "hastened")], wheel.advance(99));
+    assert_eq!(Some(100), wheel.expiry(delayed));
+    assert_eq!(vec![(delayed, "delayed")], wheel.advance(10_000));
+    assert!(wheel.is_empty());
+}
+
+#[test]
+fn test_timer_wheel_drops_stale_copies() {
+    let mut wheel = TimerWheel::new();
+    let rearmed = wheel.insert(100, "rearmed");
+    // re-arm the timer on every tick, like an idle timeout which keeps being pushed back
+    for tick in 1..=10_000 {
+        assert!(wheel.advance(tick).is_empty());
+        assert!(wheel.reset(rearmed, tick + 100));
+    }
+    // the copies left in the old slots are dropped rather than cascaded, so they're
+    // bounded by how far the timer is pushed back rather than by the number of resets
+    assert!(wheel.slotted() < 150, "{} copies", wheel.slotted());
+    assert_eq!(vec![(rearmed, "rearmed")], wheel.advance(20_000));
+    assert!(wheel.is_empty());
+}
+
+#[test]
+#[cfg(all(not(feature = "async-std"), not(target_arch = "wasm32")))]
+fn test_global_timer_service_per_runtime() {
+    use super::TimerService;
+
+    let runtime = || {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()
+            .unwrap()
+    };
+    let first = runtime();
+    let second = runtime();
+
+    let first_service = first.block_on(async { TimerService::global() });
+
+    // the timers of the second runtime keep firing after the first one shuts down
+    let fired = Arc::new(AtomicU8::new(0));
+    let counter = fired.clone();
+    second.block_on(async move {
+        TimerService::global().start(Duration::from_millis(100), move || {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+    });
+    first.shutdown_timeout(Duration::from_secs(1));
+    assert!(!first_service.is_running());
+    second.block_on(async {
+        periodic_check(
+            || fired.load(Ordering::Relaxed) == 1,
+            Duration::from_secs(2),
+        )
+        .await;
+    });
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_start_timer() {
+    let counter = Arc::new(AtomicU8::new(0u8));
+
+    struct TestActor;
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for TestActor {
+        type Msg = u8;
+        type State = Arc<AtomicU8>;
+        type Arguments = Arc<AtomicU8>;
+        async fn pre_start(
+            &self,
+            _this_actor: ActorRef<Self::Msg>,
+            counter: Arc<AtomicU8>,
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(counter)
+        }
+        async fn handle(
+            &self,
+            _this_actor: ActorRef<Self::Msg>,
+            message: Self::Msg,
+            state: &mut Self::State,
+        ) -> Result<(), ActorProcessingErr> {
+            state.fetch_add(message, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    let (actor_ref, actor_handle) = Actor::spawn(None, TestActor, counter.clone())
+        .await
+        .expect("Failed to create test actor");
+
+    let fired = actor_ref.start_timer(Duration::from_millis(10), || 1);
+    let cancelled = actor_ref.start_timer(Duration::from_millis(10), || 10);
+    let reset = actor_ref.start_timer(Duration::from_millis(10), || 100);
+
+    assert!(super::cancel_timer(&cancelled));
+    assert!(super::reset_timer(&reset, Duration::from_millis(200)));
+    assert!(fired.is_active());
+    assert!(!cancelled.is_active());
+
+    periodic_check(
+        || counter.load(Ordering::Relaxed) == 1,
+        Duration::from_millis(500),
+    )
+    .await;
+    assert!(!fired.is_active());
+    assert!(!fired.cancel());
+    assert!(!fired.reset(Duration::from_millis(10)));
+    assert!(reset.is_active());
+    assert!(reset.remaining().is_some());
+
+    periodic_check(
+        || counter.load(Ordering::Relaxed) == 101,
+        Duration::from_millis(1000),
+    )
+    .await;
+    assert!(!reset.is_active());
+    assert_eq!(None, reset.remaining());
+
+    actor_ref.stop(None);
+    actor_handle.await.unwrap();
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_keyed_timers() {
+    let counter = Arc::new(AtomicU8::new(0u8));
+
+    struct TestActor;
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for TestActor {
+        type Msg = u8;
+        type State = Arc<AtomicU8>;
+        type Arguments = Arc<AtomicU8>;
+        async fn pre_start(
+            &self,
+            _this_actor: ActorRef<Self::Msg>,
+            counter: Arc<AtomicU8>,
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(counter)
+        }
+        async fn handle(
+            &self,
+            _this_actor: ActorRef<Self::Msg>,
+            message: Self::Msg,
+            state: &mut Self::State,
+        ) -> Result<(), ActorProcessingErr> {
+            state.fetch_add(message, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    let (actor_ref, actor_handle) = Actor::spawn(None, TestActor, counter.clone())
+        .await
+        .expect("Failed to create test actor");
+
+    let replaced = actor_ref.send_after_keyed("b".to_string(), Duration::from_millis(10), || 100);
+    let kept = actor_ref.send_after_keyed("a".to_string(), Duration::from_millis(10), || 1);
+    // replaces the pending timer with the same key
+    let replacement = actor_ref.send_after_keyed("b".to_string(), Duration::from_millis(10), || 10);
+    let cancelled =
+        actor_ref.send_interval_keyed("c".to_string(), Duration::from_millis(10), || 50);
+
+    assert_eq!(vec!["a", "b", "c"], actor_ref.get_timers());
+    assert!(actor_ref.cancel_timer("c"));
+    assert!(!actor_ref.cancel_timer("c"));
+    assert_eq!(vec!["a", "b"], actor_ref.get_timers());
+
+    periodic_check(
+        || replaced.is_finished() && kept.is_finished() && replacement.is_finished(),
+        Duration::from_millis(500),
+    )
+    .await;
+    periodic_check(|| cancelled.is_finished(), Duration::from_millis(500)).await;
+    periodic_check(
+        || counter.load(Ordering::Relaxed) == 11,
+        Duration::from_millis(500),
+    )
+    .await;
+    assert!(actor_ref.get_timers().is_empty());
+
+    actor_ref.stop(None);
+    actor_handle.await.unwrap();
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_timers_cancelled_on_stop() {
+    struct TestActor;
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for TestActor {
+        type Msg = ();
+        type State = ();
+        type Arguments = ();
+        async fn pre_start(
+            &self,
+            _this_actor: ActorRef<Self::Msg>,
+            _: (),
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(())
+        }
+    }
+
+    let (actor_ref, actor_handle) = Actor::spawn(None, TestActor, ())
+        .await
+        .expect("Failed to create test actor");
+
+    let period = Duration::from_secs(3600);
+    let handles = [
+        actor_ref.send_interval(period, || ()),
+        actor_ref.send_interval_keyed("interval".to_string(), period, || ()),
+        actor_ref.send_after_keyed("after".to_string(), period, || ()),
+        actor_ref.exit_after(period),
+        actor_ref.kill_after(period),
+    ];
+    let derived: crate::DerivedActorRef<()> = actor_ref.get_derived();
+    let sends = [
+        actor_ref.send_after(period, || ()),
+        derived.send_after(period, || ()),
+    ];
+    let wheel_timers = [
+        actor_ref.start_timer(period, || ()),
+        derived.start_timer(period, || ()),
+    ];
+    assert_eq!(vec!["after", "interval"], actor_ref.get_timers());
+
+    actor_ref.stop(None);
+    actor_handle.await.unwrap();
+
+    // the timers are cancelled with the actor, rather than waiting out their period
+    periodic_check(
+        || {
+            handles.iter().all(|handle| handle.is_finished())
+                && sends.iter().all(|handle| handle.is_finished())
+        },
+        Duration::from_millis(500),
+    )
+    .await;
+    assert!(actor_ref.get_timers().is_empty());
+    assert!(wheel_timers.iter().all(|timer| !timer.is_active()));
+    for send in sends {
+        assert!(matches!(
+            send.await.unwrap(),
+            Err(crate::MessagingErr::ChannelClosed)
+        ));
+    }
+
+    // and timers started after the actor stopped never run
+    let late = actor_ref.send_after_keyed("late".to_string(), period, || ());
+    periodic_check(|| late.is_finished(), Duration::from_millis(500)).await;
+    assert!(actor_ref.get_timers().is_empty());
+}
+
+#[test]
+fn test_cron_next_after() {
+    // 2024-01-01T10:00:00Z, a Monday
+    let monday = 1_704_103_200;
+    let next = |expression: &str, after: u64| {
+        CronSchedule::parse(expression)
+            .expect("Failed to parse cron expression")
+            .next_after(after)
+    };
+
+    // the next midnight
+    assert_eq!(Some(1_704_153_600), next("0 0 * * *", monday));
+    assert_eq!(Some(1_704_153_600), next("@daily", monday));
+    // with a seconds field
+    assert_eq!(Some(monday + 30), next("30 * * * * *", monday));
+    // strictly after the provided time
+    assert_eq!(Some(monday + 15 * 60), next("*/15 * * * *", monday));
+    // from a Saturday, skips to Monday morning
+    assert_eq!(
+        Some(1_704_704_400),
+        next("*/15 9-17 * * MON-FRI", 1_704_542_400)
+    );
+    // the next leap day, from 2024-03-01
+    assert_eq!(Some(1_835_395_200), next("0 0 29 FEB *", 1_709_251_200));
+    // a restricted day-of-month and day-of-week match either, i.e. Friday the 5th
+    assert_eq!(Some(1_704_456_000), next("0 12 13 * FRI", monday));
+    // 7 is Sunday too
+    assert_eq!(next("0 0 * * 0", monday), next("0 0 * * 7", monday));
+    // never matches
+    assert_eq!(None, next("0 0 30 2 *", monday));
+}
+
+#[test]
+fn test_cron_parse_errors() {
+    assert!(matches!(
+        CronSchedule::parse("* * *"),
+        Err(CronParseErr::InvalidFormat(_))
+    ));
+    assert!(matches!(
+        CronSchedule::parse("@fortnightly"),
+        Err(CronParseErr::InvalidFormat(_))
+    ));
+    for (expression, field) in [
+        ("60 * * * *", "minute"),
+        ("* 24 * * *", "hour"),
+        ("* * 0 * *", "day-of-month"),
+        ("* * * FOO *", "month"),
+        ("* * * * 8", "day-of-week"),
+        ("*/0 * * * *", "minute"),
+        ("5-1 * * * *", "minute"),
+    ] {
+        match CronSchedule::parse(expression) {
+            Err(CronParseErr::InvalidField { field: invalid, .. }) => assert_eq!(field, invalid),
+            other => panic!("Expected an invalid {field} field for '{expression}', got {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn test_schedule_deadlines() {
+    let period = Duration::from_secs(1);
+
+    // fixed rate keeps its cadence from the start, and skips missed sends
+    let start = crate::concurrency::Instant::now();
+    let mut state = Schedule::fixed_rate(period).start();
+    let first = state.next_deadline(start).unwrap();
+    assert!(first >= start + period && first <= start + 2 * period);
+    let late = first + 3 * period + period / 2;
+    assert_eq!(late, state.next_deadline(late).unwrap());
+    let realigned = state.next_deadline(late).unwrap();
+    assert!(realigned > late && realigned < late + period);
+
+    // fixed delay is measured from the previous send
+    let mut state = Schedule::fixed_delay(period).start();
+    assert_eq!(late + period, state.next_deadline(late).unwrap());
+
+    // backoff doubles up to the max, and stops at the limit
+    let mut state = Schedule::exponential_backoff(period, 3 * period)
+        .with_limit(4)
+        .start();
+    let delays = std::iter::from_fn(|| state.next_deadline(start))
+        .map(|deadline| deadline - start)
+        .collect::<Vec<_>>();
+    assert_eq!(vec![period, 2 * period, 3 * period, 3 * period], delays);
+
+    // cron times follow the clock of the deadlines, even when it runs ahead of the wall clock
+    let minute = Duration::from_secs(60);
+    let mut state = Schedule::cron("* * * * *").unwrap().start();
+    let now = crate::concurrency::Instant::now();
+    let first = state.next_deadline(now).unwrap();
+    assert!(first > now && first <= now + minute);
+    let second = state.next_deadline(first).unwrap();
+    assert_eq!(first + minute, second);
+    assert_eq!(second + minute, state.next_deadline(second).unwrap());
+
+    // jitter only ever delays
+    let mut state = Schedule::fixed_delay(period).with_jitter(period).start();
+    for _ in 0..100 {
+        let deadline = state.next_deadline(start).unwrap();
+        assert!(deadline >= start + period && deadline <= start + 2 * period);
+    }
+}
+
+#[crate::concurrency::test]
+#[cfg_attr(
+    not(all(target_arch = "wasm32", target_os = "unknown")),
+    tracing_test::traced_test
+)]
+async fn test_send_on_schedule() {
+    let counter = Arc::new(AtomicU8::new(0u8));
+
+    struct TestActor;
+
+    #[cfg_attr(feature = "async-trait", crate::async_trait)]
+    impl Actor for TestActor {
+        type Msg = ();
+        type State = Arc<AtomicU8>;
+        type Arguments = Arc<AtomicU8>;
+        async fn pre_start(
+            &self,
+            _this_actor: ActorRef<Self::Msg>,
+            counter: Arc<AtomicU8>,
+        ) -> Result<Self::State, ActorProcessingErr> {
+            Ok(counter)
+        }
+        async fn handle(
+            &self,
+            _this_actor: ActorRef<Self::Msg>,
+            _message: Self::Msg,
+            state: &mut Self::State,
+        ) -> Result<(), ActorProcessingErr> {
+            state.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    let (actor_ref, actor_handle) = Actor::spawn(None, TestActor, counter.clone())
+        .await
+        .expect("Failed to create test actor");
+
+    let limited = actor_ref.send_on_schedule(
+        Schedule::fixed_delay(Duration::from_millis(5))
+            .with_jitter(Duration::from_millis(5))
+            .with_limit(3),
+        || (),
+    );
+    periodic_check(|| limited.is_finished(), Duration::from_millis(500)).await;
+    // the last send may not have been handled yet
+    periodic_check(
+        || counter.load(Ordering::Relaxed) == 3,
+        Duration::from_millis(500),
+    )
+    .await;
+
+    let unlimited =
+        actor_ref.send_on_schedule(Schedule::fixed_rate(Duration::from_millis(5)), || ());
+    periodic_check(
+        || counter.load(Ordering::Relaxed) >= 6,
+        Duration::from_millis(500),
+    )
+    .await;
+
+    // stops with the actor
+    actor_ref.stop(None);
+    actor_handle.await.unwrap();
+    periodic_check(|| unlimited.is_finished(), Duration::from_millis(500)).await;
+}
@@ -0,0 +1,214 @@
+// Copyright (c) Sean Lawlor
+//
+// This source code is licensed under both the MIT license found in the
+// LICENSE-MIT file in the root directory of this source tree.
+
+//! A hierarchical timer wheel, which stores timers by their expiry tick.
+//!
+//! The wheel has [LEVELS] levels of [SLOTS] slots. Level `l` holds the timers which expire
+//! within `SLOTS^(l+1)` ticks, in the slot picked by the expiry's bits for that level. When
+//! time reaches the start of a higher level slot, its timers cascade down to the lower levels,
+//! until they reach level 0 and fire. Inserting, cancelling, and resetting a timer are O(1).
+//!
+//! Timers are removed lazily: slots only hold timer ids, and an id is skipped when the slot is
+//! processed if the timer was cancelled since. Resetting a timer places it in a new slot under
+//! a new generation, and the copy left in its old slot is dropped once that slot is processed.
+
+use std::collections::HashMap;
+
+/// The number of bits of the expiry tick which index the slots of a level
+const SLOT_BITS: u32 = 6;
+/// The number of slots per level
+const SLOTS: usize = 1 << SLOT_BITS;
+/// The number of levels of the wheel
+const LEVELS: usize = 4;
+/// The furthest into the future (in ticks) a timer can be placed, further timers are placed
+/// at the horizon and re-placed when they cascade
+const HORIZON: u64 = (1 << (SLOT_BITS * LEVELS as u32)) - 1;
+
+/// The identifier of a timer in a [TimerWheel]
+pub(crate) type TimerId = u64;
+
+struct Entry<T> {
+    expiry: u64,
+    /// Bumped on every reset, so that the copies of the timer placed before it are stale
+    generation: u64,
+    value: T,
+}
+
+/// A timer as it's placed in a slot, along with the generation of the timer it was placed at
+type Slotted = (TimerId, u64);
+
+/// A hierarchical timer wheel of values of type `T`
+pub(crate) struct TimerWheel<T> {
+    /// The current tick. All the timers expiring at or before it have fired.
+    now: u64,
+    levels: Vec<Vec<Vec<Slotted>>>,
+    entries: HashMap<TimerId, Entry<T>>,
+    next_id: TimerId,
+}
+
+impl<T> TimerWheel<T> {
+    /// Create a new wheel, starting at tick 0
+    pub(crate) fn new() -> Self {
+        Self {
+            now: 0,
+            levels: (0..LEVELS).map(|_| vec![vec![]; SLOTS]).collect(),
+            entries: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    /// The number of pending timers
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no pending timers
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Whether the timer is pending
+    pub(crate) fn contains(&self, id: TimerId) -> bool {
+        self.entries.contains_key(&id)
+    }
+
+    /// The number of timers placed in the slots, including stale copies which haven't been
+    /// dropped yet
+    #[cfg(test)]
+    pub(crate) fn slotted(&self) -> usize {
+        self.levels.iter().flatten().map(Vec::len).sum()
+    }
+
+    /// The expiry tick of a pending timer
+    pub(crate) fn expiry(&self, id: TimerId) -> Option<u64> {
+        self.entries.get(&id).map(|entry| entry.expiry)
+    }
+
+    /// Add a timer which fires at the `expiry` tick, or on the next tick if it's not in
+    /// the future
+    pub(crate) fn insert(&mut self, expiry: u64, value: T) -> TimerId {
+        let id = self.next_id;
+        self.next_id += 1;
+        let expiry = expiry.max(self.now + 1);
+        self.entries.insert(
+            id,
+            Entry {
+                expiry,
+                generation: 0,
+                value,
+            },
+        );
+        self.place((id, 0), expiry);
+        id
+    }
+
+    /// Remove a pending timer, returning its value
+    pub(crate) fn remove(&mut self, id: TimerId) -> Option<T> {
+        self.entries.remove(&id).map(|entry| entry.value)
+    }
+
+    /// Move a pending timer to fire at the `expiry` tick instead, or on the next tick if
+    /// it's not in the future. Returns false if the timer isn't pending.
+    pub(crate) fn reset(&mut self, id: TimerId, expiry: u64) -> bool {
+        let expiry = expiry.max(self.now + 1);
+        match self.entries.get_mut(&id) {
+            Some(entry) => {
+                entry.expiry = expiry;
+                entry.generation += 1;
+                let generation = entry.generation;
+                self.place((id, generation), expiry);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Advance the wheel to the `target` tick, returning the timers which fired in
+    /// order of expiry
+    pub(crate) fn advance(&mut self, target: u64) -> Vec<(TimerId, T)> {
+        let mut fired = vec![];
+        while self.now < target {
+            if self.entries.is_empty() {
+                // nothing can fire, and any stale ids left in the slots are harmless
+                self.now = target;
+                break;
+            }
+            match self.next_tick() {
+                Some(next) if next <= target => {
+                    self.now = next;
+                    self.process_tick(&mut fired);
+                }
+                _ => self.now = target,
+            }
+        }
+        fired
+    }
+
+    /// The next tick at which a slot needs processing, i.e. the latest tick the wheel can
+    /// be left alone until. [None] if the wheel is empty.
+    pub(crate) fn next_tick(&self) -> Option<u64> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let mut next = None::<u64>;
+        for (level, slots) in self.levels.iter().enumerate() {
+            let shift = SLOT_BITS * level as u32;
+            for offset in 1..=SLOTS as u64 {
+                let tick = ((self.now >> shift) + offset) << shift;
+                if !slots[Self::slot_index(tick, level)].is_empty() {
+                    next = Some(next.map_or(tick, |next| next.min(tick)));
+                    break;
+                }
+            }
+        }
+        next
+    }
+
+    fn slot_index(tick: u64, level: usize) -> usize {
+        ((tick >> (SLOT_BITS * level as u32)) as usize) & (SLOTS - 1)
+    }
+
+    /// Put the timer in the slot it should wait in, relative to the current tick
+    fn place(&mut self, timer: Slotted, expiry: u64) {
+        let expiry = expiry.min(self.now + HORIZON);
+        let delta = expiry - self.now;
+        let level = (0..LEVELS)
+            .find(|level| delta < 1 << (SLOT_BITS * (*level as u32 + 1)))
+            .unwrap_or(LEVELS - 1);
+        self.levels[level][Self::slot_index(expiry, level)].push(timer);
+    }
+
+    /// Process the slots reached at the current tick, cascading the higher levels
+    /// first and then firing level 0
+    fn process_tick(&mut self, fired: &mut Vec<(TimerId, T)>) {
+        for level in (0..LEVELS).rev() {
+            let shift = SLOT_BITS * level as u32;
+            if level > 0 && self.now & ((1 << shift) - 1) != 0 {
+                continue;
+            }
+            let slot = Self::slot_index(self.now, level);
+            let timers = std::mem::take(&mut self.levels[level][slot]);
+            let mut expired = vec![];
+            for (id, generation) in timers {
+                match self.entries.get(&id) {
+                    Some(entry) if entry.generation != generation => {
+                        // a stale copy, left behind by a reset
+                    }
+                    Some(entry) if entry.expiry <= self.now => expired.push((entry.expiry, id)),
+                    // cascading down to a lower level
+                    Some(entry) => self.place((id, generation), entry.expiry),
+                    // cancelled, or a stale copy of a reset timer which already fired
+                    None => {}
+                }
+            }
+            expired.sort_unstable();
+            for (_, id) in expired {
+                if let Some(entry) = self.entries.remove(&id) {
+                    fired.push((id, entry.value));
+                }
+            }
+        }
+    }
+}
@@ -18,12 +18,17 @@ use ractor::message::SerializedMessage;
 use ractor::pg::get_scoped_local_members;
 use ractor::pg::which_scopes_and_groups;
 use ractor::pg::GroupChangeMessage;
+use ractor::pg::MemberMeta;
+use ractor::pg::MetaValue;
 use ractor::registry::PidLifecycleEvent;
 use ractor::rpc::CallResult;
 use ractor::Actor;
+use ractor::ActorCell;
 use ractor::ActorId;
 use ractor::ActorProcessingErr;
 use ractor::ActorRef;
+use ractor::GroupName;
+use ractor::ScopeName;
 use ractor::SpawnErr;
 use ractor::SupervisionEvent;
 use rand::Rng;
@@ -579,7 +584,12 @@ impl NodeSession {
                             join.group,
                             cells.len()
                         );
-                        ractor::pg::join_scoped(join.scope, join.group, cells);
+                        ractor::pg::join_scoped_with_meta(
+                            join.scope,
+                            join.group,
+                            cells,
+                            meta_from_protobuf(join.meta),
+                        );
                     }
                 }
                 control_protocol::control_message::Msg::PgLeave(leave) => {
@@ -739,23 +749,10 @@ impl NodeSession {
         // Scan all scopes with their PG groups + synchronize them
         let scopes_and_groups = which_scopes_and_groups();
         for key in scopes_and_groups {
-            let local_members = get_scoped_local_members(&key.get_scope(), &key.get_group())
-                .into_iter()
-                .filter(|v| v.supports_remoting())
-                .map(|act| control_protocol::Actor {
-                    name: act.get_name(),
-                    pid: act.get_id().pid(),
-                })
-                .collect::<Vec<_>>();
-            if !local_members.is_empty() {
+            let local_members = get_scoped_local_members(&key.get_scope(), &key.get_group());
+            for join in pg_joins(&key.get_scope(), &key.get_group(), local_members) {
                 let control_message = control_protocol::ControlMessage {
-                    msg: Some(control_protocol::control_message::Msg::PgJoin(
-                        control_protocol::PgJoin {
-                            scope: key.get_scope(),
-                            group: key.get_group(),
-                            actors: local_members,
-                        },
-                    )),
+                    msg: Some(control_protocol::control_message::Msg::PgJoin(join)),
                 };
                 state.tcp_send_control(control_message);
             }
@@ -816,6 +813,77 @@ pub struct NodeSessionState {
     remote_actors: HashMap<u64, ActorRef<RemoteActorMessage>>,
 }
 
+/// Build the [control_protocol::PgJoin]s announcing the members of a process group which
+/// support remoting, along with their current metadata. Members are batched by their
+/// metadata, as a join carries the metadata of all its actors.
+fn pg_joins(
+    scope: &ScopeName,
+    group: &GroupName,
+    actors: Vec<ActorCell>,
+) -> Vec<control_protocol::PgJoin> {
+    let metas = ractor::pg::get_scoped_members_with_meta(scope, group)
+        .into_iter()
+        .map(|(actor, meta)| (actor.get_id(), meta))
+        .collect::<HashMap<_, _>>();
+    let mut batches: Vec<(MemberMeta, Vec<control_protocol::Actor>)> = vec![];
+    for actor in actors.into_iter().filter(|act| act.supports_remoting()) {
+        let meta = metas.get(&actor.get_id()).cloned().unwrap_or_default();
+        let member = control_protocol::Actor {
+            name: actor.get_name(),
+            pid: actor.get_id().pid(),
+        };
+        match batches
+            .iter_mut()
+            .find(|(batch_meta, _)| *batch_meta == meta)
+        {
+            Some((_, members)) => members.push(member),
+            None => batches.push((meta, vec![member])),
+        }
+    }
+    batches
+        .into_iter()
+        .map(|(meta, actors)| control_protocol::PgJoin {
+            scope: scope.clone(),
+            group: group.clone(),
+            actors,
+            meta: meta_to_protobuf(&meta),
+        })
+        .collect()
+}
+
+fn meta_to_protobuf(meta: &MemberMeta) -> HashMap<String, control_protocol::MetaValue> {
+    use control_protocol::meta_value::Value;
+    meta.iter()
+        .map(|(key, value)| {
+            let value = match value {
+                MetaValue::String(value) => Value::StringValue(value.clone()),
+                MetaValue::Int(value) => Value::IntValue(*value),
+                MetaValue::UInt(value) => Value::UintValue(*value),
+                MetaValue::Bool(value) => Value::BoolValue(*value),
+            };
+            (
+                key.clone(),
+                control_protocol::MetaValue { value: Some(value) },
+            )
+        })
+        .collect()
+}
+
+fn meta_from_protobuf(meta: HashMap<String, control_protocol::MetaValue>) -> MemberMeta {
+    use control_protocol::meta_value::Value;
+    meta.into_iter()
+        .filter_map(|(key, value)| {
+            let value = match value.value? {
+                Value::StringValue(value) => MetaValue::String(value),
+                Value::IntValue(value) => MetaValue::Int(value),
+                Value::UintValue(value) => MetaValue::UInt(value),
+                Value::BoolValue(value) => MetaValue::Bool(value),
+            };
+            Some((key, value))
+        })
+        .collect()
+}
+
 impl NodeSessionState {
     fn is_tcp_actor(&self, actor: ActorId) -> bool {
         self.tcp
@@ -1077,24 +1145,12 @@ impl Actor for NodeSession {
             }
             // ======== Lifecycle event handlers (PG groups + PID registry) ======== //
             SupervisionEvent::ProcessGroupChanged(change) => match change {
-                GroupChangeMessage::Join(scope, group, actors) => {
-                    let filtered = actors
-                        .into_iter()
-                        .filter(|act| act.supports_remoting())
-                        .map(|act| control_protocol::Actor {
-                            name: act.get_name(),
-                            pid: act.get_id().pid(),
-                        })
-                        .collect::<Vec<_>>();
-                    if !filtered.is_empty() {
+                GroupChangeMessage::Join(scope, group, actors, _meta) => {
+                    // the members' current metadata is sent, since a plain join keeps the
+                    // metadata the actors joined with before
+                    for join in pg_joins(&scope, &group, actors) {
                         let msg = control_protocol::ControlMessage {
-                            msg: Some(control_protocol::control_message::Msg::PgJoin(
-                                control_protocol::PgJoin {
-                                    scope,
-                                    group,
-                                    actors: filtered,
-                                },
-                            )),
+                            msg: Some(control_protocol::control_message::Msg::PgJoin(join)),
                         };
                         state.tcp_send_control(msg);
                     }
@@ -1151,6 +1207,8 @@ impl Actor for NodeSession {
                     }
                 }
             },
+            // the session doesn't watch any registry names
+            SupervisionEvent::NameChanged(_) => {}
         }
         Ok(())
     }
@@ -787,6 +787,7 @@ async fn node_session_handle_control() {
                             name: None,
                             pid: 43,
                         }],
+                        meta: meta_to_protobuf(&ractor::pg::MemberMeta::new().with("shard", 7u32)),
                     },
                 )),
             },
@@ -803,6 +804,16 @@ async fn node_session_handle_control() {
         node_id: 1,
         pid: 43
     }));
+    // the member's metadata is synchronized with it
+    let members =
+        ractor::pg::get_scoped_members_with_meta(&scope_name.to_string(), &group_name.to_string());
+    assert_eq!(
+        Some(7),
+        members[0]
+            .1
+            .get("shard")
+            .and_then(ractor::pg::MetaValue::as_u64)
+    );
 
     let id_set = ractor::pg::get_members(&group_name.to_string())
         .into_iter()
@@ -21,6 +21,7 @@ pub(crate) mod node {
 /// Control messages between nodes
 pub(crate) mod control {
     #![allow(unreachable_pub)]
+    #![allow(clippy::enum_variant_names)]
     include!(concat!(env!("OUT_DIR"), "/control.rs"));
 }
 